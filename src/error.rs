@@ -0,0 +1,141 @@
+use std::fmt;
+
+// Every failure carries the table it happened in and the byte offset into the file,
+// the directory itself is reported as the "sfnt" table
+#[derive(Debug)]
+pub enum Error {
+    Io(std::io::Error),
+    MissingTable(String),
+    Truncated {
+        table: String,
+        offset: usize,
+    },
+    BadMagic {
+        table: String,
+        offset: usize,
+        magic: u32,
+    },
+    ChecksumMismatch {
+        table: String,
+        offset: usize,
+        expected: u32,
+        actual: u32,
+    },
+    UnsupportedFormat {
+        table: String,
+        offset: usize,
+        format: u32,
+    },
+    InvalidPlatformId {
+        table: String,
+        offset: usize,
+        platform_id: u16,
+    },
+    Malformed {
+        table: String,
+        offset: usize,
+        reason: &'static str,
+    },
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    pub fn table(&self) -> Option<&str> {
+        match self {
+            Error::Io(_) => return None,
+            Error::MissingTable(table)
+            | Error::Truncated { table, .. }
+            | Error::BadMagic { table, .. }
+            | Error::ChecksumMismatch { table, .. }
+            | Error::UnsupportedFormat { table, .. }
+            | Error::InvalidPlatformId { table, .. }
+            | Error::Malformed { table, .. } => return Some(table),
+        }
+    }
+
+    pub fn offset(&self) -> Option<usize> {
+        match self {
+            Error::Io(_) | Error::MissingTable(_) => return None,
+            Error::Truncated { offset, .. }
+            | Error::BadMagic { offset, .. }
+            | Error::ChecksumMismatch { offset, .. }
+            | Error::UnsupportedFormat { offset, .. }
+            | Error::InvalidPlatformId { offset, .. }
+            | Error::Malformed { offset, .. } => return Some(*offset),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io(err) => write!(f, "can't read the font: {}", err),
+            Error::MissingTable(table) => write!(f, "the {} table is missing", table),
+            Error::Truncated { table, offset } => {
+                write!(f, "{} table is truncated at byte {}", table, offset)
+            }
+            Error::BadMagic {
+                table,
+                offset,
+                magic,
+            } => write!(
+                f,
+                "wrong magic number {:#010x} in the {} table at byte {}",
+                magic, table, offset
+            ),
+            Error::ChecksumMismatch {
+                table,
+                offset,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "checksums don't match for the {} table at byte {}, expected {:#010x} got {:#010x}",
+                table, offset, expected, actual
+            ),
+            Error::UnsupportedFormat {
+                table,
+                offset,
+                format,
+            } => write!(
+                f,
+                "{} is not a format of the {} table supported by lipi, at byte {}",
+                format, table, offset
+            ),
+            Error::InvalidPlatformId {
+                table,
+                offset,
+                platform_id,
+            } => write!(
+                f,
+                "{} is not a valid platform id in the {} table at byte {}",
+                platform_id, table, offset
+            ),
+            Error::Malformed {
+                table,
+                offset,
+                reason,
+            } => write!(
+                f,
+                "malformed {} table at byte {}: {}",
+                table, offset, reason
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(err) => return Some(err),
+            _ => return None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        return Error::Io(err);
+    }
+}
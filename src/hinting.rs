@@ -0,0 +1,375 @@
+use crate::error::{Error, Result};
+use crate::outline::{self, OutlineBuilder};
+use crate::raster;
+use crate::tables::glyf::GlyphData;
+use crate::truetype::TrueTypeFont;
+use crate::GlyphId;
+mod disassembler;
+mod interpreter;
+
+pub use disassembler::{disassemble, Instruction};
+use interpreter::{GraphicsState, Interpreter, RoundState, Zone};
+
+// Programs can throw points anywhere, a hinted glyph wider or taller than this many ems
+// would only make for a huge bitmap
+const MAX_EXTENT: i64 = 8;
+
+// Grid-fitted glyph, the points are 26.6 fixed point pixels with the origin moved onto
+// the hinted left side bearing point
+#[derive(Debug, Clone, Default)]
+pub struct HintedGlyph {
+    pub end_pts_of_contours: Vec<u16>,
+    pub flags: Vec<u8>,
+    pub points: Vec<(i32, i32)>,
+    pub advance_width: i32, // 26.6 as well
+}
+
+impl HintedGlyph {
+    // Same as TrueTypeFont::outline_glyph but in pixels
+    pub fn outline(&self, builder: &mut dyn OutlineBuilder) {
+        let points: Vec<(f32, f32, bool)> = self
+            .points
+            .iter()
+            .zip(self.flags.iter())
+            .map(|(&(x, y), &flag)| (x as f32 / 64., y as f32 / 64., (flag & 1) != 0))
+            .collect();
+
+        outline::outline_points(&self.end_pts_of_contours, &points, builder);
+    }
+
+    pub fn rasterize(&self, offset: (f32, f32)) -> raster::Bitmap {
+        if self.points.is_empty() {
+            return raster::Bitmap::default();
+        }
+
+        let (mut x_min, mut y_min) = (i32::MAX, i32::MAX);
+        let (mut x_max, mut y_max) = (i32::MIN, i32::MIN);
+        for &(x, y) in self.points.iter() {
+            x_min = x_min.min(x);
+            y_min = y_min.min(y);
+            x_max = x_max.max(x);
+            y_max = y_max.max(y);
+        }
+
+        let left = (x_min as f32 / 64. + offset.0).floor() as i32;
+        let right = (x_max as f32 / 64. + offset.0).ceil() as i32;
+        let top = (y_max as f32 / 64. + offset.1).ceil() as i32;
+        let bottom = (y_min as f32 / 64. + offset.1).floor() as i32;
+
+        let width = (right - left).max(0) as u32;
+        let height = (top - bottom).max(0) as u32;
+        let origin = (offset.0 - left as f32, top as f32 - offset.1);
+        let mut rasterizer = raster::Rasterizer::new(width, height, 1., origin);
+        self.outline(&mut rasterizer);
+
+        return raster::Bitmap {
+            width,
+            height,
+            left,
+            top,
+            coverage: rasterizer.coverage(),
+        };
+    }
+}
+
+// Runs the font's hinting programs for one pixel size. Creating it runs fpgm and prep,
+// every glyph asked for afterwards runs its own program on top of that state.
+pub struct Hinter<'f, 'a> {
+    font: &'f TrueTypeFont<'a>,
+    interpreter: Interpreter,
+    cvt: Vec<i32>,     // as prep left it, glyph programs only change their own copy
+    storage: Vec<i32>, // same
+}
+
+impl<'f, 'a> Hinter<'f, 'a> {
+    pub fn new(font: &'f TrueTypeFont<'a>, ppem: u16) -> Result<Self> {
+        let maxp = font.maxp();
+        let scale = interpreter::div_fix(ppem as i32 * 64, font.head().units_per_em.max(1) as i32);
+
+        let cvt = match font.cvt() {
+            Some(cvt) => cvt
+                .values
+                .iter()
+                .map(|&value| interpreter::mul_fix(value as i32, scale))
+                .collect(),
+            None => Vec::new(),
+        };
+
+        let mut interpreter = Interpreter::new(
+            ppem,
+            scale,
+            cvt,
+            maxp.max_storage as usize,
+            maxp.max_twilight_points as usize,
+            maxp.max_stack_elements as usize,
+        );
+
+        if let Some(fpgm) = font.fpgm() {
+            interpreter.run("fpgm", &fpgm.instructions)?;
+        }
+
+        interpreter.graphics_state = GraphicsState::default();
+        if let Some(prep) = font.prep() {
+            interpreter.run("prep", &prep.instructions)?;
+        }
+
+        // whatever prep leaves in the graphics state is where every glyph program starts,
+        // except for the vectors, zones and rounding which start afresh
+        interpreter.default_graphics_state = GraphicsState {
+            dual_vector: interpreter::X_AXIS,
+            freedom_vector: interpreter::X_AXIS,
+            projection_vector: interpreter::X_AXIS,
+            loop_count: 1,
+            round_state: RoundState::ToGrid,
+            rp0: 0,
+            rp1: 0,
+            rp2: 0,
+            zp0: 1,
+            zp1: 1,
+            zp2: 1,
+            ..interpreter.graphics_state
+        };
+
+        return Ok(Hinter {
+            font,
+            cvt: interpreter.cvt.clone(),
+            storage: interpreter.storage.clone(),
+            interpreter,
+        });
+    }
+
+    pub fn ppem(&self) -> u16 {
+        return self.interpreter.ppem;
+    }
+
+    // Hints a glyph, glyphs without contours still come back for their advance width
+    pub fn glyph(&mut self, glyph: GlyphId) -> Result<HintedGlyph> {
+        let max_depth = self.font.max_component_depth();
+        self.interpreter.cvt.clone_from(&self.cvt);
+        self.interpreter.storage.clone_from(&self.storage);
+        let zone = self.load(glyph.0 as u32, 0, max_depth)?;
+
+        let count = zone.len() - 4;
+        let origin = zone.current[count].0;
+        // glyph programs may move the advance off the grid again
+        let advance_width = (zone.current[count + 1].0.wrapping_sub(origin) + 32) & !63;
+
+        let extent = |axis: fn(&(i32, i32)) -> i32| {
+            let values = zone.current[..count].iter().map(axis);
+            return values.clone().max().unwrap_or(0) as i64 - values.min().unwrap_or(0) as i64;
+        };
+        let limit = MAX_EXTENT * self.ppem() as i64 * 64;
+        if extent(|point| point.0) > limit || extent(|point| point.1) > limit {
+            return Err(Error::Malformed {
+                table: "glyf".into(),
+                offset: self.font.glyph_offset(glyph.0 as u32)? as usize,
+                reason: "hinted glyph far larger than its pixel size",
+            });
+        }
+
+        return Ok(HintedGlyph {
+            end_pts_of_contours: zone.contour_ends.iter().map(|&end| end as u16).collect(),
+            flags: zone.on_curve[..count]
+                .iter()
+                .map(|&on_curve| on_curve as u8)
+                .collect(),
+            points: zone.current[..count]
+                .iter()
+                .map(|&(x, y)| (x.wrapping_sub(origin), y))
+                .collect(),
+            advance_width,
+        });
+    }
+
+    fn scale(&self, value: i32) -> i32 {
+        return self.interpreter.scale(value);
+    }
+
+    // The four phantom points carry the horizontal and vertical metrics through hinting,
+    // the vertical ones sit on the top of the glyph's vertical advance and its bottom
+    fn push_phantom_points(&self, zone: &mut Zone, index: u32, x_min: i16, y_max: i16) {
+        let metric = self.font.hmtx().metric(index as u16);
+        let vertical = self.font.vertical_metric(GlyphId(index as u16), y_max);
+
+        let left = x_min as i32 - metric.left_side_bearing as i32;
+        let top = y_max as i32 + vertical.top_side_bearing as i32;
+        let phantom = [
+            (left, 0),
+            (left + metric.advance_width as i32, 0),
+            (0, top),
+            (0, top - vertical.advance_height as i32),
+        ];
+
+        for &(x, y) in phantom.iter() {
+            zone.push((x, y), (self.scale(x), self.scale(y)), false);
+        }
+    }
+
+    // Composites are hinted on top of their already hinted components, which also stand in
+    // for the unscaled outline
+    fn hint(&mut self, mut zone: Zone, instructions: &[u8], composite: bool) -> Result<Zone> {
+        if composite {
+            zone.original = zone.current.clone();
+            zone.unscaled = zone.current.clone();
+        }
+
+        // composites without instructions are left as assembled, phantom points included
+        if composite && instructions.is_empty() {
+            return Ok(zone);
+        }
+
+        // the phantom points snap to the grid so advances end up whole pixels
+        let count = zone.len() - 4;
+        for i in 0..4 {
+            let point = &mut zone.current[count + i];
+            match i {
+                0 | 1 => point.0 = (point.0 + 32) & !63,
+                _ => point.1 = (point.1 + 32) & !63,
+            }
+        }
+
+        let instruct_control = self.interpreter.default_graphics_state.instruct_control;
+        if instructions.is_empty() || (instruct_control & 1) != 0 {
+            return Ok(zone);
+        }
+
+        self.interpreter.zones[1] = zone;
+        self.interpreter.graphics_state = self.interpreter.default_graphics_state;
+        self.interpreter.original_scale = match composite {
+            true => 0x10000,
+            false => self.interpreter.scale,
+        };
+        let result = self.interpreter.run("glyf", instructions);
+        let zone = std::mem::take(&mut self.interpreter.zones[1]);
+        result?;
+
+        return Ok(zone);
+    }
+
+    fn load(&mut self, index: u32, depth: u16, max_depth: u16) -> Result<Zone> {
+        let mut zone = Zone::default();
+
+        let glyph = match self.font.glyph(index)? {
+            Some(val) => val,
+            None => {
+                self.push_phantom_points(&mut zone, index, 0, 0);
+                return self.hint(zone, &[], false);
+            }
+        };
+
+        let components = match glyph.glyph_data {
+            GlyphData::Simple(simple) => {
+                for ((&x, &y), &flag) in simple
+                    .x_coordinates
+                    .iter()
+                    .zip(simple.y_coordinates.iter())
+                    .zip(simple.flags.iter())
+                {
+                    let (x, y) = (x as i32, y as i32);
+                    zone.push((x, y), (self.scale(x), self.scale(y)), (flag & 1) != 0);
+                }
+                zone.contour_ends = simple
+                    .end_pts_of_contours
+                    .iter()
+                    .map(|&end| end as usize)
+                    .collect();
+                self.push_phantom_points(&mut zone, index, glyph.xmin, glyph.ymax);
+
+                return self.hint(zone, &glyph.instructions, false);
+            }
+            GlyphData::Composite(components) => components,
+        };
+
+        if depth >= max_depth {
+            return Err(Error::Malformed {
+                table: "glyf".into(),
+                offset: self.font.glyph_offset(index)? as usize,
+                reason: "composite glyph nested deeper than maxp allows",
+            });
+        }
+
+        let mut phantom = None;
+        for component in components.iter() {
+            let child = self.load(component.index as u32, depth + 1, max_depth)?;
+            let count = child.len() - 4;
+
+            // the 2.14 matrix as 16.16
+            let (a, b, c, d, _, _) = component.matrix;
+            let matrix = [a, b, c, d].map(|value| (value * 65536.).round() as i32);
+            let transform = |(x, y): (i32, i32)| {
+                return (
+                    interpreter::mul_fix(x, matrix[0])
+                        .wrapping_add(interpreter::mul_fix(y, matrix[2])),
+                    interpreter::mul_fix(x, matrix[1])
+                        .wrapping_add(interpreter::mul_fix(y, matrix[3])),
+                );
+            };
+            let points: Vec<(i32, i32)> = child.current[..count]
+                .iter()
+                .map(|&point| transform(point))
+                .collect();
+
+            let (dx, dy) = if component.args_are_xy_values() {
+                let offset = (component.matrix.4, component.matrix.5);
+                let (x, y) = match component.scaled_component_offset() {
+                    true => component.transform(offset.0, offset.1),
+                    false => offset,
+                };
+                let (x, y) = (self.scale(x.round() as i32), self.scale(y.round() as i32));
+                match component.round_xy_to_grid() {
+                    true => ((x + 32) & !63, (y + 32) & !63),
+                    false => (x, y),
+                }
+            } else {
+                // points line up on their hinted positions
+                let destination = component.destination_index as usize;
+                let source = component.source_index as usize;
+                match (zone.current.get(destination), points.get(source)) {
+                    (Some(&parent), Some(&child)) => (
+                        parent.0.wrapping_sub(child.0),
+                        parent.1.wrapping_sub(child.1),
+                    ),
+                    _ => {
+                        return Err(Error::Malformed {
+                            table: "glyf".into(),
+                            offset: self.font.glyph_offset(index)? as usize,
+                            reason: "component anchor point out of range",
+                        })
+                    }
+                }
+            };
+
+            // point numbers have to fit in the u16 contour ends
+            let base = zone.len();
+            if base + count > u16::MAX as usize {
+                return Err(Error::Malformed {
+                    table: "glyf".into(),
+                    offset: self.font.glyph_offset(index)? as usize,
+                    reason: "composite glyph with too many points",
+                });
+            }
+            for end in child.contour_ends.iter() {
+                zone.contour_ends.push(end + base);
+            }
+            for (i, &(x, y)) in points.iter().enumerate() {
+                let point = (x.wrapping_add(dx), y.wrapping_add(dy));
+                zone.push(point, point, child.on_curve[i]);
+            }
+
+            if component.use_my_metrics() {
+                phantom = Some(child.current[count..].to_vec());
+            }
+        }
+
+        match phantom {
+            Some(points) => {
+                for point in points {
+                    zone.push(point, point, false);
+                }
+            }
+            None => self.push_phantom_points(&mut zone, index, glyph.xmin, glyph.ymax),
+        }
+
+        return self.hint(zone, &glyph.instructions, true);
+    }
+}
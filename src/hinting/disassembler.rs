@@ -0,0 +1,214 @@
+use crate::error::{Error, Result};
+use std::fmt;
+
+// One instruction of a program, the push instructions carry the values they push
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Instruction {
+    pub offset: usize, // into the program
+    pub opcode: u8,
+    pub operands: Vec<i32>,
+}
+
+impl Instruction {
+    // Name with the flag bits of the opcode in brackets like the spec writes them, e.g.
+    // MIRP[10101]. Opcodes nothing defines come out as their hex value, fonts can still
+    // give them a meaning with IDEF.
+    pub fn mnemonic(&self) -> String {
+        return match name(self.opcode) {
+            Some((name, 0)) => name.to_string(),
+            Some((name, bits)) => {
+                let flags = self.opcode & ((1 << bits) - 1);
+                format!("{}[{:0width$b}]", name, flags, width = bits as usize)
+            }
+            None => format!("0x{:02X}", self.opcode),
+        };
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.mnemonic())?;
+        for operand in self.operands.iter() {
+            write!(f, " {}", operand)?;
+        }
+        return Ok(());
+    }
+}
+
+// Name of an opcode and how many of its low bits are flags
+fn name(opcode: u8) -> Option<(&'static str, u8)> {
+    let name = match opcode {
+        0x00..=0x01 => ("SVTCA", 1),
+        0x02..=0x03 => ("SPVTCA", 1),
+        0x04..=0x05 => ("SFVTCA", 1),
+        0x06..=0x07 => ("SPVTL", 1),
+        0x08..=0x09 => ("SFVTL", 1),
+        0x0A => ("SPVFS", 0),
+        0x0B => ("SFVFS", 0),
+        0x0C => ("GPV", 0),
+        0x0D => ("GFV", 0),
+        0x0E => ("SFVTPV", 0),
+        0x0F => ("ISECT", 0),
+        0x10 => ("SRP0", 0),
+        0x11 => ("SRP1", 0),
+        0x12 => ("SRP2", 0),
+        0x13 => ("SZP0", 0),
+        0x14 => ("SZP1", 0),
+        0x15 => ("SZP2", 0),
+        0x16 => ("SZPS", 0),
+        0x17 => ("SLOOP", 0),
+        0x18 => ("RTG", 0),
+        0x19 => ("RTHG", 0),
+        0x1A => ("SMD", 0),
+        0x1B => ("ELSE", 0),
+        0x1C => ("JMPR", 0),
+        0x1D => ("SCVTCI", 0),
+        0x1E => ("SSWCI", 0),
+        0x1F => ("SSW", 0),
+        0x20 => ("DUP", 0),
+        0x21 => ("POP", 0),
+        0x22 => ("CLEAR", 0),
+        0x23 => ("SWAP", 0),
+        0x24 => ("DEPTH", 0),
+        0x25 => ("CINDEX", 0),
+        0x26 => ("MINDEX", 0),
+        0x27 => ("ALIGNPTS", 0),
+        0x29 => ("UTP", 0),
+        0x2A => ("LOOPCALL", 0),
+        0x2B => ("CALL", 0),
+        0x2C => ("FDEF", 0),
+        0x2D => ("ENDF", 0),
+        0x2E..=0x2F => ("MDAP", 1),
+        0x30..=0x31 => ("IUP", 1),
+        0x32..=0x33 => ("SHP", 1),
+        0x34..=0x35 => ("SHC", 1),
+        0x36..=0x37 => ("SHZ", 1),
+        0x38 => ("SHPIX", 0),
+        0x39 => ("IP", 0),
+        0x3A..=0x3B => ("MSIRP", 1),
+        0x3C => ("ALIGNRP", 0),
+        0x3D => ("RTDG", 0),
+        0x3E..=0x3F => ("MIAP", 1),
+        0x40 => ("NPUSHB", 0),
+        0x41 => ("NPUSHW", 0),
+        0x42 => ("WS", 0),
+        0x43 => ("RS", 0),
+        0x44 => ("WCVTP", 0),
+        0x45 => ("RCVT", 0),
+        0x46..=0x47 => ("GC", 1),
+        0x48 => ("SCFS", 0),
+        0x49..=0x4A => ("MD", 1),
+        0x4B => ("MPPEM", 0),
+        0x4C => ("MPS", 0),
+        0x4D => ("FLIPON", 0),
+        0x4E => ("FLIPOFF", 0),
+        0x4F => ("DEBUG", 0),
+        0x50 => ("LT", 0),
+        0x51 => ("LTEQ", 0),
+        0x52 => ("GT", 0),
+        0x53 => ("GTEQ", 0),
+        0x54 => ("EQ", 0),
+        0x55 => ("NEQ", 0),
+        0x56 => ("ODD", 0),
+        0x57 => ("EVEN", 0),
+        0x58 => ("IF", 0),
+        0x59 => ("EIF", 0),
+        0x5A => ("AND", 0),
+        0x5B => ("OR", 0),
+        0x5C => ("NOT", 0),
+        0x5D => ("DELTAP1", 0),
+        0x5E => ("SDB", 0),
+        0x5F => ("SDS", 0),
+        0x60 => ("ADD", 0),
+        0x61 => ("SUB", 0),
+        0x62 => ("DIV", 0),
+        0x63 => ("MUL", 0),
+        0x64 => ("ABS", 0),
+        0x65 => ("NEG", 0),
+        0x66 => ("FLOOR", 0),
+        0x67 => ("CEILING", 0),
+        0x68..=0x6B => ("ROUND", 2),
+        0x6C..=0x6F => ("NROUND", 2),
+        0x70 => ("WCVTF", 0),
+        0x71 => ("DELTAP2", 0),
+        0x72 => ("DELTAP3", 0),
+        0x73 => ("DELTAC1", 0),
+        0x74 => ("DELTAC2", 0),
+        0x75 => ("DELTAC3", 0),
+        0x76 => ("SROUND", 0),
+        0x77 => ("S45ROUND", 0),
+        0x78 => ("JROT", 0),
+        0x79 => ("JROF", 0),
+        0x7A => ("ROFF", 0),
+        0x7C => ("RUTG", 0),
+        0x7D => ("RDTG", 0),
+        0x7E => ("SANGW", 0),
+        0x7F => ("AA", 0),
+        0x80 => ("FLIPPT", 0),
+        0x81 => ("FLIPRGON", 0),
+        0x82 => ("FLIPRGOFF", 0),
+        0x85 => ("SCANCTRL", 0),
+        0x86..=0x87 => ("SDPVTL", 1),
+        0x88 => ("GETINFO", 0),
+        0x89 => ("IDEF", 0),
+        0x8A => ("ROLL", 0),
+        0x8B => ("MAX", 0),
+        0x8C => ("MIN", 0),
+        0x8D => ("SCANTYPE", 0),
+        0x8E => ("INSTCTRL", 0),
+        0xB0..=0xB7 => ("PUSHB", 0),
+        0xB8..=0xBF => ("PUSHW", 0),
+        0xC0..=0xDF => ("MDRP", 5),
+        0xE0..=0xFF => ("MIRP", 5),
+        _ => return None,
+    };
+    return Some(name);
+}
+
+// Splits a program into its instructions, table is what a push running past the end
+// gets reported against
+pub fn disassemble(table: &str, code: &[u8]) -> Result<Vec<Instruction>> {
+    let mut instructions = Vec::new();
+    let mut pc = 0;
+
+    while pc < code.len() {
+        let opcode = code[pc];
+        let truncated = || Error::Malformed {
+            table: table.into(),
+            offset: pc,
+            reason: "push runs past the end of the program",
+        };
+
+        // the pushes tell their count (for the N variants in the byte after the opcode)
+        // and whether the values are bytes or words
+        let (start, count, words) = match opcode {
+            0x40 | 0x41 => {
+                let count = *code.get(pc + 1).ok_or_else(truncated)?;
+                (pc + 2, count as usize, opcode == 0x41)
+            }
+            0xB0..=0xB7 => (pc + 1, (opcode - 0xAF) as usize, false),
+            0xB8..=0xBF => (pc + 1, (opcode - 0xB7) as usize, true),
+            _ => (pc + 1, 0, false),
+        };
+
+        let size = if words { 2 } else { 1 };
+        let end = start + count * size;
+        let data = code.get(start..end).ok_or_else(truncated)?;
+        let operands = match words {
+            true => data
+                .chunks_exact(2)
+                .map(|value| i16::from_be_bytes([value[0], value[1]]) as i32)
+                .collect(),
+            false => data.iter().map(|&value| value as i32).collect(),
+        };
+
+        instructions.push(Instruction {
+            offset: pc,
+            opcode,
+            operands,
+        });
+        pc = end;
+    }
+
+    return Ok(instructions);
+}
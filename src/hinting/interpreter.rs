@@ -0,0 +1,1564 @@
+use crate::error::{Error, Result};
+use std::collections::HashMap;
+use std::rc::Rc;
+
+pub const TOUCHED_X: u8 = 1;
+pub const TOUCHED_Y: u8 = 2;
+
+// Runaway programs are cut off after this many instructions, and calls this deep
+const MAX_INSTRUCTIONS: usize = 10_000_000;
+const MAX_CALL_DEPTH: usize = 64;
+// Room on top of the stack size maxp gives, plenty of fonts get it a little wrong
+const STACK_SLACK: usize = 32;
+
+// The points instructions work on, all in 26.6 fixed point pixels. Zone 0 is the twilight
+// zone made up for the programs, zone 1 the glyph being hinted with its phantom points.
+#[derive(Debug, Clone, Default)]
+pub struct Zone {
+    pub unscaled: Vec<(i32, i32)>, // font units, or the hinted components for composites
+    pub original: Vec<(i32, i32)>,
+    pub current: Vec<(i32, i32)>,
+    pub touched: Vec<u8>,
+    pub on_curve: Vec<bool>,
+    pub contour_ends: Vec<usize>,
+}
+
+impl Zone {
+    pub fn twilight(size: usize) -> Zone {
+        return Zone {
+            unscaled: vec![(0, 0); size],
+            original: vec![(0, 0); size],
+            current: vec![(0, 0); size],
+            touched: vec![0; size],
+            on_curve: vec![false; size],
+            contour_ends: Vec::new(),
+        };
+    }
+
+    pub fn push(&mut self, unscaled: (i32, i32), point: (i32, i32), on_curve: bool) {
+        self.unscaled.push(unscaled);
+        self.original.push(point);
+        self.current.push(point);
+        self.touched.push(0);
+        self.on_curve.push(on_curve);
+    }
+
+    pub fn len(&self) -> usize {
+        return self.current.len();
+    }
+
+    // Points past the last contour are the phantom ones, the instructions that sweep over
+    // every point leave them alone
+    fn outline_len(&self) -> usize {
+        match self.contour_ends.last() {
+            Some(&end) => return end + 1,
+            None => return self.len(),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RoundState {
+    ToGrid,
+    ToHalfGrid,
+    ToDoubleGrid,
+    DownToGrid,
+    UpToGrid,
+    Off,
+    Super {
+        period: i32,
+        phase: i32,
+        threshold: i32,
+    },
+}
+
+// Vectors are unit length in 2.14, distances 26.6 and zone pointers index Interpreter::zones
+#[derive(Debug, Copy, Clone)]
+pub struct GraphicsState {
+    pub auto_flip: bool,
+    pub control_value_cut_in: i32,
+    pub delta_base: i32,
+    pub delta_shift: i32,
+    pub dual_vector: (i32, i32),
+    pub freedom_vector: (i32, i32),
+    pub projection_vector: (i32, i32),
+    pub instruct_control: i32,
+    pub loop_count: i32,
+    pub minimum_distance: i32,
+    pub round_state: RoundState,
+    pub rp0: usize,
+    pub rp1: usize,
+    pub rp2: usize,
+    pub scan_control: i32,
+    pub scan_type: i32,
+    pub single_width_cut_in: i32,
+    pub single_width_value: i32,
+    pub zp0: usize,
+    pub zp1: usize,
+    pub zp2: usize,
+}
+
+impl Default for GraphicsState {
+    fn default() -> Self {
+        return GraphicsState {
+            auto_flip: true,
+            control_value_cut_in: 68, // 17/16 of a pixel
+            delta_base: 9,
+            delta_shift: 3,
+            dual_vector: X_AXIS,
+            freedom_vector: X_AXIS,
+            projection_vector: X_AXIS,
+            instruct_control: 0,
+            loop_count: 1,
+            minimum_distance: 64,
+            round_state: RoundState::ToGrid,
+            rp0: 0,
+            rp1: 0,
+            rp2: 0,
+            scan_control: 0,
+            scan_type: 0,
+            single_width_cut_in: 0,
+            single_width_value: 0,
+            zp0: 1,
+            zp1: 1,
+            zp2: 1,
+        };
+    }
+}
+
+pub const X_AXIS: (i32, i32) = (0x4000, 0);
+pub const Y_AXIS: (i32, i32) = (0, 0x4000);
+
+// The fixed point helpers round the way FreeType does, so the hinted outlines come out
+// the same to the last 1/64th of a pixel
+pub fn mul_div(a: i32, b: i32, c: i32) -> i32 {
+    let sign = (a as i64).signum() * (b as i64).signum() * (c as i64).signum();
+    let (a, b, c) = ((a as i64).abs(), (b as i64).abs(), (c as i64).abs());
+    let value = match c {
+        0 => 0x7FFF_FFFF,
+        _ => (a * b + (c >> 1)) / c,
+    };
+    return (if sign < 0 { -value } else { value }) as i32;
+}
+
+// a * b with b in 16.16
+pub fn mul_fix(a: i32, b: i32) -> i32 {
+    let value = ((a as i64).abs() * (b as i64).abs() + 0x8000) >> 16;
+    return (if (a < 0) != (b < 0) { -value } else { value }) as i32;
+}
+
+// a / b as 16.16
+pub fn div_fix(a: i32, b: i32) -> i32 {
+    return mul_div(a, 0x10000, b);
+}
+
+// a * b with b in 2.14
+fn mul_fix14(a: i32, b: i32) -> i32 {
+    let value = a as i64 * b as i64;
+    return ((value + 0x2000 + (value >> 63)) >> 14) as i32;
+}
+
+fn dot14(dx: i32, dy: i32, vector: (i32, i32)) -> i32 {
+    let value = dx as i64 * vector.0 as i64 + dy as i64 * vector.1 as i64;
+    return ((value + 0x2000 + (value >> 63)) >> 14) as i32;
+}
+
+// Unit vector in 2.14, computed with the same Newton iterations FreeType uses since
+// fonts tune their programs to its rounding
+fn normalize(x: i32, y: i32) -> (i32, i32) {
+    let (sx, sy) = (if x < 0 { -1 } else { 1 }, if y < 0 { -1 } else { 1 });
+    let (mut ux, mut uy) = (x.unsigned_abs(), y.unsigned_abs());
+
+    if ux == 0 && uy == 0 {
+        return X_AXIS;
+    }
+    if ux == 0 {
+        return (0, sy * 0x4000);
+    }
+    if uy == 0 {
+        return (sx * 0x4000, 0);
+    }
+
+    // prenormalize so the estimated length is between 2/3 and 4/3 in 16.16
+    let estimate = |x: u32, y: u32| if x > y { x + (y >> 1) } else { y + (x >> 1) };
+    let mut length = estimate(ux, uy);
+    let mut shift = length.leading_zeros() as i32;
+    shift -= 15 + (length >= (0xAAAA_AAAAu32 >> shift)) as i32;
+    if shift > 0 {
+        ux <<= shift;
+        uy <<= shift;
+        length = estimate(ux, uy);
+    } else {
+        ux >>= -shift;
+        uy >>= -shift;
+        length >>= -shift;
+    }
+
+    let mut b = 0x10000 - length as i32;
+    let (x, y) = (ux as i32, uy as i32);
+    let (mut u, mut v);
+    loop {
+        u = (x + (x.wrapping_mul(b) >> 16)) as u32;
+        v = (y + (y.wrapping_mul(b) >> 16)) as u32;
+
+        let z = (u.wrapping_mul(u).wrapping_add(v.wrapping_mul(v)) as i32).wrapping_neg() / 0x200;
+        let z = z * ((0x10000 + b) >> 8) / 0x10000;
+        b += z;
+        if z <= 0 {
+            break;
+        }
+    }
+
+    return (sx * (u as i32) / 4, sy * (v as i32) / 4);
+}
+
+// Rounds the magnitude and keeps the sign, a distance never flips over because of rounding
+fn round_magnitude(n: i32, round: impl Fn(i32) -> i32) -> i32 {
+    if n >= 0 {
+        return round(n).max(0);
+    }
+    return -round(n.wrapping_neg()).max(0);
+}
+
+pub struct Interpreter {
+    pub ppem: u16,
+    pub scale: i32,          // font units to 26.6, in 16.16
+    pub original_scale: i32, // unscaled points to 26.6, 1.0 for composites
+    pub graphics_state: GraphicsState,
+    pub default_graphics_state: GraphicsState,
+    pub cvt: Vec<i32>,
+    pub storage: Vec<i32>,
+    pub zones: [Zone; 2],
+    stack: Vec<i32>,
+    max_stack: usize,
+    functions: HashMap<i32, Rc<[u8]>>,
+    instruction_defs: HashMap<u8, Rc<[u8]>>,
+    table: &'static str, // program running, for errors
+    pc: usize,
+    executed: usize,
+    depth: usize,
+}
+
+impl Interpreter {
+    pub fn new(
+        ppem: u16,
+        scale: i32,
+        cvt: Vec<i32>,
+        storage: usize,
+        twilight: usize,
+        max_stack: usize,
+    ) -> Self {
+        return Interpreter {
+            ppem,
+            scale,
+            original_scale: scale,
+            graphics_state: GraphicsState::default(),
+            default_graphics_state: GraphicsState::default(),
+            cvt,
+            storage: vec![0; storage],
+            zones: [Zone::twilight(twilight), Zone::default()],
+            stack: Vec::new(),
+            max_stack: max_stack + STACK_SLACK,
+            functions: HashMap::new(),
+            instruction_defs: HashMap::new(),
+            table: "",
+            pc: 0,
+            executed: 0,
+            depth: 0,
+        };
+    }
+
+    pub fn scale(&self, value: i32) -> i32 {
+        return mul_fix(value, self.scale);
+    }
+
+    // Runs a whole program, table is what errors get reported against and their offset
+    // is the position inside the program (or function) that failed
+    pub fn run(&mut self, table: &'static str, code: &[u8]) -> Result<()> {
+        self.table = table;
+        self.stack.clear();
+        self.executed = 0;
+        self.depth = 0;
+        return self.execute(code);
+    }
+
+    fn error(&self, reason: &'static str) -> Error {
+        return Error::Malformed {
+            table: self.table.into(),
+            offset: self.pc,
+            reason,
+        };
+    }
+
+    fn execute(&mut self, code: &[u8]) -> Result<()> {
+        let mut pc = 0;
+
+        while pc < code.len() {
+            self.count_instruction()?;
+            self.pc = pc;
+            match self.step(code, pc)? {
+                Some(next) => pc = next,
+                None => return Ok(()),
+            }
+        }
+
+        return Ok(());
+    }
+
+    fn count_instruction(&mut self) -> Result<()> {
+        self.executed += 1;
+        if self.executed > MAX_INSTRUCTIONS {
+            return Err(self.error("program never finishes"));
+        }
+        return Ok(());
+    }
+
+    // Every call counts against the budget, so looping over an empty function still ends
+    fn call(&mut self, body: &[u8]) -> Result<()> {
+        self.count_instruction()?;
+        if self.depth >= MAX_CALL_DEPTH {
+            return Err(self.error("function calls nested too deep"));
+        }
+
+        self.depth += 1;
+        let result = self.execute(body);
+        self.depth -= 1;
+        return result;
+    }
+
+    // Offset of the instruction following the one at pc, jumping over inline push data
+    fn next_instruction(&self, code: &[u8], pc: usize) -> Result<usize> {
+        let opcode = code[pc];
+        let length = match opcode {
+            0x40 => {
+                2 + *code
+                    .get(pc + 1)
+                    .ok_or_else(|| self.error("truncated push"))? as usize
+            }
+            0x41 => {
+                2 + 2 * *code
+                    .get(pc + 1)
+                    .ok_or_else(|| self.error("truncated push"))? as usize
+            }
+            0xB0..=0xB7 => 2 + (opcode - 0xB0) as usize,
+            0xB8..=0xBF => 3 + 2 * (opcode - 0xB8) as usize,
+            _ => 1,
+        };
+
+        if pc + length > code.len() {
+            return Err(self.error("truncated push"));
+        }
+        return Ok(pc + length);
+    }
+
+    // Skips a branch not taken, landing right after the matching EIF or, when asked for,
+    // the matching ELSE
+    fn skip_branch(&self, code: &[u8], pc: usize, stop_at_else: bool) -> Result<usize> {
+        let mut depth = 0;
+        let mut pc = self.next_instruction(code, pc)?;
+
+        while pc < code.len() {
+            match code[pc] {
+                0x58 => depth += 1,
+                0x1B if depth == 0 && stop_at_else => return Ok(pc + 1),
+                0x59 if depth == 0 => return Ok(pc + 1),
+                0x59 => depth -= 1,
+                _ => {}
+            }
+            pc = self.next_instruction(code, pc)?;
+        }
+
+        return Err(self.error("IF without a matching EIF"));
+    }
+
+    // Body of a FDEF or IDEF starting at pc, and where to carry on after its ENDF
+    fn definition(&self, code: &[u8], pc: usize) -> Result<(Rc<[u8]>, usize)> {
+        let start = pc + 1;
+        let mut pc = start;
+
+        while pc < code.len() {
+            if code[pc] == 0x2D {
+                return Ok((code[start..pc].into(), pc + 1));
+            }
+            pc = self.next_instruction(code, pc)?;
+        }
+
+        return Err(self.error("definition without a matching ENDF"));
+    }
+
+    fn push(&mut self, value: i32) -> Result<()> {
+        if self.stack.len() >= self.max_stack {
+            return Err(self.error("stack overflow"));
+        }
+        self.stack.push(value);
+        return Ok(());
+    }
+
+    // Popping an empty stack gives zero, fonts in the wild rely on that
+    fn pop(&mut self) -> i32 {
+        return self.stack.pop().unwrap_or(0);
+    }
+
+    fn point(&self, zone: usize, point: i32) -> Result<usize> {
+        if point < 0 || point as usize >= self.zones[zone].len() {
+            return Err(self.error("point index out of range"));
+        }
+        return Ok(point as usize);
+    }
+
+    fn pop_point(&mut self, zone: usize) -> Result<usize> {
+        let point = self.pop();
+        return self.point(zone, point);
+    }
+
+    fn pop_zone(&mut self) -> Result<usize> {
+        match self.pop() {
+            0 => return Ok(0),
+            1 => return Ok(1),
+            _ => return Err(self.error("zone pointer out of range")),
+        }
+    }
+
+    // The storage area and the cvt are lenient like most rasterizers, reads out of range
+    // give 0 and writes are dropped
+    fn read_cvt(&self, index: i32) -> i32 {
+        return self.cvt.get(index as usize).copied().unwrap_or(0);
+    }
+
+    fn write_cvt(&mut self, index: i32, value: i32) {
+        if let Some(entry) = self.cvt.get_mut(index as usize) {
+            *entry = value;
+        }
+    }
+
+    fn project(&self, dx: i32, dy: i32) -> i32 {
+        return dot14(dx, dy, self.graphics_state.projection_vector);
+    }
+
+    fn dual_project(&self, dx: i32, dy: i32) -> i32 {
+        return dot14(dx, dy, self.graphics_state.dual_vector);
+    }
+
+    fn current(&self, zone: usize, point: usize) -> (i32, i32) {
+        return self.zones[zone].current[point];
+    }
+
+    fn original(&self, zone: usize, point: usize) -> (i32, i32) {
+        return self.zones[zone].original[point];
+    }
+
+    fn project_current(&self, a: (usize, usize), b: (usize, usize)) -> i32 {
+        let (p, q) = (self.current(a.0, a.1), self.current(b.0, b.1));
+        return self.project(p.0.wrapping_sub(q.0), p.1.wrapping_sub(q.1));
+    }
+
+    fn project_original(&self, a: (usize, usize), b: (usize, usize)) -> i32 {
+        let (p, q) = (self.original(a.0, a.1), self.original(b.0, b.1));
+        return self.dual_project(p.0.wrapping_sub(q.0), p.1.wrapping_sub(q.1));
+    }
+
+    // Distance along the dual vector in the unscaled outline
+    fn project_unscaled(&self, a: (usize, usize), b: (usize, usize)) -> i32 {
+        let (p, q) = (self.zones[a.0].unscaled[a.1], self.zones[b.0].unscaled[b.1]);
+        return self.dual_project(p.0.wrapping_sub(q.0), p.1.wrapping_sub(q.1));
+    }
+
+    // Distance in the original outline, measured on the unscaled points for precision
+    // unless the twilight zone is involved
+    fn original_distance(&self, a: (usize, usize), b: (usize, usize)) -> i32 {
+        if a.0 == 0 || b.0 == 0 {
+            return self.project_original(a, b);
+        }
+        return mul_fix(self.project_unscaled(a, b), self.original_scale);
+    }
+
+    fn freedom_dot_projection(&self) -> i32 {
+        let (fx, fy) = self.graphics_state.freedom_vector;
+        let (px, py) = self.graphics_state.projection_vector;
+        let dot = ((fx as i64 * px as i64 + fy as i64 * py as i64) >> 14) as i32;
+        // nearly perpendicular vectors would send points flying
+        if dot.abs() < 0x400 {
+            return 0x4000;
+        }
+        return dot;
+    }
+
+    // How far a point has to go along the freedom vector to move distance along the
+    // projection vector
+    fn displacement(&self, distance: i32) -> (i32, i32) {
+        let (fx, fy) = self.graphics_state.freedom_vector;
+        let dot = self.freedom_dot_projection();
+        return (mul_div(distance, fx, dot), mul_div(distance, fy, dot));
+    }
+
+    fn shift_point(&mut self, zone: usize, point: usize, delta: (i32, i32), touch: bool) {
+        let (fx, fy) = self.graphics_state.freedom_vector;
+        let zone = &mut self.zones[zone];
+
+        let current = &mut zone.current[point];
+        current.0 = current.0.wrapping_add(delta.0);
+        current.1 = current.1.wrapping_add(delta.1);
+
+        if touch {
+            if fx != 0 {
+                zone.touched[point] |= TOUCHED_X;
+            }
+            if fy != 0 {
+                zone.touched[point] |= TOUCHED_Y;
+            }
+        }
+    }
+
+    fn move_point(&mut self, zone: usize, point: usize, distance: i32) {
+        let delta = self.displacement(distance);
+        self.shift_point(zone, point, delta, true);
+    }
+
+    fn round(&self, n: i32) -> i32 {
+        match self.graphics_state.round_state {
+            RoundState::ToGrid => return round_magnitude(n, |v| v.saturating_add(32) & !63),
+            RoundState::ToHalfGrid => return round_magnitude(n, |v| (v & !63).wrapping_add(32)),
+            RoundState::ToDoubleGrid => return round_magnitude(n, |v| v.saturating_add(16) & !31),
+            RoundState::DownToGrid => return round_magnitude(n, |v| v & !63),
+            RoundState::UpToGrid => return round_magnitude(n, |v| v.saturating_add(63) & !63),
+            RoundState::Off => return n,
+            RoundState::Super {
+                period,
+                phase,
+                threshold,
+            } => {
+                let round = |v: i32| {
+                    return v
+                        .wrapping_sub(phase)
+                        .wrapping_add(threshold)
+                        .div_euclid(period)
+                        .wrapping_mul(period)
+                        .wrapping_add(phase);
+                };
+
+                if n >= 0 {
+                    let value = round(n);
+                    return if value < 0 { phase } else { value };
+                }
+                let value = round(n.wrapping_neg()).wrapping_neg();
+                return if value > 0 { -phase } else { value };
+            }
+        }
+    }
+
+    // SROUND and S45ROUND pack period, phase and threshold into a byte, grid is the
+    // period they count from in 2.14
+    fn super_round(&self, selector: i32, grid: i32) -> RoundState {
+        let period = match (selector >> 6) & 3 {
+            0 => grid / 2,
+            2 => grid * 2,
+            _ => grid,
+        };
+        let phase = match (selector >> 4) & 3 {
+            0 => 0,
+            1 => period / 4,
+            2 => period / 2,
+            _ => period * 3 / 4,
+        };
+        let threshold = match selector & 15 {
+            0 => period - 1,
+            val => (val - 4) * period / 8,
+        };
+
+        return RoundState::Super {
+            period: (period >> 8).max(1),
+            phase: phase >> 8,
+            threshold: threshold >> 8,
+        };
+    }
+
+    // Minimum distance keeps the sign of the original distance
+    fn keep_minimum_distance(&self, distance: i32, original: i32) -> i32 {
+        let minimum = self.graphics_state.minimum_distance;
+        if original >= 0 {
+            return distance.max(minimum);
+        }
+        return distance.min(minimum.wrapping_neg());
+    }
+
+    fn single_width(&self, distance: i32) -> i32 {
+        let value = self.graphics_state.single_width_value;
+        if distance.wrapping_sub(value).wrapping_abs() < self.graphics_state.single_width_cut_in {
+            return if distance >= 0 {
+                value
+            } else {
+                value.wrapping_neg()
+            };
+        }
+        return distance;
+    }
+
+    // Line from the point popped second (in zp1) to the one popped first (in zp2), turned
+    // a quarter to the left for the odd opcodes. Gives the vector along the current and
+    // along the original outline.
+    fn line_vectors(&mut self, opcode: u8) -> Result<((i32, i32), (i32, i32))> {
+        let (zp1, zp2) = (self.graphics_state.zp1, self.graphics_state.zp2);
+        let p2 = self.pop_point(zp2)?;
+        let p1 = self.pop_point(zp1)?;
+
+        let vector = |a: (i32, i32), b: (i32, i32)| {
+            let (dx, dy) = (a.0.wrapping_sub(b.0), a.1.wrapping_sub(b.1));
+            match (opcode & 1) != 0 {
+                true => return normalize(dy.wrapping_neg(), dx),
+                false => return normalize(dx, dy),
+            }
+        };
+
+        return Ok((
+            vector(self.current(zp1, p1), self.current(zp2, p2)),
+            vector(self.original(zp1, p1), self.original(zp2, p2)),
+        ));
+    }
+
+    fn pop_vector(&mut self) -> (i32, i32) {
+        let y = self.pop() as i16 as i32;
+        let x = self.pop() as i16 as i32;
+        return normalize(x, y);
+    }
+
+    // Instructions looping over more points than the stack holds are skipped, like other
+    // rasterizers do
+    fn take_loop(&mut self) -> i32 {
+        let count = self.graphics_state.loop_count;
+        self.graphics_state.loop_count = 1;
+        if count as usize > self.stack.len() {
+            return 0;
+        }
+        return count;
+    }
+
+    // Reference point SHP, SHC and SHZ move along with, and the move it went through
+    fn shift_reference(&mut self, opcode: u8) -> Result<(usize, usize, (i32, i32))> {
+        let (zone, point) = match (opcode & 1) != 0 {
+            true => (self.graphics_state.zp0, self.graphics_state.rp1),
+            false => (self.graphics_state.zp1, self.graphics_state.rp2),
+        };
+        let point = self.point(zone, point as i32)?;
+
+        let current = self.current(zone, point);
+        let original = self.original(zone, point);
+        let distance = self.project(
+            current.0.wrapping_sub(original.0),
+            current.1.wrapping_sub(original.1),
+        );
+        return Ok((zone, point, self.displacement(distance)));
+    }
+
+    fn delta_distance(&self, argument: i32) -> i32 {
+        let mut step = (argument & 15) - 8;
+        if step >= 0 {
+            step += 1;
+        }
+        return step * 64 / (1 << self.graphics_state.delta_shift.clamp(0, 6));
+    }
+
+    fn delta_ppem(&self, argument: i32, range: i32) -> i32 {
+        return self
+            .graphics_state
+            .delta_base
+            .wrapping_add(((argument >> 4) & 15) + range * 16);
+    }
+
+    // A delta list running past the bottom of the stack ends there, again like other
+    // rasterizers do
+    fn delta_point(&mut self, range: i32) -> Result<()> {
+        let count = self.pop();
+        for _ in 0..count {
+            if self.stack.len() < 2 {
+                self.stack.clear();
+                break;
+            }
+            let point = self.pop();
+            let argument = self.pop();
+            if self.delta_ppem(argument, range) != self.ppem as i32 {
+                continue;
+            }
+
+            let zone = self.graphics_state.zp0;
+            if let Ok(point) = self.point(zone, point) {
+                self.move_point(zone, point, self.delta_distance(argument));
+            }
+        }
+        return Ok(());
+    }
+
+    fn delta_cvt(&mut self, range: i32) -> Result<()> {
+        let count = self.pop();
+        for _ in 0..count {
+            if self.stack.len() < 2 {
+                self.stack.clear();
+                break;
+            }
+            let index = self.pop();
+            let argument = self.pop();
+            if self.delta_ppem(argument, range) != self.ppem as i32 {
+                continue;
+            }
+
+            let value = self
+                .read_cvt(index)
+                .wrapping_add(self.delta_distance(argument));
+            self.write_cvt(index, value);
+        }
+        return Ok(());
+    }
+
+    // Moves the untouched points of every contour in between their touched neighbours,
+    // keeping the proportions they had in the unscaled outline
+    fn interpolate_untouched(&mut self, x_axis: bool) {
+        let flag = if x_axis { TOUCHED_X } else { TOUCHED_Y };
+        let zone = &mut self.zones[1];
+        let axis = |point: (i32, i32)| if x_axis { point.0 } else { point.1 };
+
+        let mut start = 0;
+        for &end in zone.contour_ends.iter() {
+            if end >= zone.len() || start > end {
+                break;
+            }
+
+            let touched: Vec<usize> = (start..=end)
+                .filter(|&point| (zone.touched[point] & flag) != 0)
+                .collect();
+            let next = |point: usize| if point == end { start } else { point + 1 };
+            let mut updates = Vec::new();
+
+            // a lone touched point drags the whole contour along
+            if touched.len() == 1 {
+                let point = touched[0];
+                let delta = axis(zone.current[point]).wrapping_sub(axis(zone.original[point]));
+                for other in (start..=end).filter(|&other| other != point) {
+                    updates.push((other, axis(zone.current[other]).wrapping_add(delta)));
+                }
+            }
+
+            for (i, &from) in touched.iter().enumerate().filter(|_| touched.len() > 1) {
+                let to = touched[(i + 1) % touched.len()];
+                let (mut first, mut second) = (from, to);
+                if axis(zone.unscaled[first]) > axis(zone.unscaled[second]) {
+                    (first, second) = (second, first);
+                }
+
+                let (unscaled1, unscaled2) =
+                    (axis(zone.unscaled[first]), axis(zone.unscaled[second]));
+                let (original1, original2) =
+                    (axis(zone.original[first]), axis(zone.original[second]));
+                let (current1, current2) = (axis(zone.current[first]), axis(zone.current[second]));
+                let scale = match current1 == current2 || unscaled1 == unscaled2 {
+                    true => None,
+                    false => Some(div_fix(
+                        current2.wrapping_sub(current1),
+                        unscaled2.wrapping_sub(unscaled1),
+                    )),
+                };
+
+                let mut point = next(from);
+                while point != to {
+                    let original = axis(zone.original[point]);
+                    let value = if original <= original1 {
+                        original.wrapping_add(current1.wrapping_sub(original1))
+                    } else if original >= original2 {
+                        original.wrapping_add(current2.wrapping_sub(original2))
+                    } else {
+                        match scale {
+                            Some(scale) => {
+                                let unscaled = axis(zone.unscaled[point]).wrapping_sub(unscaled1);
+                                current1.wrapping_add(mul_fix(unscaled, scale))
+                            }
+                            None => current1,
+                        }
+                    };
+
+                    updates.push((point, value));
+                    point = next(point);
+                }
+            }
+
+            for (point, value) in updates {
+                match x_axis {
+                    true => zone.current[point].0 = value,
+                    false => zone.current[point].1 = value,
+                }
+            }
+
+            start = end + 1;
+        }
+    }
+
+    // Executes the instruction at pc, returning where to go next or None once a
+    // function is done
+    fn step(&mut self, code: &[u8], pc: usize) -> Result<Option<usize>> {
+        let opcode = code[pc];
+        let next = pc + 1;
+
+        match opcode {
+            // SVTCA, SPVTCA, SFVTCA
+            0x00..=0x05 => {
+                let axis = if (opcode & 1) != 0 { X_AXIS } else { Y_AXIS };
+                match opcode {
+                    0x00 | 0x01 => {
+                        self.graphics_state.projection_vector = axis;
+                        self.graphics_state.dual_vector = axis;
+                        self.graphics_state.freedom_vector = axis;
+                    }
+                    0x02 | 0x03 => {
+                        self.graphics_state.projection_vector = axis;
+                        self.graphics_state.dual_vector = axis;
+                    }
+                    _ => self.graphics_state.freedom_vector = axis,
+                }
+            }
+            // SPVTL
+            0x06 | 0x07 => {
+                let (vector, _) = self.line_vectors(opcode)?;
+                self.graphics_state.projection_vector = vector;
+                self.graphics_state.dual_vector = vector;
+            }
+            // SFVTL
+            0x08 | 0x09 => {
+                self.graphics_state.freedom_vector = self.line_vectors(opcode)?.0;
+            }
+            // SPVFS
+            0x0A => {
+                let vector = self.pop_vector();
+                self.graphics_state.projection_vector = vector;
+                self.graphics_state.dual_vector = vector;
+            }
+            // SFVFS
+            0x0B => self.graphics_state.freedom_vector = self.pop_vector(),
+            // GPV, GFV
+            0x0C | 0x0D => {
+                let (x, y) = match opcode {
+                    0x0C => self.graphics_state.projection_vector,
+                    _ => self.graphics_state.freedom_vector,
+                };
+                self.push(x)?;
+                self.push(y)?;
+            }
+            // SFVTPV
+            0x0E => self.graphics_state.freedom_vector = self.graphics_state.projection_vector,
+            // ISECT
+            0x0F => {
+                let (zp0, zp1) = (self.graphics_state.zp0, self.graphics_state.zp1);
+                let b1 = self.pop_point(zp0)?;
+                let b0 = self.pop_point(zp0)?;
+                let a1 = self.pop_point(zp1)?;
+                let a0 = self.pop_point(zp1)?;
+                let zone = self.graphics_state.zp2;
+                let point = self.pop_point(zone)?;
+
+                let (a0, a1) = (self.current(zp1, a0), self.current(zp1, a1));
+                let (b0, b1) = (self.current(zp0, b0), self.current(zp0, b1));
+
+                let (dax, day) = (a1.0.wrapping_sub(a0.0), a1.1.wrapping_sub(a0.1));
+                let (dbx, dby) = (b1.0.wrapping_sub(b0.0), b1.1.wrapping_sub(b0.1));
+                let (dx, dy) = (b0.0.wrapping_sub(a0.0), b0.1.wrapping_sub(a0.1));
+                let cross = |x: i32, y: i32| {
+                    mul_div(x, dby.wrapping_neg(), 64).wrapping_add(mul_div(y, dbx, 64))
+                };
+                let discriminant = cross(dax, day);
+                let dot = mul_div(dax, dbx, 64).wrapping_add(mul_div(day, dby, 64));
+
+                // nearly parallel lines meet halfway between them
+                let average = |a: i32, b: i32, c: i32, d: i32| {
+                    return ((a as i64 + b as i64 + c as i64 + d as i64) / 4) as i32;
+                };
+                let point_at = if 19 * (discriminant as i64).abs() > (dot as i64).abs() {
+                    let value = cross(dx, dy);
+                    (
+                        a0.0.wrapping_add(mul_div(value, dax, discriminant)),
+                        a0.1.wrapping_add(mul_div(value, day, discriminant)),
+                    )
+                } else {
+                    (
+                        average(a0.0, a1.0, b0.0, b1.0),
+                        average(a0.1, a1.1, b0.1, b1.1),
+                    )
+                };
+
+                self.zones[zone].current[point] = point_at;
+                self.zones[zone].touched[point] |= TOUCHED_X | TOUCHED_Y;
+            }
+            // SRP0, SRP1, SRP2
+            0x10 => self.graphics_state.rp0 = self.pop() as usize,
+            0x11 => self.graphics_state.rp1 = self.pop() as usize,
+            0x12 => self.graphics_state.rp2 = self.pop() as usize,
+            // SZP0, SZP1, SZP2, SZPS
+            0x13 => self.graphics_state.zp0 = self.pop_zone()?,
+            0x14 => self.graphics_state.zp1 = self.pop_zone()?,
+            0x15 => self.graphics_state.zp2 = self.pop_zone()?,
+            0x16 => {
+                let zone = self.pop_zone()?;
+                self.graphics_state.zp0 = zone;
+                self.graphics_state.zp1 = zone;
+                self.graphics_state.zp2 = zone;
+            }
+            // SLOOP
+            0x17 => {
+                let count = self.pop();
+                if count < 0 {
+                    return Err(self.error("negative loop count"));
+                }
+                self.graphics_state.loop_count = count.min(0xFFFF);
+            }
+            // RTG, RTHG
+            0x18 => self.graphics_state.round_state = RoundState::ToGrid,
+            0x19 => self.graphics_state.round_state = RoundState::ToHalfGrid,
+            // SMD
+            0x1A => self.graphics_state.minimum_distance = self.pop(),
+            // ELSE, only ever reached at the end of a taken IF branch
+            0x1B => return Ok(Some(self.skip_branch(code, pc, false)?)),
+            // JMPR
+            0x1C => {
+                let offset = self.pop();
+                return self.jump(code, pc, offset).map(Some);
+            }
+            // SCVTCI, SSWCI, SSW
+            0x1D => self.graphics_state.control_value_cut_in = self.pop(),
+            0x1E => self.graphics_state.single_width_cut_in = self.pop(),
+            0x1F => {
+                let value = self.pop();
+                self.graphics_state.single_width_value = self.scale(value);
+            }
+            // DUP
+            0x20 => {
+                let value = self.pop();
+                self.push(value)?;
+                self.push(value)?;
+            }
+            // POP
+            0x21 => {
+                self.pop();
+            }
+            // CLEAR
+            0x22 => self.stack.clear(),
+            // SWAP
+            0x23 => {
+                let b = self.pop();
+                let a = self.pop();
+                self.push(b)?;
+                self.push(a)?;
+            }
+            // DEPTH
+            0x24 => self.push(self.stack.len() as i32)?,
+            // CINDEX, MINDEX
+            0x25 | 0x26 => {
+                let index = self.pop();
+                if index <= 0 || index as usize > self.stack.len() {
+                    return Err(self.error("stack index out of range"));
+                }
+
+                let position = self.stack.len() - index as usize;
+                let value = match opcode {
+                    0x25 => self.stack[position],
+                    _ => self.stack.remove(position),
+                };
+                self.push(value)?;
+            }
+            // ALIGNPTS
+            0x27 => {
+                let (zp0, zp1) = (self.graphics_state.zp0, self.graphics_state.zp1);
+                let p2 = self.pop_point(zp0)?;
+                let p1 = self.pop_point(zp1)?;
+                let distance = self.project_current((zp0, p2), (zp1, p1)) / 2;
+                self.move_point(zp1, p1, distance);
+                self.move_point(zp0, p2, distance.wrapping_neg());
+            }
+            // UTP
+            0x29 => {
+                let zone = self.graphics_state.zp0;
+                let point = self.pop_point(zone)?;
+                let (fx, fy) = self.graphics_state.freedom_vector;
+                if fx != 0 {
+                    self.zones[zone].touched[point] &= !TOUCHED_X;
+                }
+                if fy != 0 {
+                    self.zones[zone].touched[point] &= !TOUCHED_Y;
+                }
+            }
+            // LOOPCALL, CALL
+            0x2A | 0x2B => {
+                let function = self.pop();
+                let count = match opcode {
+                    0x2A => self.pop(),
+                    _ => 1,
+                };
+                let body = self
+                    .functions
+                    .get(&function)
+                    .cloned()
+                    .ok_or_else(|| self.error("call to an undefined function"))?;
+
+                for _ in 0..count {
+                    self.call(&body)?;
+                }
+            }
+            // FDEF
+            0x2C => {
+                let function = self.pop();
+                let (body, next) = self.definition(code, pc)?;
+                self.functions.insert(function, body);
+                return Ok(Some(next));
+            }
+            // ENDF
+            0x2D => return Ok(None),
+            // MDAP
+            0x2E | 0x2F => {
+                let zone = self.graphics_state.zp0;
+                let point = self.pop_point(zone)?;
+                let distance = match (opcode & 1) != 0 {
+                    true => {
+                        let (x, y) = self.current(zone, point);
+                        let distance = self.project(x, y);
+                        self.round(distance).wrapping_sub(distance)
+                    }
+                    false => 0,
+                };
+
+                self.move_point(zone, point, distance);
+                self.graphics_state.rp0 = point;
+                self.graphics_state.rp1 = point;
+            }
+            // IUP
+            0x30 | 0x31 => self.interpolate_untouched((opcode & 1) != 0),
+            // SHP
+            0x32 | 0x33 => {
+                let (_, _, delta) = self.shift_reference(opcode)?;
+                let zone = self.graphics_state.zp2;
+                for _ in 0..self.take_loop() {
+                    let point = self.pop_point(zone)?;
+                    self.shift_point(zone, point, delta, true);
+                }
+            }
+            // SHC
+            0x34 | 0x35 => {
+                let (reference_zone, reference, delta) = self.shift_reference(opcode)?;
+                let zone = self.graphics_state.zp2;
+                let contour = self.pop();
+
+                let ends = &self.zones[zone].contour_ends;
+                if contour < 0 || contour as usize >= ends.len() {
+                    return Err(self.error("contour index out of range"));
+                }
+                let start = match contour {
+                    0 => 0,
+                    val => ends[val as usize - 1] + 1,
+                };
+                let end = ends[contour as usize].min(self.zones[zone].len().saturating_sub(1));
+
+                for point in start..=end {
+                    if zone != reference_zone || point != reference {
+                        self.shift_point(zone, point, delta, true);
+                    }
+                }
+            }
+            // SHZ
+            0x36 | 0x37 => {
+                let (reference_zone, reference, delta) = self.shift_reference(opcode)?;
+                let zone = self.pop_zone()?;
+
+                for point in 0..self.zones[zone].outline_len() {
+                    if zone != reference_zone || point != reference {
+                        self.shift_point(zone, point, delta, false);
+                    }
+                }
+            }
+            // SHPIX
+            0x38 => {
+                let distance = self.pop();
+                let (fx, fy) = self.graphics_state.freedom_vector;
+                let delta = (mul_fix14(distance, fx), mul_fix14(distance, fy));
+                let zone = self.graphics_state.zp2;
+
+                for _ in 0..self.take_loop() {
+                    let point = self.pop_point(zone)?;
+                    self.shift_point(zone, point, delta, true);
+                }
+            }
+            // IP
+            0x39 => {
+                let (zp0, zp1, zp2) = (
+                    self.graphics_state.zp0,
+                    self.graphics_state.zp1,
+                    self.graphics_state.zp2,
+                );
+                let twilight = zp0 == 0 || zp1 == 0 || zp2 == 0;
+                let rp1 = self.point(zp0, self.graphics_state.rp1 as i32)?;
+                let rp2 = self.point(zp1, self.graphics_state.rp2 as i32)?;
+
+                // only the ratio matters, so unscaled distances stay unscaled
+                let original = |interpreter: &Interpreter, point: (usize, usize)| match twilight {
+                    true => interpreter.project_original(point, (zp0, rp1)),
+                    false => interpreter.project_unscaled(point, (zp0, rp1)),
+                };
+                let original_range = original(self, (zp1, rp2));
+                let current_range = self.project_current((zp1, rp2), (zp0, rp1));
+
+                for _ in 0..self.take_loop() {
+                    let point = self.pop_point(zp2)?;
+                    let original = original(self, (zp2, point));
+                    let current = self.project_current((zp2, point), (zp0, rp1));
+
+                    let distance = match (original, original_range) {
+                        (0, _) => 0,
+                        (_, 0) => current,
+                        _ => mul_div(original, current_range, original_range),
+                    };
+                    self.move_point(zp2, point, distance.wrapping_sub(current));
+                }
+            }
+            // MSIRP
+            0x3A | 0x3B => {
+                let distance = self.pop();
+                let (zp0, zp1) = (self.graphics_state.zp0, self.graphics_state.zp1);
+                let point = self.pop_point(zp1)?;
+                let rp0 = self.point(zp0, self.graphics_state.rp0 as i32)?;
+
+                if zp1 == 0 {
+                    let (dx, dy) = self.displacement(distance);
+                    let reference = self.original(zp0, rp0);
+                    let original = (reference.0.wrapping_add(dx), reference.1.wrapping_add(dy));
+                    self.zones[zp1].original[point] = original;
+                    self.zones[zp1].current[point] = original;
+                }
+
+                let current = self.project_current((zp1, point), (zp0, rp0));
+                self.move_point(zp1, point, distance.wrapping_sub(current));
+
+                self.graphics_state.rp1 = rp0;
+                self.graphics_state.rp2 = point;
+                if (opcode & 1) != 0 {
+                    self.graphics_state.rp0 = point;
+                }
+            }
+            // ALIGNRP
+            0x3C => {
+                let (zp0, zp1) = (self.graphics_state.zp0, self.graphics_state.zp1);
+                let rp0 = self.point(zp0, self.graphics_state.rp0 as i32)?;
+
+                for _ in 0..self.take_loop() {
+                    let point = self.pop_point(zp1)?;
+                    let distance = self.project_current((zp1, point), (zp0, rp0));
+                    self.move_point(zp1, point, distance.wrapping_neg());
+                }
+            }
+            // RTDG
+            0x3D => self.graphics_state.round_state = RoundState::ToDoubleGrid,
+            // MIAP
+            0x3E | 0x3F => {
+                let index = self.pop();
+                let zone = self.graphics_state.zp0;
+                let point = self.pop_point(zone)?;
+                let mut distance = self.read_cvt(index);
+
+                if zone == 0 {
+                    let (fx, fy) = self.graphics_state.freedom_vector;
+                    let position = (mul_fix14(distance, fx), mul_fix14(distance, fy));
+                    self.zones[0].original[point] = position;
+                    self.zones[0].current[point] = position;
+                }
+
+                let (x, y) = self.current(zone, point);
+                let current = self.project(x, y);
+                if (opcode & 1) != 0 {
+                    if distance.wrapping_sub(current).wrapping_abs()
+                        > self.graphics_state.control_value_cut_in
+                    {
+                        distance = current;
+                    }
+                    distance = self.round(distance);
+                }
+
+                self.move_point(zone, point, distance.wrapping_sub(current));
+                self.graphics_state.rp0 = point;
+                self.graphics_state.rp1 = point;
+            }
+            // NPUSHB, NPUSHW, PUSHB, PUSHW
+            0x40 | 0x41 | 0xB0..=0xBF => {
+                let end = self.next_instruction(code, pc)?;
+                let (start, words) = match opcode {
+                    0x40 => (pc + 2, false),
+                    0x41 => (pc + 2, true),
+                    0xB0..=0xB7 => (pc + 1, false),
+                    _ => (pc + 1, true),
+                };
+
+                match words {
+                    true => {
+                        for word in code[start..end].chunks_exact(2) {
+                            self.push(i16::from_be_bytes([word[0], word[1]]) as i32)?;
+                        }
+                    }
+                    false => {
+                        for &byte in code[start..end].iter() {
+                            self.push(byte as i32)?;
+                        }
+                    }
+                }
+                return Ok(Some(end));
+            }
+            // WS
+            0x42 => {
+                let value = self.pop();
+                let index = self.pop();
+                if let Some(entry) = self.storage.get_mut(index as usize) {
+                    *entry = value;
+                }
+            }
+            // RS
+            0x43 => {
+                let index = self.pop();
+                self.push(self.storage.get(index as usize).copied().unwrap_or(0))?;
+            }
+            // WCVTP
+            0x44 => {
+                let value = self.pop();
+                let index = self.pop();
+                self.write_cvt(index, value);
+            }
+            // RCVT
+            0x45 => {
+                let index = self.pop();
+                self.push(self.read_cvt(index))?;
+            }
+            // GC
+            0x46 | 0x47 => {
+                let zone = self.graphics_state.zp2;
+                let point = self.pop_point(zone)?;
+                let value = match opcode {
+                    0x46 => {
+                        let (x, y) = self.current(zone, point);
+                        self.project(x, y)
+                    }
+                    _ => {
+                        let (x, y) = self.original(zone, point);
+                        self.dual_project(x, y)
+                    }
+                };
+                self.push(value)?;
+            }
+            // SCFS
+            0x48 => {
+                let value = self.pop();
+                let zone = self.graphics_state.zp2;
+                let point = self.pop_point(zone)?;
+
+                let (x, y) = self.current(zone, point);
+                let current = self.project(x, y);
+                self.move_point(zone, point, value.wrapping_sub(current));
+
+                if zone == 0 {
+                    self.zones[0].original[point] = self.zones[0].current[point];
+                }
+            }
+            // MD
+            0x49 | 0x4A => {
+                let (zp0, zp1) = (self.graphics_state.zp0, self.graphics_state.zp1);
+                let p1 = self.pop_point(zp1)?;
+                let p2 = self.pop_point(zp0)?;
+                let distance = match (opcode & 1) != 0 {
+                    true => self.project_current((zp0, p2), (zp1, p1)),
+                    false => self.original_distance((zp0, p2), (zp1, p1)),
+                };
+                self.push(distance)?;
+            }
+            // MPPEM, MPS
+            0x4B | 0x4C => self.push(self.ppem as i32)?,
+            // FLIPON, FLIPOFF
+            0x4D => self.graphics_state.auto_flip = true,
+            0x4E => self.graphics_state.auto_flip = false,
+            // DEBUG
+            0x4F => {
+                self.pop();
+            }
+            // LT, LTEQ, GT, GTEQ, EQ, NEQ
+            0x50..=0x55 => {
+                let b = self.pop();
+                let a = self.pop();
+                let result = match opcode {
+                    0x50 => a < b,
+                    0x51 => a <= b,
+                    0x52 => a > b,
+                    0x53 => a >= b,
+                    0x54 => a == b,
+                    _ => a != b,
+                };
+                self.push(result as i32)?;
+            }
+            // ODD, EVEN
+            0x56 | 0x57 => {
+                let value = self.pop();
+                let value = self.round(value) & 127;
+                let result = match opcode {
+                    0x56 => value == 64,
+                    _ => value == 0,
+                };
+                self.push(result as i32)?;
+            }
+            // IF
+            0x58 => {
+                if self.pop() == 0 {
+                    return Ok(Some(self.skip_branch(code, pc, true)?));
+                }
+            }
+            // EIF
+            0x59 => {}
+            // AND, OR
+            0x5A | 0x5B => {
+                let b = self.pop() != 0;
+                let a = self.pop() != 0;
+                let result = match opcode {
+                    0x5A => a && b,
+                    _ => a || b,
+                };
+                self.push(result as i32)?;
+            }
+            // NOT
+            0x5C => {
+                let value = self.pop();
+                self.push((value == 0) as i32)?;
+            }
+            // DELTAP1, SDB, SDS
+            0x5D => self.delta_point(0)?,
+            0x5E => self.graphics_state.delta_base = self.pop(),
+            0x5F => self.graphics_state.delta_shift = self.pop(),
+            // ADD, SUB, DIV, MUL
+            0x60..=0x63 => {
+                let b = self.pop();
+                let a = self.pop();
+                let result = match opcode {
+                    0x60 => a.wrapping_add(b),
+                    0x61 => a.wrapping_sub(b),
+                    0x62 => {
+                        if b == 0 {
+                            return Err(self.error("division by zero"));
+                        }
+                        (a as i64 * 64 / b as i64) as i32
+                    }
+                    _ => mul_div(a, b, 64),
+                };
+                self.push(result)?;
+            }
+            // ABS, NEG, FLOOR, CEILING
+            0x64..=0x67 => {
+                let value = self.pop();
+                let result = match opcode {
+                    0x64 => value.wrapping_abs(),
+                    0x65 => value.wrapping_neg(),
+                    0x66 => value & !63,
+                    _ => value.wrapping_add(63) & !63,
+                };
+                self.push(result)?;
+            }
+            // ROUND, the engine compensation is always 0 here
+            0x68..=0x6B => {
+                let value = self.pop();
+                self.push(self.round(value))?;
+            }
+            // NROUND
+            0x6C..=0x6F => {}
+            // WCVTF
+            0x70 => {
+                let value = self.pop();
+                let index = self.pop();
+                self.write_cvt(index, self.scale(value));
+            }
+            // DELTAP2, DELTAP3, DELTAC1, DELTAC2, DELTAC3
+            0x71 => self.delta_point(1)?,
+            0x72 => self.delta_point(2)?,
+            0x73 => self.delta_cvt(0)?,
+            0x74 => self.delta_cvt(1)?,
+            0x75 => self.delta_cvt(2)?,
+            // SROUND, S45ROUND
+            0x76 | 0x77 => {
+                let selector = self.pop();
+                let grid = if opcode == 0x76 { 0x4000 } else { 0x2D41 };
+                self.graphics_state.round_state = self.super_round(selector, grid);
+            }
+            // JROT, JROF
+            0x78 | 0x79 => {
+                let condition = self.pop() != 0;
+                let offset = self.pop();
+                if condition == (opcode == 0x78) {
+                    return self.jump(code, pc, offset).map(Some);
+                }
+            }
+            // ROFF, RUTG, RDTG
+            0x7A => self.graphics_state.round_state = RoundState::Off,
+            0x7C => self.graphics_state.round_state = RoundState::UpToGrid,
+            0x7D => self.graphics_state.round_state = RoundState::DownToGrid,
+            // SANGW, AA are obsolete
+            0x7E | 0x7F => {
+                self.pop();
+            }
+            // FLIPPT
+            0x80 => {
+                for _ in 0..self.take_loop() {
+                    let point = self.pop_point(1)?;
+                    self.zones[1].on_curve[point] = !self.zones[1].on_curve[point];
+                }
+            }
+            // FLIPRGON, FLIPRGOFF
+            0x81 | 0x82 => {
+                let high = self.pop_point(1)?;
+                let low = self.pop_point(1)?;
+                for point in low..=high {
+                    self.zones[1].on_curve[point] = opcode == 0x81;
+                }
+            }
+            // SCANCTRL
+            0x85 => self.graphics_state.scan_control = self.pop(),
+            // SDPVTL
+            0x86 | 0x87 => {
+                let (current, original) = self.line_vectors(opcode)?;
+                self.graphics_state.projection_vector = current;
+                self.graphics_state.dual_vector = original;
+            }
+            // GETINFO, we claim to be the classic rasterizer drawing in grayscale
+            0x88 => {
+                let selector = self.pop();
+                let mut result = 0;
+                if (selector & 1) != 0 {
+                    result |= 35;
+                }
+                if (selector & 32) != 0 {
+                    result |= 1 << 12;
+                }
+                self.push(result)?;
+            }
+            // IDEF
+            0x89 => {
+                let instruction = self.pop() as u8;
+                let (body, next) = self.definition(code, pc)?;
+                self.instruction_defs.insert(instruction, body);
+                return Ok(Some(next));
+            }
+            // ROLL
+            0x8A => {
+                let a = self.pop();
+                let b = self.pop();
+                let c = self.pop();
+                self.push(b)?;
+                self.push(a)?;
+                self.push(c)?;
+            }
+            // MAX, MIN
+            0x8B | 0x8C => {
+                let b = self.pop();
+                let a = self.pop();
+                self.push(if opcode == 0x8B { a.max(b) } else { a.min(b) })?;
+            }
+            // SCANTYPE
+            0x8D => self.graphics_state.scan_type = self.pop(),
+            // INSTCTRL
+            0x8E => {
+                let selector = self.pop();
+                let value = self.pop();
+                if (1..=3).contains(&selector) {
+                    let bit = 1 << (selector - 1);
+                    self.graphics_state.instruct_control =
+                        (self.graphics_state.instruct_control & !bit) | (value & bit);
+                }
+            }
+            // MDRP
+            0xC0..=0xDF => {
+                let (zp0, zp1) = (self.graphics_state.zp0, self.graphics_state.zp1);
+                let point = self.pop_point(zp1)?;
+                let rp0 = self.point(zp0, self.graphics_state.rp0 as i32)?;
+
+                let original = self.original_distance((zp1, point), (zp0, rp0));
+                let original = self.single_width(original);
+                let mut distance = match (opcode & 4) != 0 {
+                    true => self.round(original),
+                    false => original,
+                };
+                if (opcode & 8) != 0 {
+                    distance = self.keep_minimum_distance(distance, original);
+                }
+
+                let current = self.project_current((zp1, point), (zp0, rp0));
+                self.move_point(zp1, point, distance.wrapping_sub(current));
+
+                self.graphics_state.rp1 = rp0;
+                self.graphics_state.rp2 = point;
+                if (opcode & 16) != 0 {
+                    self.graphics_state.rp0 = point;
+                }
+            }
+            // MIRP
+            0xE0..=0xFF => {
+                let index = self.pop();
+                let (zp0, zp1) = (self.graphics_state.zp0, self.graphics_state.zp1);
+                let point = self.pop_point(zp1)?;
+                let rp0 = self.point(zp0, self.graphics_state.rp0 as i32)?;
+
+                let mut cvt_distance = self.single_width(self.read_cvt(index));
+
+                if zp1 == 0 {
+                    let (fx, fy) = self.graphics_state.freedom_vector;
+                    let reference = self.original(zp0, rp0);
+                    let position = (
+                        reference.0.wrapping_add(mul_fix14(cvt_distance, fx)),
+                        reference.1.wrapping_add(mul_fix14(cvt_distance, fy)),
+                    );
+                    self.zones[0].original[point] = position;
+                    self.zones[0].current[point] = position;
+                }
+
+                let original = self.project_original((zp1, point), (zp0, rp0));
+                let current = self.project_current((zp1, point), (zp0, rp0));
+
+                if self.graphics_state.auto_flip && (original ^ cvt_distance) < 0 {
+                    cvt_distance = cvt_distance.wrapping_neg();
+                }
+
+                let mut distance = match (opcode & 4) != 0 {
+                    true => {
+                        if zp0 == zp1
+                            && cvt_distance.wrapping_sub(original).wrapping_abs()
+                                > self.graphics_state.control_value_cut_in
+                        {
+                            cvt_distance = original;
+                        }
+                        self.round(cvt_distance)
+                    }
+                    false => cvt_distance,
+                };
+                if (opcode & 8) != 0 {
+                    distance = self.keep_minimum_distance(distance, original);
+                }
+
+                self.move_point(zp1, point, distance.wrapping_sub(current));
+
+                self.graphics_state.rp1 = rp0;
+                self.graphics_state.rp2 = point;
+                if (opcode & 16) != 0 {
+                    self.graphics_state.rp0 = point;
+                }
+            }
+            // anything else has to come from an IDEF
+            _ => {
+                let body = self
+                    .instruction_defs
+                    .get(&opcode)
+                    .cloned()
+                    .ok_or_else(|| self.error("unknown instruction"))?;
+                self.call(&body)?;
+            }
+        }
+
+        return Ok(Some(next));
+    }
+
+    fn jump(&self, code: &[u8], pc: usize, offset: i32) -> Result<usize> {
+        let target = pc as i64 + offset as i64;
+        if offset == 0 || target < 0 || target > code.len() as i64 {
+            return Err(self.error("jump out of the program"));
+        }
+        return Ok(target as usize);
+    }
+}
@@ -0,0 +1,345 @@
+use crate::tables::gdef::Gdef;
+use crate::tables::gpos::Gpos;
+use crate::tables::gsub::Gsub;
+use crate::tables::layout::{Coverage, LangSys, LayoutTable};
+use crate::truetype::TrueTypeFont;
+use crate::GlyphId;
+mod matching;
+mod positioning;
+mod substitution;
+
+pub(crate) use positioning::propagate_attachments;
+
+// Every glyph starts out with this bit in its mask, features meant for the whole text use
+// it as theirs
+pub const GLOBAL_MASK: u32 = 1;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct GlyphInfo {
+    pub glyph: GlyphId,
+    pub cluster: u32, // the character it came from, glyphs formed from several share the lowest
+    pub mask: u32,    // features only touch glyphs whose mask shares a bit with theirs
+    pub glyph_class: u16, // GDEF class, see tables::layout::BASE_GLYPH and the rest
+    pub mark_attachment_class: u16, // GDEF mark attachment class, 0 for none
+    pub ligature_id: u16, // shared by a ligature and the marks between its components, 0 for none
+    pub ligature_component: u16, // the component such a mark follows, counting from 1
+    pub(crate) default_ignorable: bool, // from an invisible character, hidden once shaped
+    pub(crate) zwnj: bool, // the one of them substitutions don't look past
+    pub(crate) zwj: bool, // looked past unless the feature says otherwise
+}
+
+impl GlyphInfo {
+    pub fn new(glyph: GlyphId, cluster: u32) -> Self {
+        return GlyphInfo {
+            glyph,
+            cluster,
+            mask: GLOBAL_MASK,
+            glyph_class: 0,
+            mark_attachment_class: 0,
+            ligature_id: 0,
+            ligature_component: 0,
+            default_ignorable: false,
+            zwnj: false,
+            zwj: false,
+        };
+    }
+}
+
+// In font units, offsets move the glyph without moving the ones after it
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct GlyphPosition {
+    pub x_advance: i32,
+    pub y_advance: i32,
+    pub x_offset: i32,
+    pub y_offset: i32,
+    attach_chain: i16, // to the glyph this one is attached to, relative to this one
+    attach_type: u8,
+}
+
+impl GlyphPosition {
+    pub fn new(x_advance: i32, y_advance: i32) -> Self {
+        return GlyphPosition {
+            x_advance,
+            y_advance,
+            ..Default::default()
+        };
+    }
+}
+
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum Direction {
+    #[default]
+    LeftToRight,
+    RightToLeft,
+}
+
+// The glyphs the lookups run over, in logical order whatever the direction. Positions are
+// only there once positioning started, one for every glyph.
+#[derive(Debug, Clone, Default)]
+pub struct Buffer {
+    pub glyphs: Vec<GlyphInfo>,
+    pub positions: Vec<GlyphPosition>,
+    pub direction: Direction,
+    next_ligature_id: u16,
+}
+
+impl Buffer {
+    // One cluster per glyph, numbered in order
+    pub fn new(glyphs: &[GlyphId]) -> Self {
+        return Buffer {
+            glyphs: glyphs
+                .iter()
+                .enumerate()
+                .map(|(cluster, &glyph)| GlyphInfo::new(glyph, cluster as u32))
+                .collect(),
+            ..Default::default()
+        };
+    }
+
+    pub fn push(&mut self, info: GlyphInfo) {
+        self.glyphs.push(info);
+    }
+
+    pub fn glyph_ids(&self) -> Vec<GlyphId> {
+        return self.glyphs.iter().map(|info| info.glyph).collect();
+    }
+
+    // Ids wrap around eventually, only marks close to their ligature compare them
+    fn allocate_ligature_id(&mut self) -> u16 {
+        self.next_ligature_id = self.next_ligature_id.wrapping_add(1).max(1);
+        return self.next_ligature_id;
+    }
+}
+
+// A feature to apply, value 0 turns it off and for alternates it picks the alternate,
+// counting from 1. Only glyphs whose mask shares a bit with mask are touched.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct FeatureSetting<'a> {
+    pub tag: &'a str,
+    pub value: u32,
+    pub mask: u32,
+    pub manual_zwj: bool, // ZWJ stops its substitutions rather than being looked past
+}
+
+impl<'a> FeatureSetting<'a> {
+    // On for the whole text
+    pub fn new(tag: &'a str) -> Self {
+        return FeatureSetting {
+            tag,
+            value: 1,
+            mask: GLOBAL_MASK,
+            manual_zwj: false,
+        };
+    }
+}
+
+// A lookup one of the features asked for, with the glyphs it may touch
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) struct PlannedLookup {
+    pub index: u16, // into the LookupList
+    pub mask: u32,
+    pub value: u32,
+    pub manual_zwj: bool,
+}
+
+// Scripts the font doesn't know fall back to its default script, and the fallback of the
+// fallback is Latin as fonts tend to put their generic features there
+pub(crate) fn lang_sys<'t, T>(
+    table: &'t LayoutTable<T>,
+    script: &str,
+    language: Option<&str>,
+) -> Option<&'t LangSys> {
+    let script = [script, "DFLT", "dflt", "latn"]
+        .iter()
+        .find_map(|&tag| table.script_list.script(tag))?;
+    return script.lang_sys(language);
+}
+
+// Whether the language system has the feature at all, on or not
+pub(crate) fn has_feature<T>(
+    table: &LayoutTable<T>,
+    script: &str,
+    language: Option<&str>,
+    tag: &str,
+) -> bool {
+    return lang_sys(table, script, language).is_some_and(|lang_sys| {
+        return lang_sys
+            .feature_indices
+            .iter()
+            .any(|&index| table.feature(index, None).is_some_and(|(t, _)| t == tag));
+    });
+}
+
+// The lookups of the features the language system has and the settings turn on, sorted the
+// way they get applied. The required feature is always on.
+pub(crate) fn plan_lookups<T>(
+    table: &LayoutTable<T>,
+    script: &str,
+    language: Option<&str>,
+    features: &[FeatureSetting],
+) -> Vec<PlannedLookup> {
+    let lang_sys = match lang_sys(table, script, language) {
+        Some(lang_sys) => lang_sys,
+        None => return Vec::new(),
+    };
+
+    let mut planned = Vec::new();
+    let mut add = |index: u16, mask: u32, value: u32, manual_zwj: bool| {
+        if let Some((_, feature)) = table.feature(index, None) {
+            for &lookup in feature.lookup_list_indices.iter() {
+                planned.push(PlannedLookup {
+                    index: lookup,
+                    mask,
+                    value,
+                    manual_zwj,
+                });
+            }
+        }
+    };
+
+    if let Some(index) = lang_sys.required_feature_index {
+        add(index, u32::MAX, 1, false);
+    }
+
+    for &index in lang_sys.feature_indices.iter() {
+        let tag = match table.feature(index, None) {
+            Some((tag, _)) => tag,
+            None => continue,
+        };
+
+        // the last setting of a feature wins
+        if let Some(setting) = features.iter().rev().find(|setting| setting.tag == tag) {
+            if setting.value != 0 {
+                add(index, setting.mask, setting.value, setting.manual_zwj);
+            }
+        }
+    }
+
+    // a lookup shared by several features runs once for all of their glyphs
+    planned.sort_by_key(|lookup| lookup.index);
+    let mut merged: Vec<PlannedLookup> = Vec::new();
+    for lookup in planned {
+        match merged.last_mut() {
+            Some(last) if last.index == lookup.index => {
+                last.mask |= lookup.mask;
+                last.manual_zwj |= lookup.manual_zwj;
+            }
+            _ => merged.push(lookup),
+        }
+    }
+
+    return merged;
+}
+
+// GDEF classes win over whatever the glyph had, fonts without them keep the guesses made
+// along the way, like ligatures being ligatures
+pub(crate) fn classify(gdef: Option<&Gdef>, info: &mut GlyphInfo) {
+    let gdef = match gdef {
+        Some(gdef) => gdef,
+        None => return,
+    };
+
+    if gdef.glyph_class_def.is_some() {
+        info.glyph_class = gdef.glyph_class(info.glyph);
+    }
+    info.mark_attachment_class = gdef.mark_attachment_class(info.glyph);
+}
+
+pub(crate) fn mark_glyph_sets(gdef: Option<&Gdef>) -> &[Coverage] {
+    return match gdef {
+        Some(gdef) => &gdef.mark_glyph_sets,
+        None => &[],
+    };
+}
+
+// Runs the GSUB lookups of the features over the buffer, fonts without GSUB leave it alone
+pub fn substitute(
+    font: &TrueTypeFont,
+    buffer: &mut Buffer,
+    script: &str,
+    language: Option<&str>,
+    features: &[FeatureSetting],
+) {
+    if let Some(gsub) = font.gsub() {
+        apply_gsub(gsub, font.gdef(), buffer, script, language, features);
+    }
+}
+
+// Without GDEF the glyph classes the buffer came with are all the lookup flags go by
+pub fn apply_gsub(
+    gsub: &Gsub,
+    gdef: Option<&Gdef>,
+    buffer: &mut Buffer,
+    script: &str,
+    language: Option<&str>,
+    features: &[FeatureSetting],
+) {
+    for info in buffer.glyphs.iter_mut() {
+        classify(gdef, info);
+    }
+
+    let max_len = substitution::max_len(buffer.glyphs.len());
+    for lookup in plan_lookups(gsub, script, language, features) {
+        substitution::apply_lookup(gsub, gdef, buffer, lookup, max_len);
+    }
+}
+
+// Starts the positions off with the advance widths and runs the GPOS lookups of the features
+// over them, fonts without GPOS keep their advances
+pub fn position(
+    font: &TrueTypeFont,
+    buffer: &mut Buffer,
+    script: &str,
+    language: Option<&str>,
+    features: &[FeatureSetting],
+) {
+    set_advances(font, buffer);
+    if let Some(gpos) = font.gpos() {
+        apply_gpos(gpos, font.gdef(), buffer, script, language, features);
+    }
+}
+
+pub(crate) fn set_advances(font: &TrueTypeFont, buffer: &mut Buffer) {
+    buffer.positions = buffer
+        .glyphs
+        .iter()
+        .map(|info| GlyphPosition::new(font.advance_width(info.glyph) as i32, 0))
+        .collect();
+}
+
+// Glyphs without a position yet get an empty one. Attached glyphs end up with offsets
+// relative to the pen position of their own, so the result can be drawn in one pass.
+pub fn apply_gpos(
+    gpos: &Gpos,
+    gdef: Option<&Gdef>,
+    buffer: &mut Buffer,
+    script: &str,
+    language: Option<&str>,
+    features: &[FeatureSetting],
+) {
+    apply_gpos_lookups(gpos, gdef, buffer, script, language, features);
+    propagate_attachments(buffer);
+}
+
+// The lookups alone, attached glyphs still have their offsets relative to what they are
+// attached to until propagate_attachments
+pub(crate) fn apply_gpos_lookups(
+    gpos: &Gpos,
+    gdef: Option<&Gdef>,
+    buffer: &mut Buffer,
+    script: &str,
+    language: Option<&str>,
+    features: &[FeatureSetting],
+) {
+    buffer
+        .positions
+        .resize(buffer.glyphs.len(), GlyphPosition::default());
+
+    for info in buffer.glyphs.iter_mut() {
+        classify(gdef, info);
+    }
+
+    for lookup in plan_lookups(gpos, script, language, features) {
+        positioning::apply_lookup(gpos, mark_glyph_sets(gdef), buffer, lookup);
+    }
+}
@@ -0,0 +1,387 @@
+use crate::layout::GlyphInfo;
+use crate::tables::layout::{
+    self, ChainedSequenceContext, Coverage, Lookup, SequenceContext, SequenceLookupRecord,
+};
+
+// How deep contextual lookups may call into each other
+pub(crate) const MAX_NESTING: u8 = 64;
+
+// Which glyphs a lookup looks past, going by its flags and the GDEF classes
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Skipper<'a> {
+    lookup_flag: u16,
+    mark_filtering_set: Option<&'a Coverage>, // None also when the font lacks the set
+    past_zwnj: bool,
+    past_zwj: bool,
+}
+
+impl<'a> Skipper<'a> {
+    pub fn new<T>(lookup: &Lookup<T>, mark_glyph_sets: &'a [Coverage]) -> Self {
+        let mark_filtering_set = lookup
+            .mark_filtering_set
+            .and_then(|set| mark_glyph_sets.get(set as usize));
+
+        return Skipper {
+            lookup_flag: lookup.lookup_flag,
+            mark_filtering_set,
+            past_zwnj: false,
+            past_zwj: true,
+        };
+    }
+
+    // For a lookup called from the one of this skipper, looking past the joiners the same way
+    pub fn nested<T>(&self, lookup: &Lookup<T>, mark_glyph_sets: &'a [Coverage]) -> Self {
+        return Skipper {
+            past_zwnj: self.past_zwnj,
+            past_zwj: self.past_zwj,
+            ..Skipper::new(lookup, mark_glyph_sets)
+        };
+    }
+
+    // GPOS looks past ZWNJ too, it only breaks up substitutions
+    pub fn past_zwnj(&self) -> Self {
+        return Skipper {
+            past_zwnj: true,
+            ..*self
+        };
+    }
+
+    // For features that only match ZWJ where they ask for it
+    pub fn stop_at_zwj(&self, stop: bool) -> Self {
+        return Skipper {
+            past_zwj: !stop,
+            ..*self
+        };
+    }
+
+    // The same mark filtering with other flags, mark attachment looks for its base that way
+    pub fn with_flag(&self, lookup_flag: u16) -> Self {
+        return Skipper {
+            lookup_flag,
+            ..*self
+        };
+    }
+
+    pub fn lookup_flag(&self) -> u16 {
+        return self.lookup_flag;
+    }
+
+    pub fn skips(&self, info: &GlyphInfo) -> bool {
+        let flag = self.lookup_flag;
+        match info.glyph_class {
+            layout::BASE_GLYPH => return flag & layout::IGNORE_BASE_GLYPHS != 0,
+            layout::LIGATURE_GLYPH => return flag & layout::IGNORE_LIGATURES != 0,
+            layout::MARK_GLYPH => {
+                if flag & layout::IGNORE_MARKS != 0 {
+                    return true;
+                }
+
+                if flag & layout::USE_MARK_FILTERING_SET != 0 {
+                    return !self
+                        .mark_filtering_set
+                        .is_some_and(|set| set.contains(info.glyph));
+                }
+
+                let attachment_type = (flag & layout::MARK_ATTACHMENT_TYPE) >> 8;
+                return attachment_type != 0 && info.mark_attachment_class != attachment_type;
+            }
+            _ => return false,
+        }
+    }
+
+    // Also past default ignorables, so a joiner between two letters doesn't keep them from
+    // ligating or kerning
+    pub fn looks_past(&self, info: &GlyphInfo) -> bool {
+        return self.skips(info)
+            || (info.default_ignorable
+                && (self.past_zwnj || !info.zwnj)
+                && (self.past_zwj || !info.zwj));
+    }
+
+    // An ignorable the lookup asks for is taken rather than looked past
+    fn looks_past_unless(&self, info: &GlyphInfo, matches: impl FnOnce() -> bool) -> bool {
+        return self.skips(info) || (self.looks_past(info) && !matches());
+    }
+}
+
+// Positions of the glyph at start and the count glyphs after it that match, looking past
+// the ones the skipper looks past. The glyphs have to be in the mask of the lookup.
+pub(crate) fn match_input(
+    glyphs: &[GlyphInfo],
+    start: usize,
+    count: usize,
+    skipper: Skipper,
+    mask: u32,
+    matches: impl Fn(usize, &GlyphInfo) -> bool,
+) -> Option<Vec<usize>> {
+    let mut positions = vec![start];
+    let mut position = start;
+    for k in 0..count {
+        position = (position + 1..glyphs.len()).find(|&i| {
+            let info = &glyphs[i];
+            return !skipper.looks_past_unless(info, || info.mask & mask != 0 && matches(k, info));
+        })?;
+        let info = &glyphs[position];
+        if info.mask & mask == 0 || !matches(k, info) {
+            return None;
+        }
+        positions.push(position);
+    }
+    return Some(positions);
+}
+
+// Count glyphs before start, the first one right before it
+pub(crate) fn match_backtrack(
+    glyphs: &[GlyphInfo],
+    start: usize,
+    count: usize,
+    skipper: Skipper,
+    matches: impl Fn(usize, &GlyphInfo) -> bool,
+) -> bool {
+    let mut position = start;
+    for k in 0..count {
+        let next = (0..position)
+            .rev()
+            .find(|&i| !skipper.looks_past_unless(&glyphs[i], || matches(k, &glyphs[i])));
+        position = match next {
+            Some(position) => position,
+            None => return false,
+        };
+        if !matches(k, &glyphs[position]) {
+            return false;
+        }
+    }
+    return true;
+}
+
+// Count glyphs after the one at last
+pub(crate) fn match_lookahead(
+    glyphs: &[GlyphInfo],
+    last: usize,
+    count: usize,
+    skipper: Skipper,
+    matches: impl Fn(usize, &GlyphInfo) -> bool,
+) -> bool {
+    let mut position = last;
+    for k in 0..count {
+        let next = (position + 1..glyphs.len())
+            .find(|&i| !skipper.looks_past_unless(&glyphs[i], || matches(k, &glyphs[i])));
+        position = match next {
+            Some(position) => position,
+            None => return false,
+        };
+        if !matches(k, &glyphs[position]) {
+            return false;
+        }
+    }
+    return true;
+}
+
+// What the contextual lookups need from the GSUB and GPOS engines
+pub(crate) trait Apply {
+    fn glyphs(&self) -> &[GlyphInfo];
+    fn skipper(&self) -> Skipper<'_>;
+    fn mask(&self) -> u32;
+    // Applies a lookup of the same table at one glyph, true when it did anything
+    fn apply_nested(&mut self, lookup_index: u16, position: usize) -> bool;
+}
+
+// Runs the lookups of a matched sequence and returns where the sequence ends now. Nested
+// lookups may add or remove glyphs, new ones are assumed to follow the glyph the lookup ran
+// at and removed ones to be the sequence glyphs after it, the same guess HarfBuzz makes.
+fn apply_records(
+    applier: &mut impl Apply,
+    mut positions: Vec<usize>,
+    records: &[SequenceLookupRecord],
+) -> usize {
+    let mut end = *positions.last().unwrap() as isize + 1;
+
+    for record in records.iter() {
+        let index = record.sequence_index as usize;
+        if index >= positions.len() {
+            continue;
+        }
+
+        let length = applier.glyphs().len();
+        let position = positions[index];
+        if position >= length || !applier.apply_nested(record.lookup_list_index, position) {
+            continue;
+        }
+
+        let mut delta = applier.glyphs().len() as isize - length as isize;
+        if delta == 0 {
+            continue;
+        }
+
+        end += delta;
+        if end < position as isize {
+            delta += position as isize - end;
+            end = position as isize;
+        }
+
+        let next = index + 1;
+        if delta > 0 {
+            let added = (1..=delta as usize).map(|i| position + i);
+            positions.splice(next..next, added);
+        } else {
+            let removed = ((-delta) as usize).min(positions.len() - next);
+            positions.drain(next..next + removed);
+            delta = -(removed as isize);
+        }
+
+        let shifted = next + delta.max(0) as usize;
+        for position in positions[shifted..].iter_mut() {
+            *position = (*position as isize + delta) as usize;
+        }
+    }
+
+    return end.max(0) as usize;
+}
+
+// Both return the position after the sequence when a rule matched
+pub(crate) fn apply_context(
+    applier: &mut impl Apply,
+    context: &SequenceContext,
+    position: usize,
+) -> Option<usize> {
+    let glyphs = applier.glyphs();
+    let (skipper, mask) = (applier.skipper(), applier.mask());
+    let glyph = glyphs[position].glyph;
+
+    let (positions, records) = match context {
+        SequenceContext::Format1 {
+            coverage,
+            seq_rule_sets,
+        } => {
+            let rules = seq_rule_sets.get(coverage.index(glyph)? as usize)?;
+            rules.iter().find_map(|rule| {
+                let input = &rule.input_sequence;
+                let positions =
+                    match_input(glyphs, position, input.len(), skipper, mask, |k, info| {
+                        info.glyph.0 == input[k]
+                    })?;
+                return Some((positions, &rule.seq_lookup_records));
+            })?
+        }
+        SequenceContext::Format2 {
+            coverage,
+            class_def,
+            class_seq_rule_sets,
+        } => {
+            coverage.index(glyph)?;
+            let rules = class_seq_rule_sets.get(class_def.class(glyph) as usize)?;
+            rules.iter().find_map(|rule| {
+                let input = &rule.input_sequence;
+                let positions =
+                    match_input(glyphs, position, input.len(), skipper, mask, |k, info| {
+                        class_def.class(info.glyph) == input[k]
+                    })?;
+                return Some((positions, &rule.seq_lookup_records));
+            })?
+        }
+        SequenceContext::Format3 {
+            coverages,
+            seq_lookup_records,
+        } => {
+            coverages.first()?.index(glyph)?;
+            let positions = match_input(
+                glyphs,
+                position,
+                coverages.len() - 1,
+                skipper,
+                mask,
+                |k, info| coverages[k + 1].contains(info.glyph),
+            )?;
+            (positions, seq_lookup_records)
+        }
+    };
+
+    return Some(apply_records(applier, positions, records));
+}
+
+pub(crate) fn apply_chained_context(
+    applier: &mut impl Apply,
+    context: &ChainedSequenceContext,
+    position: usize,
+) -> Option<usize> {
+    let glyphs = applier.glyphs();
+    let (skipper, mask) = (applier.skipper(), applier.mask());
+    let glyph = glyphs[position].glyph;
+
+    // input first, the backtrack starts at its first glyph and the lookahead after its last
+    let matched = |backtrack: &dyn Fn(usize, &GlyphInfo) -> bool,
+                   backtrack_count: usize,
+                   input: &dyn Fn(usize, &GlyphInfo) -> bool,
+                   input_count: usize,
+                   lookahead: &dyn Fn(usize, &GlyphInfo) -> bool,
+                   lookahead_count: usize| {
+        let positions = match_input(glyphs, position, input_count, skipper, mask, input)?;
+        let last = *positions.last().unwrap();
+        if !match_backtrack(glyphs, position, backtrack_count, skipper, backtrack)
+            || !match_lookahead(glyphs, last, lookahead_count, skipper, lookahead)
+        {
+            return None;
+        }
+        return Some(positions);
+    };
+
+    let (positions, records) = match context {
+        ChainedSequenceContext::Format1 {
+            coverage,
+            chained_seq_rule_sets,
+        } => {
+            let rules = chained_seq_rule_sets.get(coverage.index(glyph)? as usize)?;
+            rules.iter().find_map(|rule| {
+                let positions = matched(
+                    &|k, info| info.glyph.0 == rule.backtrack_sequence[k],
+                    rule.backtrack_sequence.len(),
+                    &|k, info| info.glyph.0 == rule.input_sequence[k],
+                    rule.input_sequence.len(),
+                    &|k, info| info.glyph.0 == rule.lookahead_sequence[k],
+                    rule.lookahead_sequence.len(),
+                )?;
+                return Some((positions, &rule.seq_lookup_records));
+            })?
+        }
+        ChainedSequenceContext::Format2 {
+            coverage,
+            backtrack_class_def,
+            input_class_def,
+            lookahead_class_def,
+            chained_class_seq_rule_sets,
+        } => {
+            coverage.index(glyph)?;
+            let rules = chained_class_seq_rule_sets.get(input_class_def.class(glyph) as usize)?;
+            rules.iter().find_map(|rule| {
+                let positions = matched(
+                    &|k, info| backtrack_class_def.class(info.glyph) == rule.backtrack_sequence[k],
+                    rule.backtrack_sequence.len(),
+                    &|k, info| input_class_def.class(info.glyph) == rule.input_sequence[k],
+                    rule.input_sequence.len(),
+                    &|k, info| lookahead_class_def.class(info.glyph) == rule.lookahead_sequence[k],
+                    rule.lookahead_sequence.len(),
+                )?;
+                return Some((positions, &rule.seq_lookup_records));
+            })?
+        }
+        ChainedSequenceContext::Format3 {
+            backtrack_coverages,
+            input_coverages,
+            lookahead_coverages,
+            seq_lookup_records,
+        } => {
+            input_coverages.first()?.index(glyph)?;
+            let positions = matched(
+                &|k, info| backtrack_coverages[k].contains(info.glyph),
+                backtrack_coverages.len(),
+                &|k, info| input_coverages[k + 1].contains(info.glyph),
+                input_coverages.len() - 1,
+                &|k, info| lookahead_coverages[k].contains(info.glyph),
+                lookahead_coverages.len(),
+            )?;
+            (positions, seq_lookup_records)
+        }
+    };
+
+    return Some(apply_records(applier, positions, records));
+}
@@ -0,0 +1,446 @@
+use crate::layout::matching::{self, Apply, Skipper};
+use crate::layout::{Buffer, Direction, GlyphInfo, GlyphPosition, PlannedLookup};
+use crate::tables::gpos::{
+    Anchor, CursivePositioning, Gpos, MarkRecord, MarkToBasePositioning, MarkToLigaturePositioning,
+    MarkToMarkPositioning, PairPositioning, PositioningSubtable, SinglePositioning, ValueRecord,
+};
+use crate::tables::layout::{self, Coverage, Lookup};
+
+// GlyphPosition::attach_type
+const ATTACH_MARK: u8 = 1;
+const ATTACH_CURSIVE: u8 = 2;
+
+struct Positioner<'a> {
+    gpos: &'a Gpos,
+    mark_glyph_sets: &'a [Coverage],
+    buffer: &'a mut Buffer,
+    skipper: Skipper<'a>, // of the lookup running now, nested ones swap theirs in
+    mask: u32,
+    nesting: u8,
+}
+
+impl<'a> Positioner<'a> {
+    // The closest glyph before position the skipper doesn't look past, if the lookup may touch it
+    fn previous(&self, position: usize, skipper: Skipper) -> Option<usize> {
+        let glyphs = &self.buffer.glyphs;
+        let previous = (0..position)
+            .rev()
+            .find(|&i| !skipper.looks_past(&glyphs[i]))?;
+        if glyphs[previous].mask & self.mask == 0 {
+            return None;
+        }
+        return Some(previous);
+    }
+
+    // Y advances only matter for vertical layout, which this doesn't do
+    fn apply_value(&mut self, position: usize, record: &ValueRecord) {
+        let glyph_position = &mut self.buffer.positions[position];
+        glyph_position.x_offset += record.x_placement as i32;
+        glyph_position.y_offset += record.y_placement as i32;
+        glyph_position.x_advance += record.x_advance as i32;
+    }
+
+    fn apply_single(&mut self, subtable: &SinglePositioning, position: usize) -> Option<usize> {
+        let glyph = self.buffer.glyphs[position].glyph;
+        let record = match subtable {
+            SinglePositioning::Format1 {
+                coverage,
+                value_record,
+            } => {
+                coverage.index(glyph)?;
+                value_record
+            }
+            SinglePositioning::Format2 {
+                coverage,
+                value_records,
+            } => value_records.get(coverage.index(glyph)? as usize)?,
+        };
+
+        self.apply_value(position, record);
+        return Some(position + 1);
+    }
+
+    // A second glyph that got no value of its own may still start the next pair
+    fn apply_pair(&mut self, subtable: &PairPositioning, position: usize) -> Option<usize> {
+        let glyphs = &self.buffer.glyphs;
+        let first = glyphs[position].glyph;
+        let (PairPositioning::Format1 { coverage, .. } | PairPositioning::Format2 { coverage, .. }) =
+            subtable;
+        let index = coverage.index(first)? as usize;
+        let next = (position + 1..glyphs.len()).find(|&i| !self.skipper.looks_past(&glyphs[i]))?;
+        if glyphs[next].mask & self.mask == 0 {
+            return None;
+        }
+        let second = glyphs[next].glyph;
+
+        let (record1, record2, value_format2) = match subtable {
+            PairPositioning::Format1 {
+                pair_sets,
+                value_format2,
+                ..
+            } => {
+                let pairs = pair_sets.get(index)?;
+                let pair = pairs
+                    .binary_search_by_key(&second.0, |pair| pair.second_glyph)
+                    .ok()?;
+                let pair = &pairs[pair];
+                (&pair.value_record1, &pair.value_record2, *value_format2)
+            }
+            PairPositioning::Format2 {
+                value_format2,
+                class_def1,
+                class_def2,
+                class1_records,
+                ..
+            } => {
+                let record = class1_records
+                    .get(class_def1.class(first) as usize)?
+                    .get(class_def2.class(second) as usize)?;
+                (&record.value_record1, &record.value_record2, *value_format2)
+            }
+        };
+
+        self.apply_value(position, record1);
+        self.apply_value(next, record2);
+        return match value_format2 {
+            0 => Some(next),
+            _ => Some(next + 1),
+        };
+    }
+
+    // The exit anchor of the glyph before meets the entry anchor of this one. One of them is
+    // moved up or down to line up with the other, the glyph at the end of the connected run
+    // stays on the baseline and that is the last one with RIGHT_TO_LEFT, the first without.
+    fn apply_cursive(&mut self, subtable: &CursivePositioning, position: usize) -> Option<usize> {
+        let glyphs = &self.buffer.glyphs;
+        let records = &subtable.entry_exit_records;
+        let index = subtable.coverage.index(glyphs[position].glyph)? as usize;
+        let entry = records.get(index)?.entry_anchor.as_ref()?;
+
+        let previous = self.previous(position, self.skipper)?;
+        let index = subtable.coverage.index(glyphs[previous].glyph)? as usize;
+        let exit = records.get(index)?.exit_anchor.as_ref()?;
+
+        let (entry_x, entry_y) = entry.coordinates();
+        let (exit_x, exit_y) = exit.coordinates();
+        let (entry_x, entry_y, exit_x, exit_y) =
+            (entry_x as i32, entry_y as i32, exit_x as i32, exit_y as i32);
+
+        let positions = &mut self.buffer.positions;
+        let (i, j) = (previous, position);
+        match self.buffer.direction {
+            Direction::LeftToRight => {
+                positions[i].x_advance = exit_x + positions[i].x_offset;
+                let d = entry_x + positions[j].x_offset;
+                positions[j].x_advance -= d;
+                positions[j].x_offset -= d;
+            }
+            Direction::RightToLeft => {
+                let d = exit_x + positions[i].x_offset;
+                positions[i].x_advance -= d;
+                positions[i].x_offset -= d;
+                positions[j].x_advance = entry_x + positions[j].x_offset;
+            }
+        }
+
+        let (mut child, mut parent, mut y_offset) = (i, j, entry_y - exit_y);
+        if self.skipper.lookup_flag() & layout::RIGHT_TO_LEFT == 0 {
+            (child, parent, y_offset) = (j, i, -y_offset);
+        }
+
+        // a child already hanging off another glyph takes its whole chain along
+        reverse_cursive_chain(positions, child, parent);
+        positions[child].attach_type = ATTACH_CURSIVE;
+        positions[child].attach_chain = (parent as isize - child as isize) as i16;
+        positions[child].y_offset = y_offset;
+        if positions[parent].attach_chain == -positions[child].attach_chain {
+            positions[parent].attach_chain = 0;
+            positions[parent].y_offset = 0;
+        }
+
+        return Some(position + 1);
+    }
+
+    // Puts the anchor of the mark on the anchor the base has for its class. The offset is
+    // from the base for now, propagate_attachments makes it relative to the mark.
+    fn attach_mark(
+        &mut self,
+        marks: &[MarkRecord],
+        mark_index: u16,
+        anchors: &[Option<Anchor>],
+        base: usize,
+        position: usize,
+    ) -> Option<usize> {
+        let mark = marks.get(mark_index as usize)?;
+        let anchor = anchors.get(mark.mark_class as usize)?.as_ref()?;
+        let (mark_x, mark_y) = mark.mark_anchor.coordinates();
+        let (base_x, base_y) = anchor.coordinates();
+
+        let glyph_position = &mut self.buffer.positions[position];
+        glyph_position.x_offset = base_x as i32 - mark_x as i32;
+        glyph_position.y_offset = base_y as i32 - mark_y as i32;
+        glyph_position.attach_type = ATTACH_MARK;
+        glyph_position.attach_chain = (base as isize - position as isize) as i16;
+        return Some(position + 1);
+    }
+
+    fn apply_mark_to_base(
+        &mut self,
+        subtable: &MarkToBasePositioning,
+        position: usize,
+    ) -> Option<usize> {
+        let glyphs = &self.buffer.glyphs;
+        let mark_index = subtable.mark_coverage.index(glyphs[position].glyph)?;
+        let base = self.previous(position, self.skipper.with_flag(layout::IGNORE_MARKS))?;
+        let base_index = subtable.base_coverage.index(glyphs[base].glyph)?;
+
+        let anchors = subtable.base_array.get(base_index as usize)?;
+        return self.attach_mark(&subtable.mark_array, mark_index, anchors, base, position);
+    }
+
+    // Marks that sat between the components when the ligature formed go on their component,
+    // any others on the last one
+    fn apply_mark_to_ligature(
+        &mut self,
+        subtable: &MarkToLigaturePositioning,
+        position: usize,
+    ) -> Option<usize> {
+        let glyphs = &self.buffer.glyphs;
+        let mark = glyphs[position];
+        let mark_index = subtable.mark_coverage.index(mark.glyph)?;
+        let ligature = self.previous(position, self.skipper.with_flag(layout::IGNORE_MARKS))?;
+        let ligature_index = subtable.ligature_coverage.index(glyphs[ligature].glyph)?;
+
+        let components = subtable.ligature_array.get(ligature_index as usize)?;
+        let count = components.len() as u16;
+        if count == 0 {
+            return None;
+        }
+        let ligature_id = glyphs[ligature].ligature_id;
+        let component = match ligature_id != 0
+            && ligature_id == mark.ligature_id
+            && mark.ligature_component > 0
+        {
+            true => count.min(mark.ligature_component) - 1,
+            false => count - 1,
+        };
+
+        let anchors = &components[component as usize];
+        return self.attach_mark(
+            &subtable.mark_array,
+            mark_index,
+            anchors,
+            ligature,
+            position,
+        );
+    }
+
+    // Only onto a mark of the same base, or the same component of a ligature
+    fn apply_mark_to_mark(
+        &mut self,
+        subtable: &MarkToMarkPositioning,
+        position: usize,
+    ) -> Option<usize> {
+        let glyphs = &self.buffer.glyphs;
+        let mark1 = glyphs[position];
+        let mark1_index = subtable.mark1_coverage.index(mark1.glyph)?;
+        let ignored = layout::IGNORE_BASE_GLYPHS | layout::IGNORE_LIGATURES | layout::IGNORE_MARKS;
+        let skipper = self
+            .skipper
+            .with_flag(self.skipper.lookup_flag() & !ignored);
+        let previous = self.previous(position, skipper)?;
+        let mark2 = glyphs[previous];
+        if mark2.glyph_class != layout::MARK_GLYPH {
+            return None;
+        }
+
+        let same_base = match mark1.ligature_id == mark2.ligature_id {
+            true => mark1.ligature_id == 0 || mark1.ligature_component == mark2.ligature_component,
+            // one of them may be a ligature of marks itself
+            false => {
+                (mark1.ligature_id > 0 && mark1.ligature_component == 0)
+                    || (mark2.ligature_id > 0 && mark2.ligature_component == 0)
+            }
+        };
+        if !same_base {
+            return None;
+        }
+
+        let mark2_index = subtable.mark2_coverage.index(mark2.glyph)?;
+        let anchors = subtable.mark2_array.get(mark2_index as usize)?;
+        return self.attach_mark(
+            &subtable.mark1_array,
+            mark1_index,
+            anchors,
+            previous,
+            position,
+        );
+    }
+
+    // Returns the position to go on from when a subtable applied
+    fn apply_subtable(&mut self, subtable: &PositioningSubtable, position: usize) -> Option<usize> {
+        match subtable {
+            PositioningSubtable::Single(single) => return self.apply_single(single, position),
+            PositioningSubtable::Pair(pair) => return self.apply_pair(pair, position),
+            PositioningSubtable::Cursive(cursive) => return self.apply_cursive(cursive, position),
+            PositioningSubtable::MarkToBase(mark) => {
+                return self.apply_mark_to_base(mark, position)
+            }
+            PositioningSubtable::MarkToLigature(mark) => {
+                return self.apply_mark_to_ligature(mark, position);
+            }
+            PositioningSubtable::MarkToMark(mark) => {
+                return self.apply_mark_to_mark(mark, position)
+            }
+            PositioningSubtable::Context(context) => {
+                return matching::apply_context(self, context, position);
+            }
+            PositioningSubtable::ChainedContext(context) => {
+                return matching::apply_chained_context(self, context, position);
+            }
+        }
+    }
+
+    fn apply_subtables(
+        &mut self,
+        lookup: &Lookup<PositioningSubtable>,
+        position: usize,
+    ) -> Option<usize> {
+        return lookup
+            .subtables
+            .iter()
+            .find_map(|subtable| self.apply_subtable(subtable, position));
+    }
+}
+
+impl Apply for Positioner<'_> {
+    fn glyphs(&self) -> &[GlyphInfo] {
+        return &self.buffer.glyphs;
+    }
+
+    fn skipper(&self) -> Skipper<'_> {
+        return self.skipper;
+    }
+
+    fn mask(&self) -> u32 {
+        return self.mask;
+    }
+
+    fn apply_nested(&mut self, lookup_index: u16, position: usize) -> bool {
+        let gpos = self.gpos;
+        let lookup = match gpos.lookup_list.lookups.get(lookup_index as usize) {
+            Some(lookup) => lookup,
+            None => return false,
+        };
+        if self.nesting >= matching::MAX_NESTING {
+            return false;
+        }
+
+        let skipper = self.skipper;
+        self.skipper = skipper.nested(lookup, self.mark_glyph_sets);
+        self.nesting += 1;
+        let applied = self.apply_subtables(lookup, position).is_some();
+        self.nesting -= 1;
+        self.skipper = skipper;
+        return applied;
+    }
+}
+
+// Flips the links from i up its old chain so that the glyphs on it hang off i, which is
+// about to get a new parent
+fn reverse_cursive_chain(positions: &mut [GlyphPosition], i: usize, new_parent: usize) {
+    let (chain, attach_type) = (positions[i].attach_chain, positions[i].attach_type);
+    if chain == 0 || attach_type & ATTACH_CURSIVE == 0 {
+        return;
+    }
+
+    positions[i].attach_chain = 0;
+    let j = (i as isize + chain as isize) as usize;
+    if j == new_parent {
+        return;
+    }
+
+    reverse_cursive_chain(positions, j, new_parent);
+    positions[j].y_offset = -positions[i].y_offset;
+    positions[j].attach_chain = -chain;
+    positions[j].attach_type = attach_type;
+}
+
+fn propagate_attachment(positions: &mut [GlyphPosition], i: usize, forward: bool, nesting: u8) {
+    let (chain, attach_type) = (positions[i].attach_chain, positions[i].attach_type);
+    if chain == 0 {
+        return;
+    }
+
+    positions[i].attach_chain = 0;
+    let j = i as isize + chain as isize;
+    if j < 0 || j as usize >= positions.len() || nesting == 0 {
+        return;
+    }
+    let j = j as usize;
+    propagate_attachment(positions, j, forward, nesting - 1);
+
+    if attach_type & ATTACH_CURSIVE != 0 {
+        positions[i].y_offset += positions[j].y_offset;
+        return;
+    }
+
+    // a mark is drawn after the glyphs between it and its base, whose advances it takes back
+    let (x_offset, y_offset) = (positions[j].x_offset, positions[j].y_offset);
+    let between = match forward {
+        true => j..i,
+        false => j + 1..i + 1,
+    };
+    let (x_advance, y_advance) = positions[between].iter().fold((0, 0), |(x, y), position| {
+        return (x + position.x_advance, y + position.y_advance);
+    });
+    let sign = match forward {
+        true => -1,
+        false => 1,
+    };
+    positions[i].x_offset += x_offset + sign * x_advance;
+    positions[i].y_offset += y_offset + sign * y_advance;
+}
+
+// Attached glyphs have offsets from what they are attached to, this adds up the chains so
+// every offset is from the glyph's own pen position
+pub(crate) fn propagate_attachments(buffer: &mut Buffer) {
+    let forward = buffer.direction == Direction::LeftToRight;
+    for i in 0..buffer.positions.len() {
+        propagate_attachment(&mut buffer.positions, i, forward, matching::MAX_NESTING);
+    }
+}
+
+// Runs a lookup over the whole buffer the same way substitution does
+pub(crate) fn apply_lookup(
+    gpos: &Gpos,
+    mark_glyph_sets: &[Coverage],
+    buffer: &mut Buffer,
+    planned: PlannedLookup,
+) {
+    let lookup = match gpos.lookup_list.lookups.get(planned.index as usize) {
+        Some(lookup) => lookup,
+        None => return,
+    };
+
+    let mut positioner = Positioner {
+        gpos,
+        mark_glyph_sets,
+        buffer,
+        skipper: Skipper::new(lookup, mark_glyph_sets).past_zwnj(),
+        mask: planned.mask,
+        nesting: 0,
+    };
+
+    let mut position = 0;
+    while position < positioner.buffer.glyphs.len() {
+        let info = &positioner.buffer.glyphs[position];
+        if info.mask & positioner.mask != 0 && !positioner.skipper.skips(info) {
+            if let Some(next) = positioner.apply_subtables(lookup, position) {
+                position = next;
+                continue;
+            }
+        }
+        position += 1;
+    }
+}
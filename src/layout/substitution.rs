@@ -0,0 +1,311 @@
+use crate::layout::matching::{self, Apply, Skipper};
+use crate::layout::{classify, mark_glyph_sets, Buffer, GlyphInfo, PlannedLookup};
+use crate::tables::gdef::Gdef;
+use crate::tables::gsub::{self, Gsub, Ligature, SingleSubstitution, SubstitutionSubtable};
+use crate::tables::layout::{self, Lookup};
+use crate::GlyphId;
+
+// Multiple substitutions stop growing the buffer at this many times the glyphs it started
+// with, like HarfBuzz does, so lookups feeding each other can't blow it up
+const MAX_LEN_FACTOR: usize = 64;
+const MAX_LEN_MIN: usize = 16384;
+
+pub(crate) fn max_len(glyph_count: usize) -> usize {
+    return glyph_count.saturating_mul(MAX_LEN_FACTOR).max(MAX_LEN_MIN);
+}
+
+struct Substituter<'a> {
+    gsub: &'a Gsub,
+    gdef: Option<&'a Gdef>,
+    buffer: &'a mut Buffer,
+    skipper: Skipper<'a>, // of the lookup running now, nested ones swap theirs in
+    mask: u32,
+    value: u32,
+    nesting: u8,
+    max_len: usize,
+}
+
+impl<'a> Substituter<'a> {
+    fn apply_single(&mut self, subtable: &SingleSubstitution, position: usize) -> Option<usize> {
+        let info = &mut self.buffer.glyphs[position];
+        let glyph = match subtable {
+            SingleSubstitution::Format1 {
+                coverage,
+                delta_glyph_id,
+            } => {
+                coverage.index(info.glyph)?;
+                info.glyph.0.wrapping_add(*delta_glyph_id as u16)
+            }
+            SingleSubstitution::Format2 {
+                coverage,
+                substitute_glyph_ids,
+            } => *substitute_glyph_ids.get(coverage.index(info.glyph)? as usize)?,
+        };
+
+        info.glyph = GlyphId(glyph);
+        classify(self.gdef, info);
+        return Some(position + 1);
+    }
+
+    // The glyphs of the sequence take the place of the one at position and keep its cluster
+    fn apply_multiple(&mut self, sequence: &[u16], position: usize) -> Option<usize> {
+        if self.buffer.glyphs.len() + sequence.len() > self.max_len + 1 {
+            return None;
+        }
+
+        let (info, gdef) = (self.buffer.glyphs[position], self.gdef);
+        let infos = sequence.iter().map(|&glyph| {
+            let mut info = GlyphInfo {
+                glyph: GlyphId(glyph),
+                ..info
+            };
+            classify(gdef, &mut info);
+            return info;
+        });
+        self.buffer.glyphs.splice(position..position + 1, infos);
+        return Some(position + sequence.len());
+    }
+
+    fn apply_alternate(&mut self, alternates: &[u16], position: usize) -> Option<usize> {
+        let alternate = *alternates.get(self.value.checked_sub(1)? as usize)?;
+        let info = &mut self.buffer.glyphs[position];
+        info.glyph = GlyphId(alternate);
+        classify(self.gdef, info);
+        return Some(position + 1);
+    }
+
+    fn apply_ligature(&mut self, ligatures: &[Ligature], position: usize) -> Option<usize> {
+        let glyphs = &self.buffer.glyphs;
+        let (ligature, positions) = ligatures.iter().find_map(|ligature| {
+            let components = &ligature.component_glyph_ids;
+            let positions = matching::match_input(
+                glyphs,
+                position,
+                components.len(),
+                self.skipper,
+                self.mask,
+                |k, info| info.glyph.0 == components[k],
+            )?;
+            return Some((ligature, positions));
+        })?;
+
+        return Some(self.ligate(&positions, GlyphId(ligature.ligature_glyph)));
+    }
+
+    // The first component becomes the ligature and the others go. Marks skipped in between
+    // stay after it and remember which component they followed, so GPOS can put them on
+    // the right part of the ligature. A ligature made only of marks is a mark itself.
+    fn ligate(&mut self, positions: &[usize], ligature: GlyphId) -> usize {
+        let (first, last) = (positions[0], *positions.last().unwrap());
+        let glyphs = &self.buffer.glyphs;
+        let marks_only = positions
+            .iter()
+            .all(|&i| glyphs[i].glyph_class == layout::MARK_GLYPH);
+        let cluster = glyphs[first..=last]
+            .iter()
+            .map(|info| info.cluster)
+            .min()
+            .unwrap();
+        let ligature_id = match marks_only {
+            true => 0,
+            false => self.buffer.allocate_ligature_id(),
+        };
+
+        let mut component = 0;
+        for (i, info) in self.buffer.glyphs[first..=last].iter_mut().enumerate() {
+            info.cluster = cluster;
+            if positions.contains(&(first + i)) {
+                component += 1;
+            } else if !marks_only && info.glyph_class == layout::MARK_GLYPH {
+                info.ligature_id = ligature_id;
+                info.ligature_component = component;
+            }
+        }
+
+        let info = &mut self.buffer.glyphs[first];
+        info.glyph = ligature;
+        info.ligature_id = ligature_id;
+        info.ligature_component = 0;
+        if !marks_only {
+            info.glyph_class = layout::LIGATURE_GLYPH;
+        }
+        classify(self.gdef, info);
+
+        for &i in positions[1..].iter().rev() {
+            self.buffer.glyphs.remove(i);
+        }
+        return last + 2 - positions.len();
+    }
+
+    fn apply_reverse_chain_single(
+        &mut self,
+        subtable: &gsub::ReverseChainSingleSubstitution,
+        position: usize,
+    ) -> bool {
+        let glyphs = &self.buffer.glyphs;
+        let index = match subtable.coverage.index(glyphs[position].glyph) {
+            Some(index) => index as usize,
+            None => return false,
+        };
+
+        let backtrack = &subtable.backtrack_coverages;
+        let lookahead = &subtable.lookahead_coverages;
+        if index >= subtable.substitute_glyph_ids.len()
+            || !matching::match_backtrack(
+                glyphs,
+                position,
+                backtrack.len(),
+                self.skipper,
+                |k, info| backtrack[k].contains(info.glyph),
+            )
+            || !matching::match_lookahead(
+                glyphs,
+                position,
+                lookahead.len(),
+                self.skipper,
+                |k, info| lookahead[k].contains(info.glyph),
+            )
+        {
+            return false;
+        }
+
+        let info = &mut self.buffer.glyphs[position];
+        info.glyph = GlyphId(subtable.substitute_glyph_ids[index]);
+        classify(self.gdef, info);
+        return true;
+    }
+
+    // Returns the position to go on from when a subtable applied
+    fn apply_subtable(
+        &mut self,
+        subtable: &SubstitutionSubtable,
+        position: usize,
+    ) -> Option<usize> {
+        let glyph = self.buffer.glyphs[position].glyph;
+        match subtable {
+            SubstitutionSubtable::Single(single) => return self.apply_single(single, position),
+            SubstitutionSubtable::Multiple(multiple) => {
+                let sequence = multiple
+                    .sequences
+                    .get(multiple.coverage.index(glyph)? as usize)?;
+                return self.apply_multiple(sequence, position);
+            }
+            SubstitutionSubtable::Alternate(alternate) => {
+                let index = alternate.coverage.index(glyph)? as usize;
+                return self.apply_alternate(alternate.alternate_sets.get(index)?, position);
+            }
+            SubstitutionSubtable::Ligature(ligature) => {
+                let index = ligature.coverage.index(glyph)? as usize;
+                return self.apply_ligature(ligature.ligature_sets.get(index)?, position);
+            }
+            SubstitutionSubtable::Context(context) => {
+                return matching::apply_context(self, context, position);
+            }
+            SubstitutionSubtable::ChainedContext(context) => {
+                return matching::apply_chained_context(self, context, position);
+            }
+            // only ever applied on its own, from the end of the buffer
+            SubstitutionSubtable::ReverseChainSingle(_) => return None,
+        }
+    }
+
+    fn apply_subtables(
+        &mut self,
+        lookup: &Lookup<SubstitutionSubtable>,
+        position: usize,
+    ) -> Option<usize> {
+        return lookup
+            .subtables
+            .iter()
+            .find_map(|subtable| self.apply_subtable(subtable, position));
+    }
+}
+
+impl Apply for Substituter<'_> {
+    fn glyphs(&self) -> &[GlyphInfo] {
+        return &self.buffer.glyphs;
+    }
+
+    fn skipper(&self) -> Skipper<'_> {
+        return self.skipper;
+    }
+
+    fn mask(&self) -> u32 {
+        return self.mask;
+    }
+
+    fn apply_nested(&mut self, lookup_index: u16, position: usize) -> bool {
+        let gsub = self.gsub;
+        let lookup = match gsub.lookup_list.lookups.get(lookup_index as usize) {
+            Some(lookup) => lookup,
+            None => return false,
+        };
+        if self.nesting >= matching::MAX_NESTING {
+            return false;
+        }
+
+        let skipper = self.skipper;
+        self.skipper = skipper.nested(lookup, mark_glyph_sets(self.gdef));
+        self.nesting += 1;
+        let applied = self.apply_subtables(lookup, position).is_some();
+        self.nesting -= 1;
+        self.skipper = skipper;
+        return applied;
+    }
+}
+
+// Runs a lookup over the whole buffer. Every glyph it doesn't skip gets the first subtable
+// that applies, and the next glyph is the one after what that subtable consumed.
+pub(crate) fn apply_lookup(
+    gsub: &Gsub,
+    gdef: Option<&Gdef>,
+    buffer: &mut Buffer,
+    planned: PlannedLookup,
+    max_len: usize,
+) {
+    let lookup = match gsub.lookup_list.lookups.get(planned.index as usize) {
+        Some(lookup) => lookup,
+        None => return,
+    };
+
+    let mut substituter = Substituter {
+        gsub,
+        gdef,
+        buffer,
+        skipper: Skipper::new(lookup, mark_glyph_sets(gdef)).stop_at_zwj(planned.manual_zwj),
+        mask: planned.mask,
+        value: planned.value,
+        nesting: 0,
+        max_len,
+    };
+    let applies = |substituter: &Substituter, position: usize| {
+        let info = &substituter.buffer.glyphs[position];
+        return info.mask & substituter.mask != 0 && !substituter.skipper.skips(info);
+    };
+
+    if lookup.lookup_type == gsub::REVERSE_CHAINED_SINGLE {
+        for position in (0..substituter.buffer.glyphs.len()).rev() {
+            if applies(&substituter, position) {
+                for subtable in lookup.subtables.iter() {
+                    if let SubstitutionSubtable::ReverseChainSingle(subtable) = subtable {
+                        if substituter.apply_reverse_chain_single(subtable, position) {
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+        return;
+    }
+
+    let mut position = 0;
+    while position < substituter.buffer.glyphs.len() {
+        if applies(&substituter, position) {
+            if let Some(next) = substituter.apply_subtables(lookup, position) {
+                position = next;
+                continue;
+            }
+        }
+        position += 1;
+    }
+}
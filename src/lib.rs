@@ -0,0 +1,23 @@
+// The tables are read with explicit returns all over, keep it that way
+#![allow(clippy::needless_return)]
+
+pub mod error;
+pub mod hinting;
+pub mod layout;
+pub mod outline;
+pub mod raster;
+pub mod reader;
+pub mod shaping;
+pub mod tables;
+pub mod truetype;
+
+pub use error::{Error, Result};
+pub use outline::{OutlineBuilder, Rect};
+pub use raster::{Bitmap, Rasterizer};
+pub use reader::FontReader;
+pub use shaping::{shape, ShapedGlyph, ShapingOptions};
+pub use truetype::TrueTypeFont;
+
+// Index of a glyph inside the font, glyph 0 is always the .notdef glyph
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct GlyphId(pub u16);
@@ -1,11 +1,14 @@
-mod reader;
-mod tables;
-pub mod truetype;
-
 fn main() {
-    let tt = match truetype::TrueTypeFont::new("FontAwesome.ttf".into()) {
-        Some(val) => val,
-        None => panic!("There some problem with the file."),
+    let filename = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "FontAwesome.ttf".into());
+
+    let tt = match lipi::TrueTypeFont::new(filename) {
+        Ok(val) => val,
+        Err(err) => {
+            eprintln!("There some problem with the file: {}", err);
+            std::process::exit(1);
+        }
     };
 
     println!("{:#?}", tt);
@@ -0,0 +1,125 @@
+use crate::tables::glyf::SimpleGlyph;
+
+// Receives the contours of a glyph in font units. TrueType outlines only ever need
+// straight lines and quadratic Béziers, and every contour is closed.
+pub trait OutlineBuilder {
+    fn move_to(&mut self, x: f32, y: f32);
+    fn line_to(&mut self, x: f32, y: f32);
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32);
+    fn close(&mut self);
+}
+
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct Rect {
+    pub x_min: i16,
+    pub y_min: i16,
+    pub x_max: i16,
+    pub y_max: i16,
+}
+
+impl Rect {
+    pub fn width(&self) -> i32 {
+        return self.x_max as i32 - self.x_min as i32;
+    }
+
+    pub fn height(&self) -> i32 {
+        return self.y_max as i32 - self.y_min as i32;
+    }
+}
+
+const ON_CURVE: u8 = 1;
+
+#[derive(Debug, Copy, Clone)]
+struct Point {
+    x: f32,
+    y: f32,
+    on_curve: bool,
+}
+
+fn midpoint(a: Point, b: Point) -> Point {
+    return Point {
+        x: (a.x + b.x) / 2.,
+        y: (a.y + b.y) / 2.,
+        on_curve: true,
+    };
+}
+
+// Two consecutive off-curve points imply an on-curve one halfway between them, and a
+// contour may well start off-curve, in which case it starts at its last point instead or
+// at the implied one if that is off-curve too
+fn contour(points: &[Point], builder: &mut dyn OutlineBuilder) {
+    let (first, last) = match (points.first(), points.last()) {
+        (Some(&first), Some(&last)) => (first, last),
+        _ => return,
+    };
+
+    let (start, rest) = if first.on_curve {
+        (first, &points[1..])
+    } else if last.on_curve {
+        (last, &points[..points.len() - 1])
+    } else {
+        (midpoint(last, first), points)
+    };
+
+    builder.move_to(start.x, start.y);
+
+    let mut control: Option<Point> = None;
+    for &point in rest {
+        match (control, point.on_curve) {
+            (Some(ctrl), true) => {
+                builder.quad_to(ctrl.x, ctrl.y, point.x, point.y);
+                control = None;
+            }
+            (Some(ctrl), false) => {
+                let mid = midpoint(ctrl, point);
+                builder.quad_to(ctrl.x, ctrl.y, mid.x, mid.y);
+                control = Some(point);
+            }
+            (None, true) => builder.line_to(point.x, point.y),
+            (None, false) => control = Some(point),
+        }
+    }
+
+    match control {
+        Some(ctrl) => builder.quad_to(ctrl.x, ctrl.y, start.x, start.y),
+        None => builder.line_to(start.x, start.y),
+    }
+    builder.close();
+}
+
+// Walks the contours of a simple (or flattened) glyph, the points are expected in font units
+pub fn outline(glyph_data: &SimpleGlyph, builder: &mut dyn OutlineBuilder) {
+    let points: Vec<(f32, f32, bool)> = glyph_data
+        .x_coordinates
+        .iter()
+        .zip(glyph_data.y_coordinates.iter())
+        .zip(glyph_data.flags.iter())
+        .map(|((&x, &y), &flag)| (x as f32, y as f32, (flag & ON_CURVE) != 0))
+        .collect();
+
+    outline_points(&glyph_data.end_pts_of_contours, &points, builder);
+}
+
+// Same walk over points in whatever unit the caller likes, as (x, y, on curve)
+pub fn outline_points(
+    end_pts_of_contours: &[u16],
+    points: &[(f32, f32, bool)],
+    builder: &mut dyn OutlineBuilder,
+) {
+    let mut start = 0;
+
+    for &end in end_pts_of_contours.iter() {
+        let end = (end as usize + 1).min(points.len());
+        if start >= end {
+            break;
+        }
+
+        let contour_points: Vec<Point> = points[start..end]
+            .iter()
+            .map(|&(x, y, on_curve)| Point { x, y, on_curve })
+            .collect();
+
+        contour(&contour_points, builder);
+        start = end;
+    }
+}
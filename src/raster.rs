@@ -0,0 +1,220 @@
+// The accumulation rasterizer below is adapted from font-rs
+// (https://github.com/raphlinus/font-rs), under this notice:
+//
+// Copyright 2015 Google Inc. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// Changed for lipi: the outline comes through OutlineBuilder, with a scale and origin
+// mapping font units to pixels.
+
+use crate::outline::OutlineBuilder;
+
+// Coverage of a rasterized glyph, one byte of alpha per pixel with the top row first.
+// left and top place the bitmap relative to the pen position on the baseline, y going up.
+#[derive(Debug, Clone, Default)]
+pub struct Bitmap {
+    pub width: u32,
+    pub height: u32,
+    pub left: i32,
+    pub top: i32,
+    pub coverage: Vec<u8>,
+}
+
+#[derive(Debug, Copy, Clone)]
+struct Point {
+    x: f32,
+    y: f32,
+}
+
+// Signed area rasterizer, every line adds the area it covers (signed by its direction)
+// to the cell it crosses and the remainder to the next one, so a running sum across the
+// buffer gives the winding of each pixel with anti-aliasing for free. Clamping the
+// absolute winding to 1 fills with the non-zero rule.
+//
+// Font units are mapped to pixels as (origin.x + x * scale, origin.y - y * scale), so
+// the origin is where the glyph's (0, 0) lands inside the bitmap, rows going down.
+pub struct Rasterizer {
+    width: usize,
+    height: usize,
+    scale: f32,
+    origin: (f32, f32),
+    accumulation: Vec<f32>,
+    start: Point,
+    current: Point,
+}
+
+impl Rasterizer {
+    pub fn new(width: u32, height: u32, scale: f32, origin: (f32, f32)) -> Rasterizer {
+        let (width, height) = (width as usize, height as usize);
+
+        return Rasterizer {
+            width,
+            height,
+            scale,
+            origin,
+            // a line touching the right edge spills one cell past the last pixel
+            accumulation: vec![0.; width * height + 2],
+            start: Point { x: 0., y: 0. },
+            current: Point { x: 0., y: 0. },
+        };
+    }
+
+    fn map(&self, x: f32, y: f32) -> Point {
+        return Point {
+            x: self.origin.0 + x * self.scale,
+            y: self.origin.1 - y * self.scale,
+        };
+    }
+
+    fn draw_line(&mut self, p0: Point, p1: Point) {
+        if (p0.y - p1.y).abs() <= f32::EPSILON {
+            return;
+        }
+
+        let width = self.width as f32;
+        let (direction, p0, p1) = match p0.y < p1.y {
+            true => (1., p0, p1),
+            false => (-1., p1, p0),
+        };
+        let dxdy = (p1.x - p0.x) / (p1.y - p0.y);
+
+        let mut x = p0.x;
+        if p0.y < 0. {
+            x -= p0.y * dxdy;
+        }
+
+        let first_row = p0.y.max(0.) as usize;
+        let last_row = self.height.min(p1.y.ceil().max(0.) as usize);
+
+        for row in first_row..last_row {
+            let line_start = row * self.width;
+            let dy = ((row + 1) as f32).min(p1.y) - (row as f32).max(p0.y);
+            let x_next = x + dxdy * dy;
+            let area = dy * direction;
+
+            // anything left or right of the bitmap still has to count, just at its edge
+            let (x0, x1) = match x < x_next {
+                true => (x.clamp(0., width), x_next.clamp(0., width)),
+                false => (x_next.clamp(0., width), x.clamp(0., width)),
+            };
+            let x0_floor = x0.floor();
+            let x0i = x0_floor as usize;
+            let x1_ceil = x1.ceil();
+            let x1i = x1_ceil as usize;
+
+            if x1i <= x0i + 1 {
+                // the line stays inside a single pixel on this row
+                let x_mid = 0.5 * (x0 + x1) - x0_floor;
+                self.accumulation[line_start + x0i] += area - area * x_mid;
+                self.accumulation[line_start + x0i + 1] += area * x_mid;
+            } else {
+                let s = (x1 - x0).recip();
+                let x0_fraction = x0 - x0_floor;
+                let a0 = 0.5 * s * (1. - x0_fraction) * (1. - x0_fraction);
+                let x1_fraction = x1 - x1_ceil + 1.;
+                let am = 0.5 * s * x1_fraction * x1_fraction;
+
+                self.accumulation[line_start + x0i] += area * a0;
+                if x1i == x0i + 2 {
+                    self.accumulation[line_start + x0i + 1] += area * (1. - a0 - am);
+                } else {
+                    let a1 = s * (1.5 - x0_fraction);
+                    self.accumulation[line_start + x0i + 1] += area * (a1 - a0);
+                    for xi in x0i + 2..x1i - 1 {
+                        self.accumulation[line_start + xi] += area * s;
+                    }
+                    let a2 = a1 + (x1i - x0i - 3) as f32 * s;
+                    self.accumulation[line_start + x1i - 1] += area * (1. - a2 - am);
+                }
+                self.accumulation[line_start + x1i] += area * am;
+            }
+
+            x = x_next;
+        }
+    }
+
+    // Splits the curve into as many lines as it takes to stay within a fraction of a pixel
+    fn draw_quad(&mut self, p0: Point, p1: Point, p2: Point) {
+        let dx = p0.x - 2. * p1.x + p2.x;
+        let dy = p0.y - 2. * p1.y + p2.y;
+        let deviation = dx * dx + dy * dy;
+
+        if deviation < 0.333 {
+            self.draw_line(p0, p2);
+            return;
+        }
+
+        let tolerance = 3.;
+        let segments = 1 + (tolerance * deviation).sqrt().sqrt().floor() as usize;
+        let step = (segments as f32).recip();
+
+        let mut previous = p0;
+        for i in 1..segments {
+            let t = i as f32 * step;
+            let mt = 1. - t;
+            let next = Point {
+                x: mt * mt * p0.x + 2. * mt * t * p1.x + t * t * p2.x,
+                y: mt * mt * p0.y + 2. * mt * t * p1.y + t * t * p2.y,
+            };
+            self.draw_line(previous, next);
+            previous = next;
+        }
+        self.draw_line(previous, p2);
+    }
+
+    pub fn width(&self) -> u32 {
+        return self.width as u32;
+    }
+
+    pub fn height(&self) -> u32 {
+        return self.height as u32;
+    }
+
+    pub fn coverage(&self) -> Vec<u8> {
+        let mut winding = 0.;
+
+        return self.accumulation[..self.width * self.height]
+            .iter()
+            .map(|area| {
+                winding += area;
+                return (f32::abs(winding).min(1.) * 255.).round() as u8;
+            })
+            .collect();
+    }
+}
+
+impl OutlineBuilder for Rasterizer {
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.start = self.map(x, y);
+        self.current = self.start;
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        let point = self.map(x, y);
+        self.draw_line(self.current, point);
+        self.current = point;
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        let control = self.map(x1, y1);
+        let point = self.map(x, y);
+        self.draw_quad(self.current, control, point);
+        self.current = point;
+    }
+
+    fn close(&mut self) {
+        self.draw_line(self.current, self.start);
+        self.current = self.start;
+    }
+}
@@ -1,78 +1,139 @@
+use crate::error::{Error, Result};
+use std::ops::Deref;
+
+// Where the bytes of a font come from, the tables only ever see the borrowed slice
 #[derive(Debug)]
-pub struct FontReader {
+pub enum FontData<'a> {
+    Borrowed(&'a [u8]),
+    Owned(Vec<u8>),
+    #[cfg(feature = "mmap")]
+    Mapped(memmap2::Mmap),
+}
+
+impl<'a> Deref for FontData<'a> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            FontData::Borrowed(data) => return data,
+            FontData::Owned(data) => return data,
+            #[cfg(feature = "mmap")]
+            FontData::Mapped(data) => return data,
+        }
+    }
+}
+
+// Borrows the font data instead of owning it, so reading never copies the file
+#[derive(Debug, Clone)]
+pub struct FontReader<'a> {
     pub pos: usize,
-    pub data: Vec<u8>,
+    pub data: &'a [u8],
+    pub table: &'static str, // tag of the table being read, used for error reporting
 }
 
-impl FontReader {
-    pub fn new(filename: String) -> Self {
+impl<'a> FontReader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
         return FontReader {
             pos: 0,
-            data: match std::fs::read(filename) {
-                Ok(val) => val,
-                Err(_) => panic!("Can't read the file!!"),
-            },
+            data,
+            table: "sfnt",
         };
     }
 
-    pub fn seek(&mut self, pos: usize) -> usize {
-        if pos >= self.data.len() {
-            panic!("Reached the end of file");
+    fn truncated(&self) -> Error {
+        return Error::Truncated {
+            table: self.table.into(),
+            offset: self.pos,
+        };
+    }
+
+    pub fn seek(&mut self, pos: usize) -> Result<usize> {
+        if pos > self.data.len() {
+            return Err(Error::Truncated {
+                table: self.table.into(),
+                offset: pos,
+            });
         }
 
         let oldpos = self.pos;
         self.pos = pos;
-        return oldpos;
+        return Ok(oldpos);
+    }
+
+    // Seeks to the start of a table and reports any further failures against it
+    pub fn seek_table(&mut self, table: &'static str, pos: usize) -> Result<usize> {
+        self.table = table;
+        return self.seek(pos);
     }
 
-    pub fn get_uint8(&mut self) -> Option<u8> {
-        let byte = self.data.get(self.pos)?.clone();
+    pub fn get_uint8(&mut self) -> Result<u8> {
+        let byte = *self.data.get(self.pos).ok_or_else(|| self.truncated())?;
         self.pos += 1;
-        return Some(byte);
+        return Ok(byte);
     }
 
-    pub fn get_uint16(&mut self) -> Option<u16> {
+    pub fn get_uint16(&mut self) -> Result<u16> {
         let byte1 = self.get_uint8()?;
         let byte2 = self.get_uint8()?;
-        return Some((byte1 as u16) << 8 | byte2 as u16);
+        return Ok((byte1 as u16) << 8 | byte2 as u16);
     }
 
-    pub fn get_uint32(&mut self) -> Option<u32> {
+    pub fn get_uint24(&mut self) -> Result<u32> {
+        let byte1 = self.get_uint16()?;
+        let byte2 = self.get_uint8()?;
+        return Ok((byte1 as u32) << 8 | byte2 as u32);
+    }
+
+    pub fn get_uint32(&mut self) -> Result<u32> {
         let byte1 = self.get_uint16()?;
         let byte2 = self.get_uint16()?;
-        return Some((byte1 as u32) << 16 | byte2 as u32);
+        return Ok((byte1 as u32) << 16 | byte2 as u32);
+    }
+
+    pub fn get_int16(&mut self) -> Result<i16> {
+        Ok(self.get_uint16()? as i16)
     }
 
-    pub fn get_int16(&mut self) -> Option<i16> {
-        Some(self.get_uint16()? as i16)
+    pub fn get_int32(&mut self) -> Result<i32> {
+        Ok(self.get_uint32()? as i32)
     }
 
-    pub fn get_int32(&mut self) -> Option<i32> {
-        Some(self.get_uint32()? as i32)
+    pub fn get_float32(&mut self) -> Result<f32> {
+        Ok(self.get_int32()? as f32 / (1 << 16) as f32)
     }
 
-    pub fn get_float32(&mut self) -> Option<f32> {
-        Some(self.get_int32()? as f32 / (1 << 16) as f32)
+    // Hands out a slice of the underlying data without copying it
+    pub fn get_bytes(&mut self, length: usize) -> Result<&'a [u8]> {
+        let end = self
+            .pos
+            .checked_add(length)
+            .ok_or_else(|| self.truncated())?;
+        let bytes = self
+            .data
+            .get(self.pos..end)
+            .ok_or_else(|| self.truncated())?;
+        self.pos = end;
+        return Ok(bytes);
     }
 
-    pub fn get_string(&mut self, length: usize) -> Option<String> {
+    pub fn get_string(&mut self, length: usize) -> Result<String> {
         let mut result = String::new();
 
         for _ in 0..length {
             result.push(self.get_uint8()? as char);
         }
 
-        return Some(result);
+        return Ok(result);
     }
 
-    pub fn get_date(&mut self) -> Option<u64> {
+    pub fn get_date(&mut self) -> Result<u64> {
         let unix_hfs_epoch_diff = 208284480;
-        let time =
-            ((self.get_uint32()? as u64) << 32 | self.get_uint32()? as u64) - unix_hfs_epoch_diff;
-        return Some(time);
+        let time = ((self.get_uint32()? as u64) << 32 | self.get_uint32()? as u64)
+            .wrapping_sub(unix_hfs_epoch_diff);
+        return Ok(time);
     }
 
-    pub fn get_f2dot14(&mut self) -> Option<f32> {
-        return Some(self.get_int16()? as f32 / (1 << 14) as f32);
+    pub fn get_f2dot14(&mut self) -> Result<f32> {
+        return Ok(self.get_int16()? as f32 / (1 << 14) as f32);
     }
 }
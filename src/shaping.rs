@@ -0,0 +1,395 @@
+use crate::layout::{self, Buffer, Direction, FeatureSetting, GlyphInfo, GlyphPosition};
+use crate::tables::layout::{BASE_GLYPH, MARK_GLYPH};
+use crate::truetype::TrueTypeFont;
+use crate::GlyphId;
+mod arabic;
+mod unicode;
+
+// Every script gets these after the features of its shaper, the same ones HarfBuzz applies
+// to horizontal text
+const COMMON_FEATURES: &[&str] = &["abvm", "blwm", "ccmp", "locl", "mark", "mkmk", "rlig"];
+const HORIZONTAL_FEATURES: &[&str] = &["calt", "clig", "curs", "dist", "kern", "liga", "rclt"];
+
+// What isn't given is worked out from the text
+#[derive(Debug, Copy, Clone, Default)]
+pub struct ShapingOptions<'a> {
+    pub script: Option<&'a str>, // OpenType script tag like "latn", else the first in the text
+    pub language: Option<&'a str>, // OpenType language system tag like "TRK "
+    pub direction: Option<Direction>, // else right to left for Arabic and Hebrew
+    pub features: &'a [FeatureSetting<'a>], // on top of the defaults, value 0 turns one off
+}
+
+// In font units, offsets move the glyph without moving the ones after it
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ShapedGlyph {
+    pub glyph: GlyphId,
+    pub cluster: u32, // byte offset in the text of the first character the glyph shows
+    pub x_advance: i32,
+    pub y_advance: i32,
+    pub x_offset: i32,
+    pub y_offset: i32,
+}
+
+// A character of the text with the cluster it belongs to, the byte offset of its base
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) struct Character {
+    pub c: char,
+    pub cluster: u32,
+}
+
+// Collects the features of a run. GSUB applies them in stages, one after the other so a
+// stage sees what the ones before did, GPOS applies them all at once. A feature stays in
+// the stage it was first added to, and ZWJ stays manual for it once a shaper asked.
+pub(crate) struct Planner<'a> {
+    stages: Vec<Vec<FeatureSetting<'a>>>,
+    next_mask: u32,
+}
+
+impl<'a> Planner<'a> {
+    fn new() -> Self {
+        return Planner {
+            stages: vec![Vec::new()],
+            next_mask: layout::GLOBAL_MASK << 1,
+        };
+    }
+
+    // On for the whole text
+    pub fn add(&mut self, tag: &'a str) {
+        self.add_setting(FeatureSetting::new(tag));
+    }
+
+    // On for the whole text with ZWJ stopping its substitutions, like the ligatures of scripts
+    // where ZWJ asks for the joined forms without the ligature
+    pub fn add_manual_zwj(&mut self, tag: &'a str) {
+        self.add_setting(FeatureSetting {
+            manual_zwj: true,
+            ..FeatureSetting::new(tag)
+        });
+    }
+
+    // On for the glyphs the shaper gives the mask of the feature
+    pub fn add_masked(&mut self, tag: &'a str) {
+        let mask = self.next_mask;
+        self.next_mask <<= 1;
+        self.add_setting(FeatureSetting {
+            mask,
+            ..FeatureSetting::new(tag)
+        });
+    }
+
+    // 0 for features that aren't planned
+    pub fn mask(&self, tag: &str) -> u32 {
+        return self.setting(tag).map_or(0, |setting| setting.mask);
+    }
+
+    // The features added from now on see what the ones before did
+    pub fn pause(&mut self) {
+        if !self.stages.last().unwrap().is_empty() {
+            self.stages.push(Vec::new());
+        }
+    }
+
+    fn setting(&self, tag: &str) -> Option<&FeatureSetting<'a>> {
+        return self
+            .stages
+            .iter()
+            .flatten()
+            .find(|setting| setting.tag == tag);
+    }
+
+    fn add_setting(&mut self, setting: FeatureSetting<'a>) {
+        let planned = self
+            .stages
+            .iter_mut()
+            .flatten()
+            .find(|planned| planned.tag == setting.tag);
+        match planned {
+            Some(planned) => {
+                *planned = FeatureSetting {
+                    manual_zwj: planned.manual_zwj || setting.manual_zwj,
+                    ..setting
+                }
+            }
+            None => self.stages.last_mut().unwrap().push(setting),
+        }
+    }
+}
+
+// What a script with shaping rules of its own adds to the common steps, each step does
+// nothing unless the shaper says otherwise
+pub(crate) trait ComplexShaper {
+    // Before the common features, which is where the forms of the script go
+    fn collect_features(&self, _planner: &mut Planner) {}
+
+    // After the marks of every base are in canonical order
+    fn reorder_marks(&self, _characters: &mut [Character]) {}
+
+    // Glyphs still go one to one with the characters here
+    fn setup_masks(
+        &self,
+        _planner: &Planner,
+        _characters: &[Character],
+        _glyphs: &mut [GlyphInfo],
+    ) {
+    }
+}
+
+// Simple scripts like Latin, Greek and Cyrillic
+struct DefaultShaper;
+
+impl ComplexShaper for DefaultShaper {}
+
+// Scripts that need more than the default shaper get theirs here
+fn shaper_for(script: &str) -> &'static dyn ComplexShaper {
+    return match script {
+        "arab" => &arabic::ArabicShaper,
+        _ => &DefaultShaper,
+    };
+}
+
+// Marks and the joiner go with the character before them
+fn form_clusters(characters: &mut [Character]) {
+    for i in 1..characters.len() {
+        let c = characters[i].c;
+        if unicode::is_mark(c) || c == unicode::ZWJ {
+            characters[i].cluster = characters[i - 1].cluster;
+        }
+    }
+}
+
+// Every run of characters with a combining class, the marks that follow a base
+pub(crate) fn for_each_mark_run(characters: &mut [Character], mut f: impl FnMut(&mut [Character])) {
+    let mut start = 0;
+    while start < characters.len() {
+        let end = start
+            + characters[start..]
+                .iter()
+                .take_while(|character| unicode::combining_class(character.c) != 0)
+                .count();
+        if end == start {
+            start += 1;
+            continue;
+        }
+
+        f(&mut characters[start..end]);
+        start = end;
+    }
+}
+
+// Puts every run of marks in canonical order, by combining class, the way normalization
+// would. Marks of the same class keep their order as it tells them apart.
+fn sort_marks(characters: &mut [Character]) {
+    for_each_mark_run(characters, |marks| {
+        let class = |mark: &Character| unicode::combining_class(mark.c);
+        if marks
+            .windows(2)
+            .any(|pair| class(&pair[0]) > class(&pair[1]))
+        {
+            marks.sort_by_key(class);
+            let cluster = marks.iter().map(|mark| mark.cluster).min().unwrap();
+            for mark in marks.iter_mut() {
+                mark.cluster = cluster;
+            }
+        }
+    });
+}
+
+// One glyph for each character, a base followed by a variation selector the font knows
+// takes the variant. Fonts without GDEF classes get marks going by the characters.
+fn map_glyphs(font: &TrueTypeFont, characters: &[Character]) -> Buffer {
+    let glyphs: Vec<GlyphId> = characters
+        .iter()
+        .enumerate()
+        .map(|(i, character)| {
+            let selector = characters
+                .get(i + 1)
+                .map(|next| next.c)
+                .filter(|&c| unicode::is_variation_selector(c));
+            return selector
+                .and_then(|selector| font.glyph_index_for_variation(character.c, selector))
+                .or_else(|| font.glyph_index(character.c))
+                .unwrap_or_default();
+        })
+        .collect();
+
+    let mut buffer = Buffer::new(&glyphs);
+    for (info, character) in buffer.glyphs.iter_mut().zip(characters) {
+        info.cluster = character.cluster;
+        info.glyph_class = match unicode::is_mark(character.c) {
+            true => MARK_GLYPH,
+            false => BASE_GLYPH,
+        };
+        info.default_ignorable = unicode::is_default_ignorable(character.c);
+        info.zwnj = character.c == unicode::ZWNJ;
+        info.zwj = character.c == unicode::ZWJ;
+        layout::classify(font.gdef(), info);
+    }
+    return buffer;
+}
+
+// The kern table knows nothing of marks or invisible characters, the pairs are the glyphs
+// around them. Each pair splits its kerning between its two glyphs, the second moving back
+// with its half.
+fn apply_kern(font: &TrueTypeFont, buffer: &mut Buffer, mask: u32) {
+    let glyphs = &buffer.glyphs;
+    let kerned = |info: &GlyphInfo| info.glyph_class != MARK_GLYPH && !info.default_ignorable;
+    for i in 0..glyphs.len() {
+        if !kerned(&glyphs[i]) || glyphs[i].mask & mask == 0 {
+            continue;
+        }
+
+        let next = (i + 1..glyphs.len()).find(|&j| kerned(&glyphs[j]));
+        if let Some(j) = next.filter(|&j| glyphs[j].mask & mask != 0) {
+            let kerning = font.kerning(glyphs[i].glyph, glyphs[j].glyph) as i32;
+            let first = kerning >> 1;
+            buffer.positions[i].x_advance += first;
+            buffer.positions[j].x_advance += kerning - first;
+            buffer.positions[j].x_offset += kerning - first;
+        }
+    }
+}
+
+// Marks lose their advance once GPOS had its say. When GPOS doesn't kern the script the kern
+// table does, and without GPOS marks drawn left to right move back over their base with the
+// advance they lose.
+fn position(
+    font: &TrueTypeFont,
+    planner: &Planner,
+    buffer: &mut Buffer,
+    script: &str,
+    language: Option<&str>,
+) {
+    layout::set_advances(font, buffer);
+
+    let gpos = font.gpos();
+    if let Some(gpos) = gpos {
+        let features = planner.stages.concat();
+        layout::apply_gpos_lookups(gpos, font.gdef(), buffer, script, language, &features);
+    }
+    if !gpos.is_some_and(|gpos| layout::has_feature(gpos, script, language, "kern")) {
+        if let Some(kern) = planner.setting("kern").filter(|kern| kern.value != 0) {
+            apply_kern(font, buffer, kern.mask);
+        }
+    }
+
+    let adjust_offsets = gpos.is_none() && buffer.direction == Direction::LeftToRight;
+
+    for (info, position) in buffer.glyphs.iter().zip(buffer.positions.iter_mut()) {
+        if info.glyph_class == MARK_GLYPH {
+            if adjust_offsets {
+                position.x_offset -= position.x_advance;
+            }
+            position.x_advance = 0;
+            position.y_advance = 0;
+        }
+    }
+
+    layout::propagate_attachments(buffer);
+}
+
+// Invisible characters become empty space glyphs, or go when the font has no space
+fn hide_default_ignorables(font: &TrueTypeFont, buffer: &mut Buffer) {
+    if let Some(space) = font.glyph_index(' ') {
+        for (info, position) in buffer.glyphs.iter_mut().zip(buffer.positions.iter_mut()) {
+            if info.default_ignorable {
+                info.glyph = space;
+                *position = GlyphPosition::default();
+            }
+        }
+        return;
+    }
+
+    let (glyphs, positions) = buffer
+        .glyphs
+        .iter()
+        .zip(buffer.positions.iter())
+        .filter(|(info, _)| !info.default_ignorable)
+        .unzip();
+    buffer.glyphs = glyphs;
+    buffer.positions = positions;
+}
+
+// Maps the text to glyphs and runs the features of its script over them. The glyphs come
+// back in visual order, left to right, for right to left text too. Text isn't normalized,
+// a precomposed character stays one glyph and a decomposed one a base with its marks.
+pub fn shape(font: &TrueTypeFont, text: &str, options: &ShapingOptions) -> Vec<ShapedGlyph> {
+    let mut characters: Vec<Character> = text
+        .char_indices()
+        .map(|(offset, c)| Character {
+            c,
+            cluster: offset as u32,
+        })
+        .collect();
+
+    let script = match options.script {
+        Some(script) => script,
+        None => characters
+            .iter()
+            .find_map(|character| unicode::script(character.c))
+            .unwrap_or("DFLT"),
+    };
+    let direction = options.direction.unwrap_or(match script {
+        "arab" | "hebr" => Direction::RightToLeft,
+        _ => Direction::LeftToRight,
+    });
+    let shaper = shaper_for(script);
+
+    let mut planner = Planner::new();
+    planner.add("rvrn");
+    planner.pause();
+    let direction_features = match direction {
+        Direction::LeftToRight => ["ltra", "ltrm"],
+        Direction::RightToLeft => ["rtla", "rtlm"],
+    };
+    for tag in direction_features {
+        planner.add(tag);
+    }
+    shaper.collect_features(&mut planner);
+    for &tag in COMMON_FEATURES.iter().chain(HORIZONTAL_FEATURES) {
+        planner.add(tag);
+    }
+    for &setting in options.features {
+        planner.add_setting(setting);
+    }
+
+    form_clusters(&mut characters);
+    sort_marks(&mut characters);
+    shaper.reorder_marks(&mut characters);
+
+    let mut buffer = map_glyphs(font, &characters);
+    buffer.direction = direction;
+    shaper.setup_masks(&planner, &characters, &mut buffer.glyphs);
+
+    if let Some(gsub) = font.gsub() {
+        for stage in planner.stages.iter() {
+            layout::apply_gsub(
+                gsub,
+                font.gdef(),
+                &mut buffer,
+                script,
+                options.language,
+                stage,
+            );
+        }
+    }
+    position(font, &planner, &mut buffer, script, options.language);
+    hide_default_ignorables(font, &mut buffer);
+
+    let mut glyphs: Vec<ShapedGlyph> = buffer
+        .glyphs
+        .iter()
+        .zip(buffer.positions.iter())
+        .map(|(info, position)| ShapedGlyph {
+            glyph: info.glyph,
+            cluster: info.cluster,
+            x_advance: position.x_advance,
+            y_advance: position.y_advance,
+            x_offset: position.x_offset,
+            y_offset: position.y_offset,
+        })
+        .collect();
+    if direction == Direction::RightToLeft {
+        glyphs.reverse();
+    }
+    return glyphs;
+}
@@ -0,0 +1,105 @@
+use crate::layout::GlyphInfo;
+use crate::shaping::unicode::{self, JoiningType};
+use crate::shaping::{for_each_mark_run, Character, ComplexShaper, Planner};
+
+// Each form of a letter in the stage of its own, in the order HarfBuzz applies them
+const FORMS: &[&str] = &["isol", "fina", "medi", "init"];
+
+// Marks like hamza above that change the letter they go with rather than sit on it, they
+// come right after it ahead of the other marks of their class
+const MODIFIER_MARKS: &[char] = &[
+    '\u{0654}', '\u{0655}', '\u{0658}', '\u{06DC}', '\u{06E3}', '\u{06E7}', '\u{06E8}', '\u{08CA}',
+    '\u{08CB}', '\u{08CD}', '\u{08CE}', '\u{08CF}', '\u{08D3}', '\u{08F3}',
+];
+
+// Letters take their form by whether they join the ones around them, going by joining types
+pub(crate) struct ArabicShaper;
+
+// The form feature of each character, None for the ones that don't join. Transparent
+// characters like marks are looked past, a letter that joins the one before it turns that
+// one from isolated to initial or from final to medial.
+fn joining_forms(characters: &[Character]) -> Vec<Option<&'static str>> {
+    let mut forms = vec![None; characters.len()];
+    let mut previous: Option<usize> = None; // a letter that joins the one after it
+    for (i, character) in characters.iter().enumerate() {
+        let joining_type = unicode::joining_type(character.c);
+        let (joins_before, joins_after) = match joining_type {
+            JoiningType::T => continue,
+            JoiningType::U => (false, false),
+            JoiningType::L => (false, true),
+            JoiningType::R => (true, false),
+            JoiningType::D | JoiningType::C => (true, true),
+        };
+
+        if joining_type != JoiningType::U {
+            forms[i] = Some("isol");
+        }
+        if let Some(previous) = previous.filter(|_| joins_before) {
+            forms[previous] = match forms[previous] {
+                Some("fina") => Some("medi"),
+                _ => Some("init"),
+            };
+            forms[i] = Some("fina");
+        }
+        previous = match joins_after {
+            true => Some(i),
+            false => None,
+        };
+    }
+    return forms;
+}
+
+// Sorted by combining class, the leading modifier marks of class 220 and then of class 230
+// move to the front of the run
+fn move_modifier_marks(marks: &mut [Character]) {
+    let (mut front, mut i) = (0, 0);
+    for class in [220, 230] {
+        while i < marks.len() && unicode::combining_class(marks[i].c) < class {
+            i += 1;
+        }
+        if i == marks.len() {
+            return;
+        }
+
+        let end = i + marks[i..]
+            .iter()
+            .take_while(|mark| {
+                unicode::combining_class(mark.c) == class && MODIFIER_MARKS.contains(&mark.c)
+            })
+            .count();
+        marks[front..end].rotate_right(end - i);
+        front += end - i;
+        i = end;
+    }
+}
+
+impl ComplexShaper for ArabicShaper {
+    fn collect_features(&self, planner: &mut Planner) {
+        planner.add_manual_zwj("ccmp");
+        planner.add_manual_zwj("locl");
+        planner.pause();
+        for &form in FORMS {
+            planner.add_masked(form);
+            planner.pause();
+        }
+        planner.add_manual_zwj("rlig");
+        planner.pause();
+        planner.add_manual_zwj("rclt");
+        planner.add_manual_zwj("calt");
+        planner.pause();
+        planner.add("mset");
+    }
+
+    fn reorder_marks(&self, characters: &mut [Character]) {
+        for_each_mark_run(characters, move_modifier_marks);
+    }
+
+    fn setup_masks(&self, planner: &Planner, characters: &[Character], glyphs: &mut [GlyphInfo]) {
+        let forms = joining_forms(characters);
+        for (info, form) in glyphs.iter_mut().zip(forms) {
+            if let Some(form) = form {
+                info.mask |= planner.mask(form);
+            }
+        }
+    }
+}
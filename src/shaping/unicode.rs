@@ -0,0 +1,482 @@
+// Character properties shaping goes by. The tables come from the Unicode 14 character
+// database, ranges are inclusive and sorted.
+
+use std::cmp::Ordering;
+
+pub(crate) const ZWNJ: char = '\u{200C}';
+pub(crate) const ZWJ: char = '\u{200D}';
+
+fn compare(start: u32, end: u32, c: char) -> Ordering {
+    if end < c as u32 {
+        return Ordering::Less;
+    } else if start > c as u32 {
+        return Ordering::Greater;
+    }
+    return Ordering::Equal;
+}
+
+fn contains(table: &[(u32, u32)], c: char) -> bool {
+    return table
+        .binary_search_by(|&(start, end)| compare(start, end, c))
+        .is_ok();
+}
+
+fn find<T: Copy>(table: &[(u32, u32, T)], c: char) -> Option<T> {
+    let index = table
+        .binary_search_by(|&(start, end, _)| compare(start, end, c))
+        .ok()?;
+    return Some(table[index].2);
+}
+
+// General category Mn, Mc or Me
+pub(crate) fn is_mark(c: char) -> bool {
+    return contains(MARKS, c);
+}
+
+// Canonical combining class, 0 for everything that isn't a combining mark. Shadda, class
+// 33, comes before the other Arabic vowel marks here as fonts expect it on top of them.
+pub(crate) fn combining_class(c: char) -> u8 {
+    return match find(COMBINING_CLASSES, c).unwrap_or(0) {
+        class @ 27..=32 => class + 1,
+        33 => 27,
+        class => class,
+    };
+}
+
+// Invisible formatting characters like the joiners and the variation selectors, fonts
+// rarely have glyphs for them
+pub(crate) fn is_default_ignorable(c: char) -> bool {
+    return contains(DEFAULT_IGNORABLES, c);
+}
+
+pub(crate) fn is_variation_selector(c: char) -> bool {
+    return matches!(c, '\u{FE00}'..='\u{FE0F}' | '\u{E0100}'..='\u{E01EF}');
+}
+
+// Unicode joining types, how a character of a cursive script like Arabic connects to the
+// ones around it
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum JoiningType {
+    U, // non-joining
+    L, // joins the character after it, left joining when read right to left
+    R, // joins the character before it
+    D, // joins both
+    C, // joins both without changing form itself, like the tatweel and ZWJ
+    T, // transparent, joining looks past it, marks mostly
+}
+use JoiningType::*;
+
+// Characters without a type of their own don't join
+pub(crate) fn joining_type(c: char) -> JoiningType {
+    return find(JOINING_TYPES, c).unwrap_or(U);
+}
+
+// OpenType tag of the script of a character, None for the ones all scripts share, like
+// digits and punctuation, and for scripts without a table here
+pub(crate) fn script(c: char) -> Option<&'static str> {
+    return find(SCRIPTS, c);
+}
+
+#[rustfmt::skip]
+const DEFAULT_IGNORABLES: &[(u32, u32)] = &[
+    (0xAD, 0xAD), (0x34F, 0x34F), (0x61C, 0x61C), (0x115F, 0x1160), (0x17B4, 0x17B5),
+    (0x180B, 0x180F), (0x200B, 0x200F), (0x202A, 0x202E), (0x2060, 0x206F), (0x3164, 0x3164),
+    (0xFE00, 0xFE0F), (0xFEFF, 0xFEFF), (0xFFA0, 0xFFA0), (0xFFF0, 0xFFF8),
+    (0x1BCA0, 0x1BCA3), (0x1D173, 0x1D17A), (0xE0000, 0xE0FFF),
+];
+
+#[rustfmt::skip]
+const MARKS: &[(u32, u32)] = &[
+    (0x300, 0x36F), (0x483, 0x489), (0x591, 0x5BD), (0x5BF, 0x5BF),
+    (0x5C1, 0x5C2), (0x5C4, 0x5C5), (0x5C7, 0x5C7), (0x610, 0x61A),
+    (0x64B, 0x65F), (0x670, 0x670), (0x6D6, 0x6DC), (0x6DF, 0x6E4),
+    (0x6E7, 0x6E8), (0x6EA, 0x6ED), (0x711, 0x711), (0x730, 0x74A),
+    (0x7A6, 0x7B0), (0x7EB, 0x7F3), (0x7FD, 0x7FD), (0x816, 0x819),
+    (0x81B, 0x823), (0x825, 0x827), (0x829, 0x82D), (0x859, 0x85B),
+    (0x898, 0x89F), (0x8CA, 0x8E1), (0x8E3, 0x903), (0x93A, 0x93C),
+    (0x93E, 0x94F), (0x951, 0x957), (0x962, 0x963), (0x981, 0x983),
+    (0x9BC, 0x9BC), (0x9BE, 0x9C4), (0x9C7, 0x9C8), (0x9CB, 0x9CD),
+    (0x9D7, 0x9D7), (0x9E2, 0x9E3), (0x9FE, 0x9FE), (0xA01, 0xA03),
+    (0xA3C, 0xA3C), (0xA3E, 0xA42), (0xA47, 0xA48), (0xA4B, 0xA4D),
+    (0xA51, 0xA51), (0xA70, 0xA71), (0xA75, 0xA75), (0xA81, 0xA83),
+    (0xABC, 0xABC), (0xABE, 0xAC5), (0xAC7, 0xAC9), (0xACB, 0xACD),
+    (0xAE2, 0xAE3), (0xAFA, 0xAFF), (0xB01, 0xB03), (0xB3C, 0xB3C),
+    (0xB3E, 0xB44), (0xB47, 0xB48), (0xB4B, 0xB4D), (0xB55, 0xB57),
+    (0xB62, 0xB63), (0xB82, 0xB82), (0xBBE, 0xBC2), (0xBC6, 0xBC8),
+    (0xBCA, 0xBCD), (0xBD7, 0xBD7), (0xC00, 0xC04), (0xC3C, 0xC3C),
+    (0xC3E, 0xC44), (0xC46, 0xC48), (0xC4A, 0xC4D), (0xC55, 0xC56),
+    (0xC62, 0xC63), (0xC81, 0xC83), (0xCBC, 0xCBC), (0xCBE, 0xCC4),
+    (0xCC6, 0xCC8), (0xCCA, 0xCCD), (0xCD5, 0xCD6), (0xCE2, 0xCE3),
+    (0xD00, 0xD03), (0xD3B, 0xD3C), (0xD3E, 0xD44), (0xD46, 0xD48),
+    (0xD4A, 0xD4D), (0xD57, 0xD57), (0xD62, 0xD63), (0xD81, 0xD83),
+    (0xDCA, 0xDCA), (0xDCF, 0xDD4), (0xDD6, 0xDD6), (0xDD8, 0xDDF),
+    (0xDF2, 0xDF3), (0xE31, 0xE31), (0xE34, 0xE3A), (0xE47, 0xE4E),
+    (0xEB1, 0xEB1), (0xEB4, 0xEBC), (0xEC8, 0xECD), (0xF18, 0xF19),
+    (0xF35, 0xF35), (0xF37, 0xF37), (0xF39, 0xF39), (0xF3E, 0xF3F),
+    (0xF71, 0xF84), (0xF86, 0xF87), (0xF8D, 0xF97), (0xF99, 0xFBC),
+    (0xFC6, 0xFC6), (0x102B, 0x103E), (0x1056, 0x1059), (0x105E, 0x1060),
+    (0x1062, 0x1064), (0x1067, 0x106D), (0x1071, 0x1074), (0x1082, 0x108D),
+    (0x108F, 0x108F), (0x109A, 0x109D), (0x135D, 0x135F), (0x1712, 0x1715),
+    (0x1732, 0x1734), (0x1752, 0x1753), (0x1772, 0x1773), (0x17B4, 0x17D3),
+    (0x17DD, 0x17DD), (0x180B, 0x180D), (0x180F, 0x180F), (0x1885, 0x1886),
+    (0x18A9, 0x18A9), (0x1920, 0x192B), (0x1930, 0x193B), (0x1A17, 0x1A1B),
+    (0x1A55, 0x1A5E), (0x1A60, 0x1A7C), (0x1A7F, 0x1A7F), (0x1AB0, 0x1ACE),
+    (0x1B00, 0x1B04), (0x1B34, 0x1B44), (0x1B6B, 0x1B73), (0x1B80, 0x1B82),
+    (0x1BA1, 0x1BAD), (0x1BE6, 0x1BF3), (0x1C24, 0x1C37), (0x1CD0, 0x1CD2),
+    (0x1CD4, 0x1CE8), (0x1CED, 0x1CED), (0x1CF4, 0x1CF4), (0x1CF7, 0x1CF9),
+    (0x1DC0, 0x1DFF), (0x20D0, 0x20F0), (0x2CEF, 0x2CF1), (0x2D7F, 0x2D7F),
+    (0x2DE0, 0x2DFF), (0x302A, 0x302F), (0x3099, 0x309A), (0xA66F, 0xA672),
+    (0xA674, 0xA67D), (0xA69E, 0xA69F), (0xA6F0, 0xA6F1), (0xA802, 0xA802),
+    (0xA806, 0xA806), (0xA80B, 0xA80B), (0xA823, 0xA827), (0xA82C, 0xA82C),
+    (0xA880, 0xA881), (0xA8B4, 0xA8C5), (0xA8E0, 0xA8F1), (0xA8FF, 0xA8FF),
+    (0xA926, 0xA92D), (0xA947, 0xA953), (0xA980, 0xA983), (0xA9B3, 0xA9C0),
+    (0xA9E5, 0xA9E5), (0xAA29, 0xAA36), (0xAA43, 0xAA43), (0xAA4C, 0xAA4D),
+    (0xAA7B, 0xAA7D), (0xAAB0, 0xAAB0), (0xAAB2, 0xAAB4), (0xAAB7, 0xAAB8),
+    (0xAABE, 0xAABF), (0xAAC1, 0xAAC1), (0xAAEB, 0xAAEF), (0xAAF5, 0xAAF6),
+    (0xABE3, 0xABEA), (0xABEC, 0xABED), (0xFB1E, 0xFB1E), (0xFE00, 0xFE0F),
+    (0xFE20, 0xFE2F), (0x101FD, 0x101FD), (0x102E0, 0x102E0), (0x10376, 0x1037A),
+    (0x10A01, 0x10A03), (0x10A05, 0x10A06), (0x10A0C, 0x10A0F), (0x10A38, 0x10A3A),
+    (0x10A3F, 0x10A3F), (0x10AE5, 0x10AE6), (0x10D24, 0x10D27), (0x10EAB, 0x10EAC),
+    (0x10F46, 0x10F50), (0x10F82, 0x10F85), (0x11000, 0x11002), (0x11038, 0x11046),
+    (0x11070, 0x11070), (0x11073, 0x11074), (0x1107F, 0x11082), (0x110B0, 0x110BA),
+    (0x110C2, 0x110C2), (0x11100, 0x11102), (0x11127, 0x11134), (0x11145, 0x11146),
+    (0x11173, 0x11173), (0x11180, 0x11182), (0x111B3, 0x111C0), (0x111C9, 0x111CC),
+    (0x111CE, 0x111CF), (0x1122C, 0x11237), (0x1123E, 0x1123E), (0x112DF, 0x112EA),
+    (0x11300, 0x11303), (0x1133B, 0x1133C), (0x1133E, 0x11344), (0x11347, 0x11348),
+    (0x1134B, 0x1134D), (0x11357, 0x11357), (0x11362, 0x11363), (0x11366, 0x1136C),
+    (0x11370, 0x11374), (0x11435, 0x11446), (0x1145E, 0x1145E), (0x114B0, 0x114C3),
+    (0x115AF, 0x115B5), (0x115B8, 0x115C0), (0x115DC, 0x115DD), (0x11630, 0x11640),
+    (0x116AB, 0x116B7), (0x1171D, 0x1172B), (0x1182C, 0x1183A), (0x11930, 0x11935),
+    (0x11937, 0x11938), (0x1193B, 0x1193E), (0x11940, 0x11940), (0x11942, 0x11943),
+    (0x119D1, 0x119D7), (0x119DA, 0x119E0), (0x119E4, 0x119E4), (0x11A01, 0x11A0A),
+    (0x11A33, 0x11A39), (0x11A3B, 0x11A3E), (0x11A47, 0x11A47), (0x11A51, 0x11A5B),
+    (0x11A8A, 0x11A99), (0x11C2F, 0x11C36), (0x11C38, 0x11C3F), (0x11C92, 0x11CA7),
+    (0x11CA9, 0x11CB6), (0x11D31, 0x11D36), (0x11D3A, 0x11D3A), (0x11D3C, 0x11D3D),
+    (0x11D3F, 0x11D45), (0x11D47, 0x11D47), (0x11D8A, 0x11D8E), (0x11D90, 0x11D91),
+    (0x11D93, 0x11D97), (0x11EF3, 0x11EF6), (0x16AF0, 0x16AF4), (0x16B30, 0x16B36),
+    (0x16F4F, 0x16F4F), (0x16F51, 0x16F87), (0x16F8F, 0x16F92), (0x16FE4, 0x16FE4),
+    (0x16FF0, 0x16FF1), (0x1BC9D, 0x1BC9E), (0x1CF00, 0x1CF2D), (0x1CF30, 0x1CF46),
+    (0x1D165, 0x1D169), (0x1D16D, 0x1D172), (0x1D17B, 0x1D182), (0x1D185, 0x1D18B),
+    (0x1D1AA, 0x1D1AD), (0x1D242, 0x1D244), (0x1DA00, 0x1DA36), (0x1DA3B, 0x1DA6C),
+    (0x1DA75, 0x1DA75), (0x1DA84, 0x1DA84), (0x1DA9B, 0x1DA9F), (0x1DAA1, 0x1DAAF),
+    (0x1E000, 0x1E006), (0x1E008, 0x1E018), (0x1E01B, 0x1E021), (0x1E023, 0x1E024),
+    (0x1E026, 0x1E02A), (0x1E130, 0x1E136), (0x1E2AE, 0x1E2AE), (0x1E2EC, 0x1E2EF),
+    (0x1E8D0, 0x1E8D6), (0x1E944, 0x1E94A), (0xE0100, 0xE01EF),
+];
+#[rustfmt::skip]
+const COMBINING_CLASSES: &[(u32, u32, u8)] = &[
+    (0x300, 0x314, 230), (0x315, 0x315, 232), (0x316, 0x319, 220),
+    (0x31A, 0x31A, 232), (0x31B, 0x31B, 216), (0x31C, 0x320, 220),
+    (0x321, 0x322, 202), (0x323, 0x326, 220), (0x327, 0x328, 202),
+    (0x329, 0x333, 220), (0x334, 0x338, 1), (0x339, 0x33C, 220),
+    (0x33D, 0x344, 230), (0x345, 0x345, 240), (0x346, 0x346, 230),
+    (0x347, 0x349, 220), (0x34A, 0x34C, 230), (0x34D, 0x34E, 220),
+    (0x350, 0x352, 230), (0x353, 0x356, 220), (0x357, 0x357, 230),
+    (0x358, 0x358, 232), (0x359, 0x35A, 220), (0x35B, 0x35B, 230),
+    (0x35C, 0x35C, 233), (0x35D, 0x35E, 234), (0x35F, 0x35F, 233),
+    (0x360, 0x361, 234), (0x362, 0x362, 233), (0x363, 0x36F, 230),
+    (0x483, 0x487, 230), (0x591, 0x591, 220), (0x592, 0x595, 230),
+    (0x596, 0x596, 220), (0x597, 0x599, 230), (0x59A, 0x59A, 222),
+    (0x59B, 0x59B, 220), (0x59C, 0x5A1, 230), (0x5A2, 0x5A7, 220),
+    (0x5A8, 0x5A9, 230), (0x5AA, 0x5AA, 220), (0x5AB, 0x5AC, 230),
+    (0x5AD, 0x5AD, 222), (0x5AE, 0x5AE, 228), (0x5AF, 0x5AF, 230),
+    (0x5B0, 0x5B0, 10), (0x5B1, 0x5B1, 11), (0x5B2, 0x5B2, 12),
+    (0x5B3, 0x5B3, 13), (0x5B4, 0x5B4, 14), (0x5B5, 0x5B5, 15),
+    (0x5B6, 0x5B6, 16), (0x5B7, 0x5B7, 17), (0x5B8, 0x5B8, 18),
+    (0x5B9, 0x5BA, 19), (0x5BB, 0x5BB, 20), (0x5BC, 0x5BC, 21),
+    (0x5BD, 0x5BD, 22), (0x5BF, 0x5BF, 23), (0x5C1, 0x5C1, 24),
+    (0x5C2, 0x5C2, 25), (0x5C4, 0x5C4, 230), (0x5C5, 0x5C5, 220),
+    (0x5C7, 0x5C7, 18), (0x610, 0x617, 230), (0x618, 0x618, 30),
+    (0x619, 0x619, 31), (0x61A, 0x61A, 32), (0x64B, 0x64B, 27),
+    (0x64C, 0x64C, 28), (0x64D, 0x64D, 29), (0x64E, 0x64E, 30),
+    (0x64F, 0x64F, 31), (0x650, 0x650, 32), (0x651, 0x651, 33),
+    (0x652, 0x652, 34), (0x653, 0x654, 230), (0x655, 0x656, 220),
+    (0x657, 0x65B, 230), (0x65C, 0x65C, 220), (0x65D, 0x65E, 230),
+    (0x65F, 0x65F, 220), (0x670, 0x670, 35), (0x6D6, 0x6DC, 230),
+    (0x6DF, 0x6E2, 230), (0x6E3, 0x6E3, 220), (0x6E4, 0x6E4, 230),
+    (0x6E7, 0x6E8, 230), (0x6EA, 0x6EA, 220), (0x6EB, 0x6EC, 230),
+    (0x6ED, 0x6ED, 220), (0x711, 0x711, 36), (0x730, 0x730, 230),
+    (0x731, 0x731, 220), (0x732, 0x733, 230), (0x734, 0x734, 220),
+    (0x735, 0x736, 230), (0x737, 0x739, 220), (0x73A, 0x73A, 230),
+    (0x73B, 0x73C, 220), (0x73D, 0x73D, 230), (0x73E, 0x73E, 220),
+    (0x73F, 0x741, 230), (0x742, 0x742, 220), (0x743, 0x743, 230),
+    (0x744, 0x744, 220), (0x745, 0x745, 230), (0x746, 0x746, 220),
+    (0x747, 0x747, 230), (0x748, 0x748, 220), (0x749, 0x74A, 230),
+    (0x7EB, 0x7F1, 230), (0x7F2, 0x7F2, 220), (0x7F3, 0x7F3, 230),
+    (0x7FD, 0x7FD, 220), (0x816, 0x819, 230), (0x81B, 0x823, 230),
+    (0x825, 0x827, 230), (0x829, 0x82D, 230), (0x859, 0x85B, 220),
+    (0x898, 0x898, 230), (0x899, 0x89B, 220), (0x89C, 0x89F, 230),
+    (0x8CA, 0x8CE, 230), (0x8CF, 0x8D3, 220), (0x8D4, 0x8E1, 230),
+    (0x8E3, 0x8E3, 220), (0x8E4, 0x8E5, 230), (0x8E6, 0x8E6, 220),
+    (0x8E7, 0x8E8, 230), (0x8E9, 0x8E9, 220), (0x8EA, 0x8EC, 230),
+    (0x8ED, 0x8EF, 220), (0x8F0, 0x8F0, 27), (0x8F1, 0x8F1, 28),
+    (0x8F2, 0x8F2, 29), (0x8F3, 0x8F5, 230), (0x8F6, 0x8F6, 220),
+    (0x8F7, 0x8F8, 230), (0x8F9, 0x8FA, 220), (0x8FB, 0x8FF, 230),
+    (0x93C, 0x93C, 7), (0x94D, 0x94D, 9), (0x951, 0x951, 230),
+    (0x952, 0x952, 220), (0x953, 0x954, 230), (0x9BC, 0x9BC, 7),
+    (0x9CD, 0x9CD, 9), (0x9FE, 0x9FE, 230), (0xA3C, 0xA3C, 7),
+    (0xA4D, 0xA4D, 9), (0xABC, 0xABC, 7), (0xACD, 0xACD, 9),
+    (0xB3C, 0xB3C, 7), (0xB4D, 0xB4D, 9), (0xBCD, 0xBCD, 9),
+    (0xC3C, 0xC3C, 7), (0xC4D, 0xC4D, 9), (0xC55, 0xC55, 84),
+    (0xC56, 0xC56, 91), (0xCBC, 0xCBC, 7), (0xCCD, 0xCCD, 9),
+    (0xD3B, 0xD3C, 9), (0xD4D, 0xD4D, 9), (0xDCA, 0xDCA, 9),
+    (0xE38, 0xE39, 103), (0xE3A, 0xE3A, 9), (0xE48, 0xE4B, 107),
+    (0xEB8, 0xEB9, 118), (0xEBA, 0xEBA, 9), (0xEC8, 0xECB, 122),
+    (0xF18, 0xF19, 220), (0xF35, 0xF35, 220), (0xF37, 0xF37, 220),
+    (0xF39, 0xF39, 216), (0xF71, 0xF71, 129), (0xF72, 0xF72, 130),
+    (0xF74, 0xF74, 132), (0xF7A, 0xF7D, 130), (0xF80, 0xF80, 130),
+    (0xF82, 0xF83, 230), (0xF84, 0xF84, 9), (0xF86, 0xF87, 230),
+    (0xFC6, 0xFC6, 220), (0x1037, 0x1037, 7), (0x1039, 0x103A, 9),
+    (0x108D, 0x108D, 220), (0x135D, 0x135F, 230), (0x1714, 0x1715, 9),
+    (0x1734, 0x1734, 9), (0x17D2, 0x17D2, 9), (0x17DD, 0x17DD, 230),
+    (0x18A9, 0x18A9, 228), (0x1939, 0x1939, 222), (0x193A, 0x193A, 230),
+    (0x193B, 0x193B, 220), (0x1A17, 0x1A17, 230), (0x1A18, 0x1A18, 220),
+    (0x1A60, 0x1A60, 9), (0x1A75, 0x1A7C, 230), (0x1A7F, 0x1A7F, 220),
+    (0x1AB0, 0x1AB4, 230), (0x1AB5, 0x1ABA, 220), (0x1ABB, 0x1ABC, 230),
+    (0x1ABD, 0x1ABD, 220), (0x1ABF, 0x1AC0, 220), (0x1AC1, 0x1AC2, 230),
+    (0x1AC3, 0x1AC4, 220), (0x1AC5, 0x1AC9, 230), (0x1ACA, 0x1ACA, 220),
+    (0x1ACB, 0x1ACE, 230), (0x1B34, 0x1B34, 7), (0x1B44, 0x1B44, 9),
+    (0x1B6B, 0x1B6B, 230), (0x1B6C, 0x1B6C, 220), (0x1B6D, 0x1B73, 230),
+    (0x1BAA, 0x1BAB, 9), (0x1BE6, 0x1BE6, 7), (0x1BF2, 0x1BF3, 9),
+    (0x1C37, 0x1C37, 7), (0x1CD0, 0x1CD2, 230), (0x1CD4, 0x1CD4, 1),
+    (0x1CD5, 0x1CD9, 220), (0x1CDA, 0x1CDB, 230), (0x1CDC, 0x1CDF, 220),
+    (0x1CE0, 0x1CE0, 230), (0x1CE2, 0x1CE8, 1), (0x1CED, 0x1CED, 220),
+    (0x1CF4, 0x1CF4, 230), (0x1CF8, 0x1CF9, 230), (0x1DC0, 0x1DC1, 230),
+    (0x1DC2, 0x1DC2, 220), (0x1DC3, 0x1DC9, 230), (0x1DCA, 0x1DCA, 220),
+    (0x1DCB, 0x1DCC, 230), (0x1DCD, 0x1DCD, 234), (0x1DCE, 0x1DCE, 214),
+    (0x1DCF, 0x1DCF, 220), (0x1DD0, 0x1DD0, 202), (0x1DD1, 0x1DF5, 230),
+    (0x1DF6, 0x1DF6, 232), (0x1DF7, 0x1DF8, 228), (0x1DF9, 0x1DF9, 220),
+    (0x1DFA, 0x1DFA, 218), (0x1DFB, 0x1DFB, 230), (0x1DFC, 0x1DFC, 233),
+    (0x1DFD, 0x1DFD, 220), (0x1DFE, 0x1DFE, 230), (0x1DFF, 0x1DFF, 220),
+    (0x20D0, 0x20D1, 230), (0x20D2, 0x20D3, 1), (0x20D4, 0x20D7, 230),
+    (0x20D8, 0x20DA, 1), (0x20DB, 0x20DC, 230), (0x20E1, 0x20E1, 230),
+    (0x20E5, 0x20E6, 1), (0x20E7, 0x20E7, 230), (0x20E8, 0x20E8, 220),
+    (0x20E9, 0x20E9, 230), (0x20EA, 0x20EB, 1), (0x20EC, 0x20EF, 220),
+    (0x20F0, 0x20F0, 230), (0x2CEF, 0x2CF1, 230), (0x2D7F, 0x2D7F, 9),
+    (0x2DE0, 0x2DFF, 230), (0x302A, 0x302A, 218), (0x302B, 0x302B, 228),
+    (0x302C, 0x302C, 232), (0x302D, 0x302D, 222), (0x302E, 0x302F, 224),
+    (0x3099, 0x309A, 8), (0xA66F, 0xA66F, 230), (0xA674, 0xA67D, 230),
+    (0xA69E, 0xA69F, 230), (0xA6F0, 0xA6F1, 230), (0xA806, 0xA806, 9),
+    (0xA82C, 0xA82C, 9), (0xA8C4, 0xA8C4, 9), (0xA8E0, 0xA8F1, 230),
+    (0xA92B, 0xA92D, 220), (0xA953, 0xA953, 9), (0xA9B3, 0xA9B3, 7),
+    (0xA9C0, 0xA9C0, 9), (0xAAB0, 0xAAB0, 230), (0xAAB2, 0xAAB3, 230),
+    (0xAAB4, 0xAAB4, 220), (0xAAB7, 0xAAB8, 230), (0xAABE, 0xAABF, 230),
+    (0xAAC1, 0xAAC1, 230), (0xAAF6, 0xAAF6, 9), (0xABED, 0xABED, 9),
+    (0xFB1E, 0xFB1E, 26), (0xFE20, 0xFE26, 230), (0xFE27, 0xFE2D, 220),
+    (0xFE2E, 0xFE2F, 230), (0x101FD, 0x101FD, 220), (0x102E0, 0x102E0, 220),
+    (0x10376, 0x1037A, 230), (0x10A0D, 0x10A0D, 220), (0x10A0F, 0x10A0F, 230),
+    (0x10A38, 0x10A38, 230), (0x10A39, 0x10A39, 1), (0x10A3A, 0x10A3A, 220),
+    (0x10A3F, 0x10A3F, 9), (0x10AE5, 0x10AE5, 230), (0x10AE6, 0x10AE6, 220),
+    (0x10D24, 0x10D27, 230), (0x10EAB, 0x10EAC, 230), (0x10F46, 0x10F47, 220),
+    (0x10F48, 0x10F4A, 230), (0x10F4B, 0x10F4B, 220), (0x10F4C, 0x10F4C, 230),
+    (0x10F4D, 0x10F50, 220), (0x10F82, 0x10F82, 230), (0x10F83, 0x10F83, 220),
+    (0x10F84, 0x10F84, 230), (0x10F85, 0x10F85, 220), (0x11046, 0x11046, 9),
+    (0x11070, 0x11070, 9), (0x1107F, 0x1107F, 9), (0x110B9, 0x110B9, 9),
+    (0x110BA, 0x110BA, 7), (0x11100, 0x11102, 230), (0x11133, 0x11134, 9),
+    (0x11173, 0x11173, 7), (0x111C0, 0x111C0, 9), (0x111CA, 0x111CA, 7),
+    (0x11235, 0x11235, 9), (0x11236, 0x11236, 7), (0x112E9, 0x112E9, 7),
+    (0x112EA, 0x112EA, 9), (0x1133B, 0x1133C, 7), (0x1134D, 0x1134D, 9),
+    (0x11366, 0x1136C, 230), (0x11370, 0x11374, 230), (0x11442, 0x11442, 9),
+    (0x11446, 0x11446, 7), (0x1145E, 0x1145E, 230), (0x114C2, 0x114C2, 9),
+    (0x114C3, 0x114C3, 7), (0x115BF, 0x115BF, 9), (0x115C0, 0x115C0, 7),
+    (0x1163F, 0x1163F, 9), (0x116B6, 0x116B6, 9), (0x116B7, 0x116B7, 7),
+    (0x1172B, 0x1172B, 9), (0x11839, 0x11839, 9), (0x1183A, 0x1183A, 7),
+    (0x1193D, 0x1193E, 9), (0x11943, 0x11943, 7), (0x119E0, 0x119E0, 9),
+    (0x11A34, 0x11A34, 9), (0x11A47, 0x11A47, 9), (0x11A99, 0x11A99, 9),
+    (0x11C3F, 0x11C3F, 9), (0x11D42, 0x11D42, 7), (0x11D44, 0x11D45, 9),
+    (0x11D97, 0x11D97, 9), (0x16AF0, 0x16AF4, 1), (0x16B30, 0x16B36, 230),
+    (0x16FF0, 0x16FF1, 6), (0x1BC9E, 0x1BC9E, 1), (0x1D165, 0x1D166, 216),
+    (0x1D167, 0x1D169, 1), (0x1D16D, 0x1D16D, 226), (0x1D16E, 0x1D172, 216),
+    (0x1D17B, 0x1D182, 220), (0x1D185, 0x1D189, 230), (0x1D18A, 0x1D18B, 220),
+    (0x1D1AA, 0x1D1AD, 230), (0x1D242, 0x1D244, 230), (0x1E000, 0x1E006, 230),
+    (0x1E008, 0x1E018, 230), (0x1E01B, 0x1E021, 230), (0x1E023, 0x1E024, 230),
+    (0x1E026, 0x1E02A, 230), (0x1E130, 0x1E136, 230), (0x1E2AE, 0x1E2AE, 230),
+    (0x1E2EC, 0x1E2EF, 230), (0x1E8D0, 0x1E8D6, 220), (0x1E944, 0x1E949, 230),
+    (0x1E94A, 0x1E94A, 7),
+];
+#[rustfmt::skip]
+const SCRIPTS: &[(u32, u32, &str)] = &[
+    (0x41, 0x5A, "latn"), (0x61, 0x7A, "latn"), (0xAA, 0xAA, "latn"),
+    (0xBA, 0xBA, "latn"), (0xC0, 0xD6, "latn"), (0xD8, 0xF6, "latn"),
+    (0xF8, 0x2B8, "latn"), (0x2E0, 0x2E4, "latn"), (0x370, 0x373, "grek"),
+    (0x375, 0x377, "grek"), (0x37A, 0x37D, "grek"), (0x37F, 0x37F, "grek"),
+    (0x384, 0x384, "grek"), (0x386, 0x386, "grek"), (0x388, 0x38A, "grek"),
+    (0x38C, 0x38C, "grek"), (0x38E, 0x3A1, "grek"), (0x3A3, 0x3E1, "grek"),
+    (0x3F0, 0x3FF, "grek"), (0x400, 0x484, "cyrl"), (0x487, 0x52F, "cyrl"),
+    (0x531, 0x556, "armn"), (0x559, 0x58A, "armn"), (0x58D, 0x58F, "armn"),
+    (0x591, 0x5C7, "hebr"), (0x5D0, 0x5EA, "hebr"), (0x5EF, 0x5F4, "hebr"),
+    (0x600, 0x604, "arab"), (0x606, 0x60B, "arab"), (0x60D, 0x61A, "arab"),
+    (0x61C, 0x61E, "arab"), (0x620, 0x63F, "arab"), (0x641, 0x64A, "arab"),
+    (0x656, 0x66F, "arab"), (0x671, 0x6DC, "arab"), (0x6DE, 0x6FF, "arab"),
+    (0x750, 0x77F, "arab"), (0x870, 0x88E, "arab"), (0x890, 0x891, "arab"),
+    (0x898, 0x8E1, "arab"), (0x8E3, 0x8FF, "arab"), (0x10A0, 0x10C5, "geor"),
+    (0x10C7, 0x10C7, "geor"), (0x10CD, 0x10CD, "geor"), (0x10D0, 0x10FA, "geor"),
+    (0x10FC, 0x10FF, "geor"), (0x1C80, 0x1C88, "cyrl"), (0x1C90, 0x1CBA, "geor"),
+    (0x1CBD, 0x1CBF, "geor"), (0x1D00, 0x1D25, "latn"), (0x1D26, 0x1D2A, "grek"),
+    (0x1D2B, 0x1D2B, "cyrl"), (0x1D2C, 0x1D5C, "latn"), (0x1D5D, 0x1D61, "grek"),
+    (0x1D62, 0x1D65, "latn"), (0x1D66, 0x1D6A, "grek"), (0x1D6B, 0x1D77, "latn"),
+    (0x1D78, 0x1D78, "cyrl"), (0x1D79, 0x1DBE, "latn"), (0x1DBF, 0x1DBF, "grek"),
+    (0x1E00, 0x1EFF, "latn"), (0x1F00, 0x1F15, "grek"), (0x1F18, 0x1F1D, "grek"),
+    (0x1F20, 0x1F45, "grek"), (0x1F48, 0x1F4D, "grek"), (0x1F50, 0x1F57, "grek"),
+    (0x1F59, 0x1F59, "grek"), (0x1F5B, 0x1F5B, "grek"), (0x1F5D, 0x1F5D, "grek"),
+    (0x1F5F, 0x1F7D, "grek"), (0x1F80, 0x1FB4, "grek"), (0x1FB6, 0x1FC4, "grek"),
+    (0x1FC6, 0x1FD3, "grek"), (0x1FD6, 0x1FDB, "grek"), (0x1FDD, 0x1FEF, "grek"),
+    (0x1FF2, 0x1FF4, "grek"), (0x1FF6, 0x1FFE, "grek"), (0x2071, 0x2071, "latn"),
+    (0x207F, 0x207F, "latn"), (0x2090, 0x209C, "latn"), (0x2126, 0x2126, "grek"),
+    (0x212A, 0x212B, "latn"), (0x2132, 0x2132, "latn"), (0x214E, 0x214E, "latn"),
+    (0x2160, 0x2188, "latn"), (0x2C60, 0x2C7F, "latn"), (0x2D00, 0x2D25, "geor"),
+    (0x2D27, 0x2D27, "geor"), (0x2D2D, 0x2D2D, "geor"), (0x2DE0, 0x2DFF, "cyrl"),
+    (0xA640, 0xA69F, "cyrl"), (0xA722, 0xA787, "latn"), (0xA78B, 0xA7CA, "latn"),
+    (0xA7D0, 0xA7D1, "latn"), (0xA7D3, 0xA7D3, "latn"), (0xA7D5, 0xA7D9, "latn"),
+    (0xA7F2, 0xA7FF, "latn"), (0xAB30, 0xAB5A, "latn"), (0xAB5C, 0xAB64, "latn"),
+    (0xAB65, 0xAB65, "grek"), (0xAB66, 0xAB69, "latn"), (0xFB00, 0xFB06, "latn"),
+    (0xFB13, 0xFB17, "armn"), (0xFB1D, 0xFB36, "hebr"), (0xFB38, 0xFB3C, "hebr"),
+    (0xFB3E, 0xFB3E, "hebr"), (0xFB40, 0xFB41, "hebr"), (0xFB43, 0xFB44, "hebr"),
+    (0xFB46, 0xFB4F, "hebr"), (0xFB50, 0xFBC2, "arab"), (0xFBD3, 0xFD3D, "arab"),
+    (0xFD40, 0xFD8F, "arab"), (0xFD92, 0xFDC7, "arab"), (0xFDCF, 0xFDCF, "arab"),
+    (0xFDF0, 0xFDFF, "arab"), (0xFE2E, 0xFE2F, "cyrl"), (0xFE70, 0xFE74, "arab"),
+    (0xFE76, 0xFEFC, "arab"), (0xFF21, 0xFF3A, "latn"), (0xFF41, 0xFF5A, "latn"),
+    (0x10140, 0x1018E, "grek"), (0x101A0, 0x101A0, "grek"), (0x10780, 0x10785, "latn"),
+    (0x10787, 0x107B0, "latn"), (0x107B2, 0x107BA, "latn"), (0x10E60, 0x10E7E, "arab"),
+    (0x1D200, 0x1D245, "grek"), (0x1DF00, 0x1DF1E, "latn"), (0x1EE00, 0x1EE03, "arab"),
+    (0x1EE05, 0x1EE1F, "arab"), (0x1EE21, 0x1EE22, "arab"), (0x1EE24, 0x1EE24, "arab"),
+    (0x1EE27, 0x1EE27, "arab"), (0x1EE29, 0x1EE32, "arab"), (0x1EE34, 0x1EE37, "arab"),
+    (0x1EE39, 0x1EE39, "arab"), (0x1EE3B, 0x1EE3B, "arab"), (0x1EE42, 0x1EE42, "arab"),
+    (0x1EE47, 0x1EE47, "arab"), (0x1EE49, 0x1EE49, "arab"), (0x1EE4B, 0x1EE4B, "arab"),
+    (0x1EE4D, 0x1EE4F, "arab"), (0x1EE51, 0x1EE52, "arab"), (0x1EE54, 0x1EE54, "arab"),
+    (0x1EE57, 0x1EE57, "arab"), (0x1EE59, 0x1EE59, "arab"), (0x1EE5B, 0x1EE5B, "arab"),
+    (0x1EE5D, 0x1EE5D, "arab"), (0x1EE5F, 0x1EE5F, "arab"), (0x1EE61, 0x1EE62, "arab"),
+    (0x1EE64, 0x1EE64, "arab"), (0x1EE67, 0x1EE6A, "arab"), (0x1EE6C, 0x1EE72, "arab"),
+    (0x1EE74, 0x1EE77, "arab"), (0x1EE79, 0x1EE7C, "arab"), (0x1EE7E, 0x1EE7E, "arab"),
+    (0x1EE80, 0x1EE89, "arab"), (0x1EE8B, 0x1EE9B, "arab"), (0x1EEA1, 0x1EEA3, "arab"),
+    (0x1EEA5, 0x1EEA9, "arab"), (0x1EEAB, 0x1EEBB, "arab"), (0x1EEF0, 0x1EEF1, "arab"),
+];
+
+#[rustfmt::skip]
+const JOINING_TYPES: &[(u32, u32, JoiningType)] = &[
+    (0xAD, 0xAD, T), (0x300, 0x36F, T), (0x483, 0x489, T), (0x591, 0x5BD, T),
+    (0x5BF, 0x5BF, T), (0x5C1, 0x5C2, T), (0x5C4, 0x5C5, T), (0x5C7, 0x5C7, T),
+    (0x610, 0x61A, T), (0x61C, 0x61C, T), (0x620, 0x620, D), (0x622, 0x625, R),
+    (0x626, 0x626, D), (0x627, 0x627, R), (0x628, 0x628, D), (0x629, 0x629, R),
+    (0x62A, 0x62E, D), (0x62F, 0x632, R), (0x633, 0x63F, D), (0x640, 0x640, C),
+    (0x641, 0x647, D), (0x648, 0x648, R), (0x649, 0x64A, D), (0x64B, 0x65F, T),
+    (0x66E, 0x66F, D), (0x670, 0x670, T), (0x671, 0x673, R), (0x675, 0x677, R),
+    (0x678, 0x687, D), (0x688, 0x699, R), (0x69A, 0x6BF, D), (0x6C0, 0x6C0, R),
+    (0x6C1, 0x6C2, D), (0x6C3, 0x6CB, R), (0x6CC, 0x6CC, D), (0x6CD, 0x6CD, R),
+    (0x6CE, 0x6CE, D), (0x6CF, 0x6CF, R), (0x6D0, 0x6D1, D), (0x6D2, 0x6D3, R),
+    (0x6D5, 0x6D5, R), (0x6D6, 0x6DC, T), (0x6DF, 0x6E4, T), (0x6E7, 0x6E8, T),
+    (0x6EA, 0x6ED, T), (0x6EE, 0x6EF, R), (0x6FA, 0x6FC, D), (0x6FF, 0x6FF, D),
+    (0x70F, 0x70F, T), (0x710, 0x710, R), (0x711, 0x711, T), (0x712, 0x714, D),
+    (0x715, 0x719, R), (0x71A, 0x71D, D), (0x71E, 0x71E, R), (0x71F, 0x727, D),
+    (0x728, 0x728, R), (0x729, 0x729, D), (0x72A, 0x72A, R), (0x72B, 0x72B, D),
+    (0x72C, 0x72C, R), (0x72D, 0x72E, D), (0x72F, 0x72F, R), (0x730, 0x74A, T),
+    (0x74D, 0x74D, R), (0x74E, 0x758, D), (0x759, 0x75B, R), (0x75C, 0x76A, D),
+    (0x76B, 0x76C, R), (0x76D, 0x770, D), (0x771, 0x771, R), (0x772, 0x772, D),
+    (0x773, 0x774, R), (0x775, 0x777, D), (0x778, 0x779, R), (0x77A, 0x77F, D),
+    (0x7A6, 0x7B0, T), (0x7CA, 0x7EA, D), (0x7EB, 0x7F3, T), (0x7FA, 0x7FA, C),
+    (0x7FD, 0x7FD, T), (0x816, 0x819, T), (0x81B, 0x823, T), (0x825, 0x827, T),
+    (0x829, 0x82D, T), (0x840, 0x840, R), (0x841, 0x845, D), (0x846, 0x847, R),
+    (0x848, 0x848, D), (0x849, 0x849, R), (0x84A, 0x853, D), (0x854, 0x854, R),
+    (0x855, 0x855, D), (0x856, 0x858, R), (0x859, 0x85B, T), (0x860, 0x860, D),
+    (0x862, 0x865, D), (0x867, 0x867, R), (0x868, 0x868, D), (0x869, 0x86A, R),
+    (0x870, 0x882, R), (0x883, 0x885, C), (0x886, 0x886, D), (0x889, 0x88D, D),
+    (0x88E, 0x88E, R), (0x898, 0x89F, T), (0x8A0, 0x8A9, D), (0x8AA, 0x8AC, R),
+    (0x8AE, 0x8AE, R), (0x8AF, 0x8B0, D), (0x8B1, 0x8B2, R), (0x8B3, 0x8B8, D),
+    (0x8B9, 0x8B9, R), (0x8BA, 0x8C8, D), (0x8CA, 0x8E1, T), (0x8E3, 0x902, T),
+    (0x93A, 0x93A, T), (0x93C, 0x93C, T), (0x941, 0x948, T), (0x94D, 0x94D, T),
+    (0x951, 0x957, T), (0x962, 0x963, T), (0x981, 0x981, T), (0x9BC, 0x9BC, T),
+    (0x9C1, 0x9C4, T), (0x9CD, 0x9CD, T), (0x9E2, 0x9E3, T), (0x9FE, 0x9FE, T),
+    (0xA01, 0xA02, T), (0xA3C, 0xA3C, T), (0xA41, 0xA42, T), (0xA47, 0xA48, T),
+    (0xA4B, 0xA4D, T), (0xA51, 0xA51, T), (0xA70, 0xA71, T), (0xA75, 0xA75, T),
+    (0xA81, 0xA82, T), (0xABC, 0xABC, T), (0xAC1, 0xAC5, T), (0xAC7, 0xAC8, T),
+    (0xACD, 0xACD, T), (0xAE2, 0xAE3, T), (0xAFA, 0xAFF, T), (0xB01, 0xB01, T),
+    (0xB3C, 0xB3C, T), (0xB3F, 0xB3F, T), (0xB41, 0xB44, T), (0xB4D, 0xB4D, T),
+    (0xB55, 0xB56, T), (0xB62, 0xB63, T), (0xB82, 0xB82, T), (0xBC0, 0xBC0, T),
+    (0xBCD, 0xBCD, T), (0xC00, 0xC00, T), (0xC04, 0xC04, T), (0xC3C, 0xC3C, T),
+    (0xC3E, 0xC40, T), (0xC46, 0xC48, T), (0xC4A, 0xC4D, T), (0xC55, 0xC56, T),
+    (0xC62, 0xC63, T), (0xC81, 0xC81, T), (0xCBC, 0xCBC, T), (0xCBF, 0xCBF, T),
+    (0xCC6, 0xCC6, T), (0xCCC, 0xCCD, T), (0xCE2, 0xCE3, T), (0xD00, 0xD01, T),
+    (0xD3B, 0xD3C, T), (0xD41, 0xD44, T), (0xD4D, 0xD4D, T), (0xD62, 0xD63, T),
+    (0xD81, 0xD81, T), (0xDCA, 0xDCA, T), (0xDD2, 0xDD4, T), (0xDD6, 0xDD6, T),
+    (0xE31, 0xE31, T), (0xE34, 0xE3A, T), (0xE47, 0xE4E, T), (0xEB1, 0xEB1, T),
+    (0xEB4, 0xEBC, T), (0xEC8, 0xECD, T), (0xF18, 0xF19, T), (0xF35, 0xF35, T),
+    (0xF37, 0xF37, T), (0xF39, 0xF39, T), (0xF71, 0xF7E, T), (0xF80, 0xF84, T),
+    (0xF86, 0xF87, T), (0xF8D, 0xF97, T), (0xF99, 0xFBC, T), (0xFC6, 0xFC6, T),
+    (0x102D, 0x1030, T), (0x1032, 0x1037, T), (0x1039, 0x103A, T), (0x103D, 0x103E, T),
+    (0x1058, 0x1059, T), (0x105E, 0x1060, T), (0x1071, 0x1074, T), (0x1082, 0x1082, T),
+    (0x1085, 0x1086, T), (0x108D, 0x108D, T), (0x109D, 0x109D, T), (0x135D, 0x135F, T),
+    (0x1712, 0x1714, T), (0x1732, 0x1733, T), (0x1752, 0x1753, T), (0x1772, 0x1773, T),
+    (0x17B4, 0x17B5, T), (0x17B7, 0x17BD, T), (0x17C6, 0x17C6, T), (0x17C9, 0x17D3, T),
+    (0x17DD, 0x17DD, T), (0x1807, 0x1807, D), (0x180A, 0x180A, C), (0x180B, 0x180D, T),
+    (0x180F, 0x180F, T), (0x1820, 0x1878, D), (0x1885, 0x1886, T), (0x1887, 0x18A8, D),
+    (0x18A9, 0x18A9, T), (0x18AA, 0x18AA, D), (0x1920, 0x1922, T), (0x1927, 0x1928, T),
+    (0x1932, 0x1932, T), (0x1939, 0x193B, T), (0x1A17, 0x1A18, T), (0x1A1B, 0x1A1B, T),
+    (0x1A56, 0x1A56, T), (0x1A58, 0x1A5E, T), (0x1A60, 0x1A60, T), (0x1A62, 0x1A62, T),
+    (0x1A65, 0x1A6C, T), (0x1A73, 0x1A7C, T), (0x1A7F, 0x1A7F, T), (0x1AB0, 0x1ACE, T),
+    (0x1B00, 0x1B03, T), (0x1B34, 0x1B34, T), (0x1B36, 0x1B3A, T), (0x1B3C, 0x1B3C, T),
+    (0x1B42, 0x1B42, T), (0x1B6B, 0x1B73, T), (0x1B80, 0x1B81, T), (0x1BA2, 0x1BA5, T),
+    (0x1BA8, 0x1BA9, T), (0x1BAB, 0x1BAD, T), (0x1BE6, 0x1BE6, T), (0x1BE8, 0x1BE9, T),
+    (0x1BED, 0x1BED, T), (0x1BEF, 0x1BF1, T), (0x1C2C, 0x1C33, T), (0x1C36, 0x1C37, T),
+    (0x1CD0, 0x1CD2, T), (0x1CD4, 0x1CE0, T), (0x1CE2, 0x1CE8, T), (0x1CED, 0x1CED, T),
+    (0x1CF4, 0x1CF4, T), (0x1CF8, 0x1CF9, T), (0x1DC0, 0x1DFF, T), (0x200B, 0x200B, T),
+    (0x200D, 0x200D, C), (0x200E, 0x200F, T), (0x202A, 0x202E, T), (0x2060, 0x2064, T),
+    (0x206A, 0x206F, T), (0x20D0, 0x20F0, T), (0x2CEF, 0x2CF1, T), (0x2D7F, 0x2D7F, T),
+    (0x2DE0, 0x2DFF, T), (0x302A, 0x302D, T), (0x3099, 0x309A, T), (0xA66F, 0xA672, T),
+    (0xA674, 0xA67D, T), (0xA69E, 0xA69F, T), (0xA6F0, 0xA6F1, T), (0xA802, 0xA802, T),
+    (0xA806, 0xA806, T), (0xA80B, 0xA80B, T), (0xA825, 0xA826, T), (0xA82C, 0xA82C, T),
+    (0xA840, 0xA871, D), (0xA872, 0xA872, L), (0xA8C4, 0xA8C5, T), (0xA8E0, 0xA8F1, T),
+    (0xA8FF, 0xA8FF, T), (0xA926, 0xA92D, T), (0xA947, 0xA951, T), (0xA980, 0xA982, T),
+    (0xA9B3, 0xA9B3, T), (0xA9B6, 0xA9B9, T), (0xA9BC, 0xA9BD, T), (0xA9E5, 0xA9E5, T),
+    (0xAA29, 0xAA2E, T), (0xAA31, 0xAA32, T), (0xAA35, 0xAA36, T), (0xAA43, 0xAA43, T),
+    (0xAA4C, 0xAA4C, T), (0xAA7C, 0xAA7C, T), (0xAAB0, 0xAAB0, T), (0xAAB2, 0xAAB4, T),
+    (0xAAB7, 0xAAB8, T), (0xAABE, 0xAABF, T), (0xAAC1, 0xAAC1, T), (0xAAEC, 0xAAED, T),
+    (0xAAF6, 0xAAF6, T), (0xABE5, 0xABE5, T), (0xABE8, 0xABE8, T), (0xABED, 0xABED, T),
+    (0xFB1E, 0xFB1E, T), (0xFE00, 0xFE0F, T), (0xFE20, 0xFE2F, T), (0xFEFF, 0xFEFF, T),
+    (0xFFF9, 0xFFFB, T), (0x101FD, 0x101FD, T), (0x102E0, 0x102E0, T), (0x10376, 0x1037A, T),
+    (0x10A01, 0x10A03, T), (0x10A05, 0x10A06, T), (0x10A0C, 0x10A0F, T), (0x10A38, 0x10A3A, T),
+    (0x10A3F, 0x10A3F, T), (0x10AC0, 0x10AC4, D), (0x10AC5, 0x10AC5, R), (0x10AC7, 0x10AC7, R),
+    (0x10AC9, 0x10ACA, R), (0x10ACD, 0x10ACD, L), (0x10ACE, 0x10AD2, R), (0x10AD3, 0x10AD6, D),
+    (0x10AD7, 0x10AD7, L), (0x10AD8, 0x10ADC, D), (0x10ADD, 0x10ADD, R), (0x10ADE, 0x10AE0, D),
+    (0x10AE1, 0x10AE1, R), (0x10AE4, 0x10AE4, R), (0x10AE5, 0x10AE6, T), (0x10AEB, 0x10AEE, D),
+    (0x10AEF, 0x10AEF, R), (0x10B80, 0x10B80, D), (0x10B81, 0x10B81, R), (0x10B82, 0x10B82, D),
+    (0x10B83, 0x10B85, R), (0x10B86, 0x10B88, D), (0x10B89, 0x10B89, R), (0x10B8A, 0x10B8B, D),
+    (0x10B8C, 0x10B8C, R), (0x10B8D, 0x10B8D, D), (0x10B8E, 0x10B8F, R), (0x10B90, 0x10B90, D),
+    (0x10B91, 0x10B91, R), (0x10BA9, 0x10BAC, R), (0x10BAD, 0x10BAE, D), (0x10D00, 0x10D00, L),
+    (0x10D01, 0x10D21, D), (0x10D22, 0x10D22, R), (0x10D23, 0x10D23, D), (0x10D24, 0x10D27, T),
+    (0x10EAB, 0x10EAC, T), (0x10F30, 0x10F32, D), (0x10F33, 0x10F33, R), (0x10F34, 0x10F44, D),
+    (0x10F46, 0x10F50, T), (0x10F51, 0x10F53, D), (0x10F54, 0x10F54, R), (0x10F70, 0x10F73, D),
+    (0x10F74, 0x10F75, R), (0x10F76, 0x10F81, D), (0x10F82, 0x10F85, T), (0x10FB0, 0x10FB0, D),
+    (0x10FB2, 0x10FB3, D), (0x10FB4, 0x10FB6, R), (0x10FB8, 0x10FB8, D), (0x10FB9, 0x10FBA, R),
+    (0x10FBB, 0x10FBC, D), (0x10FBD, 0x10FBD, R), (0x10FBE, 0x10FBF, D), (0x10FC1, 0x10FC1, D),
+    (0x10FC2, 0x10FC3, R), (0x10FC4, 0x10FC4, D), (0x10FC9, 0x10FC9, R), (0x10FCA, 0x10FCA, D),
+    (0x10FCB, 0x10FCB, L), (0x11001, 0x11001, T), (0x11038, 0x11046, T), (0x11070, 0x11070, T),
+    (0x11073, 0x11074, T), (0x1107F, 0x11081, T), (0x110B3, 0x110B6, T), (0x110B9, 0x110BA, T),
+    (0x110C2, 0x110C2, T), (0x11100, 0x11102, T), (0x11127, 0x1112B, T), (0x1112D, 0x11134, T),
+    (0x11173, 0x11173, T), (0x11180, 0x11181, T), (0x111B6, 0x111BE, T), (0x111C9, 0x111CC, T),
+    (0x111CF, 0x111CF, T), (0x1122F, 0x11231, T), (0x11234, 0x11234, T), (0x11236, 0x11237, T),
+    (0x1123E, 0x1123E, T), (0x112DF, 0x112DF, T), (0x112E3, 0x112EA, T), (0x11300, 0x11301, T),
+    (0x1133B, 0x1133C, T), (0x11340, 0x11340, T), (0x11366, 0x1136C, T), (0x11370, 0x11374, T),
+    (0x11438, 0x1143F, T), (0x11442, 0x11444, T), (0x11446, 0x11446, T), (0x1145E, 0x1145E, T),
+    (0x114B3, 0x114B8, T), (0x114BA, 0x114BA, T), (0x114BF, 0x114C0, T), (0x114C2, 0x114C3, T),
+    (0x115B2, 0x115B5, T), (0x115BC, 0x115BD, T), (0x115BF, 0x115C0, T), (0x115DC, 0x115DD, T),
+    (0x11633, 0x1163A, T), (0x1163D, 0x1163D, T), (0x1163F, 0x11640, T), (0x116AB, 0x116AB, T),
+    (0x116AD, 0x116AD, T), (0x116B0, 0x116B5, T), (0x116B7, 0x116B7, T), (0x1171D, 0x1171F, T),
+    (0x11722, 0x11725, T), (0x11727, 0x1172B, T), (0x1182F, 0x11837, T), (0x11839, 0x1183A, T),
+    (0x1193B, 0x1193C, T), (0x1193E, 0x1193E, T), (0x11943, 0x11943, T), (0x119D4, 0x119D7, T),
+    (0x119DA, 0x119DB, T), (0x119E0, 0x119E0, T), (0x11A01, 0x11A0A, T), (0x11A33, 0x11A38, T),
+    (0x11A3B, 0x11A3E, T), (0x11A47, 0x11A47, T), (0x11A51, 0x11A56, T), (0x11A59, 0x11A5B, T),
+    (0x11A8A, 0x11A96, T), (0x11A98, 0x11A99, T), (0x11C30, 0x11C36, T), (0x11C38, 0x11C3D, T),
+    (0x11C3F, 0x11C3F, T), (0x11C92, 0x11CA7, T), (0x11CAA, 0x11CB0, T), (0x11CB2, 0x11CB3, T),
+    (0x11CB5, 0x11CB6, T), (0x11D31, 0x11D36, T), (0x11D3A, 0x11D3A, T), (0x11D3C, 0x11D3D, T),
+    (0x11D3F, 0x11D45, T), (0x11D47, 0x11D47, T), (0x11D90, 0x11D91, T), (0x11D95, 0x11D95, T),
+    (0x11D97, 0x11D97, T), (0x11EF3, 0x11EF4, T), (0x13430, 0x13438, T), (0x16AF0, 0x16AF4, T),
+    (0x16B30, 0x16B36, T), (0x16F4F, 0x16F4F, T), (0x16F8F, 0x16F92, T), (0x16FE4, 0x16FE4, T),
+    (0x1BC9D, 0x1BC9E, T), (0x1BCA0, 0x1BCA3, T), (0x1CF00, 0x1CF2D, T), (0x1CF30, 0x1CF46, T),
+    (0x1D167, 0x1D169, T), (0x1D173, 0x1D182, T), (0x1D185, 0x1D18B, T), (0x1D1AA, 0x1D1AD, T),
+    (0x1D242, 0x1D244, T), (0x1DA00, 0x1DA36, T), (0x1DA3B, 0x1DA6C, T), (0x1DA75, 0x1DA75, T),
+    (0x1DA84, 0x1DA84, T), (0x1DA9B, 0x1DA9F, T), (0x1DAA1, 0x1DAAF, T), (0x1E000, 0x1E006, T),
+    (0x1E008, 0x1E018, T), (0x1E01B, 0x1E021, T), (0x1E023, 0x1E024, T), (0x1E026, 0x1E02A, T),
+    (0x1E130, 0x1E136, T), (0x1E2AE, 0x1E2AE, T), (0x1E2EC, 0x1E2EF, T), (0x1E8D0, 0x1E8D6, T),
+    (0x1E900, 0x1E943, D), (0x1E944, 0x1E94B, T), (0xE0001, 0xE0001, T), (0xE0020, 0xE007F, T),
+    (0xE0100, 0xE01EF, T),
+];
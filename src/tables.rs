@@ -1,10 +1,23 @@
 pub mod cmap;
+pub mod cvt;
+pub mod fpgm;
+pub mod gasp;
+pub mod gdef;
 pub mod glyf;
+pub mod gpos;
+pub mod gsub;
 pub mod head;
 pub mod hhea;
 pub mod hmtx;
+pub mod kern;
+pub mod layout;
 pub mod loca;
 pub mod maxp;
 pub mod name;
 pub mod offset;
 pub mod offset_sub;
+pub mod os2;
+pub mod post;
+pub mod prep;
+pub mod vhea;
+pub mod vmtx;
@@ -1,8 +1,11 @@
+use crate::error::Result;
 use crate::reader;
 use crate::tables::offset::OffsetTable;
+use crate::GlyphId;
+mod lookup;
 mod processor;
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum CmapPlatform {
     Unicode,   // Indicates Unicode version
     Macintosh, // Script Manager code.
@@ -20,6 +23,7 @@ pub struct CmapEncoding {
     pub platform_id: CmapPlatform, // Platform identifier
     pub platform_specific_id: u16, // Platform-specific encoding identifier
     pub offset: u32,               // Offset of the mapping table
+    pub format_table: usize,       // Index of the parsed mapping table in Cmap::format_tables
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -31,11 +35,21 @@ pub struct CmapFormat0 {
 }
 
 #[derive(Debug, Copy, Clone)]
+pub struct CmapFormat2SubHeader {
+    pub first_code: u16,      // First valid low byte for this subHeader
+    pub entry_count: u16,     // Number of valid low bytes for this subHeader
+    pub id_delta: u16,        // Delta added to non zero glyphs from the glyph index array
+    pub id_range_offset: u16, // Offset in bytes from this field to the glyph index for first_code
+}
+
+#[derive(Debug, Clone)]
 pub struct CmapFormat2 {
     pub format: u16,
     pub length: u16,
     pub language_code: u16,
     pub sub_header_keys: [u16; 256], // Array that maps high bytes to subHeaders: value is index * 8
+    pub sub_headers: Vec<CmapFormat2SubHeader>, // subHeader 0 is used for single byte codes
+    pub glyph_index_array: Vec<u16>, // Glyph index array, indexed through id_range_offset
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -56,6 +70,7 @@ pub struct CmapFormat4 {
     pub entry_selector: u16, // log2(searchRange/2)
     pub range_shift: u16,    // (2 * segCount) - searchRange
     pub segments: Vec<CmapFormat4Segment>,
+    pub glyph_index_array: Vec<u16>, // Glyph index array, indexed through id_range_offset
 }
 
 #[derive(Debug, Clone)]
@@ -106,6 +121,55 @@ pub struct CmapFormat120 {
     pub groups: Vec<CmapFormat120Group>,
 }
 
+pub type CmapFormat130Group = CmapFormat80Group; // start_glyph_code is used for the whole group
+
+#[derive(Debug, Clone)]
+pub struct CmapFormat130 {
+    pub format: f32,
+    pub length: u32,
+    pub language_code: u32,
+    pub n_groups: u32, // Number of groupings which follow
+    pub groups: Vec<CmapFormat130Group>,
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct CmapFormat140DefaultRange {
+    pub start_unicode_value: u32, // First value in this range
+    pub additional_count: u8,     // Number of additional values in this range
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct CmapFormat140Mapping {
+    pub unicode_value: u32, // Base Unicode value of the UVS
+    pub glyph_id: u16,      // Glyph ID of the UVS
+}
+
+#[derive(Debug, Clone)]
+pub struct CmapFormat140Record {
+    pub var_selector: u32,                           // Variation selector
+    pub default_uvs_offset: u32,                     // Offset to Default UVS Table, may be 0
+    pub non_default_uvs_offset: u32,                 // Offset to Non-Default UVS Table, may be 0
+    pub default_uvs: Vec<CmapFormat140DefaultRange>, // Sequences using the default glyph
+    pub non_default_uvs: Vec<CmapFormat140Mapping>,  // Sequences with a glyph of their own
+}
+
+#[derive(Debug, Clone)]
+pub struct CmapFormat140 {
+    pub format: u16,                   // Set to 14, not a fixed point number unlike 13.0
+    pub length: u32,                   // Byte length of this subtable (including this header)
+    pub num_var_selector_records: u32, // Number of variation Selector Records
+    pub var_selector_records: Vec<CmapFormat140Record>, // Sorted by increasing var_selector
+}
+
+// What a variation sequence resolves to in a format 14 subtable
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CmapVariation {
+    Default,        // Use whatever the regular cmap maps the base character to
+    Glyph(GlyphId), // The sequence has a glyph of its own
+}
+
+// The fixed size lookup arrays make the variants lopsided, boxing them buys nothing here
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug, Clone)]
 pub enum CmapFormatTable {
     Format0(CmapFormat0),
@@ -115,18 +179,20 @@ pub enum CmapFormatTable {
     Format80(CmapFormat80),
     Format100(CmapFormat100),
     Format120(CmapFormat120),
-    // TODO: No idea about Format13.0 and Format14.0, will hack them another day not now
+    Format130(CmapFormat130),
+    Format140(CmapFormat140),
 }
 
 #[derive(Debug, Clone)]
 pub struct Cmap {
     pub index: CmapIndex,
     pub encodings: Vec<CmapEncoding>,
-    pub format_table: CmapFormatTable,
+    pub format_tables: Vec<CmapFormatTable>, // Encodings sharing an offset share the table
+    pub preferred_encoding: Option<usize>,   // Index of the encoding used for lookups
 }
 
-pub fn read(r: &mut reader::FontReader, cmap_offset_table: OffsetTable) -> Option<Cmap> {
-    let _ = r.seek(cmap_offset_table.offset as usize);
+pub fn read(r: &mut reader::FontReader, cmap_offset_table: OffsetTable) -> Result<Cmap> {
+    r.seek_table("cmap", cmap_offset_table.offset as usize)?;
 
     let version = r.get_uint16()?;
     let subtable_count = r.get_uint16()?;
@@ -136,12 +202,35 @@ pub fn read(r: &mut reader::FontReader, cmap_offset_table: OffsetTable) -> Optio
         subtable_count,
     };
 
-    let encodings = processor::cmap_encoding_tables(r, subtable_count)?;
-    let format_table = processor::cmap_format_table(r)?;
-
-    return Some(Cmap {
+    let mut encodings = Vec::new();
+    let mut format_tables = Vec::new();
+    let mut offsets: Vec<u32> = Vec::new();
+
+    for mut encoding in processor::cmap_encoding_tables(r, subtable_count)? {
+        if let Some(index) = offsets.iter().position(|&offset| offset == encoding.offset) {
+            encoding.format_table = index;
+            encodings.push(encoding);
+            continue;
+        }
+
+        // an encoding whose subtable can't be read is left out, the others still work
+        let offset = cmap_offset_table.offset.wrapping_add(encoding.offset) as usize;
+        let format_table = match r.seek(offset).and_then(|_| processor::cmap_format_table(r)) {
+            Ok(val) => val,
+            Err(_) => continue,
+        };
+        format_tables.push(format_table);
+        offsets.push(encoding.offset);
+        encoding.format_table = format_tables.len() - 1;
+        encodings.push(encoding);
+    }
+
+    let preferred_encoding = lookup::preferred_encoding(&encodings);
+
+    return Ok(Cmap {
         index,
         encodings,
-        format_table,
+        format_tables,
+        preferred_encoding,
     });
 }
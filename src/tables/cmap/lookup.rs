@@ -0,0 +1,326 @@
+use crate::tables::cmap;
+use crate::GlyphId;
+use std::cmp::Ordering;
+
+// Glyph 0 means the character is missing, so it never makes it out of a lookup
+fn glyph_id(glyph: u32) -> Option<GlyphId> {
+    match glyph {
+        0 => return None,
+        val if val > u16::MAX as u32 => return None,
+        val => return Some(GlyphId(val as u16)),
+    }
+}
+
+fn find_group(groups: &[cmap::CmapFormat80Group], code: u32) -> Option<GlyphId> {
+    let index = groups
+        .binary_search_by(|group| {
+            if group.end_char_code < code {
+                Ordering::Less
+            } else if group.start_char_code > code {
+                Ordering::Greater
+            } else {
+                Ordering::Equal
+            }
+        })
+        .ok()?;
+
+    let group = groups[index];
+    return glyph_id(
+        group
+            .start_glyph_code
+            .checked_add(code - group.start_char_code)?,
+    );
+}
+
+impl cmap::CmapFormat0 {
+    pub fn glyph_index(&self, code: u32) -> Option<GlyphId> {
+        return glyph_id(*self.glyph_index_array.get(code as usize)? as u32);
+    }
+}
+
+impl cmap::CmapFormat2 {
+    fn sub_header_glyph(&self, sub_header_index: usize, low_byte: u8) -> Option<GlyphId> {
+        let sub_header = self.sub_headers.get(sub_header_index)?;
+        let index = (low_byte as u16).checked_sub(sub_header.first_code)?;
+        if index >= sub_header.entry_count {
+            return None;
+        }
+
+        // id_range_offset counts from its own field, which is 6 bytes into the subHeader,
+        // the glyph index array starts right after the last subHeader
+        let field_offset = sub_header_index * 8 + 6;
+        let array_start = self.sub_headers.len() * 8;
+        let array_index =
+            (field_offset + sub_header.id_range_offset as usize).checked_sub(array_start)? / 2
+                + index as usize;
+        let glyph = *self.glyph_index_array.get(array_index)?;
+
+        if glyph == 0 {
+            return None;
+        }
+
+        return glyph_id(glyph.wrapping_add(sub_header.id_delta) as u32);
+    }
+
+    // Whether the byte starts a two byte character, single byte ones map to subHeader 0
+    pub fn is_lead_byte(&self, byte: u8) -> bool {
+        return self.sub_header_keys[byte as usize] != 0;
+    }
+
+    // Looks up the character at the start of a mixed 8/16-bit byte sequence, also
+    // returning the number of bytes it took so the caller can move on to the next one
+    pub fn glyph_index_for_bytes(&self, bytes: &[u8]) -> (Option<GlyphId>, usize) {
+        let high_byte = match bytes.first() {
+            Some(&val) => val,
+            None => return (None, 0),
+        };
+
+        if !self.is_lead_byte(high_byte) {
+            return (self.sub_header_glyph(0, high_byte), 1);
+        }
+
+        match bytes.get(1) {
+            Some(&low_byte) => {
+                let sub_header_index = (self.sub_header_keys[high_byte as usize] / 8) as usize;
+                return (self.sub_header_glyph(sub_header_index, low_byte), 2);
+            }
+            // a lead byte without its trail byte is a broken sequence
+            None => return (None, 1),
+        }
+    }
+
+    pub fn glyph_indices_for_bytes(&self, bytes: &[u8]) -> Vec<Option<GlyphId>> {
+        let mut glyphs = Vec::new();
+        let mut pos = 0;
+
+        while pos < bytes.len() {
+            let (glyph, consumed) = self.glyph_index_for_bytes(&bytes[pos..]);
+            glyphs.push(glyph);
+            pos += consumed;
+        }
+
+        return glyphs;
+    }
+
+    // Character codes are the byte sequence read as a big endian number, so a single
+    // byte code is below 0x100 and a two byte code carries its lead byte up top
+    pub fn glyph_index(&self, code: u32) -> Option<GlyphId> {
+        if code < 0x100 {
+            if self.is_lead_byte(code as u8) {
+                return None;
+            }
+            return self.glyph_index_for_bytes(&[code as u8]).0;
+        }
+
+        if code > 0xFFFF {
+            return None;
+        }
+
+        let bytes = [(code >> 8) as u8, code as u8];
+        if !self.is_lead_byte(bytes[0]) {
+            return None;
+        }
+
+        return self.glyph_index_for_bytes(&bytes).0;
+    }
+}
+
+impl cmap::CmapFormat4 {
+    pub fn glyph_index(&self, code: u32) -> Option<GlyphId> {
+        if code > 0xFFFF {
+            return None;
+        }
+        let code = code as u16;
+
+        // segments are sorted by end code, the first one ending at or after the code is it
+        let index = self
+            .segments
+            .binary_search_by(|segment| segment.end_code.cmp(&code))
+            .unwrap_or_else(|index| index);
+        let segment = self.segments.get(index)?;
+
+        if segment.start_code > code {
+            return None;
+        }
+
+        if segment.id_range_offset == 0 {
+            return glyph_id(code.wrapping_add(segment.id_delta) as u32);
+        }
+
+        // id_range_offset is relative to its own slot in the idRangeOffset array, which sits
+        // (segcount - index) words before the start of the glyph index array
+        let array_index =
+            (segment.id_range_offset / 2) as usize + (code - segment.start_code) as usize;
+        let array_index = array_index.checked_sub(self.segments.len() - index)?;
+        let glyph = *self.glyph_index_array.get(array_index)?;
+
+        if glyph == 0 {
+            return None;
+        }
+
+        return glyph_id(glyph.wrapping_add(segment.id_delta) as u32);
+    }
+}
+
+impl cmap::CmapFormat6 {
+    pub fn glyph_index(&self, code: u32) -> Option<GlyphId> {
+        let index = code.checked_sub(self.first_code as u32)?;
+        return glyph_id(*self.glyph_index_array.get(index as usize)? as u32);
+    }
+}
+
+impl cmap::CmapFormat80 {
+    pub fn glyph_index(&self, code: u32) -> Option<GlyphId> {
+        return find_group(&self.groups, code);
+    }
+}
+
+impl cmap::CmapFormat100 {
+    pub fn glyph_index(&self, code: u32) -> Option<GlyphId> {
+        let index = code.checked_sub(self.start_char_code)?;
+        if index >= self.num_chars {
+            return None;
+        }
+
+        return glyph_id(*self.glyphs.get(index as usize)? as u32);
+    }
+}
+
+impl cmap::CmapFormat120 {
+    pub fn glyph_index(&self, code: u32) -> Option<GlyphId> {
+        return find_group(&self.groups, code);
+    }
+}
+
+impl cmap::CmapFormat130 {
+    pub fn glyph_index(&self, code: u32) -> Option<GlyphId> {
+        let group = self
+            .groups
+            .iter()
+            .find(|group| group.start_char_code <= code && code <= group.end_char_code)?;
+
+        return glyph_id(group.start_glyph_code);
+    }
+}
+
+impl cmap::CmapFormat140 {
+    pub fn variation(&self, base: u32, selector: u32) -> Option<cmap::CmapVariation> {
+        let index = self
+            .var_selector_records
+            .binary_search_by(|record| record.var_selector.cmp(&selector))
+            .ok()?;
+        let record = &self.var_selector_records[index];
+
+        let is_default = record.default_uvs.iter().any(|range| {
+            range.start_unicode_value <= base
+                && base <= range.start_unicode_value + range.additional_count as u32
+        });
+        if is_default {
+            return Some(cmap::CmapVariation::Default);
+        }
+
+        let index = record
+            .non_default_uvs
+            .binary_search_by(|mapping| mapping.unicode_value.cmp(&base))
+            .ok()?;
+
+        return Some(cmap::CmapVariation::Glyph(GlyphId(
+            record.non_default_uvs[index].glyph_id,
+        )));
+    }
+}
+
+impl cmap::CmapFormatTable {
+    pub fn glyph_index(&self, code: u32) -> Option<GlyphId> {
+        match self {
+            cmap::CmapFormatTable::Format0(table) => return table.glyph_index(code),
+            cmap::CmapFormatTable::Format2(table) => return table.glyph_index(code),
+            cmap::CmapFormatTable::Format4(table) => return table.glyph_index(code),
+            cmap::CmapFormatTable::Format6(table) => return table.glyph_index(code),
+            cmap::CmapFormatTable::Format80(table) => return table.glyph_index(code),
+            cmap::CmapFormatTable::Format100(table) => return table.glyph_index(code),
+            cmap::CmapFormatTable::Format120(table) => return table.glyph_index(code),
+            cmap::CmapFormatTable::Format130(table) => return table.glyph_index(code),
+            // variation sequences need a selector, see Cmap::glyph_index_for_variation
+            cmap::CmapFormatTable::Format140(_) => return None,
+        }
+    }
+}
+
+// Lower is better: Unicode full repertoire > Windows UCS-4 > Windows BMP > Unicode BMP >
+// Windows symbol > Mac Roman > Unicode last resort. Anything else can't be looked up with
+// a char, including the Unicode variation sequences (0, 5).
+fn encoding_rank(encoding: &cmap::CmapEncoding) -> Option<u8> {
+    match (encoding.platform_id, encoding.platform_specific_id) {
+        (cmap::CmapPlatform::Unicode, 4) => return Some(0),
+        (cmap::CmapPlatform::Microsoft, 10) => return Some(1),
+        (cmap::CmapPlatform::Microsoft, 1) => return Some(2),
+        (cmap::CmapPlatform::Unicode, 0..=3) => return Some(3),
+        (cmap::CmapPlatform::Microsoft, 0) => return Some(4),
+        (cmap::CmapPlatform::Macintosh, 0) => return Some(5),
+        // last resort fonts map whole ranges to a single glyph
+        (cmap::CmapPlatform::Unicode, 6) => return Some(6),
+        _ => return None,
+    }
+}
+
+pub fn preferred_encoding(encodings: &[cmap::CmapEncoding]) -> Option<usize> {
+    return encodings
+        .iter()
+        .enumerate()
+        .filter_map(|(index, encoding)| Some((encoding_rank(encoding)?, index)))
+        .min()
+        .map(|(_, index)| index);
+}
+
+impl cmap::Cmap {
+    pub fn preferred_encoding(&self) -> Option<&cmap::CmapEncoding> {
+        return self.encodings.get(self.preferred_encoding?);
+    }
+
+    pub fn preferred_table(&self) -> Option<&cmap::CmapFormatTable> {
+        return self
+            .format_tables
+            .get(self.preferred_encoding()?.format_table);
+    }
+
+    pub fn table(
+        &self,
+        platform_id: cmap::CmapPlatform,
+        platform_specific_id: u16,
+    ) -> Option<&cmap::CmapFormatTable> {
+        let encoding = self.encodings.iter().find(|encoding| {
+            encoding.platform_id == platform_id
+                && encoding.platform_specific_id == platform_specific_id
+        })?;
+
+        return self.format_tables.get(encoding.format_table);
+    }
+
+    pub fn glyph_index(&self, c: char) -> Option<GlyphId> {
+        return self.preferred_table()?.glyph_index(c as u32);
+    }
+
+    pub fn variation_table(&self) -> Option<&cmap::CmapFormat140> {
+        return self
+            .encodings
+            .iter()
+            .filter_map(|encoding| self.format_tables.get(encoding.format_table))
+            .find_map(|table| match table {
+                cmap::CmapFormatTable::Format140(table) => Some(table),
+                _ => None,
+            });
+    }
+
+    // Default sequences fall back to the regular mapping of the base character, a selector
+    // the font knows nothing about for this base gives None
+    pub fn glyph_index_for_variation(&self, base: char, selector: char) -> Option<GlyphId> {
+        match self
+            .variation_table()?
+            .variation(base as u32, selector as u32)?
+        {
+            cmap::CmapVariation::Default => return self.glyph_index(base),
+            cmap::CmapVariation::Glyph(glyph) => return Some(glyph),
+        }
+    }
+}
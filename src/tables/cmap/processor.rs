@@ -1,25 +1,25 @@
+use crate::error::{Error, Result};
 use crate::reader;
 use crate::tables::cmap;
 
 pub fn cmap_encoding_tables(
     r: &mut reader::FontReader,
     count: u16,
-) -> Option<Vec<cmap::CmapEncoding>> {
+) -> Result<Vec<cmap::CmapEncoding>> {
     let mut encoding_tables = Vec::new();
     for _ in 0..count {
+        let platform_offset = r.pos;
         let platform_id = match r.get_uint16()? {
             0 => cmap::CmapPlatform::Unicode,
             1 => cmap::CmapPlatform::Macintosh,
-            2 => {
-                println!(
-                    "2 is not advised to be used a platform id and reserved for specific purpose"
-                );
-                return None;
-            }
+            // 2 is not advised to be used a platform id and reserved for specific purpose
             3 => cmap::CmapPlatform::Microsoft,
             val => {
-                println!("{} is not a valid platform id.", val);
-                return None;
+                return Err(Error::InvalidPlatformId {
+                    table: "cmap".into(),
+                    offset: platform_offset,
+                    platform_id: val,
+                });
             }
         };
 
@@ -30,22 +30,23 @@ pub fn cmap_encoding_tables(
             platform_id,
             platform_specific_id,
             offset,
+            format_table: 0, // filled in once the subtables are read
         });
     }
 
-    return Some(encoding_tables);
+    return Ok(encoding_tables);
 }
 
-pub fn read_cmap_format0(r: &mut reader::FontReader) -> Option<cmap::CmapFormat0> {
+pub fn read_cmap_format0(r: &mut reader::FontReader) -> Result<cmap::CmapFormat0> {
     let format = 0;
     let length = r.get_uint16()?;
     let language_code = r.get_uint16()?;
     let mut glyph_index_array = [0; 256];
-    for i in 0..256 {
-        glyph_index_array[i] = r.get_uint8()?;
+    for glyph_index in glyph_index_array.iter_mut() {
+        *glyph_index = r.get_uint8()?;
     }
 
-    return Some(cmap::CmapFormat0 {
+    return Ok(cmap::CmapFormat0 {
         format,
         length,
         language_code,
@@ -53,24 +54,46 @@ pub fn read_cmap_format0(r: &mut reader::FontReader) -> Option<cmap::CmapFormat0
     });
 }
 
-pub fn read_cmap_format2(r: &mut reader::FontReader) -> Option<cmap::CmapFormat2> {
+pub fn read_cmap_format2(r: &mut reader::FontReader) -> Result<cmap::CmapFormat2> {
     let format = 2;
     let length = r.get_uint16()?;
     let language_code = r.get_uint16()?;
     let mut sub_header_keys = [0; 256];
-    for i in 0..256 {
-        sub_header_keys[i] = r.get_uint16()?;
+    for key in sub_header_keys.iter_mut() {
+        *key = r.get_uint16()?;
+    }
+
+    // there are as many subHeaders as the largest key points to
+    let sub_header_count = sub_header_keys.iter().max().unwrap_or(&0) / 8 + 1;
+    let mut sub_headers = Vec::new();
+
+    for _ in 0..sub_header_count {
+        sub_headers.push(cmap::CmapFormat2SubHeader {
+            first_code: r.get_uint16()?,
+            entry_count: r.get_uint16()?,
+            id_delta: r.get_uint16()?,
+            id_range_offset: r.get_uint16()?,
+        });
+    }
+
+    // whatever is left of the subtable after the subHeaders is the glyph index array
+    let header_length = 6 + 512 + sub_header_count as usize * 8;
+    let mut glyph_index_array = Vec::new();
+    for _ in 0..((length as usize).saturating_sub(header_length) / 2) {
+        glyph_index_array.push(r.get_uint16()?);
     }
 
-    return Some(cmap::CmapFormat2 {
+    return Ok(cmap::CmapFormat2 {
         format,
         length,
         language_code,
         sub_header_keys,
+        sub_headers,
+        glyph_index_array,
     });
 }
 
-pub fn read_cmap_format4(r: &mut reader::FontReader) -> Option<cmap::CmapFormat4> {
+pub fn read_cmap_format4(r: &mut reader::FontReader) -> Result<cmap::CmapFormat4> {
     let format = 4;
     let length = r.get_uint16()?;
     let language_code = r.get_uint16()?;
@@ -93,19 +116,26 @@ pub fn read_cmap_format4(r: &mut reader::FontReader) -> Option<cmap::CmapFormat4
 
     r.get_uint16()?; // reservedPad
 
-    for i in 0..segcount as usize {
-        segments[i].start_code = r.get_uint16()?;
+    for segment in segments.iter_mut() {
+        segment.start_code = r.get_uint16()?;
     }
 
-    for i in 0..segcount as usize {
-        segments[i].id_delta = r.get_uint16()?;
+    for segment in segments.iter_mut() {
+        segment.id_delta = r.get_uint16()?;
     }
 
-    for i in 0..segcount as usize {
-        segments[i].id_range_offset = r.get_uint16()?;
+    for segment in segments.iter_mut() {
+        segment.id_range_offset = r.get_uint16()?;
+    }
+
+    // whatever is left of the subtable after the segments is the glyph index array
+    let header_length = 16 + segcount as usize * 8;
+    let mut glyph_index_array = Vec::new();
+    for _ in 0..((length as usize).saturating_sub(header_length) / 2) {
+        glyph_index_array.push(r.get_uint16()?);
     }
 
-    return Some(cmap::CmapFormat4 {
+    return Ok(cmap::CmapFormat4 {
         format,
         length,
         language_code,
@@ -114,10 +144,11 @@ pub fn read_cmap_format4(r: &mut reader::FontReader) -> Option<cmap::CmapFormat4
         entry_selector,
         range_shift,
         segments,
+        glyph_index_array,
     });
 }
 
-pub fn read_cmap_format6(r: &mut reader::FontReader) -> Option<cmap::CmapFormat6> {
+pub fn read_cmap_format6(r: &mut reader::FontReader) -> Result<cmap::CmapFormat6> {
     let format = 6;
     let length = r.get_uint16()?;
     let language_code = r.get_uint16()?;
@@ -129,7 +160,7 @@ pub fn read_cmap_format6(r: &mut reader::FontReader) -> Option<cmap::CmapFormat6
         glyph_index_array.push(r.get_uint16()?);
     }
 
-    return Some(cmap::CmapFormat6 {
+    return Ok(cmap::CmapFormat6 {
         format,
         length,
         language_code,
@@ -139,15 +170,15 @@ pub fn read_cmap_format6(r: &mut reader::FontReader) -> Option<cmap::CmapFormat6
     });
 }
 
-pub fn read_cmap_format80(r: &mut reader::FontReader) -> Option<cmap::CmapFormat80> {
+pub fn read_cmap_format80(r: &mut reader::FontReader) -> Result<cmap::CmapFormat80> {
     let format = 8.0;
     r.get_uint16()?; // just for that 32 bit float thing
     let length = r.get_uint32()?;
     let language_code = r.get_uint32()?;
     let mut is_32 = [0; 8192];
 
-    for i in 0..8192 {
-        is_32[i] = r.get_uint8()?;
+    for byte in is_32.iter_mut() {
+        *byte = r.get_uint8()?;
     }
 
     let n_groups = r.get_uint32()?;
@@ -161,7 +192,7 @@ pub fn read_cmap_format80(r: &mut reader::FontReader) -> Option<cmap::CmapFormat
         });
     }
 
-    return Some(cmap::CmapFormat80 {
+    return Ok(cmap::CmapFormat80 {
         format,
         length,
         language_code,
@@ -171,21 +202,21 @@ pub fn read_cmap_format80(r: &mut reader::FontReader) -> Option<cmap::CmapFormat
     });
 }
 
-pub fn read_cmap_format100(r: &mut reader::FontReader) -> Option<cmap::CmapFormat100> {
+pub fn read_cmap_format100(r: &mut reader::FontReader) -> Result<cmap::CmapFormat100> {
     let format = 10.0;
     r.get_uint16()?; // just for that 32 bit float thing
     let length = r.get_uint32()?;
     let language_code = r.get_uint32()?;
     let start_char_code = r.get_uint32()?;
     let num_chars = r.get_uint32()?;
-    let remaining_length = length - 20; // 5 * 4 byte entries
+    let remaining_length = length.saturating_sub(20); // 5 * 4 byte entries
     let mut glyphs = Vec::new();
     for _ in 0..(remaining_length / 2) {
         // a glyph is 2 byte
         glyphs.push(r.get_uint16()?);
     }
 
-    return Some(cmap::CmapFormat100 {
+    return Ok(cmap::CmapFormat100 {
         format,
         length,
         language_code,
@@ -195,8 +226,8 @@ pub fn read_cmap_format100(r: &mut reader::FontReader) -> Option<cmap::CmapForma
     });
 }
 
-pub fn read_cmap_format120(r: &mut reader::FontReader) -> Option<cmap::CmapFormat120> {
-    let format = 10.0;
+pub fn read_cmap_format120(r: &mut reader::FontReader) -> Result<cmap::CmapFormat120> {
+    let format = 12.0;
     r.get_uint16()?; // just for that 32 bit float thing
     let length = r.get_uint32()?;
     let language_code = r.get_uint32()?;
@@ -211,7 +242,7 @@ pub fn read_cmap_format120(r: &mut reader::FontReader) -> Option<cmap::CmapForma
         });
     }
 
-    return Some(cmap::CmapFormat120 {
+    return Ok(cmap::CmapFormat120 {
         format,
         length,
         language_code,
@@ -220,7 +251,85 @@ pub fn read_cmap_format120(r: &mut reader::FontReader) -> Option<cmap::CmapForma
     });
 }
 
-pub fn cmap_format_table(r: &mut reader::FontReader) -> Option<cmap::CmapFormatTable> {
+pub fn read_cmap_format130(r: &mut reader::FontReader) -> Result<cmap::CmapFormat130> {
+    let format = 13.0;
+    r.get_uint16()?; // just for that 32 bit float thing
+    let length = r.get_uint32()?;
+    let language_code = r.get_uint32()?;
+    let n_groups = r.get_uint32()?;
+    let mut groups = Vec::new();
+
+    for _ in 0..n_groups {
+        groups.push(cmap::CmapFormat130Group {
+            start_char_code: r.get_uint32()?,
+            end_char_code: r.get_uint32()?,
+            start_glyph_code: r.get_uint32()?,
+        });
+    }
+
+    return Ok(cmap::CmapFormat130 {
+        format,
+        length,
+        language_code,
+        n_groups,
+        groups,
+    });
+}
+
+// The UVS tables are pointed to from the start of the subtable, hence the extra offset
+pub fn read_cmap_format140(
+    r: &mut reader::FontReader,
+    subtable_offset: usize,
+) -> Result<cmap::CmapFormat140> {
+    let format = 14;
+    let length = r.get_uint32()?;
+    let num_var_selector_records = r.get_uint32()?;
+    let mut var_selector_records = Vec::new();
+
+    for _ in 0..num_var_selector_records {
+        var_selector_records.push(cmap::CmapFormat140Record {
+            var_selector: r.get_uint24()?,
+            default_uvs_offset: r.get_uint32()?,
+            non_default_uvs_offset: r.get_uint32()?,
+            default_uvs: Vec::new(),
+            non_default_uvs: Vec::new(),
+        });
+    }
+
+    for record in var_selector_records.iter_mut() {
+        if record.default_uvs_offset != 0 {
+            r.seek(subtable_offset + record.default_uvs_offset as usize)?;
+            let num_unicode_value_ranges = r.get_uint32()?;
+            for _ in 0..num_unicode_value_ranges {
+                record.default_uvs.push(cmap::CmapFormat140DefaultRange {
+                    start_unicode_value: r.get_uint24()?,
+                    additional_count: r.get_uint8()?,
+                });
+            }
+        }
+
+        if record.non_default_uvs_offset != 0 {
+            r.seek(subtable_offset + record.non_default_uvs_offset as usize)?;
+            let num_uvs_mappings = r.get_uint32()?;
+            for _ in 0..num_uvs_mappings {
+                record.non_default_uvs.push(cmap::CmapFormat140Mapping {
+                    unicode_value: r.get_uint24()?,
+                    glyph_id: r.get_uint16()?,
+                });
+            }
+        }
+    }
+
+    return Ok(cmap::CmapFormat140 {
+        format,
+        length,
+        num_var_selector_records,
+        var_selector_records,
+    });
+}
+
+pub fn cmap_format_table(r: &mut reader::FontReader) -> Result<cmap::CmapFormatTable> {
+    let format_offset = r.pos;
     let table = match r.get_uint16()? {
         0 => cmap::CmapFormatTable::Format0(read_cmap_format0(r)?),
         2 => cmap::CmapFormatTable::Format2(read_cmap_format2(r)?),
@@ -229,14 +338,16 @@ pub fn cmap_format_table(r: &mut reader::FontReader) -> Option<cmap::CmapFormatT
         8 => cmap::CmapFormatTable::Format80(read_cmap_format80(r)?),
         10 => cmap::CmapFormatTable::Format100(read_cmap_format100(r)?),
         12 => cmap::CmapFormatTable::Format120(read_cmap_format120(r)?),
+        13 => cmap::CmapFormatTable::Format130(read_cmap_format130(r)?),
+        14 => cmap::CmapFormatTable::Format140(read_cmap_format140(r, format_offset)?),
         val => {
-            println!(
-                "{} is not a valid format table version, at least not supported by lipi.",
-                val
-            );
-            return None;
+            return Err(Error::UnsupportedFormat {
+                table: "cmap".into(),
+                offset: format_offset,
+                format: val as u32,
+            });
         }
     };
 
-    return Some(table);
+    return Ok(table);
 }
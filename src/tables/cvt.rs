@@ -0,0 +1,20 @@
+use crate::error::Result;
+use crate::reader;
+use crate::tables::offset::OffsetTable;
+
+// Control values the hinting programs refer to by index, in font units
+#[derive(Debug, Clone, Default)]
+pub struct Cvt {
+    pub values: Vec<i16>, // FWords, as many as fit in the table
+}
+
+pub fn read(r: &mut reader::FontReader, cvt_offset_table: OffsetTable) -> Result<Cvt> {
+    r.seek_table("cvt ", cvt_offset_table.offset as usize)?;
+
+    let mut values = Vec::new();
+    for _ in 0..cvt_offset_table.length / 2 {
+        values.push(r.get_int16()?);
+    }
+
+    return Ok(Cvt { values });
+}
@@ -0,0 +1,17 @@
+use crate::error::Result;
+use crate::reader;
+use crate::tables::offset::OffsetTable;
+
+// Font program, run once when the font is first used to define the functions the other
+// programs call. hinting::disassemble turns the instructions into mnemonics.
+#[derive(Debug, Clone, Default)]
+pub struct Fpgm {
+    pub instructions: Vec<u8>,
+}
+
+pub fn read(r: &mut reader::FontReader, fpgm_offset_table: OffsetTable) -> Result<Fpgm> {
+    r.seek_table("fpgm", fpgm_offset_table.offset as usize)?;
+
+    let instructions = r.get_bytes(fpgm_offset_table.length as usize)?.to_vec();
+    return Ok(Fpgm { instructions });
+}
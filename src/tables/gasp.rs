@@ -0,0 +1,67 @@
+use crate::error::{Error, Result};
+use crate::reader;
+use crate::tables::offset::OffsetTable;
+
+// rangeGaspBehavior bits
+pub const GRIDFIT: u16 = 1;
+pub const DOGRAY: u16 = 1 << 1;
+pub const SYMMETRIC_GRIDFIT: u16 = 1 << 2; // version 1 only
+pub const SYMMETRIC_SMOOTHING: u16 = 1 << 3; // version 1 only
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct GaspRange {
+    pub range_max_ppem: u16,      // upper limit of the range, inclusive
+    pub range_gasp_behavior: u16, // flags for the sizes in the range
+}
+
+// How the font wants to be rendered at each size, sorted by range_max_ppem with the last
+// range reaching up to 0xFFFF
+#[derive(Debug, Clone)]
+pub struct Gasp {
+    pub version: u16, // 0 or 1
+    pub ranges: Vec<GaspRange>,
+}
+
+impl Gasp {
+    // Flags of the range ppem falls in, 0 past the last one
+    pub fn behavior(&self, ppem: u16) -> u16 {
+        return self
+            .ranges
+            .iter()
+            .find(|range| ppem <= range.range_max_ppem)
+            .map(|range| range.range_gasp_behavior)
+            .unwrap_or(0);
+    }
+
+    pub fn grid_fit(&self, ppem: u16) -> bool {
+        return (self.behavior(ppem) & GRIDFIT) != 0;
+    }
+
+    pub fn smooth(&self, ppem: u16) -> bool {
+        return (self.behavior(ppem) & DOGRAY) != 0;
+    }
+}
+
+pub fn read(r: &mut reader::FontReader, gasp_offset_table: OffsetTable) -> Result<Gasp> {
+    r.seek_table("gasp", gasp_offset_table.offset as usize)?;
+
+    let version = r.get_uint16()?;
+    if version > 1 {
+        return Err(Error::UnsupportedFormat {
+            table: "gasp".into(),
+            offset: gasp_offset_table.offset as usize,
+            format: version as u32,
+        });
+    }
+
+    let num_ranges = r.get_uint16()?;
+    let mut ranges = Vec::new();
+    for _ in 0..num_ranges {
+        ranges.push(GaspRange {
+            range_max_ppem: r.get_uint16()?,
+            range_gasp_behavior: r.get_uint16()?,
+        });
+    }
+
+    return Ok(Gasp { version, ranges });
+}
@@ -0,0 +1,244 @@
+use crate::error::{Error, Result};
+use crate::reader;
+use crate::tables::layout::{self, ClassDef, Coverage, Device};
+use crate::tables::offset::OffsetTable;
+use crate::GlyphId;
+
+// Contour points glyphs attach to, by coverage index
+#[derive(Debug, Clone)]
+pub struct AttachList {
+    pub coverage: Coverage,
+    pub attach_points: Vec<Vec<u16>>, // in increasing order
+}
+
+// Where the caret goes between two components of a ligature
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CaretValue {
+    Format1 {
+        coordinate: i16, // in font units, along the direction of the text
+    },
+    // the point of the hinted outline, the coordinate comes from the glyph
+    Format2 {
+        caret_value_point_index: u16,
+    },
+    Format3 {
+        coordinate: i16,
+        device: Option<Device>,
+    },
+}
+
+// Carets of each ligature glyph, by coverage index. A ligature of n components has n - 1.
+#[derive(Debug, Clone)]
+pub struct LigCaretList {
+    pub coverage: Coverage,
+    pub lig_glyphs: Vec<Vec<CaretValue>>, // in increasing coordinate order
+}
+
+#[derive(Debug, Clone)]
+pub struct Gdef {
+    pub major_version: u16,                // 1
+    pub minor_version: u16,                // 0, 2 or 3, 2 adds the mark glyph sets
+    pub glyph_class_def: Option<ClassDef>, // tables::layout::BASE_GLYPH and the rest
+    pub attach_list: Option<AttachList>,
+    pub lig_caret_list: Option<LigCaretList>,
+    pub mark_attach_class_def: Option<ClassDef>,
+    pub mark_glyph_sets: Vec<Coverage>, // picked by lookups with USE_MARK_FILTERING_SET
+}
+
+impl Gdef {
+    // 0 for glyphs the font doesn't classify
+    pub fn glyph_class(&self, glyph: GlyphId) -> u16 {
+        return match self.glyph_class_def.as_ref() {
+            Some(class_def) => class_def.class(glyph),
+            None => 0,
+        };
+    }
+
+    pub fn mark_attachment_class(&self, glyph: GlyphId) -> u16 {
+        return match self.mark_attach_class_def.as_ref() {
+            Some(class_def) => class_def.class(glyph),
+            None => 0,
+        };
+    }
+
+    pub fn attach_points(&self, glyph: GlyphId) -> Option<&[u16]> {
+        let attach_list = self.attach_list.as_ref()?;
+        let index = attach_list.coverage.index(glyph)?;
+        return attach_list
+            .attach_points
+            .get(index as usize)
+            .map(|points| points.as_slice());
+    }
+
+    pub fn ligature_carets(&self, glyph: GlyphId) -> Option<&[CaretValue]> {
+        let lig_caret_list = self.lig_caret_list.as_ref()?;
+        let index = lig_caret_list.coverage.index(glyph)?;
+        return lig_caret_list
+            .lig_glyphs
+            .get(index as usize)
+            .map(|carets| carets.as_slice());
+    }
+}
+
+fn read_offset(r: &mut reader::FontReader, base: usize) -> Result<Option<usize>> {
+    return match r.get_uint16()? {
+        0 => Ok(None),
+        offset => Ok(Some(base + offset as usize)),
+    };
+}
+
+fn read_offset_array(r: &mut reader::FontReader, base: usize) -> Result<Vec<usize>> {
+    let count = r.get_uint16()?;
+    let mut offsets = Vec::new();
+    for _ in 0..count {
+        offsets.push(base + r.get_uint16()? as usize);
+    }
+    return Ok(offsets);
+}
+
+fn read_attach_list(r: &mut reader::FontReader, offset: usize) -> Result<AttachList> {
+    r.seek(offset)?;
+    let coverage_offset = offset + r.get_uint16()? as usize;
+    let point_offsets = read_offset_array(r, offset)?;
+
+    let mut attach_points = Vec::new();
+    for point_offset in point_offsets {
+        r.seek(point_offset)?;
+        let count = r.get_uint16()?;
+        let mut points = Vec::new();
+        for _ in 0..count {
+            points.push(r.get_uint16()?);
+        }
+        attach_points.push(points);
+    }
+
+    return Ok(AttachList {
+        coverage: layout::read_coverage(r, coverage_offset)?,
+        attach_points,
+    });
+}
+
+fn read_caret_value(r: &mut reader::FontReader, offset: usize) -> Result<CaretValue> {
+    r.seek(offset)?;
+
+    match r.get_uint16()? {
+        1 => {
+            return Ok(CaretValue::Format1 {
+                coordinate: r.get_int16()?,
+            });
+        }
+        2 => {
+            return Ok(CaretValue::Format2 {
+                caret_value_point_index: r.get_uint16()?,
+            });
+        }
+        3 => {
+            let coordinate = r.get_int16()?;
+            let device = read_offset(r, offset)?
+                .map(|offset| layout::read_device(r, offset))
+                .transpose()?;
+            return Ok(CaretValue::Format3 { coordinate, device });
+        }
+        format => {
+            return Err(Error::UnsupportedFormat {
+                table: r.table.into(),
+                offset,
+                format: format as u32,
+            });
+        }
+    }
+}
+
+fn read_lig_caret_list(r: &mut reader::FontReader, offset: usize) -> Result<LigCaretList> {
+    r.seek(offset)?;
+    let coverage_offset = offset + r.get_uint16()? as usize;
+    let lig_glyph_offsets = read_offset_array(r, offset)?;
+
+    let mut lig_glyphs = Vec::new();
+    for lig_glyph_offset in lig_glyph_offsets {
+        r.seek(lig_glyph_offset)?;
+        let caret_offsets = read_offset_array(r, lig_glyph_offset)?;
+        let mut carets = Vec::new();
+        for caret_offset in caret_offsets {
+            carets.push(read_caret_value(r, caret_offset)?);
+        }
+        lig_glyphs.push(carets);
+    }
+
+    return Ok(LigCaretList {
+        coverage: layout::read_coverage(r, coverage_offset)?,
+        lig_glyphs,
+    });
+}
+
+fn read_mark_glyph_sets(r: &mut reader::FontReader, offset: usize) -> Result<Vec<Coverage>> {
+    r.seek(offset)?;
+    let format = r.get_uint16()?;
+    if format != 1 {
+        return Err(Error::UnsupportedFormat {
+            table: r.table.into(),
+            offset,
+            format: format as u32,
+        });
+    }
+
+    // the only offsets of the table that are 32 bits
+    let count = r.get_uint16()?;
+    let mut coverage_offsets = Vec::new();
+    for _ in 0..count {
+        coverage_offsets.push(offset + r.get_uint32()? as usize);
+    }
+
+    let mut mark_glyph_sets = Vec::new();
+    for coverage_offset in coverage_offsets {
+        mark_glyph_sets.push(layout::read_coverage(r, coverage_offset)?);
+    }
+    return Ok(mark_glyph_sets);
+}
+
+pub fn read(r: &mut reader::FontReader, gdef_offset_table: OffsetTable) -> Result<Gdef> {
+    let offset = gdef_offset_table.offset as usize;
+    r.seek_table("GDEF", offset)?;
+
+    let major_version = r.get_uint16()?;
+    let minor_version = r.get_uint16()?;
+    if major_version != 1 {
+        return Err(Error::UnsupportedFormat {
+            table: "GDEF".into(),
+            offset,
+            format: major_version as u32,
+        });
+    }
+
+    let glyph_class_def_offset = read_offset(r, offset)?;
+    let attach_list_offset = read_offset(r, offset)?;
+    let lig_caret_list_offset = read_offset(r, offset)?;
+    let mark_attach_class_def_offset = read_offset(r, offset)?;
+    // the variation store that follows in 1.3 is only reached through variation devices
+    let mark_glyph_sets_def_offset = match minor_version {
+        0 | 1 => None,
+        _ => read_offset(r, offset)?,
+    };
+
+    // a broken subtable is left out rather than losing the rest of the table with it
+    let glyph_class_def =
+        glyph_class_def_offset.and_then(|offset| layout::read_class_def(r, offset).ok());
+    let attach_list = attach_list_offset.and_then(|offset| read_attach_list(r, offset).ok());
+    let lig_caret_list =
+        lig_caret_list_offset.and_then(|offset| read_lig_caret_list(r, offset).ok());
+    let mark_attach_class_def =
+        mark_attach_class_def_offset.and_then(|offset| layout::read_class_def(r, offset).ok());
+    let mark_glyph_sets = mark_glyph_sets_def_offset
+        .and_then(|offset| read_mark_glyph_sets(r, offset).ok())
+        .unwrap_or_default();
+
+    return Ok(Gdef {
+        major_version,
+        minor_version,
+        glyph_class_def,
+        attach_list,
+        lig_caret_list,
+        mark_attach_class_def,
+        mark_glyph_sets,
+    });
+}
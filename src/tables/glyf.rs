@@ -1,26 +1,84 @@
 // Yes it is spelt correctly
 
-pub struct GlyphData {
+// Component flags
+pub const ARG_1_AND_2_ARE_WORDS: u16 = 1;
+pub const ARGS_ARE_XY_VALUES: u16 = 2;
+pub const ROUND_XY_TO_GRID: u16 = 4;
+pub const WE_HAVE_A_SCALE: u16 = 8;
+// obsolete should be zero = 16
+pub const MORE_COMPONENTS: u16 = 32;
+pub const WE_HAVE_AN_X_AND_Y_SCALE: u16 = 64;
+pub const WE_HAVE_A_TWO_BY_TWO: u16 = 128;
+pub const WE_HAVE_INSTRUCTIONS: u16 = 256;
+pub const USE_MY_METRICS: u16 = 512;
+pub const OVERLAP_COMPOUND: u16 = 1024;
+pub const SCALED_COMPONENT_OFFSET: u16 = 2048;
+pub const UNSCALED_COMPONENT_OFFSET: u16 = 4096;
+
+#[derive(Debug, Clone)]
+pub struct SimpleGlyph {
     pub end_pts_of_contours: Vec<u16>,
-    pub instruction_length: Option<u16>,
-    pub instructions: Option<Vec<u8>>,
     pub flags: Vec<u8>,
     pub x_coordinates: Vec<i16>,
     pub y_coordinates: Vec<i16>,
 }
 
+#[derive(Debug, Clone)]
+pub enum GlyphData {
+    Simple(SimpleGlyph),
+    Composite(Vec<Component>),
+}
+
+#[derive(Debug, Clone)]
 pub struct Glyph {
     pub number_of_contours: i16,
     pub xmin: i16,
     pub ymin: i16,
     pub xmax: i16,
     pub ymax: i16,
+    pub instructions: Vec<u8>,
     pub glyph_data: GlyphData,
 }
 
+#[derive(Debug, Clone)]
 pub struct Component {
+    pub flags: u16,
     pub index: u16,
+    // (xscale, scale01, scale10, yscale, dx, dy) which maps a point to
+    // (xscale * x + scale10 * y + dx, scale01 * x + yscale * y + dy)
     pub matrix: (f64, f64, f64, f64, f64, f64),
-    pub destination_index: i32,
-    pub source_index: i32,
+    pub destination_index: i32, // point of the glyph so far, when aligning by points
+    pub source_index: i32,      // point of this component, when aligning by points
+}
+
+impl Component {
+    // Whether the component is placed by an offset or by matching two points
+    pub fn args_are_xy_values(&self) -> bool {
+        return (self.flags & ARGS_ARE_XY_VALUES) > 0;
+    }
+
+    pub fn round_xy_to_grid(&self) -> bool {
+        return (self.flags & ROUND_XY_TO_GRID) > 0;
+    }
+
+    // The composite takes its advance and side bearings from this component
+    pub fn use_my_metrics(&self) -> bool {
+        return (self.flags & USE_MY_METRICS) > 0;
+    }
+
+    pub fn overlap_compound(&self) -> bool {
+        return (self.flags & OVERLAP_COMPOUND) > 0;
+    }
+
+    // Neither flag set is left to the implementation, lipi goes with the unscaled offset
+    // like Windows and FreeType do
+    pub fn scaled_component_offset(&self) -> bool {
+        return (self.flags & SCALED_COMPONENT_OFFSET) > 0
+            && (self.flags & UNSCALED_COMPONENT_OFFSET) == 0;
+    }
+
+    pub fn transform(&self, x: f64, y: f64) -> (f64, f64) {
+        let (a, b, c, d, _, _) = self.matrix;
+        return (a * x + c * y, b * x + d * y);
+    }
 }
@@ -0,0 +1,589 @@
+use crate::error::{Error, Result};
+use crate::reader;
+use crate::tables::layout::{
+    self, ChainedSequenceContext, ClassDef, Coverage, Device, LayoutTable, SequenceContext,
+};
+use crate::tables::offset::OffsetTable;
+
+// Lookup types, 9 only wraps the others and never shows up after reading
+pub const SINGLE: u16 = 1;
+pub const PAIR: u16 = 2;
+pub const CURSIVE: u16 = 3;
+pub const MARK_TO_BASE: u16 = 4;
+pub const MARK_TO_LIGATURE: u16 = 5;
+pub const MARK_TO_MARK: u16 = 6;
+pub const CONTEXT: u16 = 7;
+pub const CHAINED_CONTEXT: u16 = 8;
+pub const EXTENSION: u16 = 9;
+
+// valueFormat bits, which fields a ValueRecord has on disk
+pub const X_PLACEMENT: u16 = 0x0001;
+pub const Y_PLACEMENT: u16 = 0x0002;
+pub const X_ADVANCE: u16 = 0x0004;
+pub const Y_ADVANCE: u16 = 0x0008;
+pub const X_PLACEMENT_DEVICE: u16 = 0x0010;
+pub const Y_PLACEMENT_DEVICE: u16 = 0x0020;
+pub const X_ADVANCE_DEVICE: u16 = 0x0040;
+pub const Y_ADVANCE_DEVICE: u16 = 0x0080;
+
+// Fields the format leaves out are 0 or None
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValueRecord {
+    pub x_placement: i16,
+    pub y_placement: i16,
+    pub x_advance: i16,
+    pub y_advance: i16, // vertical layout only
+    pub x_placement_device: Option<Device>,
+    pub y_placement_device: Option<Device>,
+    pub x_advance_device: Option<Device>,
+    pub y_advance_device: Option<Device>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Anchor {
+    Format1 {
+        x_coordinate: i16,
+        y_coordinate: i16,
+    },
+    // the point of the hinted outline wins over the coordinates when hinting
+    Format2 {
+        x_coordinate: i16,
+        y_coordinate: i16,
+        anchor_point: u16,
+    },
+    Format3 {
+        x_coordinate: i16,
+        y_coordinate: i16,
+        x_device: Option<Device>,
+        y_device: Option<Device>,
+    },
+}
+
+impl Anchor {
+    // In font units, without hinting or variations
+    pub fn coordinates(&self) -> (i16, i16) {
+        match *self {
+            Anchor::Format1 {
+                x_coordinate,
+                y_coordinate,
+            }
+            | Anchor::Format2 {
+                x_coordinate,
+                y_coordinate,
+                ..
+            }
+            | Anchor::Format3 {
+                x_coordinate,
+                y_coordinate,
+                ..
+            } => return (x_coordinate, y_coordinate),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum SinglePositioning {
+    Format1 {
+        coverage: Coverage,
+        value_record: ValueRecord, // the same for every covered glyph
+    },
+    Format2 {
+        coverage: Coverage,
+        value_records: Vec<ValueRecord>, // by coverage index
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PairValueRecord {
+    pub second_glyph: u16,
+    pub value_record1: ValueRecord,
+    pub value_record2: ValueRecord,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Class2Record {
+    pub value_record1: ValueRecord,
+    pub value_record2: ValueRecord,
+}
+
+// The value formats are kept since a second glyph without a value record of its own may
+// start the next pair
+#[derive(Debug, Clone)]
+pub enum PairPositioning {
+    Format1 {
+        coverage: Coverage,
+        value_format1: u16,
+        value_format2: u16,
+        pair_sets: Vec<Vec<PairValueRecord>>, // by coverage index, sorted by second glyph
+    },
+    Format2 {
+        coverage: Coverage,
+        value_format1: u16,
+        value_format2: u16,
+        class_def1: ClassDef,
+        class_def2: ClassDef,
+        class1_records: Vec<Vec<Class2Record>>, // by class of the first glyph, then the second
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntryExitRecord {
+    pub entry_anchor: Option<Anchor>,
+    pub exit_anchor: Option<Anchor>,
+}
+
+// Connects the exit anchor of a glyph to the entry anchor of the next, for cursive scripts
+#[derive(Debug, Clone)]
+pub struct CursivePositioning {
+    pub coverage: Coverage,
+    pub entry_exit_records: Vec<EntryExitRecord>, // by coverage index
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MarkRecord {
+    pub mark_class: u16,
+    pub mark_anchor: Anchor,
+}
+
+// Every base has an anchor for each mark class, or None when marks of the class don't go on it
+pub type AnchorMatrix = Vec<Vec<Option<Anchor>>>;
+
+#[derive(Debug, Clone)]
+pub struct MarkToBasePositioning {
+    pub mark_coverage: Coverage,
+    pub base_coverage: Coverage,
+    pub mark_class_count: u16,
+    pub mark_array: Vec<MarkRecord>, // by mark coverage index
+    pub base_array: AnchorMatrix,    // by base coverage index
+}
+
+#[derive(Debug, Clone)]
+pub struct MarkToLigaturePositioning {
+    pub mark_coverage: Coverage,
+    pub ligature_coverage: Coverage,
+    pub mark_class_count: u16,
+    pub mark_array: Vec<MarkRecord>,
+    pub ligature_array: Vec<AnchorMatrix>, // by ligature coverage index, then component
+}
+
+#[derive(Debug, Clone)]
+pub struct MarkToMarkPositioning {
+    pub mark1_coverage: Coverage, // the marks being attached
+    pub mark2_coverage: Coverage, // the marks they attach to
+    pub mark_class_count: u16,
+    pub mark1_array: Vec<MarkRecord>,
+    pub mark2_array: AnchorMatrix,
+}
+
+#[derive(Debug, Clone)]
+pub enum PositioningSubtable {
+    Single(SinglePositioning),
+    Pair(PairPositioning),
+    Cursive(CursivePositioning),
+    MarkToBase(MarkToBasePositioning),
+    MarkToLigature(MarkToLigaturePositioning),
+    MarkToMark(MarkToMarkPositioning),
+    Context(SequenceContext),
+    ChainedContext(ChainedSequenceContext),
+}
+
+pub type Gpos = LayoutTable<PositioningSubtable>;
+
+fn unsupported(r: &reader::FontReader, offset: usize, format: u16) -> Error {
+    return Error::UnsupportedFormat {
+        table: r.table.into(),
+        offset,
+        format: format as u32,
+    };
+}
+
+fn read_offset(r: &mut reader::FontReader, base: usize) -> Result<Option<usize>> {
+    return match r.get_uint16()? {
+        0 => Ok(None),
+        offset => Ok(Some(base + offset as usize)),
+    };
+}
+
+fn read_offset_array(r: &mut reader::FontReader, base: usize) -> Result<Vec<Option<usize>>> {
+    let count = r.get_uint16()?;
+    let mut offsets = Vec::new();
+    for _ in 0..count {
+        offsets.push(read_offset(r, base)?);
+    }
+    return Ok(offsets);
+}
+
+// Device offsets count from base, the subtable or PairSet holding the record. The reader is
+// left right after the record.
+fn read_value_record(
+    r: &mut reader::FontReader,
+    base: usize,
+    value_format: u16,
+) -> Result<ValueRecord> {
+    let mut value = |flag: u16| -> Result<i16> {
+        return match value_format & flag {
+            0 => Ok(0),
+            _ => r.get_int16(),
+        };
+    };
+    let x_placement = value(X_PLACEMENT)?;
+    let y_placement = value(Y_PLACEMENT)?;
+    let x_advance = value(X_ADVANCE)?;
+    let y_advance = value(Y_ADVANCE)?;
+
+    let mut device_offsets = [None; 4];
+    let device_flags = [
+        X_PLACEMENT_DEVICE,
+        Y_PLACEMENT_DEVICE,
+        X_ADVANCE_DEVICE,
+        Y_ADVANCE_DEVICE,
+    ];
+    for (device_offset, flag) in device_offsets.iter_mut().zip(device_flags) {
+        if value_format & flag != 0 {
+            *device_offset = read_offset(r, base)?;
+        }
+    }
+
+    let mut record = ValueRecord {
+        x_placement,
+        y_placement,
+        x_advance,
+        y_advance,
+        ..Default::default()
+    };
+    if device_offsets.iter().any(|offset| offset.is_some()) {
+        let position = r.pos;
+        let mut device = |offset: Option<usize>| -> Result<Option<Device>> {
+            return offset
+                .map(|offset| layout::read_device(r, offset))
+                .transpose();
+        };
+        record.x_placement_device = device(device_offsets[0])?;
+        record.y_placement_device = device(device_offsets[1])?;
+        record.x_advance_device = device(device_offsets[2])?;
+        record.y_advance_device = device(device_offsets[3])?;
+        r.seek(position)?;
+    }
+
+    return Ok(record);
+}
+
+fn read_anchor(r: &mut reader::FontReader, offset: usize) -> Result<Anchor> {
+    r.seek(offset)?;
+    let format = r.get_uint16()?;
+    let x_coordinate = r.get_int16()?;
+    let y_coordinate = r.get_int16()?;
+
+    match format {
+        1 => {
+            return Ok(Anchor::Format1 {
+                x_coordinate,
+                y_coordinate,
+            });
+        }
+        2 => {
+            return Ok(Anchor::Format2 {
+                x_coordinate,
+                y_coordinate,
+                anchor_point: r.get_uint16()?,
+            });
+        }
+        3 => {
+            let x_device_offset = read_offset(r, offset)?;
+            let y_device_offset = read_offset(r, offset)?;
+            let x_device = x_device_offset
+                .map(|offset| layout::read_device(r, offset))
+                .transpose()?;
+            let y_device = y_device_offset
+                .map(|offset| layout::read_device(r, offset))
+                .transpose()?;
+            return Ok(Anchor::Format3 {
+                x_coordinate,
+                y_coordinate,
+                x_device,
+                y_device,
+            });
+        }
+        format => return Err(unsupported(r, offset, format)),
+    }
+}
+
+fn read_optional_anchor(
+    r: &mut reader::FontReader,
+    offset: Option<usize>,
+) -> Result<Option<Anchor>> {
+    return offset.map(|offset| read_anchor(r, offset)).transpose();
+}
+
+fn read_mark_array(r: &mut reader::FontReader, offset: usize) -> Result<Vec<MarkRecord>> {
+    r.seek(offset)?;
+    let count = r.get_uint16()?;
+    let mut classes = Vec::new();
+    for _ in 0..count {
+        classes.push((r.get_uint16()?, offset + r.get_uint16()? as usize));
+    }
+
+    let mut records = Vec::new();
+    for (mark_class, anchor_offset) in classes {
+        records.push(MarkRecord {
+            mark_class,
+            mark_anchor: read_anchor(r, anchor_offset)?,
+        });
+    }
+    return Ok(records);
+}
+
+// BaseArray, Mark2Array and LigatureAttach are all a count of rows with an anchor offset
+// for every mark class
+fn read_anchor_matrix(
+    r: &mut reader::FontReader,
+    offset: usize,
+    mark_class_count: u16,
+) -> Result<AnchorMatrix> {
+    r.seek(offset)?;
+    let count = r.get_uint16()?;
+    let mut offsets = Vec::new();
+    for _ in 0..count as usize * mark_class_count as usize {
+        offsets.push(read_offset(r, offset)?);
+    }
+
+    let mut rows = Vec::new();
+    for row_offsets in offsets.chunks(mark_class_count.max(1) as usize) {
+        let mut anchors = Vec::new();
+        for &anchor_offset in row_offsets {
+            anchors.push(read_optional_anchor(r, anchor_offset)?);
+        }
+        rows.push(anchors);
+    }
+    rows.resize(count as usize, Vec::new());
+    return Ok(rows);
+}
+
+fn read_single(r: &mut reader::FontReader, offset: usize) -> Result<SinglePositioning> {
+    let format = r.get_uint16()?;
+    let coverage_offset = offset + r.get_uint16()? as usize;
+    let value_format = r.get_uint16()?;
+
+    match format {
+        1 => {
+            let value_record = read_value_record(r, offset, value_format)?;
+            return Ok(SinglePositioning::Format1 {
+                coverage: layout::read_coverage(r, coverage_offset)?,
+                value_record,
+            });
+        }
+        2 => {
+            let count = r.get_uint16()?;
+            let mut value_records = Vec::new();
+            for _ in 0..count {
+                value_records.push(read_value_record(r, offset, value_format)?);
+            }
+            return Ok(SinglePositioning::Format2 {
+                coverage: layout::read_coverage(r, coverage_offset)?,
+                value_records,
+            });
+        }
+        format => return Err(unsupported(r, offset, format)),
+    }
+}
+
+fn read_pair(r: &mut reader::FontReader, offset: usize) -> Result<PairPositioning> {
+    let format = r.get_uint16()?;
+    let coverage_offset = offset + r.get_uint16()? as usize;
+    let value_format1 = r.get_uint16()?;
+    let value_format2 = r.get_uint16()?;
+
+    match format {
+        1 => {
+            let mut pair_sets = Vec::new();
+            for set_offset in read_offset_array(r, offset)? {
+                let mut pairs = Vec::new();
+                if let Some(set_offset) = set_offset {
+                    let position = r.pos;
+                    r.seek(set_offset)?;
+                    let count = r.get_uint16()?;
+                    for _ in 0..count {
+                        pairs.push(PairValueRecord {
+                            second_glyph: r.get_uint16()?,
+                            value_record1: read_value_record(r, set_offset, value_format1)?,
+                            value_record2: read_value_record(r, set_offset, value_format2)?,
+                        });
+                    }
+                    pairs.sort_by_key(|pair: &PairValueRecord| pair.second_glyph);
+                    r.seek(position)?;
+                }
+                pair_sets.push(pairs);
+            }
+
+            return Ok(PairPositioning::Format1 {
+                coverage: layout::read_coverage(r, coverage_offset)?,
+                value_format1,
+                value_format2,
+                pair_sets,
+            });
+        }
+        2 => {
+            let class_def1_offset = offset + r.get_uint16()? as usize;
+            let class_def2_offset = offset + r.get_uint16()? as usize;
+            let class1_count = r.get_uint16()?;
+            let class2_count = r.get_uint16()?;
+
+            let mut class1_records = Vec::new();
+            for _ in 0..class1_count {
+                let mut class2_records = Vec::new();
+                for _ in 0..class2_count {
+                    class2_records.push(Class2Record {
+                        value_record1: read_value_record(r, offset, value_format1)?,
+                        value_record2: read_value_record(r, offset, value_format2)?,
+                    });
+                }
+                class1_records.push(class2_records);
+            }
+
+            return Ok(PairPositioning::Format2 {
+                coverage: layout::read_coverage(r, coverage_offset)?,
+                value_format1,
+                value_format2,
+                class_def1: layout::read_class_def(r, class_def1_offset)?,
+                class_def2: layout::read_class_def(r, class_def2_offset)?,
+                class1_records,
+            });
+        }
+        format => return Err(unsupported(r, offset, format)),
+    }
+}
+
+fn read_cursive(r: &mut reader::FontReader, offset: usize) -> Result<CursivePositioning> {
+    let coverage_offset = offset + r.get_uint16()? as usize;
+    let count = r.get_uint16()?;
+    let mut anchor_offsets = Vec::new();
+    for _ in 0..count {
+        anchor_offsets.push((read_offset(r, offset)?, read_offset(r, offset)?));
+    }
+
+    let mut entry_exit_records = Vec::new();
+    for (entry_offset, exit_offset) in anchor_offsets {
+        entry_exit_records.push(EntryExitRecord {
+            entry_anchor: read_optional_anchor(r, entry_offset)?,
+            exit_anchor: read_optional_anchor(r, exit_offset)?,
+        });
+    }
+
+    return Ok(CursivePositioning {
+        coverage: layout::read_coverage(r, coverage_offset)?,
+        entry_exit_records,
+    });
+}
+
+// Mark-to-base and mark-to-mark are laid out the same
+fn read_mark_attachment(
+    r: &mut reader::FontReader,
+    offset: usize,
+) -> Result<(Coverage, Coverage, u16, Vec<MarkRecord>, AnchorMatrix)> {
+    let mark_coverage_offset = offset + r.get_uint16()? as usize;
+    let base_coverage_offset = offset + r.get_uint16()? as usize;
+    let mark_class_count = r.get_uint16()?;
+    let mark_array_offset = offset + r.get_uint16()? as usize;
+    let base_array_offset = offset + r.get_uint16()? as usize;
+
+    return Ok((
+        layout::read_coverage(r, mark_coverage_offset)?,
+        layout::read_coverage(r, base_coverage_offset)?,
+        mark_class_count,
+        read_mark_array(r, mark_array_offset)?,
+        read_anchor_matrix(r, base_array_offset, mark_class_count)?,
+    ));
+}
+
+fn read_mark_to_ligature(
+    r: &mut reader::FontReader,
+    offset: usize,
+) -> Result<MarkToLigaturePositioning> {
+    let mark_coverage_offset = offset + r.get_uint16()? as usize;
+    let ligature_coverage_offset = offset + r.get_uint16()? as usize;
+    let mark_class_count = r.get_uint16()?;
+    let mark_array_offset = offset + r.get_uint16()? as usize;
+    let ligature_array_offset = offset + r.get_uint16()? as usize;
+
+    r.seek(ligature_array_offset)?;
+    let mut ligature_array = Vec::new();
+    for attach_offset in read_offset_array(r, ligature_array_offset)? {
+        ligature_array.push(match attach_offset {
+            Some(attach_offset) => read_anchor_matrix(r, attach_offset, mark_class_count)?,
+            None => Vec::new(),
+        });
+    }
+
+    return Ok(MarkToLigaturePositioning {
+        mark_coverage: layout::read_coverage(r, mark_coverage_offset)?,
+        ligature_coverage: layout::read_coverage(r, ligature_coverage_offset)?,
+        mark_class_count,
+        mark_array: read_mark_array(r, mark_array_offset)?,
+        ligature_array,
+    });
+}
+
+fn read_subtable(
+    r: &mut reader::FontReader,
+    lookup_type: u16,
+    offset: usize,
+) -> Result<PositioningSubtable> {
+    r.seek(offset)?;
+
+    let subtable = match lookup_type {
+        SINGLE => PositioningSubtable::Single(read_single(r, offset)?),
+        PAIR => PositioningSubtable::Pair(read_pair(r, offset)?),
+        CURSIVE | MARK_TO_BASE | MARK_TO_LIGATURE | MARK_TO_MARK => {
+            let format = r.get_uint16()?;
+            if format != 1 {
+                return Err(unsupported(r, offset, format));
+            }
+
+            match lookup_type {
+                CURSIVE => PositioningSubtable::Cursive(read_cursive(r, offset)?),
+                MARK_TO_LIGATURE => {
+                    PositioningSubtable::MarkToLigature(read_mark_to_ligature(r, offset)?)
+                }
+                MARK_TO_BASE => {
+                    let (mark_coverage, base_coverage, mark_class_count, mark_array, base_array) =
+                        read_mark_attachment(r, offset)?;
+                    PositioningSubtable::MarkToBase(MarkToBasePositioning {
+                        mark_coverage,
+                        base_coverage,
+                        mark_class_count,
+                        mark_array,
+                        base_array,
+                    })
+                }
+                _ => {
+                    let (
+                        mark1_coverage,
+                        mark2_coverage,
+                        mark_class_count,
+                        mark1_array,
+                        mark2_array,
+                    ) = read_mark_attachment(r, offset)?;
+                    PositioningSubtable::MarkToMark(MarkToMarkPositioning {
+                        mark1_coverage,
+                        mark2_coverage,
+                        mark_class_count,
+                        mark1_array,
+                        mark2_array,
+                    })
+                }
+            }
+        }
+        CONTEXT => PositioningSubtable::Context(layout::read_sequence_context(r, offset)?),
+        CHAINED_CONTEXT => {
+            PositioningSubtable::ChainedContext(layout::read_chained_sequence_context(r, offset)?)
+        }
+        // left out of the lookup like a subtable format it doesn't know
+        lookup_type => return Err(unsupported(r, offset, lookup_type)),
+    };
+
+    return Ok(subtable);
+}
+
+pub fn read(r: &mut reader::FontReader, gpos_offset_table: OffsetTable) -> Result<Gpos> {
+    return layout::read(r, "GPOS", gpos_offset_table, EXTENSION, &read_subtable);
+}
@@ -0,0 +1,253 @@
+use crate::error::{Error, Result};
+use crate::reader;
+use crate::tables::layout::{self, ChainedSequenceContext, Coverage, LayoutTable, SequenceContext};
+use crate::tables::offset::OffsetTable;
+
+// Lookup types, 7 only wraps the others and never shows up after reading
+pub const SINGLE: u16 = 1;
+pub const MULTIPLE: u16 = 2;
+pub const ALTERNATE: u16 = 3;
+pub const LIGATURE: u16 = 4;
+pub const CONTEXT: u16 = 5;
+pub const CHAINED_CONTEXT: u16 = 6;
+pub const EXTENSION: u16 = 7;
+pub const REVERSE_CHAINED_SINGLE: u16 = 8;
+
+#[derive(Debug, Clone)]
+pub enum SingleSubstitution {
+    Format1 {
+        coverage: Coverage,
+        delta_glyph_id: i16, // added to the glyph id, modulo 65536
+    },
+    Format2 {
+        coverage: Coverage,
+        substitute_glyph_ids: Vec<u16>, // by coverage index
+    },
+}
+
+// One glyph into many, an empty sequence deletes the glyph
+#[derive(Debug, Clone)]
+pub struct MultipleSubstitution {
+    pub coverage: Coverage,
+    pub sequences: Vec<Vec<u16>>, // by coverage index
+}
+
+#[derive(Debug, Clone)]
+pub struct AlternateSubstitution {
+    pub coverage: Coverage,
+    pub alternate_sets: Vec<Vec<u16>>, // by coverage index
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ligature {
+    pub ligature_glyph: u16,
+    pub component_glyph_ids: Vec<u16>, // from the second component on
+}
+
+// The ligatures starting with a glyph are in order of preference, longer ones come first
+#[derive(Debug, Clone)]
+pub struct LigatureSubstitution {
+    pub coverage: Coverage,
+    pub ligature_sets: Vec<Vec<Ligature>>, // by coverage index
+}
+
+// Applied from the end of the text back to its start, so the context it looks at has
+// already been substituted
+#[derive(Debug, Clone)]
+pub struct ReverseChainSingleSubstitution {
+    pub coverage: Coverage,
+    pub backtrack_coverages: Vec<Coverage>, // starting with the glyph right before
+    pub lookahead_coverages: Vec<Coverage>,
+    pub substitute_glyph_ids: Vec<u16>, // by coverage index
+}
+
+#[derive(Debug, Clone)]
+pub enum SubstitutionSubtable {
+    Single(SingleSubstitution),
+    Multiple(MultipleSubstitution),
+    Alternate(AlternateSubstitution),
+    Ligature(LigatureSubstitution),
+    Context(SequenceContext),
+    ChainedContext(ChainedSequenceContext),
+    ReverseChainSingle(ReverseChainSingleSubstitution),
+}
+
+pub type Gsub = LayoutTable<SubstitutionSubtable>;
+
+fn unsupported(r: &reader::FontReader, offset: usize, format: u16) -> Error {
+    return Error::UnsupportedFormat {
+        table: r.table.into(),
+        offset,
+        format: format as u32,
+    };
+}
+
+fn read_glyph_array(r: &mut reader::FontReader) -> Result<Vec<u16>> {
+    let count = r.get_uint16()?;
+    let mut glyphs = Vec::new();
+    for _ in 0..count {
+        glyphs.push(r.get_uint16()?);
+    }
+    return Ok(glyphs);
+}
+
+fn read_offset_array(r: &mut reader::FontReader, base: usize) -> Result<Vec<usize>> {
+    let count = r.get_uint16()?;
+    let mut offsets = Vec::new();
+    for _ in 0..count {
+        offsets.push(base + r.get_uint16()? as usize);
+    }
+    return Ok(offsets);
+}
+
+// Multiple and alternate substitutions are the same thing on disk, a glyph array for each
+// covered glyph
+fn read_glyph_arrays(
+    r: &mut reader::FontReader,
+    offset: usize,
+) -> Result<(Coverage, Vec<Vec<u16>>)> {
+    let coverage_offset = offset + r.get_uint16()? as usize;
+    let array_offsets = read_offset_array(r, offset)?;
+
+    let mut arrays = Vec::new();
+    for array_offset in array_offsets {
+        r.seek(array_offset)?;
+        arrays.push(read_glyph_array(r)?);
+    }
+
+    return Ok((layout::read_coverage(r, coverage_offset)?, arrays));
+}
+
+fn read_single(r: &mut reader::FontReader, offset: usize) -> Result<SingleSubstitution> {
+    match r.get_uint16()? {
+        1 => {
+            let coverage_offset = offset + r.get_uint16()? as usize;
+            let delta_glyph_id = r.get_int16()?;
+            return Ok(SingleSubstitution::Format1 {
+                coverage: layout::read_coverage(r, coverage_offset)?,
+                delta_glyph_id,
+            });
+        }
+        2 => {
+            let coverage_offset = offset + r.get_uint16()? as usize;
+            let substitute_glyph_ids = read_glyph_array(r)?;
+            return Ok(SingleSubstitution::Format2 {
+                coverage: layout::read_coverage(r, coverage_offset)?,
+                substitute_glyph_ids,
+            });
+        }
+        format => return Err(unsupported(r, offset, format)),
+    }
+}
+
+fn read_ligature(r: &mut reader::FontReader, offset: usize) -> Result<LigatureSubstitution> {
+    let coverage_offset = offset + r.get_uint16()? as usize;
+    let set_offsets = read_offset_array(r, offset)?;
+
+    let mut ligature_sets = Vec::new();
+    for set_offset in set_offsets {
+        r.seek(set_offset)?;
+        let mut ligatures = Vec::new();
+        for ligature_offset in read_offset_array(r, set_offset)? {
+            r.seek(ligature_offset)?;
+            let ligature_glyph = r.get_uint16()?;
+            let component_count = r.get_uint16()?;
+            let mut component_glyph_ids = Vec::new();
+            for _ in 1..component_count {
+                component_glyph_ids.push(r.get_uint16()?);
+            }
+            ligatures.push(Ligature {
+                ligature_glyph,
+                component_glyph_ids,
+            });
+        }
+        ligature_sets.push(ligatures);
+    }
+
+    return Ok(LigatureSubstitution {
+        coverage: layout::read_coverage(r, coverage_offset)?,
+        ligature_sets,
+    });
+}
+
+fn read_reverse_chain_single(
+    r: &mut reader::FontReader,
+    offset: usize,
+) -> Result<ReverseChainSingleSubstitution> {
+    let coverage_offset = offset + r.get_uint16()? as usize;
+    let backtrack_offsets = read_offset_array(r, offset)?;
+    let lookahead_offsets = read_offset_array(r, offset)?;
+    let substitute_glyph_ids = read_glyph_array(r)?;
+
+    let mut backtrack_coverages = Vec::new();
+    for coverage_offset in backtrack_offsets {
+        backtrack_coverages.push(layout::read_coverage(r, coverage_offset)?);
+    }
+
+    let mut lookahead_coverages = Vec::new();
+    for coverage_offset in lookahead_offsets {
+        lookahead_coverages.push(layout::read_coverage(r, coverage_offset)?);
+    }
+
+    return Ok(ReverseChainSingleSubstitution {
+        coverage: layout::read_coverage(r, coverage_offset)?,
+        backtrack_coverages,
+        lookahead_coverages,
+        substitute_glyph_ids,
+    });
+}
+
+fn read_subtable(
+    r: &mut reader::FontReader,
+    lookup_type: u16,
+    offset: usize,
+) -> Result<SubstitutionSubtable> {
+    r.seek(offset)?;
+
+    let subtable = match lookup_type {
+        SINGLE => SubstitutionSubtable::Single(read_single(r, offset)?),
+        MULTIPLE | ALTERNATE => {
+            let format = r.get_uint16()?;
+            if format != 1 {
+                return Err(unsupported(r, offset, format));
+            }
+
+            let (coverage, arrays) = read_glyph_arrays(r, offset)?;
+            match lookup_type {
+                MULTIPLE => SubstitutionSubtable::Multiple(MultipleSubstitution {
+                    coverage,
+                    sequences: arrays,
+                }),
+                _ => SubstitutionSubtable::Alternate(AlternateSubstitution {
+                    coverage,
+                    alternate_sets: arrays,
+                }),
+            }
+        }
+        LIGATURE | REVERSE_CHAINED_SINGLE => {
+            let format = r.get_uint16()?;
+            if format != 1 {
+                return Err(unsupported(r, offset, format));
+            }
+
+            match lookup_type {
+                LIGATURE => SubstitutionSubtable::Ligature(read_ligature(r, offset)?),
+                _ => {
+                    SubstitutionSubtable::ReverseChainSingle(read_reverse_chain_single(r, offset)?)
+                }
+            }
+        }
+        CONTEXT => SubstitutionSubtable::Context(layout::read_sequence_context(r, offset)?),
+        CHAINED_CONTEXT => {
+            SubstitutionSubtable::ChainedContext(layout::read_chained_sequence_context(r, offset)?)
+        }
+        // left out of the lookup like a subtable format it doesn't know
+        lookup_type => return Err(unsupported(r, offset, lookup_type)),
+    };
+
+    return Ok(subtable);
+}
+
+pub fn read(r: &mut reader::FontReader, gsub_offset_table: OffsetTable) -> Result<Gsub> {
+    return layout::read(r, "GSUB", gsub_offset_table, EXTENSION, &read_subtable);
+}
@@ -1,3 +1,4 @@
+use crate::error::{Error, Result};
 use crate::reader;
 use crate::tables::offset::OffsetTable;
 
@@ -22,16 +23,20 @@ pub struct Head {
     pub glyph_data_format: i16,   // 0 for current format
 }
 
-pub fn read(r: &mut reader::FontReader, head_offset_table: OffsetTable) -> Option<Head> {
-    let _ = r.seek(head_offset_table.offset as usize);
+pub fn read(r: &mut reader::FontReader, head_offset_table: OffsetTable) -> Result<Head> {
+    r.seek_table("head", head_offset_table.offset as usize)?;
 
     let version = r.get_float32()?;
     let font_revision = r.get_float32()?;
     let checksum_adjustment = r.get_uint32()?;
+    let magic_offset = r.pos;
     let magic_number = r.get_uint32()?;
     if magic_number != 0x5f0f3cf5 {
-        println!("Wrong magic number is head table");
-        return None;
+        return Err(Error::BadMagic {
+            table: "head".into(),
+            offset: magic_offset,
+            magic: magic_number,
+        });
     }
     let flags = r.get_uint16()?;
     let units_per_em = r.get_uint16()?;
@@ -47,7 +52,7 @@ pub fn read(r: &mut reader::FontReader, head_offset_table: OffsetTable) -> Optio
     let index_to_loc_format = r.get_int16()?;
     let glyph_data_format = r.get_int16()?;
 
-    return Some(Head {
+    return Ok(Head {
         version,
         font_revision,
         checksum_adjustment,
@@ -1,3 +1,4 @@
+use crate::error::Result;
 use crate::reader;
 use crate::tables::offset::OffsetTable;
 
@@ -18,8 +19,8 @@ pub struct Hhea {
     pub num_of_long_hor_metrics: u16, // number of advance widths in metrics table
 }
 
-pub fn read(r: &mut reader::FontReader, hhea_offset_table: OffsetTable) -> Option<Hhea> {
-    let _ = r.seek(hhea_offset_table.offset as usize);
+pub fn read(r: &mut reader::FontReader, hhea_offset_table: OffsetTable) -> Result<Hhea> {
+    r.seek_table("hhea", hhea_offset_table.offset as usize)?;
 
     let version = r.get_float32()?;
     let ascent = r.get_int16()?;
@@ -41,7 +42,7 @@ pub fn read(r: &mut reader::FontReader, hhea_offset_table: OffsetTable) -> Optio
     let metric_data_format = r.get_int16()?;
     let num_of_long_hor_metrics = r.get_uint16()?;
 
-    return Some(Hhea {
+    return Ok(Hhea {
         version,
         ascent,
         descent,
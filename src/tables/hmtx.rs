@@ -1,3 +1,4 @@
+use crate::error::Result;
 use crate::reader;
 use crate::tables::offset::OffsetTable;
 
@@ -19,8 +20,8 @@ pub fn read(
     hmtx_offset_table: OffsetTable,
     long_hor_metric_count: u16,
     glyph_count: u16,
-) -> Option<Hmtx> {
-    let _ = r.seek(hmtx_offset_table.offset as usize);
+) -> Result<Hmtx> {
+    r.seek_table("hmtx", hmtx_offset_table.offset as usize)?;
     let mut hmetrics = Vec::new();
 
     for _ in 0..long_hor_metric_count {
@@ -32,12 +33,38 @@ pub fn read(
 
     let mut left_side_bearings = Vec::new();
 
-    for _ in 0..(glyph_count - long_hor_metric_count) {
+    for _ in 0..glyph_count.saturating_sub(long_hor_metric_count) {
         left_side_bearings.push(r.get_int16()?);
     }
 
-    return Some(Hmtx {
+    return Ok(Hmtx {
         hmetrics,
         left_side_bearings,
     });
 }
+
+impl Hmtx {
+    // Glyphs past the last long metric share its advance and only have their own bearing
+    pub fn metric(&self, glyph_index: u16) -> HmtxLongHorMetric {
+        let index = glyph_index as usize;
+        if let Some(&metric) = self.hmetrics.get(index) {
+            return metric;
+        }
+
+        let advance_width = self
+            .hmetrics
+            .last()
+            .map(|metric| metric.advance_width)
+            .unwrap_or(0);
+        let left_side_bearing = self
+            .left_side_bearings
+            .get(index - self.hmetrics.len())
+            .copied()
+            .unwrap_or(0);
+
+        return HmtxLongHorMetric {
+            advance_width,
+            left_side_bearing,
+        };
+    }
+}
@@ -0,0 +1,278 @@
+use crate::error::{Error, Result};
+use crate::reader;
+use crate::tables::offset::OffsetTable;
+use crate::GlyphId;
+
+// Microsoft coverage bits, Apple's are translated to the same meaning while reading
+pub const HORIZONTAL: u16 = 1;
+pub const MINIMUM: u16 = 1 << 1;
+pub const CROSS_STREAM: u16 = 1 << 2;
+pub const OVERRIDE: u16 = 1 << 3;
+pub const VARIATION: u16 = 1 << 4; // Apple only, the values depend on a variation tuple
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct KernPair {
+    pub left: u16,
+    pub right: u16,
+    pub value: i16, // in font units
+}
+
+// Pairs sorted by left and then right glyph
+#[derive(Debug, Clone)]
+pub struct KernFormat0 {
+    pub pairs: Vec<KernPair>,
+}
+
+impl KernFormat0 {
+    pub fn kerning(&self, left: GlyphId, right: GlyphId) -> Option<i16> {
+        let index = self
+            .pairs
+            .binary_search_by_key(&(left.0, right.0), |pair| (pair.left, pair.right))
+            .ok()?;
+        return Some(self.pairs[index].value);
+    }
+}
+
+// Glyphs from first_glyph on map to the value at their position, everything else to 0
+#[derive(Debug, Clone)]
+pub struct KernClassTable {
+    pub first_glyph: u16,
+    pub values: Vec<u16>,
+}
+
+impl KernClassTable {
+    pub fn class(&self, glyph: GlyphId) -> u16 {
+        return match glyph.0.checked_sub(self.first_glyph) {
+            Some(index) => self.values.get(index as usize).copied().unwrap_or(0),
+            None => 0,
+        };
+    }
+}
+
+// A two dimensional array of values indexed by the classes of both glyphs. The left
+// classes are byte offsets of a row from the start of the subtable, the right ones byte
+// offsets into the row, so their sum points straight at the value.
+#[derive(Debug, Clone)]
+pub struct KernFormat2 {
+    pub row_width: u16, // in bytes
+    pub left_classes: KernClassTable,
+    pub right_classes: KernClassTable,
+    pub array_offset: u16, // from the start of the subtable
+    pub values: Vec<i16>,  // everything from array_offset to the end of the subtable
+}
+
+impl KernFormat2 {
+    pub fn kerning(&self, left: GlyphId, right: GlyphId) -> Option<i16> {
+        let offset =
+            self.left_classes.class(left) as usize + self.right_classes.class(right) as usize;
+        let index = offset.checked_sub(self.array_offset as usize)? / 2;
+        return self.values.get(index).copied();
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum KernFormatTable {
+    Format0(KernFormat0),
+    Format2(KernFormat2),
+    Unsupported(u8), // Apple's state machine and compact formats, 1 and 3
+}
+
+#[derive(Debug, Clone)]
+pub struct KernSubtable {
+    pub coverage: u16,    // the constants above
+    pub tuple_index: u16, // Apple only, which variation tuple the values belong to
+    pub format_table: KernFormatTable,
+}
+
+impl KernSubtable {
+    pub fn kerning(&self, left: GlyphId, right: GlyphId) -> Option<i16> {
+        return match &self.format_table {
+            KernFormatTable::Format0(table) => table.kerning(left, right),
+            KernFormatTable::Format2(table) => table.kerning(left, right),
+            KernFormatTable::Unsupported(_) => None,
+        };
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Kern {
+    pub version: u32, // 0 for the Microsoft header, 0x00010000 for Apple's
+    pub subtables: Vec<KernSubtable>,
+}
+
+impl Kern {
+    // Horizontal kerning of a pair, the subtables add up unless one overrides what the
+    // ones before it came up with. Minimum, cross-stream and variation subtables don't
+    // move glyphs apart along the line so they are left out.
+    pub fn kerning(&self, left: GlyphId, right: GlyphId) -> i16 {
+        let mut kerning = 0i32;
+        for subtable in self.subtables.iter() {
+            if subtable.coverage & (HORIZONTAL | MINIMUM | CROSS_STREAM | VARIATION) != HORIZONTAL {
+                continue;
+            }
+
+            if let Some(value) = subtable.kerning(left, right) {
+                kerning = match subtable.coverage & OVERRIDE {
+                    0 => kerning + value as i32,
+                    _ => value as i32,
+                };
+            }
+        }
+
+        return kerning.clamp(i16::MIN as i32, i16::MAX as i32) as i16;
+    }
+}
+
+fn read_format0(r: &mut reader::FontReader) -> Result<KernFormat0> {
+    // nPairs is all that matters, fonts with many pairs overflow the 16 bit subtable
+    // length so it can't be used to find the end
+    let pair_count = r.get_uint16()?;
+    let _search_range = r.get_uint16()?;
+    let _entry_selector = r.get_uint16()?;
+    let _range_shift = r.get_uint16()?;
+
+    let mut pairs = Vec::new();
+    for _ in 0..pair_count {
+        pairs.push(KernPair {
+            left: r.get_uint16()?,
+            right: r.get_uint16()?,
+            value: r.get_int16()?,
+        });
+    }
+
+    // the binary search needs them in order, which not every font gets right
+    let key = |pair: &KernPair| (pair.left, pair.right);
+    if pairs.windows(2).any(|pair| key(&pair[0]) > key(&pair[1])) {
+        pairs.sort_by_key(key);
+    }
+
+    return Ok(KernFormat0 { pairs });
+}
+
+fn read_class_table(r: &mut reader::FontReader, offset: usize) -> Result<KernClassTable> {
+    r.seek(offset)?;
+    let first_glyph = r.get_uint16()?;
+    let glyph_count = r.get_uint16()?;
+
+    let mut values = Vec::new();
+    for _ in 0..glyph_count {
+        values.push(r.get_uint16()?);
+    }
+
+    return Ok(KernClassTable {
+        first_glyph,
+        values,
+    });
+}
+
+// start is where the subtable header begins, every offset in format 2 counts from there
+fn read_format2(r: &mut reader::FontReader, start: usize, end: usize) -> Result<KernFormat2> {
+    let row_width = r.get_uint16()?;
+    let left_class_offset = r.get_uint16()?;
+    let right_class_offset = r.get_uint16()?;
+    let array_offset = r.get_uint16()?;
+
+    let left_classes = read_class_table(r, start + left_class_offset as usize)?;
+    let right_classes = read_class_table(r, start + right_class_offset as usize)?;
+
+    r.seek(start + array_offset as usize)?;
+    let mut values = Vec::new();
+    for _ in 0..end.saturating_sub(start + array_offset as usize) / 2 {
+        values.push(r.get_int16()?);
+    }
+
+    return Ok(KernFormat2 {
+        row_width,
+        left_classes,
+        right_classes,
+        array_offset,
+        values,
+    });
+}
+
+fn read_format_table(
+    r: &mut reader::FontReader,
+    format: u8,
+    start: usize,
+    end: usize,
+) -> Result<KernFormatTable> {
+    return Ok(match format {
+        0 => KernFormatTable::Format0(read_format0(r)?),
+        2 => KernFormatTable::Format2(read_format2(r, start, end)?),
+        format => KernFormatTable::Unsupported(format),
+    });
+}
+
+pub fn read(r: &mut reader::FontReader, kern_offset_table: OffsetTable) -> Result<Kern> {
+    let table_start = kern_offset_table.offset as usize;
+    let table_end = table_start + kern_offset_table.length as usize;
+    r.seek_table("kern", table_start)?;
+
+    // the Microsoft header starts with a 16 bit version of 0, Apple's with a 32 bit 1.0
+    let version = match r.get_uint16()? {
+        0 => 0,
+        1 if r.get_uint16()? == 0 => 0x00010000,
+        version => {
+            return Err(Error::UnsupportedFormat {
+                table: "kern".into(),
+                offset: table_start,
+                format: version as u32,
+            });
+        }
+    };
+
+    let subtable_count = match version {
+        0 => r.get_uint16()? as u32,
+        _ => r.get_uint32()?,
+    };
+
+    let mut subtables = Vec::new();
+    for _ in 0..subtable_count {
+        let start = r.pos;
+
+        let (length, coverage, format, tuple_index) = if version == 0 {
+            let _version = r.get_uint16()?;
+            let length = r.get_uint16()? as usize;
+            let coverage = r.get_uint16()?;
+            (length, coverage & 0x000F, (coverage >> 8) as u8, 0)
+        } else {
+            let length = r.get_uint32()? as usize;
+            let apple_coverage = r.get_uint16()?;
+            let tuple_index = r.get_uint16()?;
+
+            // vertical, cross-stream and variation flags in the high byte
+            let mut coverage = 0;
+            if apple_coverage & 0x8000 == 0 {
+                coverage |= HORIZONTAL;
+            }
+            if apple_coverage & 0x4000 != 0 {
+                coverage |= CROSS_STREAM;
+            }
+            if apple_coverage & 0x2000 != 0 {
+                coverage |= VARIATION;
+            }
+            (length, coverage, apple_coverage as u8, tuple_index)
+        };
+
+        let end = (start + length).min(table_end);
+        let format_table = read_format_table(r, format, start, end)?;
+        subtables.push(KernSubtable {
+            coverage,
+            tuple_index,
+            format_table,
+        });
+
+        // a format 0 subtable too big for its length field ends where its pairs do
+        let next = match &subtables.last().unwrap().format_table {
+            KernFormatTable::Format0(_) => r.pos.max(start + length),
+            _ => start + length,
+        };
+        // a length of zero would read the same subtable over and over
+        if next <= start || next >= table_end {
+            break;
+        }
+        r.seek(next)?;
+    }
+
+    return Ok(Kern { version, subtables });
+}
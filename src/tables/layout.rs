@@ -0,0 +1,575 @@
+use crate::error::{Error, Result};
+use crate::reader;
+use crate::tables::offset::OffsetTable;
+use std::collections::HashMap;
+mod context;
+mod coverage;
+mod device;
+mod variations;
+
+pub use context::{
+    read_chained_sequence_context, read_sequence_context, ChainedSequenceContext,
+    ChainedSequenceRule, SequenceContext, SequenceLookupRecord, SequenceRule,
+};
+pub use coverage::{
+    read_class_def, read_coverage, ClassDef, ClassRangeRecord, Coverage, RangeRecord,
+};
+pub use device::{read_device, Device};
+pub use variations::{
+    Condition, ConditionSet, FeatureTableSubstitution, FeatureTableSubstitutionRecord,
+    FeatureVariationRecord, FeatureVariations,
+};
+
+// GDEF glyph classes, the lookup flags skip glyphs by them. 0 means the font didn't say.
+pub const BASE_GLYPH: u16 = 1;
+pub const LIGATURE_GLYPH: u16 = 2;
+pub const MARK_GLYPH: u16 = 3;
+pub const COMPONENT_GLYPH: u16 = 4;
+
+// lookupFlag bits
+pub const RIGHT_TO_LEFT: u16 = 0x0001; // cursive attachment only, the last glyph stays put
+pub const IGNORE_BASE_GLYPHS: u16 = 0x0002;
+pub const IGNORE_LIGATURES: u16 = 0x0004;
+pub const IGNORE_MARKS: u16 = 0x0008;
+pub const USE_MARK_FILTERING_SET: u16 = 0x0010;
+pub const MARK_ATTACHMENT_TYPE: u16 = 0xFF00; // skip marks of any other GDEF attachment class
+
+#[derive(Debug, Clone, Default)]
+pub struct LangSys {
+    pub required_feature_index: Option<u16>, // 0xFFFF in the font when there is none
+    pub feature_indices: Vec<u16>,           // into the FeatureList
+}
+
+#[derive(Debug, Clone)]
+pub struct LangSysRecord {
+    pub tag: String, // OpenType language system tag, e.g. "TRK "
+    pub lang_sys: LangSys,
+}
+
+#[derive(Debug, Clone)]
+pub struct Script {
+    pub default_lang_sys: Option<LangSys>,
+    pub lang_sys_records: Vec<LangSysRecord>, // sorted by tag
+}
+
+impl Script {
+    // The language system of a language, languages the script doesn't list and None get
+    // the default one
+    pub fn lang_sys(&self, language: Option<&str>) -> Option<&LangSys> {
+        if let Some(language) = language {
+            if let Some(record) = self
+                .lang_sys_records
+                .iter()
+                .find(|record| record.tag == language)
+            {
+                return Some(&record.lang_sys);
+            }
+        }
+
+        return self.default_lang_sys.as_ref();
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ScriptRecord {
+    pub tag: String, // OpenType script tag, e.g. "latn" or "DFLT"
+    pub script: Script,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ScriptList {
+    pub scripts: Vec<ScriptRecord>, // sorted by tag
+}
+
+impl ScriptList {
+    pub fn script(&self, tag: &str) -> Option<&Script> {
+        return self
+            .scripts
+            .iter()
+            .find(|record| record.tag == tag)
+            .map(|record| &record.script);
+    }
+}
+
+// Only a handful of features define parameters, which ones is up to the feature tag
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FeatureParams {
+    Size {
+        design_size: u16,       // in decipoints
+        subfamily_id: u16,      // identifies the fonts of a family that differ only in size
+        subfamily_name_id: u16, // name table entry of the subfamily, 0 without one
+        range_start: u16,       // small end of the sizes the font is meant for, exclusive
+        range_end: u16,         // large end of the sizes the font is meant for, inclusive
+    },
+    StylisticSet {
+        // ss01 to ss20
+        version: u16,
+        ui_name_id: u16, // name table entry describing the set, 0 without one
+    },
+    CharacterVariant {
+        // cv01 to cv99
+        format: u16,
+        feat_ui_label_name_id: u16,
+        feat_ui_tooltip_text_name_id: u16,
+        sample_text_name_id: u16,
+        num_named_parameters: u16,
+        first_param_ui_label_name_id: u16,
+        characters: Vec<u32>, // the Unicode characters the feature applies to
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct Feature {
+    pub feature_params: Option<FeatureParams>,
+    pub lookup_list_indices: Vec<u16>, // in the order they apply
+}
+
+#[derive(Debug, Clone)]
+pub struct FeatureRecord {
+    pub tag: String, // e.g. "liga" or "kern"
+    pub feature: Feature,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct FeatureList {
+    pub features: Vec<FeatureRecord>, // sorted by tag, the same tag can show up many times
+}
+
+// T is the table's own subtable type. Extension subtables are unwrapped while reading, so
+// lookup_type is always the type of the actual subtables.
+#[derive(Debug, Clone)]
+pub struct Lookup<T> {
+    pub lookup_type: u16,
+    pub lookup_flag: u16, // the constants above
+    pub subtables: Vec<T>,
+    pub mark_filtering_set: Option<u16>, // into the GDEF mark glyph sets, with USE_MARK_FILTERING_SET
+}
+
+impl<T> Lookup<T> {
+    pub fn mark_attachment_type(&self) -> u16 {
+        return (self.lookup_flag & MARK_ATTACHMENT_TYPE) >> 8;
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LookupList<T> {
+    pub lookups: Vec<Lookup<T>>,
+}
+
+// What GSUB and GPOS share, both are just a different kind of lookup on top of this
+#[derive(Debug, Clone)]
+pub struct LayoutTable<T> {
+    pub major_version: u16, // 1
+    pub minor_version: u16, // 0 or 1, 1 adds the feature variations
+    pub script_list: ScriptList,
+    pub feature_list: FeatureList,
+    pub lookup_list: LookupList<T>,
+    pub feature_variations: Option<FeatureVariations>,
+}
+
+impl<T> LayoutTable<T> {
+    // Tag and feature at index, the feature being whatever replaces it in substitution when
+    // there is one, see FeatureVariations::find
+    pub fn feature<'t>(
+        &'t self,
+        index: u16,
+        substitution: Option<&'t FeatureTableSubstitution>,
+    ) -> Option<(&'t str, &'t Feature)> {
+        let record = self.feature_list.features.get(index as usize)?;
+        let feature = substitution
+            .and_then(|substitution| substitution.feature(index))
+            .unwrap_or(&record.feature);
+        return Some((&record.tag, feature));
+    }
+}
+
+fn read_lang_sys(r: &mut reader::FontReader, offset: usize) -> Result<LangSys> {
+    r.seek(offset)?;
+    let _lookup_order_offset = r.get_uint16()?; // reserved
+    let required_feature_index = match r.get_uint16()? {
+        0xFFFF => None,
+        index => Some(index),
+    };
+
+    let feature_index_count = r.get_uint16()?;
+    let mut feature_indices = Vec::new();
+    for _ in 0..feature_index_count {
+        feature_indices.push(r.get_uint16()?);
+    }
+
+    return Ok(LangSys {
+        required_feature_index,
+        feature_indices,
+    });
+}
+
+fn read_script(r: &mut reader::FontReader, offset: usize) -> Result<Script> {
+    r.seek(offset)?;
+    let default_lang_sys_offset = r.get_uint16()? as usize;
+    let lang_sys_count = r.get_uint16()?;
+
+    let mut records = Vec::new();
+    for _ in 0..lang_sys_count {
+        records.push((r.get_string(4)?, r.get_uint16()? as usize));
+    }
+
+    let default_lang_sys = match default_lang_sys_offset {
+        0 => None,
+        _ => Some(read_lang_sys(r, offset + default_lang_sys_offset)?),
+    };
+
+    let mut lang_sys_records = Vec::new();
+    for (tag, lang_sys_offset) in records {
+        lang_sys_records.push(LangSysRecord {
+            tag,
+            lang_sys: read_lang_sys(r, offset + lang_sys_offset)?,
+        });
+    }
+
+    return Ok(Script {
+        default_lang_sys,
+        lang_sys_records,
+    });
+}
+
+pub fn read_script_list(r: &mut reader::FontReader, offset: usize) -> Result<ScriptList> {
+    r.seek(offset)?;
+    let script_count = r.get_uint16()?;
+
+    let mut records = Vec::new();
+    for _ in 0..script_count {
+        records.push((r.get_string(4)?, r.get_uint16()? as usize));
+    }
+
+    let mut scripts = Vec::new();
+    for (tag, script_offset) in records {
+        scripts.push(ScriptRecord {
+            tag,
+            script: read_script(r, offset + script_offset)?,
+        });
+    }
+
+    return Ok(ScriptList { scripts });
+}
+
+fn read_size_params(r: &mut reader::FontReader, offset: usize) -> Result<FeatureParams> {
+    r.seek(offset)?;
+    return Ok(FeatureParams::Size {
+        design_size: r.get_uint16()?,
+        subfamily_id: r.get_uint16()?,
+        subfamily_name_id: r.get_uint16()?,
+        range_start: r.get_uint16()?,
+        range_end: r.get_uint16()?,
+    });
+}
+
+// The sanity checks the spec suggests, either no size range at all or one around the
+// design size with a proper name
+fn valid_size_params(params: &FeatureParams) -> bool {
+    return match *params {
+        FeatureParams::Size {
+            design_size,
+            subfamily_id,
+            subfamily_name_id,
+            range_start,
+            range_end,
+        } => {
+            design_size != 0
+                && ((subfamily_id == 0
+                    && subfamily_name_id == 0
+                    && range_start == 0
+                    && range_end == 0)
+                    || (range_start <= design_size
+                        && design_size <= range_end
+                        && (256..=32767).contains(&subfamily_name_id)))
+        }
+        _ => false,
+    };
+}
+
+fn read_feature_params(
+    r: &mut reader::FontReader,
+    tag: &str,
+    offset: usize,
+    feature_list_offset: usize,
+    params_offset: usize,
+) -> Result<Option<FeatureParams>> {
+    if tag == "size" {
+        // early fonts counted the offset from the FeatureList, the spec lets readers
+        // try both and the wrong one may well point past the table
+        for base in [offset, feature_list_offset] {
+            let params = read_size_params(r, base + params_offset).ok();
+            if let Some(params) = params.filter(valid_size_params) {
+                return Ok(Some(params));
+            }
+        }
+        return Ok(None);
+    }
+
+    r.seek(offset + params_offset)?;
+    if tag.starts_with("ss") {
+        return Ok(Some(FeatureParams::StylisticSet {
+            version: r.get_uint16()?,
+            ui_name_id: r.get_uint16()?,
+        }));
+    }
+
+    if tag.starts_with("cv") {
+        let format = r.get_uint16()?;
+        let feat_ui_label_name_id = r.get_uint16()?;
+        let feat_ui_tooltip_text_name_id = r.get_uint16()?;
+        let sample_text_name_id = r.get_uint16()?;
+        let num_named_parameters = r.get_uint16()?;
+        let first_param_ui_label_name_id = r.get_uint16()?;
+        let char_count = r.get_uint16()?;
+        let mut characters = Vec::new();
+        for _ in 0..char_count {
+            characters.push(r.get_uint24()?);
+        }
+
+        return Ok(Some(FeatureParams::CharacterVariant {
+            format,
+            feat_ui_label_name_id,
+            feat_ui_tooltip_text_name_id,
+            sample_text_name_id,
+            num_named_parameters,
+            first_param_ui_label_name_id,
+            characters,
+        }));
+    }
+
+    return Ok(None);
+}
+
+// The tag decides how the parameters are read, feature_list_offset is only there for the
+// old size parameters
+fn read_feature(
+    r: &mut reader::FontReader,
+    tag: &str,
+    offset: usize,
+    feature_list_offset: usize,
+) -> Result<Feature> {
+    r.seek(offset)?;
+    let params_offset = r.get_uint16()? as usize;
+    let lookup_index_count = r.get_uint16()?;
+
+    let mut lookup_list_indices = Vec::new();
+    for _ in 0..lookup_index_count {
+        lookup_list_indices.push(r.get_uint16()?);
+    }
+
+    let feature_params = match params_offset {
+        0 => None,
+        _ => read_feature_params(r, tag, offset, feature_list_offset, params_offset)?,
+    };
+
+    return Ok(Feature {
+        feature_params,
+        lookup_list_indices,
+    });
+}
+
+pub fn read_feature_list(r: &mut reader::FontReader, offset: usize) -> Result<FeatureList> {
+    r.seek(offset)?;
+    let feature_count = r.get_uint16()?;
+
+    let mut records = Vec::new();
+    for _ in 0..feature_count {
+        records.push((r.get_string(4)?, r.get_uint16()? as usize));
+    }
+
+    let mut features = Vec::new();
+    for (tag, feature_offset) in records {
+        let feature = read_feature(r, &tag, offset + feature_offset, offset)?;
+        features.push(FeatureRecord { tag, feature });
+    }
+
+    return Ok(FeatureList { features });
+}
+
+// Every subtable of an extension lookup points at a subtable of the real type, which all
+// have to agree
+fn read_extension(
+    r: &mut reader::FontReader,
+    offset: usize,
+    lookup_type: &mut Option<u16>,
+) -> Result<usize> {
+    r.seek(offset)?;
+    let format = r.get_uint16()?;
+    if format != 1 {
+        return Err(Error::UnsupportedFormat {
+            table: r.table.into(),
+            offset,
+            format: format as u32,
+        });
+    }
+
+    let extension_lookup_type = r.get_uint16()?;
+    let extension_offset = r.get_uint32()? as usize;
+    if *lookup_type.get_or_insert(extension_lookup_type) != extension_lookup_type {
+        return Err(Error::Malformed {
+            table: r.table.into(),
+            offset,
+            reason: "extension subtables of different lookup types",
+        });
+    }
+
+    return Ok(offset + extension_offset);
+}
+
+// Lookups can point at the same subtable any number of times, so every subtable is read
+// once and there is a limit to how many the lookups can list in all
+const MAX_SUBTABLES: usize = 1 << 16;
+
+// read_subtable gets the lookup type and where the subtable starts, extension_type is the
+// lookup type that wraps the others in this table
+pub fn read_lookup_list<T: Clone>(
+    r: &mut reader::FontReader,
+    offset: usize,
+    extension_type: u16,
+    read_subtable: &dyn Fn(&mut reader::FontReader, u16, usize) -> Result<T>,
+) -> Result<LookupList<T>> {
+    r.seek(offset)?;
+    let lookup_count = r.get_uint16()?;
+
+    let mut lookup_offsets = Vec::new();
+    for _ in 0..lookup_count {
+        lookup_offsets.push(offset + r.get_uint16()? as usize);
+    }
+
+    let mut lookups = Vec::new();
+    let mut parsed: HashMap<(u16, usize), Option<T>> = HashMap::new();
+    let mut total = 0;
+    for lookup_offset in lookup_offsets {
+        r.seek(lookup_offset)?;
+        let mut lookup_type = r.get_uint16()?;
+        let lookup_flag = r.get_uint16()?;
+        let subtable_count = r.get_uint16()?;
+
+        total += subtable_count as usize;
+        if total > MAX_SUBTABLES {
+            return Err(Error::Malformed {
+                table: r.table.into(),
+                offset: lookup_offset,
+                reason: "too many lookup subtables",
+            });
+        }
+
+        let mut subtable_offsets = Vec::new();
+        for _ in 0..subtable_count {
+            subtable_offsets.push(lookup_offset + r.get_uint16()? as usize);
+        }
+
+        let mark_filtering_set = match lookup_flag & USE_MARK_FILTERING_SET {
+            0 => None,
+            _ => Some(r.get_uint16()?),
+        };
+
+        if lookup_type == extension_type {
+            let mut extension_lookup_type = None;
+            for subtable_offset in subtable_offsets.iter_mut() {
+                *subtable_offset = read_extension(r, *subtable_offset, &mut extension_lookup_type)?;
+            }
+            lookup_type = extension_lookup_type.unwrap_or(extension_type);
+        }
+
+        // subtables of a lookup type or format the reader doesn't know are left out, the
+        // lookup stays in the list so the indices to the ones after it still hold
+        let mut subtables = Vec::new();
+        for subtable_offset in subtable_offsets {
+            let key = (lookup_type, subtable_offset);
+            if let Some(subtable) = parsed.get(&key) {
+                subtables.extend(subtable.clone());
+                continue;
+            }
+
+            let subtable = match read_subtable(r, lookup_type, subtable_offset) {
+                Ok(subtable) => Some(subtable),
+                Err(Error::UnsupportedFormat { .. }) => None,
+                Err(err) => return Err(err),
+            };
+            subtables.extend(subtable.clone());
+            parsed.insert(key, subtable);
+        }
+
+        lookups.push(Lookup {
+            lookup_type,
+            lookup_flag,
+            subtables,
+            mark_filtering_set,
+        });
+    }
+
+    return Ok(LookupList { lookups });
+}
+
+// Reads the header and the lists of a GSUB or GPOS table, null offsets leave a list empty
+pub fn read<T: Clone>(
+    r: &mut reader::FontReader,
+    table: &'static str,
+    offset_table: OffsetTable,
+    extension_type: u16,
+    read_subtable: &dyn Fn(&mut reader::FontReader, u16, usize) -> Result<T>,
+) -> Result<LayoutTable<T>> {
+    let offset = offset_table.offset as usize;
+    r.seek_table(table, offset)?;
+
+    let major_version = r.get_uint16()?;
+    let minor_version = r.get_uint16()?;
+    if major_version != 1 {
+        return Err(Error::UnsupportedFormat {
+            table: table.into(),
+            offset,
+            format: major_version as u32,
+        });
+    }
+
+    let script_list_offset = r.get_uint16()? as usize;
+    let feature_list_offset = r.get_uint16()? as usize;
+    let lookup_list_offset = r.get_uint16()? as usize;
+    let feature_variations_offset = match minor_version {
+        0 => 0,
+        _ => r.get_uint32()? as usize,
+    };
+
+    let script_list = match script_list_offset {
+        0 => ScriptList::default(),
+        _ => read_script_list(r, offset + script_list_offset)?,
+    };
+
+    let feature_list = match feature_list_offset {
+        0 => FeatureList::default(),
+        _ => read_feature_list(r, offset + feature_list_offset)?,
+    };
+
+    let lookup_list = match lookup_list_offset {
+        0 => LookupList {
+            lookups: Vec::new(),
+        },
+        _ => read_lookup_list(
+            r,
+            offset + lookup_list_offset,
+            extension_type,
+            read_subtable,
+        )?,
+    };
+
+    let feature_variations = match feature_variations_offset {
+        0 => None,
+        _ => Some(variations::read_feature_variations(
+            r,
+            offset + feature_variations_offset,
+            &feature_list,
+            offset + feature_list_offset,
+        )?),
+    };
+
+    return Ok(LayoutTable {
+        major_version,
+        minor_version,
+        script_list,
+        feature_list,
+        lookup_list,
+        feature_variations,
+    });
+}
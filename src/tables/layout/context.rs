@@ -0,0 +1,304 @@
+use crate::error::{Error, Result};
+use crate::reader;
+use crate::tables::layout::{read_class_def, read_coverage, ClassDef, Coverage};
+
+// Applies a lookup at one glyph of a matched sequence
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SequenceLookupRecord {
+    pub sequence_index: u16,    // into the input sequence, 0 is the first glyph
+    pub lookup_list_index: u16, // into the LookupList of the same table
+}
+
+// Glyphs or classes depending on the format, the first input glyph is left out since the
+// coverage already matched it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SequenceRule {
+    pub input_sequence: Vec<u16>,
+    pub seq_lookup_records: Vec<SequenceLookupRecord>,
+}
+
+// Backtrack is stored in reading order away from the input, so its first entry is the glyph
+// right before it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChainedSequenceRule {
+    pub backtrack_sequence: Vec<u16>,
+    pub input_sequence: Vec<u16>, // without the first glyph like SequenceRule
+    pub lookahead_sequence: Vec<u16>,
+    pub seq_lookup_records: Vec<SequenceLookupRecord>,
+}
+
+// Contextual lookups, GSUB type 5 and GPOS type 7
+#[derive(Debug, Clone)]
+pub enum SequenceContext {
+    Format1 {
+        coverage: Coverage,
+        seq_rule_sets: Vec<Vec<SequenceRule>>, // by coverage index
+    },
+    Format2 {
+        coverage: Coverage,
+        class_def: ClassDef,
+        class_seq_rule_sets: Vec<Vec<SequenceRule>>, // by class of the first glyph
+    },
+    Format3 {
+        coverages: Vec<Coverage>, // one for every input glyph
+        seq_lookup_records: Vec<SequenceLookupRecord>,
+    },
+}
+
+// Chained contextual lookups, GSUB type 6 and GPOS type 8
+#[derive(Debug, Clone)]
+pub enum ChainedSequenceContext {
+    Format1 {
+        coverage: Coverage,
+        chained_seq_rule_sets: Vec<Vec<ChainedSequenceRule>>, // by coverage index
+    },
+    Format2 {
+        coverage: Coverage,
+        backtrack_class_def: ClassDef,
+        input_class_def: ClassDef,
+        lookahead_class_def: ClassDef,
+        chained_class_seq_rule_sets: Vec<Vec<ChainedSequenceRule>>, // by input class of the first glyph
+    },
+    Format3 {
+        backtrack_coverages: Vec<Coverage>, // same order as ChainedSequenceRule::backtrack_sequence
+        input_coverages: Vec<Coverage>,
+        lookahead_coverages: Vec<Coverage>,
+        seq_lookup_records: Vec<SequenceLookupRecord>,
+    },
+}
+
+fn read_offsets(r: &mut reader::FontReader, base: usize) -> Result<Vec<Option<usize>>> {
+    let count = r.get_uint16()?;
+    let mut offsets = Vec::new();
+    for _ in 0..count {
+        offsets.push(match r.get_uint16()? {
+            0 => None,
+            offset => Some(base + offset as usize),
+        });
+    }
+    return Ok(offsets);
+}
+
+fn read_glyphs(r: &mut reader::FontReader, count: u16) -> Result<Vec<u16>> {
+    let mut glyphs = Vec::new();
+    for _ in 0..count {
+        glyphs.push(r.get_uint16()?);
+    }
+    return Ok(glyphs);
+}
+
+fn read_lookup_records(
+    r: &mut reader::FontReader,
+    count: u16,
+) -> Result<Vec<SequenceLookupRecord>> {
+    let mut records = Vec::new();
+    for _ in 0..count {
+        records.push(SequenceLookupRecord {
+            sequence_index: r.get_uint16()?,
+            lookup_list_index: r.get_uint16()?,
+        });
+    }
+    return Ok(records);
+}
+
+fn read_coverages(r: &mut reader::FontReader, base: usize, count: u16) -> Result<Vec<Coverage>> {
+    let mut offsets = Vec::new();
+    for _ in 0..count {
+        offsets.push(base + r.get_uint16()? as usize);
+    }
+
+    let mut coverages = Vec::new();
+    for offset in offsets {
+        coverages.push(read_coverage(r, offset)?);
+    }
+    return Ok(coverages);
+}
+
+// A null class definition puts every glyph in class 0
+fn read_optional_class_def(
+    r: &mut reader::FontReader,
+    base: usize,
+    offset: u16,
+) -> Result<ClassDef> {
+    return match offset {
+        0 => Ok(ClassDef::Format2(Vec::new())),
+        _ => read_class_def(r, base + offset as usize),
+    };
+}
+
+// Rule sets of formats 1 and 2, a null rule set simply has no rules
+fn read_rule_sets<T>(
+    r: &mut reader::FontReader,
+    offsets: Vec<Option<usize>>,
+    read_rule: fn(&mut reader::FontReader) -> Result<T>,
+) -> Result<Vec<Vec<T>>> {
+    let mut rule_sets = Vec::new();
+    for offset in offsets {
+        let mut rules = Vec::new();
+        if let Some(offset) = offset {
+            r.seek(offset)?;
+            for rule_offset in read_offsets(r, offset)?.into_iter().flatten() {
+                r.seek(rule_offset)?;
+                rules.push(read_rule(r)?);
+            }
+        }
+        rule_sets.push(rules);
+    }
+    return Ok(rule_sets);
+}
+
+fn read_sequence_rule(r: &mut reader::FontReader) -> Result<SequenceRule> {
+    let glyph_count = r.get_uint16()?;
+    let seq_lookup_count = r.get_uint16()?;
+    let input_sequence = read_glyphs(r, glyph_count.saturating_sub(1))?;
+    let seq_lookup_records = read_lookup_records(r, seq_lookup_count)?;
+
+    return Ok(SequenceRule {
+        input_sequence,
+        seq_lookup_records,
+    });
+}
+
+fn read_chained_sequence_rule(r: &mut reader::FontReader) -> Result<ChainedSequenceRule> {
+    let backtrack_glyph_count = r.get_uint16()?;
+    let backtrack_sequence = read_glyphs(r, backtrack_glyph_count)?;
+    let input_glyph_count = r.get_uint16()?;
+    let input_sequence = read_glyphs(r, input_glyph_count.saturating_sub(1))?;
+    let lookahead_glyph_count = r.get_uint16()?;
+    let lookahead_sequence = read_glyphs(r, lookahead_glyph_count)?;
+    let seq_lookup_count = r.get_uint16()?;
+    let seq_lookup_records = read_lookup_records(r, seq_lookup_count)?;
+
+    return Ok(ChainedSequenceRule {
+        backtrack_sequence,
+        input_sequence,
+        lookahead_sequence,
+        seq_lookup_records,
+    });
+}
+
+pub fn read_sequence_context(r: &mut reader::FontReader, offset: usize) -> Result<SequenceContext> {
+    r.seek(offset)?;
+
+    match r.get_uint16()? {
+        1 => {
+            let coverage_offset = offset + r.get_uint16()? as usize;
+            let rule_set_offsets = read_offsets(r, offset)?;
+            return Ok(SequenceContext::Format1 {
+                coverage: read_coverage(r, coverage_offset)?,
+                seq_rule_sets: read_rule_sets(r, rule_set_offsets, read_sequence_rule)?,
+            });
+        }
+        2 => {
+            let coverage_offset = offset + r.get_uint16()? as usize;
+            let class_def_offset = r.get_uint16()?;
+            let rule_set_offsets = read_offsets(r, offset)?;
+            return Ok(SequenceContext::Format2 {
+                coverage: read_coverage(r, coverage_offset)?,
+                class_def: read_optional_class_def(r, offset, class_def_offset)?,
+                class_seq_rule_sets: read_rule_sets(r, rule_set_offsets, read_sequence_rule)?,
+            });
+        }
+        3 => {
+            let glyph_count = r.get_uint16()?;
+            let seq_lookup_count = r.get_uint16()?;
+            let position = r.pos;
+            let coverages = read_coverages(r, offset, glyph_count)?;
+
+            r.seek(position + glyph_count as usize * 2)?;
+            let seq_lookup_records = read_lookup_records(r, seq_lookup_count)?;
+            return Ok(SequenceContext::Format3 {
+                coverages,
+                seq_lookup_records,
+            });
+        }
+        format => {
+            return Err(Error::UnsupportedFormat {
+                table: r.table.into(),
+                offset,
+                format: format as u32,
+            });
+        }
+    }
+}
+
+pub fn read_chained_sequence_context(
+    r: &mut reader::FontReader,
+    offset: usize,
+) -> Result<ChainedSequenceContext> {
+    r.seek(offset)?;
+
+    match r.get_uint16()? {
+        1 => {
+            let coverage_offset = offset + r.get_uint16()? as usize;
+            let rule_set_offsets = read_offsets(r, offset)?;
+            return Ok(ChainedSequenceContext::Format1 {
+                coverage: read_coverage(r, coverage_offset)?,
+                chained_seq_rule_sets: read_rule_sets(
+                    r,
+                    rule_set_offsets,
+                    read_chained_sequence_rule,
+                )?,
+            });
+        }
+        2 => {
+            let coverage_offset = offset + r.get_uint16()? as usize;
+            let backtrack_class_def_offset = r.get_uint16()?;
+            let input_class_def_offset = r.get_uint16()?;
+            let lookahead_class_def_offset = r.get_uint16()?;
+            let rule_set_offsets = read_offsets(r, offset)?;
+            return Ok(ChainedSequenceContext::Format2 {
+                coverage: read_coverage(r, coverage_offset)?,
+                backtrack_class_def: read_optional_class_def(
+                    r,
+                    offset,
+                    backtrack_class_def_offset,
+                )?,
+                input_class_def: read_optional_class_def(r, offset, input_class_def_offset)?,
+                lookahead_class_def: read_optional_class_def(
+                    r,
+                    offset,
+                    lookahead_class_def_offset,
+                )?,
+                chained_class_seq_rule_sets: read_rule_sets(
+                    r,
+                    rule_set_offsets,
+                    read_chained_sequence_rule,
+                )?,
+            });
+        }
+        3 => {
+            // the coverage arrays sit between the counts, each is read before moving on
+            let backtrack_glyph_count = r.get_uint16()?;
+            let position = r.pos;
+            let backtrack_coverages = read_coverages(r, offset, backtrack_glyph_count)?;
+            r.seek(position + backtrack_glyph_count as usize * 2)?;
+
+            let input_glyph_count = r.get_uint16()?;
+            let position = r.pos;
+            let input_coverages = read_coverages(r, offset, input_glyph_count)?;
+            r.seek(position + input_glyph_count as usize * 2)?;
+
+            let lookahead_glyph_count = r.get_uint16()?;
+            let position = r.pos;
+            let lookahead_coverages = read_coverages(r, offset, lookahead_glyph_count)?;
+            r.seek(position + lookahead_glyph_count as usize * 2)?;
+
+            let seq_lookup_count = r.get_uint16()?;
+            let seq_lookup_records = read_lookup_records(r, seq_lookup_count)?;
+            return Ok(ChainedSequenceContext::Format3 {
+                backtrack_coverages,
+                input_coverages,
+                lookahead_coverages,
+                seq_lookup_records,
+            });
+        }
+        format => {
+            return Err(Error::UnsupportedFormat {
+                table: r.table.into(),
+                offset,
+                format: format as u32,
+            });
+        }
+    }
+}
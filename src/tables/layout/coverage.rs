@@ -0,0 +1,184 @@
+use crate::error::{Error, Result};
+use crate::reader;
+use crate::GlyphId;
+use std::cmp::Ordering;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct RangeRecord {
+    pub start_glyph_id: u16,
+    pub end_glyph_id: u16,         // inclusive
+    pub start_coverage_index: u16, // coverage index of start_glyph_id
+}
+
+// The glyphs a subtable applies to, a glyph's coverage index picks the data that goes with
+// it in the subtable
+#[derive(Debug, Clone)]
+pub enum Coverage {
+    Format1(Vec<u16>), // sorted glyphs, the coverage index is the position
+    Format2(Vec<RangeRecord>),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ClassRangeRecord {
+    pub start_glyph_id: u16,
+    pub end_glyph_id: u16, // inclusive
+    pub class: u16,
+}
+
+// Sorts glyphs into classes, glyphs it doesn't mention are all in class 0
+#[derive(Debug, Clone)]
+pub enum ClassDef {
+    Format1 {
+        start_glyph_id: u16,
+        class_values: Vec<u16>, // one for each glyph from start_glyph_id on
+    },
+    Format2(Vec<ClassRangeRecord>),
+}
+
+fn compare_range(start: u16, end: u16, glyph: u16) -> Ordering {
+    if end < glyph {
+        return Ordering::Less;
+    } else if start > glyph {
+        return Ordering::Greater;
+    }
+    return Ordering::Equal;
+}
+
+impl Coverage {
+    pub fn index(&self, glyph: GlyphId) -> Option<u16> {
+        match self {
+            Coverage::Format1(glyphs) => {
+                return glyphs
+                    .binary_search(&glyph.0)
+                    .ok()
+                    .map(|index| index as u16);
+            }
+            Coverage::Format2(ranges) => {
+                let index = ranges
+                    .binary_search_by(|range| {
+                        compare_range(range.start_glyph_id, range.end_glyph_id, glyph.0)
+                    })
+                    .ok()?;
+                let range = ranges[index];
+                return range
+                    .start_coverage_index
+                    .checked_add(glyph.0 - range.start_glyph_id);
+            }
+        }
+    }
+
+    pub fn contains(&self, glyph: GlyphId) -> bool {
+        return self.index(glyph).is_some();
+    }
+}
+
+impl ClassDef {
+    pub fn class(&self, glyph: GlyphId) -> u16 {
+        match self {
+            ClassDef::Format1 {
+                start_glyph_id,
+                class_values,
+            } => {
+                return match glyph.0.checked_sub(*start_glyph_id) {
+                    Some(index) => class_values.get(index as usize).copied().unwrap_or(0),
+                    None => 0,
+                };
+            }
+            ClassDef::Format2(ranges) => {
+                return ranges
+                    .binary_search_by(|range| {
+                        compare_range(range.start_glyph_id, range.end_glyph_id, glyph.0)
+                    })
+                    .map(|index| ranges[index].class)
+                    .unwrap_or(0);
+            }
+        }
+    }
+}
+
+pub fn read_coverage(r: &mut reader::FontReader, offset: usize) -> Result<Coverage> {
+    r.seek(offset)?;
+
+    match r.get_uint16()? {
+        1 => {
+            let glyph_count = r.get_uint16()?;
+            let mut glyphs = Vec::new();
+            for _ in 0..glyph_count {
+                glyphs.push(r.get_uint16()?);
+            }
+            return Ok(Coverage::Format1(glyphs));
+        }
+        2 => {
+            let range_count = r.get_uint16()?;
+            let mut ranges = Vec::new();
+            for _ in 0..range_count {
+                ranges.push(RangeRecord {
+                    start_glyph_id: r.get_uint16()?,
+                    end_glyph_id: r.get_uint16()?,
+                    start_coverage_index: r.get_uint16()?,
+                });
+            }
+
+            // every range carries its own coverage index, so ordering them is harmless
+            if ranges
+                .windows(2)
+                .any(|pair| pair[0].start_glyph_id > pair[1].start_glyph_id)
+            {
+                ranges.sort_by_key(|range| range.start_glyph_id);
+            }
+            return Ok(Coverage::Format2(ranges));
+        }
+        format => {
+            return Err(Error::UnsupportedFormat {
+                table: r.table.into(),
+                offset,
+                format: format as u32,
+            });
+        }
+    }
+}
+
+pub fn read_class_def(r: &mut reader::FontReader, offset: usize) -> Result<ClassDef> {
+    r.seek(offset)?;
+
+    match r.get_uint16()? {
+        1 => {
+            let start_glyph_id = r.get_uint16()?;
+            let glyph_count = r.get_uint16()?;
+            let mut class_values = Vec::new();
+            for _ in 0..glyph_count {
+                class_values.push(r.get_uint16()?);
+            }
+            return Ok(ClassDef::Format1 {
+                start_glyph_id,
+                class_values,
+            });
+        }
+        2 => {
+            let range_count = r.get_uint16()?;
+            let mut ranges = Vec::new();
+            for _ in 0..range_count {
+                ranges.push(ClassRangeRecord {
+                    start_glyph_id: r.get_uint16()?,
+                    end_glyph_id: r.get_uint16()?,
+                    class: r.get_uint16()?,
+                });
+            }
+
+            if ranges
+                .windows(2)
+                .any(|pair| pair[0].start_glyph_id > pair[1].start_glyph_id)
+            {
+                ranges.sort_by_key(|range| range.start_glyph_id);
+            }
+            return Ok(ClassDef::Format2(ranges));
+        }
+        format => {
+            return Err(Error::UnsupportedFormat {
+                table: r.table.into(),
+                offset,
+                format: format as u32,
+            });
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use crate::error::{Error, Result};
+use crate::reader;
+
+// Hinting devices correct a value by a few pixels at small sizes, variation devices point
+// into the ItemVariationStore of GDEF instead
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Device {
+    Hinting {
+        start_size: u16,
+        end_size: u16,
+        delta_values: Vec<i8>, // in pixels, one per size from start_size to end_size
+    },
+    VariationIndex {
+        delta_set_outer_index: u16,
+        delta_set_inner_index: u16,
+    },
+}
+
+impl Device {
+    // Pixels to add at ppem, variation deltas need the variation store and count as none
+    pub fn delta(&self, ppem: u16) -> i16 {
+        match self {
+            Device::Hinting {
+                start_size,
+                end_size,
+                delta_values,
+            } => {
+                if ppem < *start_size || ppem > *end_size {
+                    return 0;
+                }
+                return delta_values[(ppem - start_size) as usize] as i16;
+            }
+            Device::VariationIndex { .. } => return 0,
+        }
+    }
+}
+
+pub fn read_device(r: &mut reader::FontReader, offset: usize) -> Result<Device> {
+    r.seek(offset)?;
+    let start_size = r.get_uint16()?;
+    let end_size = r.get_uint16()?;
+    let delta_format = r.get_uint16()?;
+
+    // formats 1 to 3 pack signed values of 2, 4 and 8 bits into words, first value on top
+    let bits = match delta_format {
+        1 => 2,
+        2 => 4,
+        3 => 8,
+        0x8000 => {
+            return Ok(Device::VariationIndex {
+                delta_set_outer_index: start_size,
+                delta_set_inner_index: end_size,
+            });
+        }
+        format => {
+            return Err(Error::UnsupportedFormat {
+                table: r.table.into(),
+                offset,
+                format: format as u32,
+            });
+        }
+    };
+
+    let count = (end_size as usize + 1).saturating_sub(start_size as usize);
+    let per_word = 16 / bits;
+    let mut delta_values = Vec::new();
+    let mut word = 0;
+    for i in 0..count {
+        if i % per_word == 0 {
+            word = r.get_uint16()?;
+        }
+        let shift = 16 - bits * (i % per_word + 1);
+        let value = (word >> shift) & ((1 << bits) - 1);
+        // sign extend from the top bit of the value
+        delta_values.push(((value << (16 - bits)) as i16 >> (16 - bits)) as i8);
+    }
+
+    return Ok(Device::Hinting {
+        start_size,
+        end_size,
+        delta_values,
+    });
+}
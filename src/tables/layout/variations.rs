@@ -0,0 +1,213 @@
+use crate::error::Result;
+use crate::reader;
+use crate::tables::layout::{self, Feature, FeatureList};
+
+// Conditions compare against normalized variation coordinates, -1 to 1 with 0 being the
+// default instance
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Condition {
+    AxisRange {
+        axis_index: u16, // into the fvar axes
+        filter_range_min_value: f32,
+        filter_range_max_value: f32,
+    },
+    Unsupported(u16), // formats newer than 1, they never match
+}
+
+impl Condition {
+    // Axes without a coordinate sit at their default
+    pub fn matches(&self, coords: &[f32]) -> bool {
+        match *self {
+            Condition::AxisRange {
+                axis_index,
+                filter_range_min_value,
+                filter_range_max_value,
+            } => {
+                let coord = coords.get(axis_index as usize).copied().unwrap_or(0.);
+                return filter_range_min_value <= coord && coord <= filter_range_max_value;
+            }
+            Condition::Unsupported(_) => return false,
+        }
+    }
+}
+
+// Matches when all of its conditions do, so an empty set always matches
+#[derive(Debug, Clone)]
+pub struct ConditionSet {
+    pub conditions: Vec<Condition>,
+}
+
+impl ConditionSet {
+    pub fn matches(&self, coords: &[f32]) -> bool {
+        return self
+            .conditions
+            .iter()
+            .all(|condition| condition.matches(coords));
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FeatureTableSubstitutionRecord {
+    pub feature_index: u16, // into the FeatureList
+    pub alternate_feature: Feature,
+}
+
+#[derive(Debug, Clone)]
+pub struct FeatureTableSubstitution {
+    pub major_version: u16,
+    pub minor_version: u16,
+    pub substitutions: Vec<FeatureTableSubstitutionRecord>, // sorted by feature_index
+}
+
+impl FeatureTableSubstitution {
+    // The feature that replaces the one at feature_index, None when it stays as it is
+    pub fn feature(&self, feature_index: u16) -> Option<&Feature> {
+        let index = self
+            .substitutions
+            .binary_search_by_key(&feature_index, |record| record.feature_index)
+            .ok()?;
+        return Some(&self.substitutions[index].alternate_feature);
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FeatureVariationRecord {
+    pub condition_set: ConditionSet,
+    pub feature_table_substitution: FeatureTableSubstitution,
+}
+
+#[derive(Debug, Clone)]
+pub struct FeatureVariations {
+    pub major_version: u16,
+    pub minor_version: u16,
+    pub records: Vec<FeatureVariationRecord>,
+}
+
+impl FeatureVariations {
+    // Only the first record whose conditions match applies
+    pub fn find(&self, coords: &[f32]) -> Option<&FeatureTableSubstitution> {
+        return self
+            .records
+            .iter()
+            .find(|record| record.condition_set.matches(coords))
+            .map(|record| &record.feature_table_substitution);
+    }
+}
+
+fn read_condition_set(r: &mut reader::FontReader, offset: usize) -> Result<ConditionSet> {
+    r.seek(offset)?;
+    let condition_count = r.get_uint16()?;
+    let mut offsets = Vec::new();
+    for _ in 0..condition_count {
+        offsets.push(r.get_uint32()? as usize);
+    }
+
+    let mut conditions = Vec::new();
+    for condition_offset in offsets {
+        r.seek(offset + condition_offset)?;
+        let condition = match r.get_uint16()? {
+            1 => Condition::AxisRange {
+                axis_index: r.get_uint16()?,
+                filter_range_min_value: r.get_f2dot14()?,
+                filter_range_max_value: r.get_f2dot14()?,
+            },
+            format => Condition::Unsupported(format),
+        };
+        conditions.push(condition);
+    }
+
+    return Ok(ConditionSet { conditions });
+}
+
+fn read_feature_table_substitution(
+    r: &mut reader::FontReader,
+    offset: usize,
+    feature_list: &FeatureList,
+    feature_list_offset: usize,
+) -> Result<FeatureTableSubstitution> {
+    r.seek(offset)?;
+    let major_version = r.get_uint16()?;
+    let minor_version = r.get_uint16()?;
+    let substitution_count = r.get_uint16()?;
+
+    let mut records = Vec::new();
+    for _ in 0..substitution_count {
+        records.push((r.get_uint16()?, r.get_uint32()? as usize));
+    }
+
+    let mut substitutions = Vec::new();
+    for (feature_index, feature_offset) in records {
+        // the alternate takes the tag of the feature it replaces, which decides how its
+        // parameters are read
+        let tag = feature_list
+            .features
+            .get(feature_index as usize)
+            .map(|record| record.tag.as_str())
+            .unwrap_or("");
+        let alternate_feature =
+            layout::read_feature(r, tag, offset + feature_offset, feature_list_offset)?;
+        substitutions.push(FeatureTableSubstitutionRecord {
+            feature_index,
+            alternate_feature,
+        });
+    }
+
+    return Ok(FeatureTableSubstitution {
+        major_version,
+        minor_version,
+        substitutions,
+    });
+}
+
+pub fn read_feature_variations(
+    r: &mut reader::FontReader,
+    offset: usize,
+    feature_list: &FeatureList,
+    feature_list_offset: usize,
+) -> Result<FeatureVariations> {
+    r.seek(offset)?;
+    let major_version = r.get_uint16()?;
+    let minor_version = r.get_uint16()?;
+    let record_count = r.get_uint32()?;
+
+    let mut offsets = Vec::new();
+    for _ in 0..record_count {
+        offsets.push((r.get_uint32()? as usize, r.get_uint32()? as usize));
+    }
+
+    let mut records = Vec::new();
+    for (condition_set_offset, substitution_offset) in offsets {
+        let condition_set = match condition_set_offset {
+            0 => ConditionSet {
+                conditions: Vec::new(),
+            },
+            _ => read_condition_set(r, offset + condition_set_offset)?,
+        };
+
+        // a record without substitutions still stops the search when it matches
+        let feature_table_substitution = match substitution_offset {
+            0 => FeatureTableSubstitution {
+                major_version: 1,
+                minor_version: 0,
+                substitutions: Vec::new(),
+            },
+            _ => read_feature_table_substitution(
+                r,
+                offset + substitution_offset,
+                feature_list,
+                feature_list_offset,
+            )?,
+        };
+
+        records.push(FeatureVariationRecord {
+            condition_set,
+            feature_table_substitution,
+        });
+    }
+
+    return Ok(FeatureVariations {
+        major_version,
+        minor_version,
+        records,
+    });
+}
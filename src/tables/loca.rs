@@ -1,3 +1,4 @@
+use crate::error::{Error, Result};
 use crate::reader;
 use crate::tables::offset::OffsetTable;
 
@@ -12,8 +13,8 @@ pub fn read(
     loca_offset_table: OffsetTable,
     glyph_count: u16,
     index_to_loc_format: i16,
-) -> Option<Loca> {
-    let _ = r.seek(loca_offset_table.offset as usize);
+) -> Result<Loca> {
+    r.seek_table("loca", loca_offset_table.offset as usize)?;
 
     match index_to_loc_format {
         0 => {
@@ -22,7 +23,7 @@ pub fn read(
                 offsets.push(r.get_uint16()?);
             }
 
-            return Some(Loca::Short(offsets));
+            return Ok(Loca::Short(offsets));
         }
         1 => {
             let mut offsets = Vec::new();
@@ -30,11 +31,14 @@ pub fn read(
                 offsets.push(r.get_uint32()?);
             }
 
-            return Some(Loca::Long(offsets));
+            return Ok(Loca::Long(offsets));
         }
         val => {
-            println!("{} is not a supported format for the loca table.", val);
-            return None;
+            return Err(Error::UnsupportedFormat {
+                table: "loca".into(),
+                offset: loca_offset_table.offset as usize,
+                format: val as u32,
+            });
         }
     }
 }
@@ -1,3 +1,4 @@
+use crate::error::Result;
 use crate::reader;
 use crate::tables::offset::OffsetTable;
 
@@ -20,10 +21,10 @@ pub struct Maxp {
     pub max_component_depth: u16, // levels of recursion, set to 0 if font has only simple glyphs
 }
 
-pub fn read(r: &mut reader::FontReader, maxp_offset_table: OffsetTable) -> Option<Maxp> {
-    let _ = r.seek(maxp_offset_table.offset as usize);
+pub fn read(r: &mut reader::FontReader, maxp_offset_table: OffsetTable) -> Result<Maxp> {
+    r.seek_table("maxp", maxp_offset_table.offset as usize)?;
 
-    return Some(Maxp {
+    return Ok(Maxp {
         version: r.get_float32()?,
         glyph_count: r.get_uint16()?,
         max_points: r.get_uint16()?,
@@ -1,7 +1,105 @@
+use crate::error::Result;
 use crate::reader;
 use crate::tables::offset::OffsetTable;
+mod encoding;
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum NameId {
+    Copyright,                      // 0
+    Family,                         // 1
+    Subfamily,                      // 2
+    UniqueId,                       // 3
+    FullName,                       // 4
+    Version,                        // 5
+    PostScriptName,                 // 6
+    Trademark,                      // 7
+    Manufacturer,                   // 8
+    Designer,                       // 9
+    Description,                    // 10
+    VendorUrl,                      // 11
+    DesignerUrl,                    // 12
+    License,                        // 13
+    LicenseUrl,                     // 14
+    TypographicFamily,              // 16
+    TypographicSubfamily,           // 17
+    CompatibleFullName,             // 18
+    SampleText,                     // 19
+    PostScriptCidFindfontName,      // 20
+    WwsFamily,                      // 21
+    WwsSubfamily,                   // 22
+    LightBackgroundPalette,         // 23
+    DarkBackgroundPalette,          // 24
+    VariationsPostScriptNamePrefix, // 25
+    Other(u16),                     // reserved or font specific (256 and above)
+}
+
+impl From<u16> for NameId {
+    fn from(id: u16) -> Self {
+        match id {
+            0 => return NameId::Copyright,
+            1 => return NameId::Family,
+            2 => return NameId::Subfamily,
+            3 => return NameId::UniqueId,
+            4 => return NameId::FullName,
+            5 => return NameId::Version,
+            6 => return NameId::PostScriptName,
+            7 => return NameId::Trademark,
+            8 => return NameId::Manufacturer,
+            9 => return NameId::Designer,
+            10 => return NameId::Description,
+            11 => return NameId::VendorUrl,
+            12 => return NameId::DesignerUrl,
+            13 => return NameId::License,
+            14 => return NameId::LicenseUrl,
+            16 => return NameId::TypographicFamily,
+            17 => return NameId::TypographicSubfamily,
+            18 => return NameId::CompatibleFullName,
+            19 => return NameId::SampleText,
+            20 => return NameId::PostScriptCidFindfontName,
+            21 => return NameId::WwsFamily,
+            22 => return NameId::WwsSubfamily,
+            23 => return NameId::LightBackgroundPalette,
+            24 => return NameId::DarkBackgroundPalette,
+            25 => return NameId::VariationsPostScriptNamePrefix,
+            val => return NameId::Other(val),
+        }
+    }
+}
+
+impl From<NameId> for u16 {
+    fn from(id: NameId) -> Self {
+        match id {
+            NameId::Copyright => return 0,
+            NameId::Family => return 1,
+            NameId::Subfamily => return 2,
+            NameId::UniqueId => return 3,
+            NameId::FullName => return 4,
+            NameId::Version => return 5,
+            NameId::PostScriptName => return 6,
+            NameId::Trademark => return 7,
+            NameId::Manufacturer => return 8,
+            NameId::Designer => return 9,
+            NameId::Description => return 10,
+            NameId::VendorUrl => return 11,
+            NameId::DesignerUrl => return 12,
+            NameId::License => return 13,
+            NameId::LicenseUrl => return 14,
+            NameId::TypographicFamily => return 16,
+            NameId::TypographicSubfamily => return 17,
+            NameId::CompatibleFullName => return 18,
+            NameId::SampleText => return 19,
+            NameId::PostScriptCidFindfontName => return 20,
+            NameId::WwsFamily => return 21,
+            NameId::WwsSubfamily => return 22,
+            NameId::LightBackgroundPalette => return 23,
+            NameId::DarkBackgroundPalette => return 24,
+            NameId::VariationsPostScriptNamePrefix => return 25,
+            NameId::Other(val) => return val,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct NameRecord {
     pub platform_id: u16,          // Platform identifier code.
     pub platform_specific_id: u16, // Platform-specific encoding identifier.
@@ -9,18 +107,71 @@ pub struct NameRecord {
     pub name_id: u16,              // Name identifiers.
     pub length: u16,               // Name string length in bytes.
     pub offset: u16,               // Name string offset in bytes from stringOffset.
+    pub string: Option<String>,    // The decoded string, None if lipi can't decode it
+    pub language: Option<String>,  // BCP 47 tag for the language identifier, if known
+}
+
+impl NameRecord {
+    pub fn id(&self) -> NameId {
+        return NameId::from(self.name_id);
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct LangTagRecord {
+    pub length: u16, // Language-tag string length (in bytes)
+    pub offset: u16, // Language-tag string offset from stringOffset (in bytes).
 }
 
 #[derive(Debug, Clone)]
 pub struct Name {
-    pub format: u16,                   // Format selector. Set to 0.
-    pub count: u16,                    // The number of nameRecords in this name table.
+    pub format: u16,                          // Format selector. 0 or 1.
+    pub count: u16,                           // The number of nameRecords in this name table.
     pub string_offset: u16, // Offset in bytes to the beginning of the name character strings.
     pub name_records: Vec<NameRecord>, // The name records array.
+    pub lang_tag_count: u16, // Number of language-tag records, format 1 only.
+    pub lang_tag_records: Vec<LangTagRecord>, // Language-tag records, format 1 only.
+    pub lang_tags: Vec<Option<String>>, // Decoded language tags, language ids 0x8000 and up.
 }
 
-pub fn read(r: &mut reader::FontReader, name_offset_table: OffsetTable) -> Option<Name> {
-    let _ = r.seek(name_offset_table.offset as usize);
+// Decodes a string from the storage area based on the platform and encoding it came with,
+// the Mac script encodings also look at the language
+fn decode(
+    platform_id: u16,
+    platform_specific_id: u16,
+    language_id: u16,
+    bytes: &[u8],
+) -> Option<String> {
+    match (platform_id, platform_specific_id) {
+        (0, _) => return encoding::decode_utf16_be(bytes),
+        (1, _) => return encoding::decode_mac(platform_specific_id, language_id, bytes),
+        (2, 0) => return encoding::decode_ascii(bytes),
+        (2, 1) => return encoding::decode_utf16_be(bytes),
+        (2, 2) => return Some(encoding::decode_latin1(bytes)),
+        (3, 0) | (3, 1) | (3, 10) => return encoding::decode_utf16_be(bytes),
+        (3, _) => return encoding::decode_windows(platform_specific_id, bytes),
+        _ => return None,
+    }
+}
+
+// A string running past the end of the table is left undecoded instead of failing the font
+fn read_storage<'a>(
+    r: &reader::FontReader<'a>,
+    storage: std::ops::Range<usize>,
+    offset: u16,
+    length: u16,
+) -> Option<&'a [u8]> {
+    let start = storage.start + offset as usize;
+    let end = start + length as usize;
+    if end > storage.end {
+        return None;
+    }
+
+    return r.data.get(start..end);
+}
+
+pub fn read(r: &mut reader::FontReader, name_offset_table: OffsetTable) -> Result<Name> {
+    r.seek_table("name", name_offset_table.offset as usize)?;
 
     let format = r.get_uint16()?;
     let count = r.get_uint16()?;
@@ -35,13 +186,145 @@ pub fn read(r: &mut reader::FontReader, name_offset_table: OffsetTable) -> Optio
             name_id: r.get_uint16()?,
             length: r.get_uint16()?,
             offset: r.get_uint16()?,
+            string: None,
+            language: None,
         });
     }
 
-    return Some(Name {
+    let mut lang_tag_count = 0;
+    let mut lang_tag_records = Vec::new();
+
+    if format == 1 {
+        lang_tag_count = r.get_uint16()?;
+        for _ in 0..lang_tag_count {
+            lang_tag_records.push(LangTagRecord {
+                length: r.get_uint16()?,
+                offset: r.get_uint16()?,
+            });
+        }
+    }
+
+    let table_end = name_offset_table.offset as usize + name_offset_table.length as usize;
+    let storage = name_offset_table.offset as usize + string_offset as usize..table_end;
+    let mut lang_tags = Vec::new();
+
+    for record in lang_tag_records.iter() {
+        let bytes = read_storage(r, storage.clone(), record.offset, record.length);
+        lang_tags.push(bytes.and_then(encoding::decode_utf16_be));
+    }
+
+    for record in name_records.iter_mut() {
+        let bytes = read_storage(r, storage.clone(), record.offset, record.length);
+        record.string = bytes.and_then(|bytes| {
+            decode(
+                record.platform_id,
+                record.platform_specific_id,
+                record.language_id,
+                bytes,
+            )
+        });
+
+        record.language = match (record.platform_id, record.language_id) {
+            (_, id) if id >= 0x8000 && format == 1 => {
+                lang_tags.get((id - 0x8000) as usize).cloned().flatten()
+            }
+            (1, id) => encoding::mac_language(id),
+            (3, id) => encoding::windows_language(id),
+            _ => None,
+        };
+    }
+
+    return Ok(Name {
         format,
         count,
         string_offset,
         name_records,
+        lang_tag_count,
+        lang_tag_records,
+        lang_tags,
     });
 }
+
+// Query view over the name table, looks up strings by id and BCP 47 language tag, see
+// Names::get for how it falls back when there is no exact match
+#[derive(Debug, Copy, Clone)]
+pub struct Names<'a> {
+    name: &'a Name,
+}
+
+impl<'a> Names<'a> {
+    pub fn new(name: &'a Name) -> Self {
+        return Names { name };
+    }
+
+    pub fn records(&self) -> &'a [NameRecord] {
+        return &self.name.name_records;
+    }
+
+    // Lower is better. Language goes first: exact tag, same primary language, English,
+    // no language at all (Unicode platform) and then whatever is left. Ties are broken by
+    // platform, Windows strings are the most reliably encoded, Mac Roman the least.
+    fn score(record: &NameRecord, lang: &str) -> (u8, u8) {
+        let primary = |tag: &str| tag.split('-').next().unwrap_or("").to_ascii_lowercase();
+
+        let language_score = match &record.language {
+            Some(tag) if tag.eq_ignore_ascii_case(lang) => 0,
+            Some(tag) if primary(tag) == primary(lang) => 1,
+            Some(tag) if tag.eq_ignore_ascii_case("en-US") => 2,
+            Some(tag) if primary(tag) == "en" => 3,
+            None => 4,
+            Some(_) => 5,
+        };
+
+        let platform_score = match record.platform_id {
+            3 => 0,
+            0 => 1,
+            1 => 2,
+            _ => 3,
+        };
+
+        return (language_score, platform_score);
+    }
+
+    pub fn get(&self, id: NameId, lang: &str) -> Option<&'a str> {
+        let id = u16::from(id);
+
+        return self
+            .name
+            .name_records
+            .iter()
+            .filter(|record| record.name_id == id && record.string.is_some())
+            .min_by_key(|record| Names::score(record, lang))?
+            .string
+            .as_deref();
+    }
+
+    // Prefers the typographic family, the plain family is capped at four styles per family
+    pub fn family(&self, lang: &str) -> Option<&'a str> {
+        return self
+            .get(NameId::TypographicFamily, lang)
+            .or_else(|| self.get(NameId::Family, lang));
+    }
+
+    pub fn subfamily(&self, lang: &str) -> Option<&'a str> {
+        return self
+            .get(NameId::TypographicSubfamily, lang)
+            .or_else(|| self.get(NameId::Subfamily, lang));
+    }
+
+    pub fn full_name(&self, lang: &str) -> Option<&'a str> {
+        return self.get(NameId::FullName, lang);
+    }
+
+    pub fn postscript_name(&self) -> Option<&'a str> {
+        return self.get(NameId::PostScriptName, "en-US");
+    }
+
+    pub fn version(&self) -> Option<&'a str> {
+        return self.get(NameId::Version, "en-US");
+    }
+
+    pub fn copyright(&self, lang: &str) -> Option<&'a str> {
+        return self.get(NameId::Copyright, lang);
+    }
+}
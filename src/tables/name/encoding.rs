@@ -0,0 +1,486 @@
+use encoding_rs::{Encoding, BIG5, EUC_KR, GBK, SHIFT_JIS};
+
+// Mac Roman, the legacy encoding the Macintosh platform strings are usually stored in.
+// The first 128 characters are plain ASCII so only the upper half is listed.
+const MAC_ROMAN: [char; 128] = [
+    '\u{00C4}', '\u{00C5}', '\u{00C7}', '\u{00C9}', '\u{00D1}', '\u{00D6}', '\u{00DC}', '\u{00E1}',
+    '\u{00E0}', '\u{00E2}', '\u{00E4}', '\u{00E3}', '\u{00E5}', '\u{00E7}', '\u{00E9}', '\u{00E8}',
+    '\u{00EA}', '\u{00EB}', '\u{00ED}', '\u{00EC}', '\u{00EE}', '\u{00EF}', '\u{00F1}', '\u{00F3}',
+    '\u{00F2}', '\u{00F4}', '\u{00F6}', '\u{00F5}', '\u{00FA}', '\u{00F9}', '\u{00FB}', '\u{00FC}',
+    '\u{2020}', '\u{00B0}', '\u{00A2}', '\u{00A3}', '\u{00A7}', '\u{2022}', '\u{00B6}', '\u{00DF}',
+    '\u{00AE}', '\u{00A9}', '\u{2122}', '\u{00B4}', '\u{00A8}', '\u{2260}', '\u{00C6}', '\u{00D8}',
+    '\u{221E}', '\u{00B1}', '\u{2264}', '\u{2265}', '\u{00A5}', '\u{00B5}', '\u{2202}', '\u{2211}',
+    '\u{220F}', '\u{03C0}', '\u{222B}', '\u{00AA}', '\u{00BA}', '\u{03A9}', '\u{00E6}', '\u{00F8}',
+    '\u{00BF}', '\u{00A1}', '\u{00AC}', '\u{221A}', '\u{0192}', '\u{2248}', '\u{2206}', '\u{00AB}',
+    '\u{00BB}', '\u{2026}', '\u{00A0}', '\u{00C0}', '\u{00C3}', '\u{00D5}', '\u{0152}', '\u{0153}',
+    '\u{2013}', '\u{2014}', '\u{201C}', '\u{201D}', '\u{2018}', '\u{2019}', '\u{00F7}', '\u{25CA}',
+    '\u{00FF}', '\u{0178}', '\u{2044}', '\u{20AC}', '\u{2039}', '\u{203A}', '\u{FB01}', '\u{FB02}',
+    '\u{2021}', '\u{00B7}', '\u{201A}', '\u{201E}', '\u{2030}', '\u{00C2}', '\u{00CA}', '\u{00C1}',
+    '\u{00CB}', '\u{00C8}', '\u{00CD}', '\u{00CE}', '\u{00CF}', '\u{00CC}', '\u{00D3}', '\u{00D4}',
+    '\u{F8FF}', '\u{00D2}', '\u{00DA}', '\u{00DB}', '\u{00D9}', '\u{0131}', '\u{02C6}', '\u{02DC}',
+    '\u{00AF}', '\u{02D8}', '\u{02D9}', '\u{02DA}', '\u{00B8}', '\u{02DD}', '\u{02DB}', '\u{02C7}',
+];
+
+// Mac Roman as the Icelandic, Turkish, Croatian and Romanian systems had it, the language
+// of a record picks the variant. Only the bytes that differ from Mac Roman are listed.
+const MAC_ICELANDIC: &[(u8, char)] = &[
+    (0xA0, '\u{00DD}'),
+    (0xDC, '\u{00D0}'),
+    (0xDD, '\u{00F0}'),
+    (0xDE, '\u{00DE}'),
+    (0xDF, '\u{00FE}'),
+    (0xE0, '\u{00FD}'),
+];
+const MAC_TURKISH: &[(u8, char)] = &[
+    (0xDA, '\u{011E}'),
+    (0xDB, '\u{011F}'),
+    (0xDC, '\u{0130}'),
+    (0xDD, '\u{0131}'),
+    (0xDE, '\u{015E}'),
+    (0xDF, '\u{015F}'),
+    (0xF5, '\u{F8A0}'),
+];
+const MAC_CROATIAN: &[(u8, char)] = &[
+    (0xA9, '\u{0160}'),
+    (0xAE, '\u{017D}'),
+    (0xB4, '\u{2206}'),
+    (0xB9, '\u{0161}'),
+    (0xBE, '\u{017E}'),
+    (0xC6, '\u{0106}'),
+    (0xC8, '\u{010C}'),
+    (0xD0, '\u{0110}'),
+    (0xD8, '\u{F8FF}'),
+    (0xD9, '\u{00A9}'),
+    (0xDE, '\u{00C6}'),
+    (0xDF, '\u{00BB}'),
+    (0xE0, '\u{2013}'),
+    (0xE6, '\u{0107}'),
+    (0xE8, '\u{010D}'),
+    (0xF0, '\u{0111}'),
+    (0xF9, '\u{03C0}'),
+    (0xFA, '\u{00CB}'),
+    (0xFD, '\u{00CA}'),
+    (0xFE, '\u{00E6}'),
+];
+const MAC_ROMANIAN: &[(u8, char)] = &[
+    (0xAE, '\u{0102}'),
+    (0xAF, '\u{0218}'),
+    (0xBE, '\u{0103}'),
+    (0xBF, '\u{0219}'),
+    (0xDE, '\u{021A}'),
+    (0xDF, '\u{021B}'),
+];
+
+// The upper halves of the other single byte Mac script encodings
+const MAC_ARABIC: [char; 128] = [
+    '\u{00C4}', '\u{00A0}', '\u{00C7}', '\u{00C9}', '\u{00D1}', '\u{00D6}', '\u{00DC}', '\u{00E1}',
+    '\u{00E0}', '\u{00E2}', '\u{00E4}', '\u{06BA}', '\u{00AB}', '\u{00E7}', '\u{00E9}', '\u{00E8}',
+    '\u{00EA}', '\u{00EB}', '\u{00ED}', '\u{2026}', '\u{00EE}', '\u{00EF}', '\u{00F1}', '\u{00F3}',
+    '\u{00BB}', '\u{00F4}', '\u{00F6}', '\u{00F7}', '\u{00FA}', '\u{00F9}', '\u{00FB}', '\u{00FC}',
+    '\u{0020}', '\u{0021}', '\u{0022}', '\u{0023}', '\u{0024}', '\u{066A}', '\u{0026}', '\u{0027}',
+    '\u{0028}', '\u{0029}', '\u{002A}', '\u{002B}', '\u{060C}', '\u{002D}', '\u{002E}', '\u{002F}',
+    '\u{0660}', '\u{0661}', '\u{0662}', '\u{0663}', '\u{0664}', '\u{0665}', '\u{0666}', '\u{0667}',
+    '\u{0668}', '\u{0669}', '\u{003A}', '\u{061B}', '\u{003C}', '\u{003D}', '\u{003E}', '\u{061F}',
+    '\u{274A}', '\u{0621}', '\u{0622}', '\u{0623}', '\u{0624}', '\u{0625}', '\u{0626}', '\u{0627}',
+    '\u{0628}', '\u{0629}', '\u{062A}', '\u{062B}', '\u{062C}', '\u{062D}', '\u{062E}', '\u{062F}',
+    '\u{0630}', '\u{0631}', '\u{0632}', '\u{0633}', '\u{0634}', '\u{0635}', '\u{0636}', '\u{0637}',
+    '\u{0638}', '\u{0639}', '\u{063A}', '\u{005B}', '\u{005C}', '\u{005D}', '\u{005E}', '\u{005F}',
+    '\u{0640}', '\u{0641}', '\u{0642}', '\u{0643}', '\u{0644}', '\u{0645}', '\u{0646}', '\u{0647}',
+    '\u{0648}', '\u{0649}', '\u{064A}', '\u{064B}', '\u{064C}', '\u{064D}', '\u{064E}', '\u{064F}',
+    '\u{0650}', '\u{0651}', '\u{0652}', '\u{067E}', '\u{0679}', '\u{0686}', '\u{06D5}', '\u{06A4}',
+    '\u{06AF}', '\u{0688}', '\u{0691}', '\u{007B}', '\u{007C}', '\u{007D}', '\u{0698}', '\u{06D2}',
+];
+
+const MAC_GREEK: [char; 128] = [
+    '\u{00C4}', '\u{00B9}', '\u{00B2}', '\u{00C9}', '\u{00B3}', '\u{00D6}', '\u{00DC}', '\u{0385}',
+    '\u{00E0}', '\u{00E2}', '\u{00E4}', '\u{0384}', '\u{00A8}', '\u{00E7}', '\u{00E9}', '\u{00E8}',
+    '\u{00EA}', '\u{00EB}', '\u{00A3}', '\u{2122}', '\u{00EE}', '\u{00EF}', '\u{2022}', '\u{00BD}',
+    '\u{2030}', '\u{00F4}', '\u{00F6}', '\u{00A6}', '\u{20AC}', '\u{00F9}', '\u{00FB}', '\u{00FC}',
+    '\u{2020}', '\u{0393}', '\u{0394}', '\u{0398}', '\u{039B}', '\u{039E}', '\u{03A0}', '\u{00DF}',
+    '\u{00AE}', '\u{00A9}', '\u{03A3}', '\u{03AA}', '\u{00A7}', '\u{2260}', '\u{00B0}', '\u{00B7}',
+    '\u{0391}', '\u{00B1}', '\u{2264}', '\u{2265}', '\u{00A5}', '\u{0392}', '\u{0395}', '\u{0396}',
+    '\u{0397}', '\u{0399}', '\u{039A}', '\u{039C}', '\u{03A6}', '\u{03AB}', '\u{03A8}', '\u{03A9}',
+    '\u{03AC}', '\u{039D}', '\u{00AC}', '\u{039F}', '\u{03A1}', '\u{2248}', '\u{03A4}', '\u{00AB}',
+    '\u{00BB}', '\u{2026}', '\u{00A0}', '\u{03A5}', '\u{03A7}', '\u{0386}', '\u{0388}', '\u{0153}',
+    '\u{2013}', '\u{2015}', '\u{201C}', '\u{201D}', '\u{2018}', '\u{2019}', '\u{00F7}', '\u{0389}',
+    '\u{038A}', '\u{038C}', '\u{038E}', '\u{03AD}', '\u{03AE}', '\u{03AF}', '\u{03CC}', '\u{038F}',
+    '\u{03CD}', '\u{03B1}', '\u{03B2}', '\u{03C8}', '\u{03B4}', '\u{03B5}', '\u{03C6}', '\u{03B3}',
+    '\u{03B7}', '\u{03B9}', '\u{03BE}', '\u{03BA}', '\u{03BB}', '\u{03BC}', '\u{03BD}', '\u{03BF}',
+    '\u{03C0}', '\u{03CE}', '\u{03C1}', '\u{03C3}', '\u{03C4}', '\u{03B8}', '\u{03C9}', '\u{03C2}',
+    '\u{03C7}', '\u{03C5}', '\u{03B6}', '\u{03CA}', '\u{03CB}', '\u{0390}', '\u{03B0}', '\u{00AD}',
+];
+
+const MAC_CYRILLIC: [char; 128] = [
+    '\u{0410}', '\u{0411}', '\u{0412}', '\u{0413}', '\u{0414}', '\u{0415}', '\u{0416}', '\u{0417}',
+    '\u{0418}', '\u{0419}', '\u{041A}', '\u{041B}', '\u{041C}', '\u{041D}', '\u{041E}', '\u{041F}',
+    '\u{0420}', '\u{0421}', '\u{0422}', '\u{0423}', '\u{0424}', '\u{0425}', '\u{0426}', '\u{0427}',
+    '\u{0428}', '\u{0429}', '\u{042A}', '\u{042B}', '\u{042C}', '\u{042D}', '\u{042E}', '\u{042F}',
+    '\u{2020}', '\u{00B0}', '\u{0490}', '\u{00A3}', '\u{00A7}', '\u{2022}', '\u{00B6}', '\u{0406}',
+    '\u{00AE}', '\u{00A9}', '\u{2122}', '\u{0402}', '\u{0452}', '\u{2260}', '\u{0403}', '\u{0453}',
+    '\u{221E}', '\u{00B1}', '\u{2264}', '\u{2265}', '\u{0456}', '\u{00B5}', '\u{0491}', '\u{0408}',
+    '\u{0404}', '\u{0454}', '\u{0407}', '\u{0457}', '\u{0409}', '\u{0459}', '\u{040A}', '\u{045A}',
+    '\u{0458}', '\u{0405}', '\u{00AC}', '\u{221A}', '\u{0192}', '\u{2248}', '\u{2206}', '\u{00AB}',
+    '\u{00BB}', '\u{2026}', '\u{00A0}', '\u{040B}', '\u{045B}', '\u{040C}', '\u{045C}', '\u{0455}',
+    '\u{2013}', '\u{2014}', '\u{201C}', '\u{201D}', '\u{2018}', '\u{2019}', '\u{00F7}', '\u{201E}',
+    '\u{040E}', '\u{045E}', '\u{040F}', '\u{045F}', '\u{2116}', '\u{0401}', '\u{0451}', '\u{044F}',
+    '\u{0430}', '\u{0431}', '\u{0432}', '\u{0433}', '\u{0434}', '\u{0435}', '\u{0436}', '\u{0437}',
+    '\u{0438}', '\u{0439}', '\u{043A}', '\u{043B}', '\u{043C}', '\u{043D}', '\u{043E}', '\u{043F}',
+    '\u{0440}', '\u{0441}', '\u{0442}', '\u{0443}', '\u{0444}', '\u{0445}', '\u{0446}', '\u{0447}',
+    '\u{0448}', '\u{0449}', '\u{044A}', '\u{044B}', '\u{044C}', '\u{044D}', '\u{044E}', '\u{20AC}',
+];
+
+const MAC_CENTRAL_EUROPEAN: [char; 128] = [
+    '\u{00C4}', '\u{0100}', '\u{0101}', '\u{00C9}', '\u{0104}', '\u{00D6}', '\u{00DC}', '\u{00E1}',
+    '\u{0105}', '\u{010C}', '\u{00E4}', '\u{010D}', '\u{0106}', '\u{0107}', '\u{00E9}', '\u{0179}',
+    '\u{017A}', '\u{010E}', '\u{00ED}', '\u{010F}', '\u{0112}', '\u{0113}', '\u{0116}', '\u{00F3}',
+    '\u{0117}', '\u{00F4}', '\u{00F6}', '\u{00F5}', '\u{00FA}', '\u{011A}', '\u{011B}', '\u{00FC}',
+    '\u{2020}', '\u{00B0}', '\u{0118}', '\u{00A3}', '\u{00A7}', '\u{2022}', '\u{00B6}', '\u{00DF}',
+    '\u{00AE}', '\u{00A9}', '\u{2122}', '\u{0119}', '\u{00A8}', '\u{2260}', '\u{0123}', '\u{012E}',
+    '\u{012F}', '\u{012A}', '\u{2264}', '\u{2265}', '\u{012B}', '\u{0136}', '\u{2202}', '\u{2211}',
+    '\u{0142}', '\u{013B}', '\u{013C}', '\u{013D}', '\u{013E}', '\u{0139}', '\u{013A}', '\u{0145}',
+    '\u{0146}', '\u{0143}', '\u{00AC}', '\u{221A}', '\u{0144}', '\u{0147}', '\u{2206}', '\u{00AB}',
+    '\u{00BB}', '\u{2026}', '\u{00A0}', '\u{0148}', '\u{0150}', '\u{00D5}', '\u{0151}', '\u{014C}',
+    '\u{2013}', '\u{2014}', '\u{201C}', '\u{201D}', '\u{2018}', '\u{2019}', '\u{00F7}', '\u{25CA}',
+    '\u{014D}', '\u{0154}', '\u{0155}', '\u{0158}', '\u{2039}', '\u{203A}', '\u{0159}', '\u{0156}',
+    '\u{0157}', '\u{0160}', '\u{201A}', '\u{201E}', '\u{0161}', '\u{015A}', '\u{015B}', '\u{00C1}',
+    '\u{0164}', '\u{0165}', '\u{00CD}', '\u{017D}', '\u{017E}', '\u{016A}', '\u{00D3}', '\u{00D4}',
+    '\u{016B}', '\u{016E}', '\u{00DA}', '\u{016F}', '\u{0170}', '\u{0171}', '\u{0172}', '\u{0173}',
+    '\u{00DD}', '\u{00FD}', '\u{0137}', '\u{017B}', '\u{0141}', '\u{017C}', '\u{0122}', '\u{02C7}',
+];
+// Compatibility letters of the Hangul consonants a syllable can start and end with
+const JOHAB_INITIALS: [char; 19] = [
+    '\u{3131}', '\u{3132}', '\u{3134}', '\u{3137}', '\u{3138}', '\u{3139}', '\u{3141}', '\u{3142}',
+    '\u{3143}', '\u{3145}', '\u{3146}', '\u{3147}', '\u{3148}', '\u{3149}', '\u{314A}', '\u{314B}',
+    '\u{314C}', '\u{314D}', '\u{314E}',
+];
+const JOHAB_FINALS: [char; 27] = [
+    '\u{3131}', '\u{3132}', '\u{3133}', '\u{3134}', '\u{3135}', '\u{3136}', '\u{3137}', '\u{3139}',
+    '\u{313A}', '\u{313B}', '\u{313C}', '\u{313D}', '\u{313E}', '\u{313F}', '\u{3140}', '\u{3141}',
+    '\u{3142}', '\u{3144}', '\u{3145}', '\u{3146}', '\u{3147}', '\u{3148}', '\u{314A}', '\u{314B}',
+    '\u{314C}', '\u{314D}', '\u{314E}',
+];
+
+// Windows language ids sorted by id, only the common ones are listed, anything else falls
+// back to the primary language of its 0x04xx sibling
+const WINDOWS_LANGUAGES: [(u16, &str); 97] = [
+    (0x0401, "ar-SA"),
+    (0x0402, "bg-BG"),
+    (0x0403, "ca-ES"),
+    (0x0404, "zh-TW"),
+    (0x0405, "cs-CZ"),
+    (0x0406, "da-DK"),
+    (0x0407, "de-DE"),
+    (0x0408, "el-GR"),
+    (0x0409, "en-US"),
+    (0x040A, "es-ES"),
+    (0x040B, "fi-FI"),
+    (0x040C, "fr-FR"),
+    (0x040D, "he-IL"),
+    (0x040E, "hu-HU"),
+    (0x040F, "is-IS"),
+    (0x0410, "it-IT"),
+    (0x0411, "ja-JP"),
+    (0x0412, "ko-KR"),
+    (0x0413, "nl-NL"),
+    (0x0414, "nb-NO"),
+    (0x0415, "pl-PL"),
+    (0x0416, "pt-BR"),
+    (0x0417, "rm-CH"),
+    (0x0418, "ro-RO"),
+    (0x0419, "ru-RU"),
+    (0x041A, "hr-HR"),
+    (0x041B, "sk-SK"),
+    (0x041C, "sq-AL"),
+    (0x041D, "sv-SE"),
+    (0x041E, "th-TH"),
+    (0x041F, "tr-TR"),
+    (0x0420, "ur-PK"),
+    (0x0421, "id-ID"),
+    (0x0422, "uk-UA"),
+    (0x0423, "be-BY"),
+    (0x0424, "sl-SI"),
+    (0x0425, "et-EE"),
+    (0x0426, "lv-LV"),
+    (0x0427, "lt-LT"),
+    (0x0429, "fa-IR"),
+    (0x042A, "vi-VN"),
+    (0x042B, "hy-AM"),
+    (0x042D, "eu-ES"),
+    (0x042F, "mk-MK"),
+    (0x0436, "af-ZA"),
+    (0x0437, "ka-GE"),
+    (0x0438, "fo-FO"),
+    (0x0439, "hi-IN"),
+    (0x043A, "mt-MT"),
+    (0x043E, "ms-MY"),
+    (0x043F, "kk-KZ"),
+    (0x0441, "sw-KE"),
+    (0x0445, "bn-IN"),
+    (0x0446, "pa-IN"),
+    (0x0447, "gu-IN"),
+    (0x0449, "ta-IN"),
+    (0x044A, "te-IN"),
+    (0x044B, "kn-IN"),
+    (0x044C, "ml-IN"),
+    (0x044E, "mr-IN"),
+    (0x0450, "mn-MN"),
+    (0x0452, "cy-GB"),
+    (0x0453, "km-KH"),
+    (0x0454, "lo-LA"),
+    (0x0456, "gl-ES"),
+    (0x045B, "si-LK"),
+    (0x0461, "ne-NP"),
+    (0x0801, "ar-IQ"),
+    (0x0804, "zh-CN"),
+    (0x0807, "de-CH"),
+    (0x0809, "en-GB"),
+    (0x080A, "es-MX"),
+    (0x080C, "fr-BE"),
+    (0x0810, "it-CH"),
+    (0x0813, "nl-BE"),
+    (0x0814, "nn-NO"),
+    (0x0816, "pt-PT"),
+    (0x081A, "sr-Latn-CS"),
+    (0x081D, "sv-FI"),
+    (0x0C01, "ar-EG"),
+    (0x0C04, "zh-HK"),
+    (0x0C07, "de-AT"),
+    (0x0C09, "en-AU"),
+    (0x0C0A, "es-ES"),
+    (0x0C0C, "fr-CA"),
+    (0x0C1A, "sr-Cyrl-CS"),
+    (0x1004, "zh-SG"),
+    (0x1007, "de-LU"),
+    (0x1009, "en-CA"),
+    (0x100C, "fr-CH"),
+    (0x1404, "zh-MO"),
+    (0x1409, "en-NZ"),
+    (0x140C, "fr-LU"),
+    (0x1809, "en-IE"),
+    (0x1C09, "en-ZA"),
+    (0x2C0A, "es-AR"),
+    (0x4009, "en-IN"),
+];
+
+// Macintosh language ids 0 to 94
+const MAC_LANGUAGES: [&str; 95] = [
+    "en", "fr", "de", "it", "nl", "sv", "es", "da", "pt", "nb", "he", "ja", "ar", "fi", "el", "is",
+    "mt", "tr", "hr", "zh-Hant", "ur", "hi", "th", "ko", "lt", "pl", "hu", "et", "lv", "se", "fo",
+    "fa", "ru", "zh-Hans", "nl-BE", "ga", "sq", "ro", "cs", "sk", "sl", "yi", "sr", "mk", "bg",
+    "uk", "be", "uz", "kk", "az-Cyrl", "az-Arab", "hy", "ka", "ro-MD", "ky", "tg", "tk", "mn-Mong",
+    "mn-Cyrl", "ps", "ku", "ks", "sd", "bo", "ne", "sa", "mr", "bn", "as", "gu", "pa", "or", "ml",
+    "kn", "ta", "te", "si", "my", "km", "lo", "vi", "id", "tl", "ms", "ms-Arab", "am", "ti", "om",
+    "so", "sw", "rw", "rn", "ny", "mg", "eo",
+];
+
+// Macintosh language ids 128 to 150
+const MAC_LANGUAGES_EXTRA: [&str; 23] = [
+    "cy",
+    "eu",
+    "ca",
+    "la",
+    "qu",
+    "gn",
+    "ay",
+    "tt",
+    "ug",
+    "dz",
+    "jv",
+    "su",
+    "gl",
+    "af",
+    "br",
+    "iu",
+    "gd",
+    "gv",
+    "ga",
+    "to",
+    "el-polyton",
+    "kl",
+    "az-Latn",
+];
+
+pub fn decode_utf16_be(bytes: &[u8]) -> Option<String> {
+    let units = bytes
+        .chunks_exact(2)
+        .map(|pair| (pair[0] as u16) << 8 | pair[1] as u16);
+
+    return char::decode_utf16(units)
+        .collect::<Result<String, _>>()
+        .ok();
+}
+
+fn decode_single_byte(bytes: &[u8], upper: &[char; 128], overrides: &[(u8, char)]) -> String {
+    return bytes
+        .iter()
+        .map(|&byte| match byte {
+            0..=0x7F => byte as char,
+            _ => match overrides.iter().find(|&&(code, _)| code == byte) {
+                Some(&(_, c)) => c,
+                None => upper[(byte - 0x80) as usize],
+            },
+        })
+        .collect();
+}
+
+fn decode_multi_byte(encoding: &'static Encoding, bytes: &[u8]) -> Option<String> {
+    return encoding
+        .decode_without_bom_handling_and_without_replacement(bytes)
+        .map(|text| text.into_owned());
+}
+
+// Script encodings of the Mac platform, the Roman one varies with the language. The CJK
+// ones were Apple's take on Shift-JIS, Big5, EUC-KR and GB 2312, the common encodings
+// decode their names just as well. Only ASCII survives in the ones lipi has no table for.
+pub fn decode_mac(platform_specific_id: u16, language_id: u16, bytes: &[u8]) -> Option<String> {
+    let (upper, overrides) = match (platform_specific_id, language_id) {
+        (0, 15) => (&MAC_ROMAN, MAC_ICELANDIC),
+        (0, 17) => (&MAC_ROMAN, MAC_TURKISH),
+        (0, 18) => (&MAC_ROMAN, MAC_CROATIAN),
+        (0, 37) => (&MAC_ROMAN, MAC_ROMANIAN),
+        (0, _) => (&MAC_ROMAN, &[][..]),
+        (4, _) => (&MAC_ARABIC, &[][..]),
+        (6, _) => (&MAC_GREEK, &[][..]),
+        (7, _) => (&MAC_CYRILLIC, &[][..]),
+        (29, _) => (&MAC_CENTRAL_EUROPEAN, &[][..]),
+        (1, _) => return decode_multi_byte(SHIFT_JIS, bytes),
+        (2, _) => return decode_multi_byte(BIG5, bytes),
+        (3, _) => return decode_multi_byte(EUC_KR, bytes),
+        (25, _) => return decode_multi_byte(GBK, bytes),
+        _ => return decode_ascii(bytes),
+    };
+
+    return Some(decode_single_byte(bytes, upper, overrides));
+}
+
+// Johab spells out Hangul syllables with a 5-bit index for each jamo, the fill index
+// standing in for a missing one
+fn johab_hangul(code: u16) -> Option<char> {
+    const FILL: i32 = -1;
+    let initial = match (code >> 10) & 0x1F {
+        1 => FILL,
+        val @ 2..=20 => val as i32 - 2,
+        _ => return None,
+    };
+    let medial = match (code >> 5) & 0x1F {
+        2 => FILL,
+        val @ 3..=7 => val as i32 - 3,
+        val @ 10..=15 => val as i32 - 5,
+        val @ 18..=23 => val as i32 - 7,
+        val @ 26..=29 => val as i32 - 9,
+        _ => return None,
+    };
+    let last = match code & 0x1F {
+        1 => 0,
+        val @ 2..=17 => val as u32 - 1,
+        val @ 19..=29 => val as u32 - 2,
+        _ => return None,
+    };
+
+    // a lone jamo is written with its compatibility letter, nothing but fill is a space
+    match (initial, medial, last) {
+        (FILL, FILL, 0) => return Some('\u{3000}'),
+        (FILL, FILL, last) => return Some(JOHAB_FINALS[last as usize - 1]),
+        (FILL, medial, 0) => return char::from_u32(0x314F + medial as u32),
+        (initial, FILL, 0) => return Some(JOHAB_INITIALS[initial as usize]),
+        (FILL, _, _) | (_, FILL, _) => return None,
+        (initial, medial, last) => {
+            return char::from_u32(0xAC00 + (initial as u32 * 21 + medial as u32) * 28 + last)
+        }
+    }
+}
+
+// Symbols and hanja are KS X 1001 with two of its rows packed behind each lead byte
+fn johab_ks_x_1001(lead: u8, trail: u8) -> Option<char> {
+    let row = match lead {
+        0xD9..=0xDE => 0x21 + (lead - 0xD9) * 2,
+        0xE0..=0xF9 => 0x4A + (lead - 0xE0) * 2,
+        _ => return None,
+    };
+    let index = match trail {
+        0x31..=0x7E => trail - 0x31,
+        0x91..=0xFE => trail - 0x91 + 0x4E,
+        _ => return None,
+    };
+
+    // the jamo of row 4 are written as syllables instead
+    let (row, column) = (row + index / 94, 0x21 + index % 94);
+    if row == 0x24 && column <= 0x53 {
+        return None;
+    }
+
+    let bytes = [row | 0x80, column | 0x80];
+    return decode_multi_byte(EUC_KR, &bytes)?.chars().next();
+}
+
+pub fn decode_johab(bytes: &[u8]) -> Option<String> {
+    let mut text = String::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let lead = bytes[i];
+        if lead < 0x80 {
+            text.push(lead as char);
+            i += 1;
+            continue;
+        }
+
+        let trail = *bytes.get(i + 1)?;
+        let c = match lead {
+            0x84..=0xD3 => johab_hangul((lead as u16) << 8 | trail as u16)?,
+            _ => johab_ks_x_1001(lead, trail)?,
+        };
+        text.push(c);
+        i += 2;
+    }
+
+    return Some(text);
+}
+
+// The CJK encodings of the Windows platform. The strings are stored in 16-bit units, so
+// single byte characters come with a zero high byte that goes before decoding.
+pub fn decode_windows(platform_specific_id: u16, bytes: &[u8]) -> Option<String> {
+    let bytes: Vec<u8> = bytes.iter().copied().filter(|&byte| byte != 0).collect();
+    match platform_specific_id {
+        2 => return decode_multi_byte(SHIFT_JIS, &bytes),
+        3 => return decode_multi_byte(GBK, &bytes),
+        4 => return decode_multi_byte(BIG5, &bytes),
+        5 => return decode_multi_byte(EUC_KR, &bytes),
+        6 => return decode_johab(&bytes),
+        _ => return None,
+    }
+}
+
+pub fn decode_ascii(bytes: &[u8]) -> Option<String> {
+    if !bytes.is_ascii() {
+        return None;
+    }
+
+    return Some(bytes.iter().map(|&byte| byte as char).collect());
+}
+
+pub fn decode_latin1(bytes: &[u8]) -> String {
+    return bytes.iter().map(|&byte| byte as char).collect();
+}
+
+pub fn windows_language(language_id: u16) -> Option<String> {
+    if let Ok(index) = WINDOWS_LANGUAGES.binary_search_by(|(id, _)| id.cmp(&language_id)) {
+        return Some(WINDOWS_LANGUAGES[index].1.into());
+    }
+
+    // the low 10 bits are the primary language, the sublanguage lives above them
+    let primary_id = 0x0400 | (language_id & 0x03FF);
+    let index = WINDOWS_LANGUAGES
+        .binary_search_by(|(id, _)| id.cmp(&primary_id))
+        .ok()?;
+    let tag = WINDOWS_LANGUAGES[index].1;
+
+    return Some(tag.split('-').next()?.into());
+}
+
+pub fn mac_language(language_id: u16) -> Option<String> {
+    match language_id {
+        0..=94 => return Some(MAC_LANGUAGES[language_id as usize].into()),
+        128..=150 => return Some(MAC_LANGUAGES_EXTRA[(language_id - 128) as usize].into()),
+        _ => return None,
+    }
+}
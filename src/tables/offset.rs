@@ -1,3 +1,4 @@
+use crate::error::{Error, Result};
 use crate::reader;
 use std::collections::HashMap;
 
@@ -8,24 +9,22 @@ pub struct OffsetTable {
     pub length: u32,   // length of this table in byte (actual length not padded length)
 }
 
-fn checksum(r: &mut reader::FontReader, offset: u32, length: u32) -> u32 {
-    let old = r.seek(offset as usize);
+fn checksum(r: &mut reader::FontReader, offset: u32, length: u32) -> Result<u32> {
+    let old = r.seek(offset as usize)?;
     let mut sum = 0;
 
-    for _ in 0..((length + 3) / 4) {
-        let temp = match r.get_uint32() {
-            Some(val) => val,
-            None => 0,
-        };
+    for _ in 0..(length.div_ceil(4)) {
+        let temp = r.get_uint32().unwrap_or_default();
         sum = (sum as u64 + temp as u64) as u32;
     }
-    r.seek(old);
-    return sum;
+    r.seek(old)?;
+    return Ok(sum);
 }
 
-pub fn read(r: &mut reader::FontReader, numtables: u16) -> Option<HashMap<String, OffsetTable>> {
+pub fn read(r: &mut reader::FontReader, numtables: u16) -> Result<HashMap<String, OffsetTable>> {
     let mut offset_tables = HashMap::new();
     for _ in 0..numtables {
+        let record_offset = r.pos;
         let tag = r.get_string(4)?;
         let table = OffsetTable {
             checksum: r.get_uint32()?,
@@ -35,10 +34,35 @@ pub fn read(r: &mut reader::FontReader, numtables: u16) -> Option<HashMap<String
 
         offset_tables.insert(tag.clone(), table);
 
-        if tag != "head" && checksum(r, table.offset, table.length) != table.checksum {
-            println!("Checksums don't match for the {} table", tag);
-            return None;
+        if tag == "head" {
+            continue;
+        }
+
+        let actual = match checksum(r, table.offset, table.length) {
+            Ok(val) => val,
+            Err(_) => {
+                return Err(Error::Truncated {
+                    table: tag,
+                    offset: table.offset as usize,
+                })
+            }
+        };
+
+        if actual != table.checksum {
+            return Err(Error::ChecksumMismatch {
+                table: tag,
+                offset: record_offset,
+                expected: table.checksum,
+                actual,
+            });
         }
     }
-    Some(offset_tables)
+    Ok(offset_tables)
+}
+
+pub fn find(offset_tables: &HashMap<String, OffsetTable>, tag: &str) -> Result<OffsetTable> {
+    return match offset_tables.get(tag) {
+        Some(table) => Ok(*table),
+        None => Err(Error::MissingTable(tag.into())),
+    };
 }
@@ -1,3 +1,4 @@
+use crate::error::Result;
 use crate::reader;
 
 #[derive(Debug, Copy, Clone)]
@@ -9,14 +10,14 @@ pub struct OffsetSubTable {
     pub range_shift: u16, // numTables*16-searchRange
 }
 
-pub fn read(r: &mut reader::FontReader) -> Option<OffsetSubTable> {
+pub fn read(r: &mut reader::FontReader) -> Result<OffsetSubTable> {
     let scalar_type = r.get_uint32()?;
     let numtables = r.get_uint16()?;
     let search_range = r.get_uint16()?;
     let entry_selector = r.get_uint16()?;
     let range_shift = r.get_uint16()?;
 
-    return Some(OffsetSubTable {
+    return Ok(OffsetSubTable {
         scalar_type,
         numtables,
         search_range,
@@ -0,0 +1,280 @@
+use crate::error::{Error, Result};
+use crate::reader;
+use crate::tables::offset::OffsetTable;
+
+// fsSelection bits
+pub const ITALIC: u16 = 1;
+pub const UNDERSCORE: u16 = 1 << 1;
+pub const NEGATIVE: u16 = 1 << 2;
+pub const OUTLINED: u16 = 1 << 3;
+pub const STRIKEOUT: u16 = 1 << 4;
+pub const BOLD: u16 = 1 << 5;
+pub const REGULAR: u16 = 1 << 6;
+pub const USE_TYPO_METRICS: u16 = 1 << 7;
+pub const WWS: u16 = 1 << 8;
+pub const OBLIQUE: u16 = 1 << 9;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Embedding {
+    Installable,  // 0, may be embedded and permanently installed
+    Restricted,   // 2, must not be embedded without permission from the legal owner
+    PreviewPrint, // 4, may be embedded for viewing and printing only
+    Editable,     // 8, may be embedded and the document edited
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct EmbeddingPermissions {
+    pub embedding: Embedding,
+    pub no_subsetting: bool, // the font must be embedded whole
+    pub bitmap_only: bool,   // only the bitmaps may be embedded, no outlines
+}
+
+// Ascender and descender are both measured up from the baseline, so the descender is
+// usually negative, the same as in hhea
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct LineMetrics {
+    pub ascender: i16,
+    pub descender: i16,
+    pub line_gap: i16,
+}
+
+// LineMetrics in pixels for one pixel size
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ScaledLineMetrics {
+    pub ascender: f32,
+    pub descender: f32,
+    pub line_gap: f32,
+}
+
+impl LineMetrics {
+    pub fn scale(&self, pixel_size: f32, units_per_em: u16) -> ScaledLineMetrics {
+        let scale = pixel_size / units_per_em.max(1) as f32;
+        return ScaledLineMetrics {
+            ascender: self.ascender as f32 * scale,
+            descender: self.descender as f32 * scale,
+            line_gap: self.line_gap as f32 * scale,
+        };
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Os2 {
+    pub version: u16,                             // 0 to 5
+    pub x_avg_char_width: i16, // average weighted advance width of lower case letters and space
+    pub us_weight_class: u16,  // visual weight (degree of blackness or thickness) of stroke
+    pub us_width_class: u16,   // relative change from the normal aspect ratio
+    pub fs_type: u16,          // font embedding licensing rights
+    pub y_subscript_x_size: i16, // horizontal font size for subscripts
+    pub y_subscript_y_size: i16, // vertical font size for subscripts
+    pub y_subscript_x_offset: i16, // horizontal offset for subscripts
+    pub y_subscript_y_offset: i16, // vertical offset from the baseline for subscripts
+    pub y_superscript_x_size: i16, // horizontal font size for superscripts
+    pub y_superscript_y_size: i16, // vertical font size for superscripts
+    pub y_superscript_x_offset: i16, // horizontal offset for superscripts
+    pub y_superscript_y_offset: i16, // vertical offset from the baseline for superscripts
+    pub y_strikeout_size: i16, // width of the strikeout stroke
+    pub y_strikeout_position: i16, // position of the top of the strikeout stroke relative to the baseline
+    pub s_family_class: i16,       // classification of font-family design
+    pub panose: [u8; 10], // 10 byte series of numbers used to describe the visual characteristics
+    pub ul_unicode_range: [u32; 4], // 128 bits of Unicode blocks the font covers
+    pub ach_vend_id: String, // four character identifier for the font vendor
+    pub fs_selection: u16, // font selection flags, see the constants above
+    pub us_first_char_index: u16, // minimum Unicode index in this font
+    pub us_last_char_index: u16, // maximum Unicode index in this font
+    pub s_typo_ascender: i16, // typographic ascender
+    pub s_typo_descender: i16, // typographic descender
+    pub s_typo_line_gap: i16, // typographic line gap
+    pub us_win_ascent: u16, // ascender metric for Windows, clipping region above the baseline
+    pub us_win_descent: u16, // descender metric for Windows, clipping region below the baseline
+    pub ul_code_page_range: Option<[u32; 2]>, // 64 bits of supported code pages, version 1 and up
+    pub sx_height: Option<i16>, // height of flat lowercase letters, version 2 and up
+    pub s_cap_height: Option<i16>, // height of flat uppercase letters, version 2 and up
+    pub us_default_char: Option<u16>, // character used for missing ones, version 2 and up
+    pub us_break_char: Option<u16>, // character used to break words, version 2 and up
+    pub us_max_context: Option<u16>, // longest glyph context needed by the layout features, version 2 and up
+    pub us_lower_optical_point_size: Option<u16>, // in TWIPs, version 5 only
+    pub us_upper_optical_point_size: Option<u16>, // in TWIPs, version 5 only
+}
+
+impl Os2 {
+    pub fn is_italic(&self) -> bool {
+        return self.fs_selection & ITALIC != 0;
+    }
+
+    pub fn is_bold(&self) -> bool {
+        return self.fs_selection & BOLD != 0;
+    }
+
+    pub fn is_oblique(&self) -> bool {
+        return self.fs_selection & OBLIQUE != 0;
+    }
+
+    pub fn use_typo_metrics(&self) -> bool {
+        return self.fs_selection & USE_TYPO_METRICS != 0;
+    }
+
+    // Unicode range bit as numbered by the spec, 0 is Basic Latin and 122 is the last one
+    pub fn has_unicode_range(&self, bit: u8) -> bool {
+        let word = (bit / 32) as usize;
+        return word < 4 && self.ul_unicode_range[word] & (1 << (bit % 32)) != 0;
+    }
+
+    pub fn has_code_page(&self, bit: u8) -> bool {
+        let word = (bit / 32) as usize;
+        return match self.ul_code_page_range {
+            Some(range) => word < 2 && range[word] & (1 << (bit % 32)) != 0,
+            None => false,
+        };
+    }
+
+    // The lowest of bits 0 to 3 wins when a broken font sets more than one of them
+    pub fn embedding_permissions(&self) -> EmbeddingPermissions {
+        let embedding = if self.fs_type & 0x000F == 0 {
+            Embedding::Installable
+        } else if self.fs_type & 0x0002 != 0 {
+            Embedding::Restricted
+        } else if self.fs_type & 0x0004 != 0 {
+            Embedding::PreviewPrint
+        } else {
+            Embedding::Editable
+        };
+
+        return EmbeddingPermissions {
+            embedding,
+            no_subsetting: self.fs_type & 0x0100 != 0,
+            bitmap_only: self.fs_type & 0x0200 != 0,
+        };
+    }
+
+    pub fn typo_metrics(&self) -> LineMetrics {
+        return LineMetrics {
+            ascender: self.s_typo_ascender,
+            descender: self.s_typo_descender,
+            line_gap: self.s_typo_line_gap,
+        };
+    }
+
+    // The win metrics have no line gap of their own, whatever the typographic line
+    // height has on top of them is used instead
+    pub fn win_metrics(&self) -> LineMetrics {
+        let ascender = self.us_win_ascent as i32;
+        let descender = -(self.us_win_descent as i32);
+        let typo_height = self.s_typo_ascender as i32 - self.s_typo_descender as i32
+            + self.s_typo_line_gap as i32;
+        let line_gap = (typo_height - (ascender - descender)).max(0);
+
+        return LineMetrics {
+            ascender: ascender.min(i16::MAX as i32) as i16,
+            descender: descender.max(i16::MIN as i32) as i16,
+            line_gap: line_gap.min(i16::MAX as i32) as i16,
+        };
+    }
+}
+
+pub fn read(r: &mut reader::FontReader, os2_offset_table: OffsetTable) -> Result<Os2> {
+    r.seek_table("OS/2", os2_offset_table.offset as usize)?;
+
+    let version = r.get_uint16()?;
+    if version > 5 {
+        return Err(Error::UnsupportedFormat {
+            table: "OS/2".into(),
+            offset: os2_offset_table.offset as usize,
+            format: version as u32,
+        });
+    }
+
+    let x_avg_char_width = r.get_int16()?;
+    let us_weight_class = r.get_uint16()?;
+    let us_width_class = r.get_uint16()?;
+    let fs_type = r.get_uint16()?;
+    let y_subscript_x_size = r.get_int16()?;
+    let y_subscript_y_size = r.get_int16()?;
+    let y_subscript_x_offset = r.get_int16()?;
+    let y_subscript_y_offset = r.get_int16()?;
+    let y_superscript_x_size = r.get_int16()?;
+    let y_superscript_y_size = r.get_int16()?;
+    let y_superscript_x_offset = r.get_int16()?;
+    let y_superscript_y_offset = r.get_int16()?;
+    let y_strikeout_size = r.get_int16()?;
+    let y_strikeout_position = r.get_int16()?;
+    let s_family_class = r.get_int16()?;
+
+    let mut panose = [0; 10];
+    for byte in panose.iter_mut() {
+        *byte = r.get_uint8()?;
+    }
+
+    let mut ul_unicode_range = [0; 4];
+    for range in ul_unicode_range.iter_mut() {
+        *range = r.get_uint32()?;
+    }
+
+    let ach_vend_id = r.get_string(4)?;
+    let fs_selection = r.get_uint16()?;
+    let us_first_char_index = r.get_uint16()?;
+    let us_last_char_index = r.get_uint16()?;
+    let s_typo_ascender = r.get_int16()?;
+    let s_typo_descender = r.get_int16()?;
+    let s_typo_line_gap = r.get_int16()?;
+    let us_win_ascent = r.get_uint16()?;
+    let us_win_descent = r.get_uint16()?;
+
+    let mut ul_code_page_range = None;
+    if version >= 1 {
+        ul_code_page_range = Some([r.get_uint32()?, r.get_uint32()?]);
+    }
+
+    let (mut sx_height, mut s_cap_height) = (None, None);
+    let (mut us_default_char, mut us_break_char, mut us_max_context) = (None, None, None);
+    if version >= 2 {
+        sx_height = Some(r.get_int16()?);
+        s_cap_height = Some(r.get_int16()?);
+        us_default_char = Some(r.get_uint16()?);
+        us_break_char = Some(r.get_uint16()?);
+        us_max_context = Some(r.get_uint16()?);
+    }
+
+    let (mut us_lower_optical_point_size, mut us_upper_optical_point_size) = (None, None);
+    if version >= 5 {
+        us_lower_optical_point_size = Some(r.get_uint16()?);
+        us_upper_optical_point_size = Some(r.get_uint16()?);
+    }
+
+    return Ok(Os2 {
+        version,
+        x_avg_char_width,
+        us_weight_class,
+        us_width_class,
+        fs_type,
+        y_subscript_x_size,
+        y_subscript_y_size,
+        y_subscript_x_offset,
+        y_subscript_y_offset,
+        y_superscript_x_size,
+        y_superscript_y_size,
+        y_superscript_x_offset,
+        y_superscript_y_offset,
+        y_strikeout_size,
+        y_strikeout_position,
+        s_family_class,
+        panose,
+        ul_unicode_range,
+        ach_vend_id,
+        fs_selection,
+        us_first_char_index,
+        us_last_char_index,
+        s_typo_ascender,
+        s_typo_descender,
+        s_typo_line_gap,
+        us_win_ascent,
+        us_win_descent,
+        ul_code_page_range,
+        sx_height,
+        s_cap_height,
+        us_default_char,
+        us_break_char,
+        us_max_context,
+        us_lower_optical_point_size,
+        us_upper_optical_point_size,
+    });
+}
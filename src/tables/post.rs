@@ -0,0 +1,407 @@
+use crate::error::{Error, Result};
+use crate::reader;
+use crate::tables::offset::OffsetTable;
+use crate::GlyphId;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone)]
+pub struct Post {
+    pub version: f32,                        // 1.0, 2.0, 2.5 or 3.0
+    pub italic_angle: f32, // Italic angle in counter-clockwise degrees from the vertical
+    pub underline_position: i16, // Suggested y-coordinate of the top of the underline
+    pub underline_thickness: i16, // Suggested values for the underline thickness
+    pub is_fixed_pitch: u32, // Set to 0 if the font is proportionally spaced
+    pub min_mem_type42: u32, // Minimum memory usage when a TrueType font is downloaded
+    pub max_mem_type42: u32, // Maximum memory usage when a TrueType font is downloaded
+    pub min_mem_type1: u32, // Minimum memory usage when downloaded as a Type 1 font
+    pub max_mem_type1: u32, // Maximum memory usage when downloaded as a Type 1 font
+    pub glyph_names: Vec<String>, // Name of every glyph, empty for version 3.0
+    pub glyph_ids: HashMap<String, GlyphId>, // Reverse of glyph_names, first glyph wins
+}
+
+impl Post {
+    pub fn glyph_name(&self, glyph: GlyphId) -> Option<&str> {
+        return self
+            .glyph_names
+            .get(glyph.0 as usize)
+            .map(|name| name.as_str());
+    }
+
+    pub fn glyph_id_by_name(&self, name: &str) -> Option<GlyphId> {
+        return self.glyph_ids.get(name).copied();
+    }
+}
+
+// Version 2.0 points into the standard names below 258 and into its own pascal strings
+// from there on
+fn read_version2(r: &mut reader::FontReader) -> Result<Vec<String>> {
+    let glyph_count = r.get_uint16()?;
+    let mut glyph_name_index = Vec::new();
+    for _ in 0..glyph_count {
+        glyph_name_index.push(r.get_uint16()?);
+    }
+
+    let custom_count = glyph_name_index
+        .iter()
+        .filter(|&&index| index >= 258)
+        .map(|&index| index as usize - 257)
+        .max()
+        .unwrap_or(0);
+
+    let mut custom_names = Vec::new();
+    for _ in 0..custom_count {
+        let length = r.get_uint8()?;
+        custom_names.push(r.get_string(length as usize)?);
+    }
+
+    let mut glyph_names = Vec::new();
+    for index in glyph_name_index {
+        let index = index as usize;
+        let name = match index {
+            0..=257 => MAC_GLYPH_NAMES[index].to_string(),
+            _ => custom_names[index - 258].clone(),
+        };
+        glyph_names.push(name);
+    }
+
+    return Ok(glyph_names);
+}
+
+// Version 2.5 only reorders the standard names, each glyph stores a signed offset into them
+fn read_version25(r: &mut reader::FontReader) -> Result<Vec<String>> {
+    let glyph_count = r.get_uint16()?;
+    let mut glyph_names = Vec::new();
+
+    for glyph in 0..glyph_count {
+        let offset = r.get_uint8()? as i8;
+        let index = glyph as i32 + offset as i32;
+        let name = match MAC_GLYPH_NAMES.get(index as usize) {
+            Some(name) if index >= 0 => name.to_string(),
+            _ => format!("glyph{}", glyph),
+        };
+        glyph_names.push(name);
+    }
+
+    return Ok(glyph_names);
+}
+
+pub fn read(
+    r: &mut reader::FontReader,
+    post_offset_table: OffsetTable,
+    glyph_count: u16,
+) -> Result<Post> {
+    r.seek_table("post", post_offset_table.offset as usize)?;
+
+    // 2.5 is stored as 0x00025000, so the raw value is what tells the versions apart
+    let raw_version = r.get_uint32()?;
+    let version = raw_version as i32 as f32 / (1 << 16) as f32;
+    let italic_angle = r.get_float32()?;
+    let underline_position = r.get_int16()?;
+    let underline_thickness = r.get_int16()?;
+    let is_fixed_pitch = r.get_uint32()?;
+    let min_mem_type42 = r.get_uint32()?;
+    let max_mem_type42 = r.get_uint32()?;
+    let min_mem_type1 = r.get_uint32()?;
+    let max_mem_type1 = r.get_uint32()?;
+
+    let glyph_names = match raw_version {
+        0x00010000 => MAC_GLYPH_NAMES
+            .iter()
+            .take(glyph_count as usize)
+            .map(|name| name.to_string())
+            .collect(),
+        0x00020000 => read_version2(r)?,
+        0x00025000 => read_version25(r)?,
+        0x00030000 => Vec::new(),
+        val => {
+            return Err(Error::UnsupportedFormat {
+                table: "post".into(),
+                offset: post_offset_table.offset as usize,
+                format: val,
+            })
+        }
+    };
+
+    let mut glyph_ids = HashMap::new();
+    for (index, name) in glyph_names.iter().enumerate() {
+        glyph_ids
+            .entry(name.clone())
+            .or_insert(GlyphId(index as u16));
+    }
+
+    return Ok(Post {
+        version,
+        italic_angle,
+        underline_position,
+        underline_thickness,
+        is_fixed_pitch,
+        min_mem_type42,
+        max_mem_type42,
+        min_mem_type1,
+        max_mem_type1,
+        glyph_names,
+        glyph_ids,
+    });
+}
+
+// The 258 glyphs of the standard Macintosh character set, in glyph order
+pub const MAC_GLYPH_NAMES: [&str; 258] = [
+    ".notdef",
+    ".null",
+    "nonmarkingreturn",
+    "space",
+    "exclam",
+    "quotedbl",
+    "numbersign",
+    "dollar",
+    "percent",
+    "ampersand",
+    "quotesingle",
+    "parenleft",
+    "parenright",
+    "asterisk",
+    "plus",
+    "comma",
+    "hyphen",
+    "period",
+    "slash",
+    "zero",
+    "one",
+    "two",
+    "three",
+    "four",
+    "five",
+    "six",
+    "seven",
+    "eight",
+    "nine",
+    "colon",
+    "semicolon",
+    "less",
+    "equal",
+    "greater",
+    "question",
+    "at",
+    "A",
+    "B",
+    "C",
+    "D",
+    "E",
+    "F",
+    "G",
+    "H",
+    "I",
+    "J",
+    "K",
+    "L",
+    "M",
+    "N",
+    "O",
+    "P",
+    "Q",
+    "R",
+    "S",
+    "T",
+    "U",
+    "V",
+    "W",
+    "X",
+    "Y",
+    "Z",
+    "bracketleft",
+    "backslash",
+    "bracketright",
+    "asciicircum",
+    "underscore",
+    "grave",
+    "a",
+    "b",
+    "c",
+    "d",
+    "e",
+    "f",
+    "g",
+    "h",
+    "i",
+    "j",
+    "k",
+    "l",
+    "m",
+    "n",
+    "o",
+    "p",
+    "q",
+    "r",
+    "s",
+    "t",
+    "u",
+    "v",
+    "w",
+    "x",
+    "y",
+    "z",
+    "braceleft",
+    "bar",
+    "braceright",
+    "asciitilde",
+    "Adieresis",
+    "Aring",
+    "Ccedilla",
+    "Eacute",
+    "Ntilde",
+    "Odieresis",
+    "Udieresis",
+    "aacute",
+    "agrave",
+    "acircumflex",
+    "adieresis",
+    "atilde",
+    "aring",
+    "ccedilla",
+    "eacute",
+    "egrave",
+    "ecircumflex",
+    "edieresis",
+    "iacute",
+    "igrave",
+    "icircumflex",
+    "idieresis",
+    "ntilde",
+    "oacute",
+    "ograve",
+    "ocircumflex",
+    "odieresis",
+    "otilde",
+    "uacute",
+    "ugrave",
+    "ucircumflex",
+    "udieresis",
+    "dagger",
+    "degree",
+    "cent",
+    "sterling",
+    "section",
+    "bullet",
+    "paragraph",
+    "germandbls",
+    "registered",
+    "copyright",
+    "trademark",
+    "acute",
+    "dieresis",
+    "notequal",
+    "AE",
+    "Oslash",
+    "infinity",
+    "plusminus",
+    "lessequal",
+    "greaterequal",
+    "yen",
+    "mu",
+    "partialdiff",
+    "summation",
+    "product",
+    "pi",
+    "integral",
+    "ordfeminine",
+    "ordmasculine",
+    "Omega",
+    "ae",
+    "oslash",
+    "questiondown",
+    "exclamdown",
+    "logicalnot",
+    "radical",
+    "florin",
+    "approxequal",
+    "Delta",
+    "guillemotleft",
+    "guillemotright",
+    "ellipsis",
+    "nonbreakingspace",
+    "Agrave",
+    "Atilde",
+    "Otilde",
+    "OE",
+    "oe",
+    "endash",
+    "emdash",
+    "quotedblleft",
+    "quotedblright",
+    "quoteleft",
+    "quoteright",
+    "divide",
+    "lozenge",
+    "ydieresis",
+    "Ydieresis",
+    "fraction",
+    "currency",
+    "guilsinglleft",
+    "guilsinglright",
+    "fi",
+    "fl",
+    "daggerdbl",
+    "periodcentered",
+    "quotesinglbase",
+    "quotedblbase",
+    "perthousand",
+    "Acircumflex",
+    "Ecircumflex",
+    "Aacute",
+    "Edieresis",
+    "Egrave",
+    "Iacute",
+    "Icircumflex",
+    "Idieresis",
+    "Igrave",
+    "Oacute",
+    "Ocircumflex",
+    "apple",
+    "Ograve",
+    "Uacute",
+    "Ucircumflex",
+    "Ugrave",
+    "dotlessi",
+    "circumflex",
+    "tilde",
+    "macron",
+    "breve",
+    "dotaccent",
+    "ring",
+    "cedilla",
+    "hungarumlaut",
+    "ogonek",
+    "caron",
+    "Lslash",
+    "lslash",
+    "Scaron",
+    "scaron",
+    "Zcaron",
+    "zcaron",
+    "brokenbar",
+    "Eth",
+    "eth",
+    "Yacute",
+    "yacute",
+    "Thorn",
+    "thorn",
+    "minus",
+    "multiply",
+    "onesuperior",
+    "twosuperior",
+    "threesuperior",
+    "onehalf",
+    "onequarter",
+    "threequarters",
+    "franc",
+    "Gbreve",
+    "gbreve",
+    "Idotaccent",
+    "Scedilla",
+    "scedilla",
+    "Cacute",
+    "cacute",
+    "Ccaron",
+    "ccaron",
+    "dcroat",
+];
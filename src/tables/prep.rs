@@ -0,0 +1,16 @@
+use crate::error::Result;
+use crate::reader;
+use crate::tables::offset::OffsetTable;
+
+// Control value program, run whenever the size changes to fit the cvt to the pixel grid
+#[derive(Debug, Clone, Default)]
+pub struct Prep {
+    pub instructions: Vec<u8>,
+}
+
+pub fn read(r: &mut reader::FontReader, prep_offset_table: OffsetTable) -> Result<Prep> {
+    r.seek_table("prep", prep_offset_table.offset as usize)?;
+
+    let instructions = r.get_bytes(prep_offset_table.length as usize)?.to_vec();
+    return Ok(Prep { instructions });
+}
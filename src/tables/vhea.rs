@@ -0,0 +1,61 @@
+use crate::error::Result;
+use crate::reader;
+use crate::tables::offset::OffsetTable;
+
+// Vertical counterpart of hhea, only fonts meant for vertical text have one
+#[derive(Debug, Copy, Clone)]
+pub struct Vhea {
+    pub version: f32,                 // 0x00010000 (1.0) or 0x00011000 (1.1)
+    pub ascent: i16,                  // vertTypoAscender in 1.1, half the advance height in 1.0
+    pub descent: i16, // vertTypoDescender in 1.1, minus half the advance height in 1.0
+    pub line_gap: i16, // vertTypoLineGap in 1.1, 0 in 1.0
+    pub advance_height_max: u16, // must be consistent with vertical metrics
+    pub min_top_side_bearing: i16, // must be consistent with vertical metrics
+    pub min_bottom_side_bearing: i16, // must be consistent with vertical metrics
+    pub y_max_extent: i16, // max(tsb + (yMax-yMin))
+    pub caret_slope_rise: i16, // 0 for a horizontal caret
+    pub caret_slope_run: i16, // 1 for a horizontal caret
+    pub caret_offset: i16, // set value to 0 for non-slanted fonts
+    pub metric_data_format: i16, // 0 for current format
+    pub num_of_long_ver_metrics: u16, // number of advance heights in metrics table
+}
+
+pub fn read(r: &mut reader::FontReader, vhea_offset_table: OffsetTable) -> Result<Vhea> {
+    r.seek_table("vhea", vhea_offset_table.offset as usize)?;
+
+    let version = r.get_float32()?;
+    let ascent = r.get_int16()?;
+    let descent = r.get_int16()?;
+    let line_gap = r.get_int16()?;
+    let advance_height_max = r.get_uint16()?;
+    let min_top_side_bearing = r.get_int16()?;
+    let min_bottom_side_bearing = r.get_int16()?;
+    let y_max_extent = r.get_int16()?;
+    let caret_slope_rise = r.get_int16()?;
+    let caret_slope_run = r.get_int16()?;
+    let caret_offset = r.get_int16()?;
+
+    // reserved like in hhea
+    for _ in 0..4 {
+        let _ = r.get_int16()?;
+    }
+
+    let metric_data_format = r.get_int16()?;
+    let num_of_long_ver_metrics = r.get_uint16()?;
+
+    return Ok(Vhea {
+        version,
+        ascent,
+        descent,
+        line_gap,
+        advance_height_max,
+        min_top_side_bearing,
+        min_bottom_side_bearing,
+        y_max_extent,
+        caret_slope_rise,
+        caret_slope_run,
+        caret_offset,
+        metric_data_format,
+        num_of_long_ver_metrics,
+    });
+}
@@ -0,0 +1,70 @@
+use crate::error::Result;
+use crate::reader;
+use crate::tables::offset::OffsetTable;
+
+// Laid out exactly like hmtx, with heights and top bearings instead
+#[derive(Debug, Copy, Clone)]
+pub struct VmtxLongVerMetric {
+    pub advance_height: u16,
+    pub top_side_bearing: i16,
+}
+
+#[derive(Debug, Clone)]
+pub struct Vmtx {
+    pub vmetrics: Vec<VmtxLongVerMetric>,
+    pub top_side_bearings: Vec<i16>,
+}
+
+pub fn read(
+    r: &mut reader::FontReader,
+    vmtx_offset_table: OffsetTable,
+    long_ver_metric_count: u16,
+    glyph_count: u16,
+) -> Result<Vmtx> {
+    r.seek_table("vmtx", vmtx_offset_table.offset as usize)?;
+    let mut vmetrics = Vec::new();
+
+    for _ in 0..long_ver_metric_count {
+        vmetrics.push(VmtxLongVerMetric {
+            advance_height: r.get_uint16()?,
+            top_side_bearing: r.get_int16()?,
+        });
+    }
+
+    let mut top_side_bearings = Vec::new();
+
+    for _ in 0..glyph_count.saturating_sub(long_ver_metric_count) {
+        top_side_bearings.push(r.get_int16()?);
+    }
+
+    return Ok(Vmtx {
+        vmetrics,
+        top_side_bearings,
+    });
+}
+
+impl Vmtx {
+    // Same repeat rule as Hmtx::metric
+    pub fn metric(&self, glyph_index: u16) -> VmtxLongVerMetric {
+        let index = glyph_index as usize;
+        if let Some(&metric) = self.vmetrics.get(index) {
+            return metric;
+        }
+
+        let advance_height = self
+            .vmetrics
+            .last()
+            .map(|metric| metric.advance_height)
+            .unwrap_or(0);
+        let top_side_bearing = self
+            .top_side_bearings
+            .get(index - self.vmetrics.len())
+            .copied()
+            .unwrap_or(0);
+
+        return VmtxLongVerMetric {
+            advance_height,
+            top_side_bearing,
+        };
+    }
+}
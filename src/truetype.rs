@@ -1,10 +1,18 @@
+use crate::error::{Error, Result};
+use crate::hinting;
+use crate::outline;
+use crate::raster;
 use crate::reader;
 use crate::tables;
+use crate::GlyphId;
 use std::collections::HashMap;
 
+// Deepest component nesting followed, whatever maxp claims
+const MAX_COMPONENT_DEPTH: u16 = 16;
+
 #[derive(Debug)]
-pub struct TrueTypeFont {
-    file: reader::FontReader,
+pub struct TrueTypeFont<'a> {
+    data: reader::FontData<'a>,
     offset_sub_table: tables::offset_sub::OffsetSubTable,
     offset_tables: HashMap<String, tables::offset::OffsetTable>,
     head: tables::head::Head,
@@ -14,38 +22,151 @@ pub struct TrueTypeFont {
     hmtx: tables::hmtx::Hmtx,
     loca: tables::loca::Loca,
     name: tables::name::Name,
+    post: Option<tables::post::Post>,
+    os2: Option<tables::os2::Os2>,
+    cvt: Option<tables::cvt::Cvt>,
+    fpgm: Option<tables::fpgm::Fpgm>,
+    prep: Option<tables::prep::Prep>,
+    gasp: Option<tables::gasp::Gasp>,
+    vhea: Option<tables::vhea::Vhea>,
+    vmtx: Option<tables::vmtx::Vmtx>,
+    kern: Option<tables::kern::Kern>,
+    gdef: Option<tables::gdef::Gdef>,
+    gsub: Option<tables::gsub::Gsub>,
+    gpos: Option<tables::gpos::Gpos>,
+    table_errors: Vec<Error>, // why the optional tables left out couldn't be read
+}
+
+// Optional tables that can't be read are left out instead of failing the whole font, the
+// error is kept around for table_errors
+fn optional<T>(result: Result<T>, errors: &mut Vec<Error>) -> Option<T> {
+    match result {
+        Ok(val) => return Some(val),
+        Err(err) => {
+            errors.push(err);
+            return None;
+        }
+    }
+}
+
+impl TrueTypeFont<'static> {
+    pub fn new(filename: String) -> Result<Self> {
+        return TrueTypeFont::parse(reader::FontData::Owned(std::fs::read(filename)?));
+    }
+
+    // The file is mapped instead of read, handy for the huge CJK fonts. The usual mmap
+    // caveat applies, the file must not be modified while the font is alive.
+    #[cfg(feature = "mmap")]
+    pub fn from_mmap(filename: String) -> Result<Self> {
+        let file = std::fs::File::open(filename)?;
+        let map = unsafe { memmap2::Mmap::map(&file)? };
+        return TrueTypeFont::parse(reader::FontData::Mapped(map));
+    }
 }
 
-impl TrueTypeFont {
-    pub fn new(filename: String) -> Option<Self> {
-        let mut file = reader::FontReader::new(filename);
+impl<'a> TrueTypeFont<'a> {
+    pub fn from_bytes(data: &'a [u8]) -> Result<Self> {
+        return TrueTypeFont::parse(reader::FontData::Borrowed(data));
+    }
+
+    fn parse(data: reader::FontData<'a>) -> Result<Self> {
+        let mut file = reader::FontReader::new(&data);
 
         let offset_sub_table = tables::offset_sub::read(&mut file)?;
         let offset_tables = tables::offset::read(&mut file, offset_sub_table.numtables)?;
-        let head = tables::head::read(&mut file, *offset_tables.get("head")?)?;
-        let maxp = tables::maxp::read(&mut file, *offset_tables.get("maxp")?)?;
-        let cmap = tables::cmap::read(&mut file, *offset_tables.get("cmap")?)?;
-        let hhea = tables::hhea::read(&mut file, *offset_tables.get("hhea")?)?;
+        let head = tables::head::read(&mut file, tables::offset::find(&offset_tables, "head")?)?;
+        let maxp = tables::maxp::read(&mut file, tables::offset::find(&offset_tables, "maxp")?)?;
+        let cmap = tables::cmap::read(&mut file, tables::offset::find(&offset_tables, "cmap")?)?;
+        let hhea = tables::hhea::read(&mut file, tables::offset::find(&offset_tables, "hhea")?)?;
         let hmtx = tables::hmtx::read(
             &mut file,
-            *offset_tables.get("hmtx")?,
+            tables::offset::find(&offset_tables, "hmtx")?,
             hhea.num_of_long_hor_metrics,
             maxp.glyph_count,
         )?;
 
         let loca = tables::loca::read(
             &mut file,
-            *offset_tables.get("loca")?,
+            tables::offset::find(&offset_tables, "loca")?,
             maxp.glyph_count,
             head.index_to_loc_format,
         )?;
 
-        let name = tables::name::read(&mut file, *offset_tables.get("name")?)?;
-
-        // TODO: skipping the post table for now, could care less about postscript anyway
-
-        return Some(TrueTypeFont {
-            file,
+        let name = tables::name::read(&mut file, tables::offset::find(&offset_tables, "name")?)?;
+
+        let mut table_errors = Vec::new();
+
+        let post = match offset_tables.get("post") {
+            Some(&table) => optional(
+                tables::post::read(&mut file, table, maxp.glyph_count),
+                &mut table_errors,
+            ),
+            None => None,
+        };
+
+        let os2 = match offset_tables.get("OS/2") {
+            Some(&table) => optional(tables::os2::read(&mut file, table), &mut table_errors),
+            None => None,
+        };
+
+        let cvt = match offset_tables.get("cvt ") {
+            Some(&table) => Some(tables::cvt::read(&mut file, table)?),
+            None => None,
+        };
+
+        let fpgm = match offset_tables.get("fpgm") {
+            Some(&table) => Some(tables::fpgm::read(&mut file, table)?),
+            None => None,
+        };
+
+        let prep = match offset_tables.get("prep") {
+            Some(&table) => Some(tables::prep::read(&mut file, table)?),
+            None => None,
+        };
+
+        let gasp = match offset_tables.get("gasp") {
+            Some(&table) => Some(tables::gasp::read(&mut file, table)?),
+            None => None,
+        };
+
+        let vhea = match offset_tables.get("vhea") {
+            Some(&table) => Some(tables::vhea::read(&mut file, table)?),
+            None => None,
+        };
+
+        // vmtx can't be read without the metric count in vhea
+        let vmtx = match (vhea, offset_tables.get("vmtx")) {
+            (Some(vhea), Some(&table)) => Some(tables::vmtx::read(
+                &mut file,
+                table,
+                vhea.num_of_long_ver_metrics,
+                maxp.glyph_count,
+            )?),
+            _ => None,
+        };
+
+        let kern = match offset_tables.get("kern") {
+            Some(&table) => optional(tables::kern::read(&mut file, table), &mut table_errors),
+            None => None,
+        };
+
+        let gdef = match offset_tables.get("GDEF") {
+            Some(&table) => optional(tables::gdef::read(&mut file, table), &mut table_errors),
+            None => None,
+        };
+
+        let gsub = match offset_tables.get("GSUB") {
+            Some(&table) => optional(tables::gsub::read(&mut file, table), &mut table_errors),
+            None => None,
+        };
+
+        let gpos = match offset_tables.get("GPOS") {
+            Some(&table) => optional(tables::gpos::read(&mut file, table), &mut table_errors),
+            None => None,
+        };
+
+        return Ok(TrueTypeFont {
+            data,
             offset_sub_table,
             offset_tables,
             head,
@@ -55,108 +176,393 @@ impl TrueTypeFont {
             hmtx,
             loca,
             name,
+            post,
+            os2,
+            cvt,
+            fpgm,
+            prep,
+            gasp,
+            vhea,
+            vmtx,
+            kern,
+            gdef,
+            gsub,
+            gpos,
+            table_errors,
         });
     }
 
-    fn glyph_offset(&mut self, index: u32) -> Option<u32> {
-        let loca_table_offset = self.offset_tables.get("loca".into())?.offset;
-        let glyf_table_offset = self.offset_tables.get("glyf".into())?.offset;
+    pub fn data(&self) -> &[u8] {
+        return &self.data;
+    }
 
-        match self.head.index_to_loc_format {
-            0 => {
-                let old = self.file.seek((loca_table_offset + index * 4) as usize);
-                let offset = self.file.get_uint32()?;
-                let next = self.file.get_uint32()?;
-                self.file.seek(old);
+    // Raw bytes of a table, None when the font doesn't have it or it runs past the file
+    pub fn table_data(&self, tag: &str) -> Option<&[u8]> {
+        let table = self.offset_tables.get(tag)?;
+        let start = table.offset as usize;
+        return self
+            .data
+            .get(start..start.checked_add(table.length as usize)?);
+    }
 
-                if offset == next {
-                    return Some(0);
-                } else {
-                    return Some(offset + glyf_table_offset);
-                }
+    pub fn offset_sub_table(&self) -> &tables::offset_sub::OffsetSubTable {
+        return &self.offset_sub_table;
+    }
+
+    pub fn offset_tables(&self) -> &HashMap<String, tables::offset::OffsetTable> {
+        return &self.offset_tables;
+    }
+
+    pub fn head(&self) -> &tables::head::Head {
+        return &self.head;
+    }
+
+    pub fn maxp(&self) -> &tables::maxp::Maxp {
+        return &self.maxp;
+    }
+
+    pub fn cmap(&self) -> &tables::cmap::Cmap {
+        return &self.cmap;
+    }
+
+    pub fn hhea(&self) -> &tables::hhea::Hhea {
+        return &self.hhea;
+    }
+
+    pub fn hmtx(&self) -> &tables::hmtx::Hmtx {
+        return &self.hmtx;
+    }
+
+    pub fn loca(&self) -> &tables::loca::Loca {
+        return &self.loca;
+    }
+
+    pub fn name(&self) -> &tables::name::Name {
+        return &self.name;
+    }
+
+    pub fn post(&self) -> Option<&tables::post::Post> {
+        return self.post.as_ref();
+    }
+
+    // Errors of the optional tables that were left out while loading the font
+    pub fn table_errors(&self) -> &[Error] {
+        return &self.table_errors;
+    }
+
+    pub fn os2(&self) -> Option<&tables::os2::Os2> {
+        return self.os2.as_ref();
+    }
+
+    pub fn cvt(&self) -> Option<&tables::cvt::Cvt> {
+        return self.cvt.as_ref();
+    }
+
+    pub fn fpgm(&self) -> Option<&tables::fpgm::Fpgm> {
+        return self.fpgm.as_ref();
+    }
+
+    pub fn prep(&self) -> Option<&tables::prep::Prep> {
+        return self.prep.as_ref();
+    }
+
+    pub fn gasp(&self) -> Option<&tables::gasp::Gasp> {
+        return self.gasp.as_ref();
+    }
+
+    pub fn vhea(&self) -> Option<&tables::vhea::Vhea> {
+        return self.vhea.as_ref();
+    }
+
+    pub fn vmtx(&self) -> Option<&tables::vmtx::Vmtx> {
+        return self.vmtx.as_ref();
+    }
+
+    pub fn kern(&self) -> Option<&tables::kern::Kern> {
+        return self.kern.as_ref();
+    }
+
+    pub fn gdef(&self) -> Option<&tables::gdef::Gdef> {
+        return self.gdef.as_ref();
+    }
+
+    pub fn gsub(&self) -> Option<&tables::gsub::Gsub> {
+        return self.gsub.as_ref();
+    }
+
+    pub fn gpos(&self) -> Option<&tables::gpos::Gpos> {
+        return self.gpos.as_ref();
+    }
+
+    // OS/2 only wins over hhea when it asks for its typographic metrics, hhea is what
+    // the Mac has always used and what most fonts keep consistent. The win metrics only
+    // stand in for a hhea with nothing in it.
+    pub fn line_metrics(&self) -> tables::os2::LineMetrics {
+        if let Some(os2) = self.os2.as_ref() {
+            if os2.use_typo_metrics() {
+                return os2.typo_metrics();
+            }
+
+            if self.hhea.ascent == 0 && self.hhea.descent == 0 {
+                return os2.win_metrics();
+            }
+        }
+
+        return tables::os2::LineMetrics {
+            ascender: self.hhea.ascent,
+            descender: self.hhea.descent,
+            line_gap: self.hhea.line_gap,
+        };
+    }
+
+    pub fn scaled_line_metrics(&self, pixel_size: f32) -> tables::os2::ScaledLineMetrics {
+        return self
+            .line_metrics()
+            .scale(pixel_size, self.head.units_per_em);
+    }
+
+    // Glyphs past the last long metric repeat its advance, monospaced fonts store a single
+    // one for all their glyphs
+    pub fn advance_width(&self, glyph: GlyphId) -> u16 {
+        return self.hmtx.metric(glyph.0).advance_width;
+    }
+
+    pub fn left_side_bearing(&self, glyph: GlyphId) -> i16 {
+        return self.hmtx.metric(glyph.0).left_side_bearing;
+    }
+
+    // Not stored anywhere, it is whatever the advance leaves after the bearing and the
+    // glyph's bounding box. Empty glyphs are all bearing.
+    pub fn right_side_bearing(&self, glyph: GlyphId) -> Result<i32> {
+        let metric = self.hmtx.metric(glyph.0);
+        let width = match self.glyph(glyph.0 as u32)? {
+            Some(header) => header.xmax as i32 - header.xmin as i32,
+            None => 0,
+        };
+
+        return Ok(metric.advance_width as i32 - metric.left_side_bearing as i32 - width);
+    }
+
+    pub fn advance_height(&self, glyph: GlyphId) -> u16 {
+        return self.vertical_metric(glyph, 0).advance_height;
+    }
+
+    pub fn top_side_bearing(&self, glyph: GlyphId) -> Result<i16> {
+        let y_max = match self.glyph(glyph.0 as u32)? {
+            Some(header) => header.ymax,
+            None => 0,
+        };
+
+        return Ok(self.vertical_metric(glyph, y_max).top_side_bearing);
+    }
+
+    // Fonts without vmtx get their vertical metrics made up the way FreeType does, every
+    // glyph hangs from the typographic ascender and advances down to the descender. Without
+    // OS/2 that falls back to hhea, and to the head bounding box when hhea is empty too.
+    pub(crate) fn vertical_metric(
+        &self,
+        glyph: GlyphId,
+        y_max: i16,
+    ) -> tables::vmtx::VmtxLongVerMetric {
+        if let Some(vmtx) = self.vmtx.as_ref() {
+            return vmtx.metric(glyph.0);
+        }
+
+        let (ascender, descender) = match self.os2.as_ref() {
+            Some(os2) => (os2.s_typo_ascender, os2.s_typo_descender),
+            None if self.hhea.ascent != 0 || self.hhea.descent != 0 => {
+                (self.hhea.ascent, self.hhea.descent)
             }
-            1 => {
-                let old = self.file.seek((loca_table_offset + index * 2) as usize);
-                let offset = self.file.get_uint16()? * 2;
-                let next = self.file.get_uint16()? * 2;
-                self.file.seek(old);
-
-                if offset == next {
-                    return Some(0);
-                } else {
-                    return Some(offset as u32 + glyf_table_offset);
+            None => (self.head.ymax, self.head.ymin),
+        };
+
+        let (ascender, descender) = (ascender as i32, descender as i32);
+        return tables::vmtx::VmtxLongVerMetric {
+            advance_height: (ascender - descender).unsigned_abs() as u16,
+            top_side_bearing: (ascender - y_max as i32) as i16,
+        };
+    }
+
+    // Only the legacy kern table, 0 for fonts without one
+    pub fn kerning(&self, left: GlyphId, right: GlyphId) -> i16 {
+        return match self.kern.as_ref() {
+            Some(kern) => kern.kerning(left, right),
+            None => 0,
+        };
+    }
+
+    // The GDEF class, tables::layout::BASE_GLYPH and the rest, 0 when the font has none
+    pub fn glyph_class(&self, glyph: GlyphId) -> u16 {
+        return match self.gdef.as_ref() {
+            Some(gdef) => gdef.glyph_class(glyph),
+            None => 0,
+        };
+    }
+
+    // Caret positions inside a ligature in font units from its origin, empty for glyphs
+    // without any. Carets on a contour point take the point's unhinted x, device
+    // corrections only matter once hinted and are left out.
+    pub fn ligature_carets(&self, glyph: GlyphId) -> Result<Vec<i16>> {
+        let carets = match self
+            .gdef
+            .as_ref()
+            .and_then(|gdef| gdef.ligature_carets(glyph))
+        {
+            Some(carets) => carets,
+            None => return Ok(Vec::new()),
+        };
+
+        let mut positions = Vec::new();
+        for caret in carets.iter() {
+            let position = match *caret {
+                tables::gdef::CaretValue::Format1 { coordinate }
+                | tables::gdef::CaretValue::Format3 { coordinate, .. } => coordinate,
+                tables::gdef::CaretValue::Format2 {
+                    caret_value_point_index,
+                } => {
+                    let point = self.resolve_glyph(glyph.0 as u32)?.and_then(|outline| {
+                        outline
+                            .x_coordinates
+                            .get(caret_value_point_index as usize)
+                            .copied()
+                    });
+                    match point {
+                        Some(x) => x,
+                        None => {
+                            return Err(Error::Malformed {
+                                table: "GDEF".into(),
+                                offset: self.offset_tables["GDEF"].offset as usize,
+                                reason: "ligature caret point out of range",
+                            })
+                        }
+                    }
+                }
+            };
+            positions.push(position);
+        }
+
+        return Ok(positions);
+    }
+
+    pub fn glyph_name(&self, glyph: GlyphId) -> Option<&str> {
+        return self.post.as_ref()?.glyph_name(glyph);
+    }
+
+    pub fn glyph_id_by_name(&self, name: &str) -> Option<GlyphId> {
+        return self.post.as_ref()?.glyph_id_by_name(name);
+    }
+
+    pub fn names(&self) -> tables::name::Names<'_> {
+        return tables::name::Names::new(&self.name);
+    }
+
+    pub fn glyph_index(&self, c: char) -> Option<GlyphId> {
+        return self.cmap.glyph_index(c);
+    }
+
+    pub fn glyph_index_for_variation(&self, base: char, selector: char) -> Option<GlyphId> {
+        return self.cmap.glyph_index_for_variation(base, selector);
+    }
+
+    pub(crate) fn glyph_offset(&self, index: u32) -> Result<u32> {
+        let glyf_table_offset = tables::offset::find(&self.offset_tables, "glyf")?.offset;
+        let index = index as usize;
+
+        let (offset, next) = match &self.loca {
+            tables::loca::Loca::Short(offsets) => {
+                match (offsets.get(index), offsets.get(index + 1)) {
+                    (Some(&offset), Some(&next)) => (offset as u32 * 2, next as u32 * 2),
+                    _ => return Ok(0),
                 }
             }
-            _ => return None,
+            tables::loca::Loca::Long(offsets) => match (offsets.get(index), offsets.get(index + 1))
+            {
+                (Some(&offset), Some(&next)) => (offset, next),
+                _ => return Ok(0),
+            },
+        };
+
+        if offset == next {
+            return Ok(0);
+        } else {
+            return Ok(offset + glyf_table_offset);
         }
     }
 
-    pub fn glyph(&mut self, index: u32) -> Option<tables::glyf::Glyph> {
+    // Empty glyphs and the ones lying outside the glyf table don't have any data to return.
+    // Composite glyphs come back as their components, see resolve_glyph to flatten them.
+    pub fn glyph(&self, index: u32) -> Result<Option<tables::glyf::Glyph>> {
         let offset = self.glyph_offset(index)?;
 
-        let glyph_offset_table = self.offset_tables.get("glyf")?;
+        let glyph_offset_table = tables::offset::find(&self.offset_tables, "glyf")?;
 
         if offset == 0 || offset >= glyph_offset_table.offset + glyph_offset_table.length {
-            return None;
+            return Ok(None);
         }
 
-        self.file.seek(offset as usize);
-        let number_of_contours = self.file.get_int16()?;
-        let xmin = self.file.get_int16()?;
-        let ymin = self.file.get_int16()?;
-        let xmax = self.file.get_int16()?;
-        let ymax = self.file.get_int16()?;
-
-        if number_of_contours < -1 {
-            return None;
-        }
+        let mut file = reader::FontReader::new(&self.data);
+        file.seek_table("glyf", offset as usize)?;
+        let number_of_contours = file.get_int16()?;
+        let xmin = file.get_int16()?;
+        let ymin = file.get_int16()?;
+        let xmax = file.get_int16()?;
+        let ymax = file.get_int16()?;
+
+        let (glyph_data, instructions) = match number_of_contours {
+            val if val < 0 => TrueTypeFont::composite_glyph(&mut file)?,
+            _ => match TrueTypeFont::simple_glyph(&mut file, number_of_contours)? {
+                Some(val) => val,
+                None => return Ok(None),
+            },
+        };
 
-        return Some(tables::glyf::Glyph {
+        return Ok(Some(tables::glyf::Glyph {
             number_of_contours,
             xmin,
             ymin,
             xmax,
             ymax,
-            glyph_data: match number_of_contours {
-                -1 => self.compound_glyph()?,
-                _ => self.simple_glyph(number_of_contours)?,
-            },
-        });
+            instructions,
+            glyph_data,
+        }));
     }
 
-    fn compound_glyph(&mut self) -> Option<tables::glyf::GlyphData> {
-        let arg_1_and_2_are_words = 1;
-        let args_are_xy_values = 2;
-        // let round_xy_to_grid = 4;
-        let we_have_a_scale = 8;
-        // obsolete should be zero = 16
-        let more_components = 32;
-        let we_have_an_x_and_y_scale = 64;
-        let we_have_a_two_by_two = 128;
-        let we_have_instructions = 256;
-        // let use_my_metrics = 512;
-        // let overlap_component = 1024;
-
-        let mut flag = more_components;
-        let mut end_pts_of_contours = Vec::new();
-        let mut x_coordinates = Vec::new();
-        let mut y_coordinates = Vec::new();
-        let mut flags = Vec::new();
-
-        while (flag & more_components) > 0 {
-            flag = self.file.get_uint16()?;
-            let mut component = tables::glyf::Component {
-                index: self.file.get_uint16()?,
+    fn composite_glyph(
+        file: &mut reader::FontReader,
+    ) -> Result<(tables::glyf::GlyphData, Vec<u8>)> {
+        use tables::glyf::*;
+
+        let mut flag = MORE_COMPONENTS;
+        let mut have_instructions = false;
+        let mut components = Vec::new();
+
+        while (flag & MORE_COMPONENTS) > 0 {
+            flag = file.get_uint16()?;
+            have_instructions |= (flag & WE_HAVE_INSTRUCTIONS) > 0;
+
+            let mut component = Component {
+                flags: flag,
+                index: file.get_uint16()?,
                 matrix: (1., 0., 0., 1., 0., 0.),
                 destination_index: 0,
                 source_index: 0,
             };
-            let (argument_1, argument_2) = match (flag & arg_1_and_2_are_words) > 0 {
-                true => (self.file.get_int16()? as i32, self.file.get_int16()? as i32),
-                false => (self.file.get_uint8()? as i32, self.file.get_uint8()? as i32),
+
+            // offsets are signed, point numbers are not
+            let (argument_1, argument_2) = match (
+                (flag & ARG_1_AND_2_ARE_WORDS) > 0,
+                (flag & ARGS_ARE_XY_VALUES) > 0,
+            ) {
+                (true, true) => (file.get_int16()? as i32, file.get_int16()? as i32),
+                (true, false) => (file.get_uint16()? as i32, file.get_uint16()? as i32),
+                (false, true) => (
+                    file.get_uint8()? as i8 as i32,
+                    file.get_uint8()? as i8 as i32,
+                ),
+                (false, false) => (file.get_uint8()? as i32, file.get_uint8()? as i32),
             };
 
-            if (flag & args_are_xy_values) > 0 {
+            if (flag & ARGS_ARE_XY_VALUES) > 0 {
                 component.matrix.4 = argument_1 as f64;
                 component.matrix.5 = argument_2 as f64;
             } else {
@@ -164,178 +570,288 @@ impl TrueTypeFont {
                 component.source_index = argument_2;
             }
 
-            if (flag & we_have_a_scale) > 0 {
-                component.matrix.0 = self.file.get_f2dot14()? as f64;
+            if (flag & WE_HAVE_A_SCALE) > 0 {
+                component.matrix.0 = file.get_f2dot14()? as f64;
                 component.matrix.3 = component.matrix.0;
-            } else if (flag & we_have_an_x_and_y_scale) > 0 {
-                component.matrix.0 = self.file.get_f2dot14()? as f64;
-                component.matrix.3 = self.file.get_f2dot14()? as f64;
-            } else if (flag & we_have_a_two_by_two) > 0 {
-                component.matrix.0 = self.file.get_f2dot14()? as f64;
-                component.matrix.1 = self.file.get_f2dot14()? as f64;
-                component.matrix.2 = self.file.get_f2dot14()? as f64;
-                component.matrix.3 = self.file.get_f2dot14()? as f64;
+            } else if (flag & WE_HAVE_AN_X_AND_Y_SCALE) > 0 {
+                component.matrix.0 = file.get_f2dot14()? as f64;
+                component.matrix.3 = file.get_f2dot14()? as f64;
+            } else if (flag & WE_HAVE_A_TWO_BY_TWO) > 0 {
+                component.matrix.0 = file.get_f2dot14()? as f64;
+                component.matrix.1 = file.get_f2dot14()? as f64;
+                component.matrix.2 = file.get_f2dot14()? as f64;
+                component.matrix.3 = file.get_f2dot14()? as f64;
             }
 
-            let old = self.file.pos;
-            match self.glyph(component.index as u32) {
-                Some(glyph) => {
-                    let simple_glyph = glyph.glyph_data;
-                    let offset = x_coordinates.len();
-                    for i in 0..simple_glyph.end_pts_of_contours.len() {
-                        end_pts_of_contours
-                            .push(simple_glyph.end_pts_of_contours[i] + offset as u16);
-                    }
+            components.push(component);
+        }
 
-                    for i in 0..simple_glyph.x_coordinates.len() {
-                        let mut x = simple_glyph.x_coordinates[i];
-                        let mut y = simple_glyph.y_coordinates[i];
+        let mut instructions = Vec::new();
+        if have_instructions {
+            let instruction_length = file.get_uint16()?;
+            instructions.extend_from_slice(file.get_bytes(instruction_length as usize)?);
+        }
 
-                        x = (component.matrix.0 * x as f64
-                            + component.matrix.1 * y as f64
-                            + component.matrix.4) as i16;
+        return Ok((GlyphData::Composite(components), instructions));
+    }
 
-                        y = (component.matrix.2 * x as f64
-                            + component.matrix.3 * y as f64
-                            + component.matrix.5) as i16;
+    // Flattens a glyph into a single point list in font units, composites are put
+    // together from their components recursively. The nesting is capped by
+    // maxp.max_component_depth so a font referencing itself can't send us spinning.
+    pub fn resolve_glyph(&self, index: u32) -> Result<Option<tables::glyf::SimpleGlyph>> {
+        let max_depth = self.max_component_depth();
+        return self.resolve_component(index, 0, max_depth);
+    }
 
-                        x_coordinates.push(x);
-                        y_coordinates.push(y);
-                        flags.push(simple_glyph.flags[i]);
-                    }
-                }
+    // maxp can ask for any depth, real fonts hardly nest more than a couple of levels
+    pub(crate) fn max_component_depth(&self) -> u16 {
+        return self.maxp.max_component_depth.clamp(1, MAX_COMPONENT_DEPTH);
+    }
 
-                None => {
-                    self.file.seek(old);
-                    continue;
-                }
-            };
-            self.file.seek(old);
+    fn resolve_component(
+        &self,
+        index: u32,
+        depth: u16,
+        max_depth: u16,
+    ) -> Result<Option<tables::glyf::SimpleGlyph>> {
+        let glyph = match self.glyph(index)? {
+            Some(val) => val,
+            None => return Ok(None),
+        };
+
+        let components = match glyph.glyph_data {
+            tables::glyf::GlyphData::Simple(simple) => return Ok(Some(simple)),
+            tables::glyf::GlyphData::Composite(components) => components,
+        };
+
+        let glyph_offset = self.glyph_offset(index)? as usize;
+        if depth >= max_depth {
+            return Err(Error::Malformed {
+                table: "glyf".into(),
+                offset: glyph_offset,
+                reason: "composite glyph nested deeper than maxp allows",
+            });
         }
 
-        match (flag & we_have_instructions) > 0 {
-            true => {
-                let instruction_length = self.file.get_uint16()?;
-                let mut instructions = Vec::new();
-                for _ in 0..instruction_length {
-                    instructions.push(self.file.get_uint8()?);
+        let mut result = tables::glyf::SimpleGlyph {
+            end_pts_of_contours: Vec::new(),
+            flags: Vec::new(),
+            x_coordinates: Vec::new(),
+            y_coordinates: Vec::new(),
+        };
+
+        for component in components.iter() {
+            let simple =
+                match self.resolve_component(component.index as u32, depth + 1, max_depth)? {
+                    Some(val) => val,
+                    None => continue,
+                };
+
+            let points: Vec<(f64, f64)> = simple
+                .x_coordinates
+                .iter()
+                .zip(simple.y_coordinates.iter())
+                .map(|(&x, &y)| component.transform(x as f64, y as f64))
+                .collect();
+
+            let (dx, dy) = if component.args_are_xy_values() {
+                let (dx, dy) = (component.matrix.4, component.matrix.5);
+                match component.scaled_component_offset() {
+                    true => component.transform(dx, dy),
+                    false => (dx, dy),
                 }
+            } else {
+                // move the component so its source point lands on the destination point
+                let destination = component.destination_index as usize;
+                let source = component.source_index as usize;
+                match (
+                    result.x_coordinates.get(destination),
+                    result.y_coordinates.get(destination),
+                    points.get(source),
+                ) {
+                    (Some(&x), Some(&y), Some(&(sx, sy))) => (x as f64 - sx, y as f64 - sy),
+                    _ => {
+                        return Err(Error::Malformed {
+                            table: "glyf".into(),
+                            offset: glyph_offset,
+                            reason: "component anchor point out of range",
+                        })
+                    }
+                }
+            };
 
-                let instruction_length = Some(instruction_length);
-                let instructions = Some(instructions);
-
-                return Some(tables::glyf::GlyphData {
-                    end_pts_of_contours,
-                    instruction_length,
-                    instructions,
-                    flags,
-                    x_coordinates,
-                    y_coordinates,
+            // point numbers have to fit in the u16 contour ends
+            let base = result.x_coordinates.len();
+            if base + points.len() > u16::MAX as usize {
+                return Err(Error::Malformed {
+                    table: "glyf".into(),
+                    offset: glyph_offset,
+                    reason: "composite glyph with too many points",
                 });
             }
-            false => {
-                let instruction_length = None;
-                let instructions = None;
-
-                return Some(tables::glyf::GlyphData {
-                    end_pts_of_contours,
-                    instruction_length,
-                    instructions,
-                    flags,
-                    x_coordinates,
-                    y_coordinates,
-                });
+            for end in simple.end_pts_of_contours.iter() {
+                result.end_pts_of_contours.push(end + base as u16);
+            }
+
+            for (&(x, y), &flag) in points.iter().zip(simple.flags.iter()) {
+                result.x_coordinates.push((x + dx).round() as i16);
+                result.y_coordinates.push((y + dy).round() as i16);
+                result.flags.push(flag);
             }
         }
+
+        return Ok(Some(result));
     }
 
-    fn simple_glyph(&mut self, number_of_contours: i16) -> Option<tables::glyf::GlyphData> {
+    // Coordinates are stored as deltas from the previous point, the first one relative to
+    // (0, 0). A short delta is a byte whose sign comes from the "same" bit, a long one is a
+    // full int16 and is skipped altogether (a zero delta) when the "same" bit is set.
+    fn glyph_coordinates(
+        file: &mut reader::FontReader,
+        flags: &[u8],
+        is_byte: u8,
+        is_same_or_positive: u8,
+    ) -> Result<Vec<i16>> {
+        let mut coordinates = Vec::with_capacity(flags.len());
+        let mut value: i16 = 0;
+
+        for &flag in flags {
+            let delta = if (flag & is_byte) > 0 {
+                let byte = file.get_uint8()? as i16;
+                match (flag & is_same_or_positive) > 0 {
+                    true => byte,
+                    false => -byte,
+                }
+            } else if (flag & is_same_or_positive) > 0 {
+                0
+            } else {
+                file.get_int16()?
+            };
+
+            value = value.wrapping_add(delta);
+            coordinates.push(value);
+        }
+
+        return Ok(coordinates);
+    }
+
+    fn simple_glyph(
+        file: &mut reader::FontReader,
+        number_of_contours: i16,
+    ) -> Result<Option<(tables::glyf::GlyphData, Vec<u8>)>> {
         // let on_curve = 1;
         let x_is_byte = 2;
         let y_is_byte = 4;
         let repeat = 8;
-        let x_delta = 16;
-        let y_delta = 32;
+        let x_is_same_or_positive = 16;
+        let y_is_same_or_positive = 32;
 
         let mut end_pts_of_contours = Vec::new();
 
         for _ in 0..number_of_contours {
-            end_pts_of_contours.push(self.file.get_uint16()?);
+            end_pts_of_contours.push(file.get_uint16()?);
         }
 
-        let instruction_length = self.file.get_uint16()?;
-        let mut instructions = Vec::new();
-        for _ in 0..instruction_length {
-            instructions.push(self.file.get_uint8()?);
-        }
+        let instruction_length = file.get_uint16()?;
+        let instructions = file.get_bytes(instruction_length as usize)?.to_vec();
 
-        if number_of_contours == 0 {
-            return None;
-        }
-
-        let numpoints = end_pts_of_contours.len() + 1;
+        // the contours are numbered by their last point, so that one tells the point count
+        let numpoints = match end_pts_of_contours.last() {
+            Some(&last) => last as usize + 1,
+            None => return Ok(None),
+        };
 
-        let mut flags = Vec::new();
-        let mut i = 0;
+        let mut flags = Vec::with_capacity(numpoints);
 
-        while i < numpoints {
-            let flag = self.file.get_uint8()?;
+        while flags.len() < numpoints {
+            let flag = file.get_uint8()?;
             flags.push(flag);
             if (flag & repeat) > 0 {
-                let repeat_count = self.file.get_uint8()?;
-                if repeat_count < 1 {
-                    return None;
-                }
-                i += repeat_count as usize;
+                let repeat_count = file.get_uint8()?;
                 for _ in 0..repeat_count {
                     flags.push(flag);
                 }
             }
         }
 
-        let mut x_coordinates = Vec::new();
-        let mut y_coordinates = Vec::new();
-
-        for i in 0..numpoints {
-            let flag = flags[i];
-            let mut value = 0;
-            if (flag & x_is_byte) > 0 {
-                if (flag & x_delta) > 0 {
-                    value += self.file.get_uint8()? as i16;
-                } else {
-                    value -= self.file.get_uint8()? as i16;
-                }
-            } else if (!flag & x_delta) > 0 {
-                value += self.file.get_int16()?;
-            }
-            x_coordinates.push(value);
-        }
-
-        for i in 0..numpoints {
-            let flag = flags[i];
-            let mut value = 0;
-            if (flag & y_is_byte) > 0 {
-                if (flag & y_delta) > 0 {
-                    value += self.file.get_uint8()? as i16;
-                } else {
-                    value -= self.file.get_uint8()? as i16;
-                }
-            } else if (!flag & y_delta) > 0 {
-                value += self.file.get_int16()?;
-            }
-            y_coordinates.push(value);
-        }
+        // a broken repeat count can run past the last point, those flags belong to nobody
+        flags.truncate(numpoints);
 
-        let instruction_length = Some(instruction_length);
-        let instructions = Some(instructions);
+        let x_coordinates =
+            TrueTypeFont::glyph_coordinates(file, &flags, x_is_byte, x_is_same_or_positive)?;
+        let y_coordinates =
+            TrueTypeFont::glyph_coordinates(file, &flags, y_is_byte, y_is_same_or_positive)?;
 
-        return Some(tables::glyf::GlyphData {
+        let glyph_data = tables::glyf::GlyphData::Simple(tables::glyf::SimpleGlyph {
             end_pts_of_contours,
-            instruction_length,
-            instructions,
             flags,
             x_coordinates,
             y_coordinates,
         });
+
+        return Ok(Some((glyph_data, instructions)));
+    }
+
+    // Feeds the contours of a glyph to the builder and hands back its bounding box, empty
+    // glyphs like the space don't call the builder at all
+    pub fn outline_glyph(
+        &self,
+        glyph: GlyphId,
+        builder: &mut dyn outline::OutlineBuilder,
+    ) -> Result<Option<outline::Rect>> {
+        let header = match self.glyph(glyph.0 as u32)? {
+            Some(val) => val,
+            None => return Ok(None),
+        };
+
+        if let Some(simple) = self.resolve_glyph(glyph.0 as u32)? {
+            outline::outline(&simple, builder);
+        }
+
+        return Ok(Some(outline::Rect {
+            x_min: header.xmin,
+            y_min: header.ymin,
+            x_max: header.xmax,
+            y_max: header.ymax,
+        }));
+    }
+
+    // Renders the glyph at pixel_size pixels per em, the offset moves it by a fraction of a
+    // pixel (y going up) for subpixel positioning. Glyphs without contours give None.
+    pub fn rasterize_glyph(
+        &self,
+        glyph: GlyphId,
+        pixel_size: f32,
+        offset: (f32, f32),
+    ) -> Result<Option<raster::Bitmap>> {
+        let header = match self.glyph(glyph.0 as u32)? {
+            Some(val) => val,
+            None => return Ok(None),
+        };
+
+        let scale = pixel_size / self.head.units_per_em.max(1) as f32;
+        let left = (header.xmin as f32 * scale + offset.0).floor() as i32;
+        let right = (header.xmax as f32 * scale + offset.0).ceil() as i32;
+        let top = (header.ymax as f32 * scale + offset.1).ceil() as i32;
+        let bottom = (header.ymin as f32 * scale + offset.1).floor() as i32;
+
+        let width = (right - left).max(0) as u32;
+        let height = (top - bottom).max(0) as u32;
+        let origin = (offset.0 - left as f32, top as f32 - offset.1);
+        let mut rasterizer = raster::Rasterizer::new(width, height, scale, origin);
+        self.outline_glyph(glyph, &mut rasterizer)?;
+
+        return Ok(Some(raster::Bitmap {
+            width,
+            height,
+            left,
+            top,
+            coverage: rasterizer.coverage(),
+        }));
+    }
+
+    // Sets up the hinting programs for the given pixels per em, see hinting::Hinter
+    pub fn hinter(&self, ppem: u16) -> Result<hinting::Hinter<'_, 'a>> {
+        return hinting::Hinter::new(self, ppem);
     }
 }
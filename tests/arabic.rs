@@ -0,0 +1,233 @@
+mod common;
+
+use lipi::layout::FeatureSetting;
+use lipi::{shape, ShapingOptions, TrueTypeFont};
+
+use common::Item::*;
+use common::{coverage, table, with_tables, words};
+
+// Letters the tests use and their glyphs, the forms of a letter follow it: isolated + 1,
+// final + 2, medial + 3 and initial + 4
+const BEH: u16 = 10; // dual joining
+const ALEF: u16 = 20; // right joining
+const LAM: u16 = 30; // dual joining
+const HAMZA: u16 = 40; // non-joining
+const TATWEEL: u16 = 60; // join causing
+const FATHA: u16 = 50; // 50 to 54 are marks
+const SHADDA: u16 = 51;
+const HAMZA_ABOVE: u16 = 52;
+const KASRA: u16 = 53;
+const MADDA: u16 = 54;
+const SPACE: u16 = 3;
+const ISOL: u16 = 1;
+const FINA: u16 = 2;
+const MEDI: u16 = 3;
+const INIT: u16 = 4;
+const LAM_ALEF: u16 = 70; // rlig of lam and final alef, calt turns it into 71
+const FATHA_MSET: u16 = 55;
+
+// Format 12 subtable for the Windows UCS-4 encoding
+fn cmap_data() -> Vec<u8> {
+    let mapping: &[(u32, u16)] = &[
+        (0x20, SPACE),
+        (0x621, HAMZA),
+        (0x627, ALEF),
+        (0x628, BEH),
+        (0x640, TATWEEL),
+        (0x644, LAM),
+        (0x64E, FATHA),
+        (0x650, KASRA),
+        (0x651, SHADDA),
+        (0x653, MADDA),
+        (0x654, HAMZA_ABOVE),
+    ];
+
+    let mut data = words(&[0, 1, 3, 10, 0, 12]);
+    let length = 16 + 12 * mapping.len() as u32;
+    data.extend(words(&[12, 0]));
+    for value in [length, 0, mapping.len() as u32] {
+        data.extend(value.to_be_bytes());
+    }
+    for &(c, glyph) in mapping {
+        for value in [c, c, glyph as u32] {
+            data.extend(value.to_be_bytes());
+        }
+    }
+    data
+}
+
+// Every feature has a lookup of its own, the Arabic script lists them all
+fn gsub_data() -> Vec<u8> {
+    let single = |glyphs: &[u16], delta: u16| {
+        let glyphs: Vec<i32> = glyphs.iter().map(|&glyph| glyph as i32).collect();
+        let subtable = table(&[W(1), O(0), W(delta as i32)], &[coverage(&glyphs)]);
+        table(&[W(1), W(0), W(1), O(0)], &[subtable])
+    };
+    let joining = [BEH, ALEF, LAM, TATWEEL];
+    let lam_alef = table(
+        &[W(1), O(0), W(2), O(1), O(1)],
+        &[
+            coverage(&[(LAM + MEDI) as i32, (LAM + INIT) as i32]),
+            table(
+                &[W(1), O(0)],
+                &[words(&[LAM_ALEF as i32, 2, (ALEF + FINA) as i32])],
+            ),
+        ],
+    );
+
+    let lookups = [
+        single(&joining, ISOL),
+        single(&joining, FINA),
+        single(&joining, MEDI),
+        single(&joining, INIT),
+        table(&[W(4), W(0), W(1), O(0)], &[lam_alef]),
+        single(&[LAM_ALEF], 1),
+        single(&[FATHA], FATHA_MSET - FATHA),
+    ];
+    let tags = ["isol", "fina", "medi", "init", "rlig", "calt", "mset"];
+
+    let mut items = vec![W(tags.len() as i32)];
+    let mut features = Vec::new();
+    for (i, tag) in tags.iter().enumerate() {
+        let tag = tag.as_bytes();
+        items.push(W(u16::from_be_bytes([tag[0], tag[1]]) as i32));
+        items.push(W(u16::from_be_bytes([tag[2], tag[3]]) as i32));
+        items.push(O(i));
+        features.push(words(&[0, 1, i as i32]));
+    }
+    let feature_list = table(&items, &features);
+
+    let mut lang_sys = vec![0, 0xFFFF, tags.len() as i32];
+    lang_sys.extend(0..tags.len() as i32);
+    let script_list = table(
+        &[W(1), W(0x6172), W(0x6162), O(0)],
+        &[table(&[O(0), W(0)], &[words(&lang_sys)])],
+    );
+
+    let mut items = vec![W(lookups.len() as i32)];
+    items.extend((0..lookups.len()).map(O));
+    let lookup_list = table(&items, &lookups);
+
+    table(
+        &[W(1), W(0), O(0), O(1), O(2)],
+        &[script_list, feature_list, lookup_list],
+    )
+}
+
+fn font_data() -> Vec<u8> {
+    with_tables(&[("cmap", cmap_data()), ("GSUB", gsub_data())])
+}
+
+// Glyphs and clusters in visual order, the last character first
+fn shaped(font: &TrueTypeFont, text: &str, options: &ShapingOptions) -> Vec<(u16, u32)> {
+    shape(font, text, options)
+        .iter()
+        .map(|glyph| (glyph.glyph.0, glyph.cluster))
+        .collect()
+}
+
+#[test]
+fn positional_forms() {
+    let data = font_data();
+    let font = TrueTypeFont::from_bytes(&data).unwrap();
+    let options = ShapingOptions::default();
+
+    assert_eq!(
+        shaped(&font, "ببب", &options),
+        [(BEH + FINA, 4), (BEH + MEDI, 2), (BEH + INIT, 0)]
+    );
+    assert_eq!(shaped(&font, "ب", &options), [(BEH + ISOL, 0)]);
+
+    // alef doesn't join the letter after it, hamza joins neither side
+    assert_eq!(
+        shaped(&font, "ابب", &options),
+        [(BEH + FINA, 4), (BEH + INIT, 2), (ALEF + ISOL, 0)]
+    );
+    assert_eq!(
+        shaped(&font, "بءب", &options),
+        [(BEH + ISOL, 4), (HAMZA, 2), (BEH + ISOL, 0)]
+    );
+
+    // marks are looked past, ZWNJ breaks the joining and the tatweel joins both sides
+    assert_eq!(
+        shaped(&font, "بَب", &options),
+        [(BEH + FINA, 4), (FATHA_MSET, 0), (BEH + INIT, 0)]
+    );
+    assert_eq!(
+        shaped(&font, "ب\u{200C}ب", &options),
+        [(BEH + ISOL, 5), (SPACE, 2), (BEH + ISOL, 0)]
+    );
+    assert_eq!(
+        shaped(&font, "ـبـ", &options),
+        [(TATWEEL + FINA, 4), (BEH + MEDI, 2), (TATWEEL + INIT, 0)]
+    );
+}
+
+#[test]
+fn ligatures_after_the_forms() {
+    let data = font_data();
+    let font = TrueTypeFont::from_bytes(&data).unwrap();
+    let options = ShapingOptions::default();
+
+    // rlig sees the forms and calt the ligature
+    assert_eq!(shaped(&font, "لا", &options), [(LAM_ALEF + 1, 0)]);
+    assert_eq!(
+        shaped(&font, "بلا", &options),
+        [(LAM_ALEF + 1, 2), (BEH + INIT, 0)]
+    );
+    assert_eq!(
+        shaped(&font, "لب", &options),
+        [(BEH + FINA, 2), (LAM + INIT, 0)]
+    );
+
+    // ZWJ keeps the letters joined without the ligature
+    assert_eq!(
+        shaped(&font, "ل\u{200D}ا", &options),
+        [(ALEF + FINA, 5), (SPACE, 0), (LAM + INIT, 0)]
+    );
+
+    let features = [FeatureSetting {
+        value: 0,
+        ..FeatureSetting::new("rlig")
+    }];
+    let options = ShapingOptions {
+        features: &features,
+        ..Default::default()
+    };
+    assert_eq!(
+        shaped(&font, "لا", &options),
+        [(ALEF + FINA, 2), (LAM + INIT, 0)]
+    );
+}
+
+#[test]
+fn mark_order() {
+    let data = font_data();
+    let font = TrueTypeFont::from_bytes(&data).unwrap();
+    let options = ShapingOptions::default();
+    let marks = |text: &str| -> Vec<u16> {
+        shaped(&font, text, &options)
+            .iter()
+            .rev()
+            .skip(1)
+            .map(|&(glyph, _)| glyph)
+            .collect()
+    };
+
+    // shadda goes before the other vowel marks
+    assert_eq!(marks("ب\u{650}\u{651}"), [SHADDA, KASRA]);
+    assert_eq!(marks("ب\u{651}\u{650}"), [SHADDA, KASRA]);
+
+    // hamza above comes right after the letter, unless another mark of its class is first
+    assert_eq!(marks("ب\u{64E}\u{654}"), [HAMZA_ABOVE, FATHA_MSET]);
+    assert_eq!(marks("ب\u{653}\u{654}"), [MADDA, HAMZA_ABOVE]);
+    assert_eq!(
+        marks("ب\u{654}\u{651}\u{650}"),
+        [HAMZA_ABOVE, SHADDA, KASRA]
+    );
+
+    // the marks stay in the cluster of their letter
+    assert!(shaped(&font, "ب\u{650}\u{651}", &options)
+        .iter()
+        .all(|&(_, cluster)| cluster == 0));
+}
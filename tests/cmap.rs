@@ -0,0 +1,362 @@
+mod common;
+
+use lipi::tables::cmap::{self, CmapFormatTable};
+use lipi::FontReader;
+
+use common::offset_table;
+
+fn words(values: &[u32]) -> Vec<u8> {
+    values
+        .iter()
+        .flat_map(|&value| (value as u16).to_be_bytes())
+        .collect()
+}
+
+fn longs(values: &[u32]) -> Vec<u8> {
+    values
+        .iter()
+        .flat_map(|&value| value.to_be_bytes())
+        .collect()
+}
+
+fn parse(data: &[u8]) -> cmap::Cmap {
+    let table = offset_table(data);
+    cmap::read(&mut FontReader::new(data), table).unwrap()
+}
+
+// A cmap with an encoding record per (platform, encoding, subtable)
+fn read(encodings: &[(u32, u32, Vec<u8>)]) -> cmap::Cmap {
+    let mut data = words(&[0, encodings.len() as u32]);
+    let mut offset = 4 + 8 * encodings.len() as u32;
+    for (platform_id, platform_specific_id, subtable) in encodings {
+        data.extend(words(&[*platform_id, *platform_specific_id]));
+        data.extend(longs(&[offset]));
+        offset += subtable.len() as u32;
+    }
+    for (_, _, subtable) in encodings {
+        data.extend(subtable);
+    }
+    parse(&data)
+}
+
+// The only subtable of a cmap, under the Windows BMP encoding
+fn subtable(data: Vec<u8>) -> CmapFormatTable {
+    read(&[(3, 1, data)]).format_tables.remove(0)
+}
+
+fn lookup(table: &CmapFormatTable, codes: &[u32]) -> Vec<Option<u16>> {
+    codes
+        .iter()
+        .map(|&code| table.glyph_index(code).map(|glyph| glyph.0))
+        .collect()
+}
+
+fn format0() -> Vec<u8> {
+    let mut glyphs = [0; 256];
+    glyphs[0x41] = 5;
+    glyphs[0xFF] = 6;
+    let mut data = words(&[0, 262, 0]);
+    data.extend(glyphs);
+    data
+}
+
+// Single byte codes A to C go through subHeader 0, lead byte 0x81 and the trail bytes
+// 0x40 and 0x41 through subHeader 1 which adds 100 to the glyphs
+fn format2() -> Vec<u8> {
+    let mut keys = [0; 256];
+    keys[0x81] = 8;
+    let mut data = words(&[2, 6 + 512 + 16 + 10, 0]);
+    data.extend(words(&keys));
+    data.extend(words(&[0x41, 3, 0, 10, 0x40, 2, 100, 8]));
+    data.extend(words(&[1, 2, 3, 7, 0]));
+    data
+}
+
+// Spaces and A to C, the latter through the glyph index array with B left out, then the
+// 0xFFFF segment every format 4 subtable has to end with
+fn format4() -> Vec<u8> {
+    let mut data = words(&[4, 46, 0, 6, 4, 1, 2]);
+    data.extend(words(&[0x22, 0x43, 0xFFFF, 0]));
+    data.extend(words(&[0x20, 0x41, 0xFFFF]));
+    data.extend(words(&[10u32.wrapping_sub(0x20), 1, 1]));
+    data.extend(words(&[0, 4, 0]));
+    data.extend(words(&[20, 0, 22]));
+    data
+}
+
+#[test]
+fn format0_bytes() {
+    let table = subtable(format0());
+    assert_eq!(
+        lookup(&table, &[0x41, 0xFF, 0x42, 0, 0x100]),
+        [Some(5), Some(6), None, None, None]
+    );
+}
+
+#[test]
+fn format2_single_bytes() {
+    let table = subtable(format2());
+    assert_eq!(
+        lookup(&table, &[0x41, 0x43, 0x40, 0x44]),
+        [Some(1), Some(3), None, None]
+    );
+}
+
+#[test]
+fn format4_segments() {
+    let table = subtable(format4());
+    match &table {
+        CmapFormatTable::Format4(format4) => {
+            let ends: Vec<u16> = format4.segments.iter().map(|s| s.end_code).collect();
+            assert_eq!(ends, [0x22, 0x43, 0xFFFF]);
+            assert_eq!(format4.glyph_index_array, [20, 0, 22]);
+        }
+        _ => panic!("expected a format 4 subtable"),
+    }
+
+    // the delta wraps around
+    assert_eq!(
+        lookup(&table, &[0x20, 0x21, 0x22]),
+        [Some(10), Some(11), Some(12)]
+    );
+    // the delta is added to what the glyph index array holds, unless that is 0
+    assert_eq!(
+        lookup(&table, &[0x41, 0x42, 0x43]),
+        [Some(21), None, Some(23)]
+    );
+    // between the segments, past the last real one and in the sentinel
+    assert_eq!(
+        lookup(&table, &[0x1F, 0x23, 0x40, 0x44, 0xFFFE, 0xFFFF, 0x10000]),
+        [None; 7]
+    );
+}
+
+#[test]
+fn format6_range() {
+    let table = subtable(words(&[6, 16, 0, 0x61, 3, 30, 0, 32]));
+    assert_eq!(
+        lookup(&table, &[0x61, 0x62, 0x63, 0x60, 0x64]),
+        [Some(30), None, Some(32), None, None]
+    );
+}
+
+#[test]
+fn format8_groups() {
+    // the high word 1 starts 32-bit codes, bits go most significant first
+    let mut is_32 = [0; 8192];
+    is_32[0] = 0x40;
+    let mut data = words(&[8, 0]);
+    data.extend(longs(&[12 + 8192 + 16, 0]));
+    data.extend(is_32);
+    data.extend(longs(&[1, 0x10000, 0x10002, 40]));
+
+    let table = subtable(data);
+    assert_eq!(
+        lookup(&table, &[0x10000, 0x10002, 0xFFFF, 0x10003]),
+        [Some(40), Some(42), None, None]
+    );
+}
+
+#[test]
+fn format10_range() {
+    let mut data = words(&[10, 0]);
+    data.extend(longs(&[26, 0, 0x1F600, 3]));
+    data.extend(words(&[50, 0, 52]));
+
+    let table = subtable(data);
+    assert_eq!(
+        lookup(&table, &[0x1F600, 0x1F601, 0x1F602, 0x1F5FF, 0x1F603]),
+        [Some(50), None, Some(52), None, None]
+    );
+}
+
+#[test]
+fn format12_groups() {
+    let mut data = words(&[12, 0]);
+    data.extend(longs(&[40, 0, 2]));
+    data.extend(longs(&[0x41, 0x43, 60, 0x1F600, 0x1F601, 70]));
+
+    let table = subtable(data);
+    assert_eq!(
+        lookup(&table, &[0x41, 0x43, 0x1F600, 0x1F601]),
+        [Some(60), Some(62), Some(70), Some(71)]
+    );
+    assert_eq!(lookup(&table, &[0x40, 0x44, 0x1F602, 0x10FFFF]), [None; 4]);
+}
+
+#[test]
+fn shared_subtables() {
+    // two encodings pointing at the same subtable share the parsed table
+    let mut data = words(&[0, 2, 0, 3]);
+    data.extend(longs(&[20]));
+    data.extend(words(&[3, 1]));
+    data.extend(longs(&[20]));
+    data.extend(format0());
+
+    let cmap = parse(&data);
+    assert_eq!(cmap.format_tables.len(), 1);
+    assert_eq!(cmap.glyph_index('A').map(|glyph| glyph.0), Some(5));
+}
+
+// Format 6 subtable mapping A alone
+fn only_a(glyph: u32) -> Vec<u8> {
+    words(&[6, 12, 0, 0x41, 1, glyph])
+}
+
+#[test]
+fn broken_subtables_are_left_out() {
+    // an unknown format and a format 4 cut short, next to a good subtable
+    let cmap = read(&[
+        (3, 10, words(&[9, 0])),
+        (0, 4, words(&[4, 100, 0, 8])),
+        (3, 1, only_a(7)),
+    ]);
+    assert_eq!(cmap.encodings.len(), 1);
+    assert_eq!(cmap.format_tables.len(), 1);
+    assert_eq!(cmap.glyph_index('A').map(|glyph| glyph.0), Some(7));
+}
+
+#[test]
+fn preferred_subtable() {
+    // each encoding maps A to a glyph of its own
+    let mut encodings = vec![(0, 3, 1), (3, 1, 2), (0, 4, 3), (3, 10, 4), (1, 0, 5)];
+
+    // Unicode full repertoire > Windows UCS-4 > Windows BMP > Unicode BMP > Mac Roman
+    for &(platform_id, platform_specific_id) in &[(0, 4), (3, 10), (3, 1), (0, 3), (1, 0)] {
+        let cmap = read(
+            &encodings
+                .iter()
+                .map(|&(platform, encoding, glyph)| (platform, encoding, only_a(glyph)))
+                .collect::<Vec<_>>(),
+        );
+        let index = encodings
+            .iter()
+            .position(|&(platform, encoding, _)| {
+                (platform, encoding) == (platform_id, platform_specific_id)
+            })
+            .unwrap();
+
+        let preferred = cmap.preferred_encoding().unwrap();
+        assert_eq!(preferred.platform_specific_id as u32, platform_specific_id);
+        assert_eq!(
+            cmap.glyph_index('A').map(|glyph| glyph.0 as u32),
+            Some(encodings[index].2)
+        );
+        encodings.remove(index);
+    }
+}
+
+#[test]
+fn no_usable_subtable() {
+    // Mac Japanese and the variation sequences can't be looked up with a char
+    let cmap = read(&[(1, 1, only_a(1)), (0, 5, words(&[14, 0, 10, 0, 0]))]);
+    assert!(cmap.preferred_encoding().is_none());
+    assert!(cmap.preferred_table().is_none());
+    assert_eq!(cmap.glyph_index('A'), None);
+
+    // the subtable is still there for whoever asks for it by encoding
+    let table = cmap.table(cmap::CmapPlatform::Macintosh, 1).unwrap();
+    assert_eq!(table.glyph_index(0x41).map(|glyph| glyph.0), Some(1));
+}
+
+#[test]
+fn format2_two_bytes() {
+    let table = match subtable(format2()) {
+        CmapFormatTable::Format2(table) => table,
+        _ => panic!("expected a format 2 subtable"),
+    };
+    assert_eq!(table.sub_headers.len(), 2);
+    assert!(table.is_lead_byte(0x81));
+    assert!(!table.is_lead_byte(0x41));
+
+    // lead byte 0x81 with trail bytes from 0x40, a zero in the array stays unmapped
+    let glyph = |code| table.glyph_index(code).map(|glyph| glyph.0);
+    assert_eq!(glyph(0x8140), Some(107));
+    assert_eq!(glyph(0x8141), None);
+    // trail bytes outside firstCode..firstCode + entryCount
+    assert_eq!(glyph(0x813F), None);
+    assert_eq!(glyph(0x8142), None);
+    // a lead byte on its own, and a two byte code whose first byte is no lead byte
+    assert_eq!(glyph(0x81), None);
+    assert_eq!(glyph(0x4140), None);
+
+    // mixed byte sequences, a lead byte at the very end is a broken character
+    let glyphs: Vec<Option<u16>> = table
+        .glyph_indices_for_bytes(&[0x41, 0x81, 0x40, 0x43, 0x81, 0x42, 0x44, 0x81])
+        .iter()
+        .map(|glyph| glyph.map(|glyph| glyph.0))
+        .collect();
+    assert_eq!(glyphs, [Some(1), Some(107), Some(3), None, None, None]);
+    assert_eq!(table.glyph_index_for_bytes(&[0x81, 0x40, 0x41]).1, 2);
+    assert_eq!(table.glyph_index_for_bytes(&[0x41, 0x81]).1, 1);
+}
+
+fn u24(value: u32) -> Vec<u8> {
+    value.to_be_bytes()[1..].to_vec()
+}
+
+#[test]
+fn format13_many_to_one() {
+    let mut data = words(&[13, 0]);
+    data.extend(longs(&[40, 0, 2]));
+    data.extend(longs(&[0x0, 0x7F, 1, 0x80, 0x10FFFF, 2]));
+
+    // every code of a group goes to the very same glyph
+    let table = subtable(data.clone());
+    assert_eq!(
+        lookup(&table, &[0x0, 0x41, 0x7F, 0x80, 0x1F600, 0x10FFFF]),
+        [Some(1), Some(1), Some(1), Some(2), Some(2), Some(2)]
+    );
+    assert_eq!(lookup(&table, &[0x110000]), [None]);
+
+    // last resort fonts are used when nothing better is around
+    let cmap = read(&[(0, 6, data)]);
+    assert_eq!(cmap.glyph_index('\u{3042}').map(|glyph| glyph.0), Some(2));
+}
+
+// U+2603 with VS16 is a default sequence and U+845B with VS17 has a glyph of its own
+fn format14() -> Vec<u8> {
+    let mut data = words(&[14]);
+    data.extend(longs(&[49, 2]));
+    data.extend(u24(0xFE0F));
+    data.extend(longs(&[32, 0]));
+    data.extend(u24(0xE0100));
+    data.extend(longs(&[0, 40]));
+
+    data.extend(longs(&[1]));
+    data.extend(u24(0x2603));
+    data.push(0);
+    data.extend(longs(&[1]));
+    data.extend(u24(0x845B));
+    data.extend(words(&[90]));
+    data
+}
+
+#[test]
+fn variation_sequences() {
+    let mut base = words(&[12, 0]);
+    base.extend(longs(&[40, 0, 2]));
+    base.extend(longs(&[0x2603, 0x2603, 81, 0x845B, 0x845B, 80]));
+
+    let cmap = read(&[(0, 5, format14()), (3, 10, base)]);
+    let glyph = |base, selector| {
+        cmap.glyph_index_for_variation(base, selector)
+            .map(|glyph| glyph.0)
+    };
+
+    // the default sequence goes to the glyph the base character maps to anyway
+    assert_eq!(glyph('\u{2603}', '\u{FE0F}'), Some(81));
+    assert_eq!(
+        cmap.variation_table().unwrap().variation(0x2603, 0xFE0F),
+        Some(cmap::CmapVariation::Default)
+    );
+    assert_eq!(glyph('\u{845B}', '\u{E0100}'), Some(90));
+
+    // a selector the font doesn't know, or one it knows for other characters only
+    assert_eq!(glyph('\u{845B}', '\u{FE0E}'), None);
+    assert_eq!(glyph('\u{2603}', '\u{E0100}'), None);
+    assert_eq!(glyph('\u{845B}', '\u{FE0F}'), None);
+
+    // the variation subtable plays no part in regular lookups
+    assert_eq!(cmap.glyph_index('\u{845B}').map(|glyph| glyph.0), Some(80));
+}
@@ -0,0 +1,260 @@
+// Fixtures shared by the integration tests, not every test uses all of them
+#![allow(dead_code)]
+
+use lipi::tables::offset::OffsetTable;
+use lipi::OutlineBuilder;
+
+pub fn font_data() -> Vec<u8> {
+    std::fs::read(concat!(env!("CARGO_MANIFEST_DIR"), "/FontAwesome.ttf")).unwrap()
+}
+
+// Words, 32 bit values, tags and offsets to the tables that follow the header in the
+// order given
+pub enum Item {
+    W(i32),
+    L(u32),
+    T(&'static str),
+    O(usize),
+}
+use Item::*;
+
+pub fn table(items: &[Item], children: &[Vec<u8>]) -> Vec<u8> {
+    let header: usize = items
+        .iter()
+        .map(|item| match item {
+            W(_) | O(_) => 2,
+            L(_) | T(_) => 4,
+        })
+        .sum();
+    let mut offsets = vec![header];
+    for child in children {
+        offsets.push(offsets.last().unwrap() + child.len());
+    }
+
+    let mut data = Vec::new();
+    for item in items {
+        match *item {
+            W(value) => data.extend((value as u16).to_be_bytes()),
+            L(value) => data.extend(value.to_be_bytes()),
+            T(tag) => data.extend(tag.as_bytes()),
+            O(child) => data.extend((offsets[child] as u16).to_be_bytes()),
+        }
+    }
+    for child in children {
+        data.extend(child);
+    }
+    data
+}
+
+pub fn words(values: &[i32]) -> Vec<u8> {
+    table(
+        &values.iter().map(|&value| W(value)).collect::<Vec<_>>(),
+        &[],
+    )
+}
+
+// Format 1 coverage of the glyphs given
+pub fn coverage(glyphs: &[i32]) -> Vec<u8> {
+    let mut values = vec![1, glyphs.len() as i32];
+    values.extend(glyphs);
+    words(&values)
+}
+
+pub fn lookup(lookup_type: i32, lookup_flag: i32, subtable: Vec<u8>) -> Vec<u8> {
+    table(&[W(lookup_type), W(lookup_flag), W(1), O(0)], &[subtable])
+}
+
+// GSUB or GPOS with a DFLT script running every feature, which run their lookups
+pub fn layout_data(features: &[(&str, &[i32])], lookups: &[Vec<u8>]) -> Vec<u8> {
+    let count = features.len() as i32;
+    let mut lang_sys = vec![W(0), W(0xFFFF), W(count)];
+    lang_sys.extend((0..count).map(W));
+    let script = table(&[O(0), W(0)], &[table(&lang_sys, &[])]);
+    let script_list = table(&[W(1), T("DFLT"), O(0)], &[script]);
+
+    let mut records = vec![W(count)];
+    let mut children = Vec::new();
+    for (i, (tag, lookups)) in features.iter().enumerate() {
+        let tag = tag.as_bytes();
+        records.push(W(((tag[0] as i32) << 8) | tag[1] as i32));
+        records.push(W(((tag[2] as i32) << 8) | tag[3] as i32));
+        records.push(O(i));
+        let mut feature = vec![0, lookups.len() as i32];
+        feature.extend(lookups.iter());
+        children.push(words(&feature));
+    }
+    let feature_list = table(&records, &children);
+
+    let mut offsets = vec![W(lookups.len() as i32)];
+    offsets.extend((0..lookups.len()).map(O));
+    let lookup_list = table(&offsets, lookups);
+
+    table(
+        &[W(1), W(0), O(0), O(1), O(2)],
+        &[script_list, feature_list, lookup_list],
+    )
+}
+
+// Record for a table read on its own from the start of the data
+pub fn offset_table(data: &[u8]) -> OffsetTable {
+    OffsetTable {
+        checksum: 0,
+        offset: 0,
+        length: data.len() as u32,
+    }
+}
+
+pub fn u32_at(font: &[u8], at: usize) -> u32 {
+    u32::from_be_bytes([font[at], font[at + 1], font[at + 2], font[at + 3]])
+}
+
+pub fn checksum(table: &[u8]) -> u32 {
+    table
+        .chunks(4)
+        .map(|chunk| {
+            let mut word = [0; 4];
+            word[..chunk.len()].copy_from_slice(chunk);
+            u32::from_be_bytes(word)
+        })
+        .fold(0u32, |sum, word| sum.wrapping_add(word))
+}
+
+// Offset of the directory record of a table and the offset of the table itself, the last
+// record of the tag like the font reads it
+pub fn record(font: &[u8], tag: &str) -> (usize, usize) {
+    let num_tables = u16::from_be_bytes([font[4], font[5]]) as usize;
+    let record = (0..num_tables)
+        .map(|i| 12 + 16 * i)
+        .rfind(|&record| &font[record..record + 4] == tag.as_bytes())
+        .unwrap();
+    (record, u32_at(font, record + 8) as usize)
+}
+
+// Overwrites bytes inside a table and fixes up its checksum so only the change is seen
+pub fn patch(font: &mut [u8], tag: &str, at: usize, bytes: &[u8]) {
+    let (record, offset) = record(font, tag);
+    font[offset + at..offset + at + bytes.len()].copy_from_slice(bytes);
+    let length = u32_at(font, record + 12) as usize;
+    let sum = checksum(&font[offset..offset + length]);
+    font[record + 4..record + 8].copy_from_slice(&sum.to_be_bytes());
+}
+
+// FontAwesome with the tables added after the others, the last of a tag is the one read
+pub fn with_tables(tables: &[(&str, Vec<u8>)]) -> Vec<u8> {
+    let font = font_data();
+    let num_tables = u16::from_be_bytes([font[4], font[5]]) as usize;
+    let directory_end = 12 + 16 * num_tables;
+    let moved = 16 * tables.len();
+
+    let mut data = font[..4].to_vec();
+    data.extend(((num_tables + tables.len()) as u16).to_be_bytes());
+    data.extend(&font[6..12]);
+
+    // every table moves down by the records added
+    for record in font[12..directory_end].chunks(16) {
+        data.extend(&record[..8]);
+        data.extend((u32_at(record, 8) + moved as u32).to_be_bytes());
+        data.extend(&record[12..]);
+    }
+    let mut offset = (font.len() + 3) & !3;
+    for (tag, table) in tables {
+        data.extend(tag.as_bytes());
+        data.extend(checksum(table).to_be_bytes());
+        data.extend(((offset + moved) as u32).to_be_bytes());
+        data.extend((table.len() as u32).to_be_bytes());
+        offset = (offset + table.len() + 3) & !3;
+    }
+
+    data.extend(&font[directory_end..]);
+    for (_, table) in tables {
+        data.resize((data.len() + 3) & !3, 0);
+        data.extend(table);
+    }
+    data.resize((data.len() + 3) & !3, 0);
+    data
+}
+
+pub fn with_table(tag: &str, table: &[u8]) -> Vec<u8> {
+    with_tables(&[(tag, table.to_vec())])
+}
+
+// Simple glyph of on curve points only
+pub fn simple_glyph(contours: &[&[(i32, i32)]], instructions: &[u8]) -> Vec<u8> {
+    let points: Vec<(i32, i32)> = contours
+        .iter()
+        .flat_map(|contour| contour.iter())
+        .copied()
+        .collect();
+    let (xs, ys): (Vec<i32>, Vec<i32>) = points.iter().copied().unzip();
+    let bounds = [
+        xs.iter().min(),
+        ys.iter().min(),
+        xs.iter().max(),
+        ys.iter().max(),
+    ];
+
+    let mut values = vec![contours.len() as i32];
+    values.extend(bounds.iter().map(|bound| *bound.unwrap_or(&0)));
+    let mut end = -1;
+    for contour in contours {
+        end += contour.len() as i32;
+        values.push(end);
+    }
+    values.push(instructions.len() as i32);
+
+    let mut data = words(&values);
+    data.extend(instructions);
+    data.extend(vec![1; points.len()]);
+    for coordinates in [xs, ys] {
+        let mut last = 0;
+        for value in coordinates {
+            data.extend(((value - last) as i16).to_be_bytes());
+            last = value;
+        }
+    }
+    data
+}
+
+// FontAwesome with the glyphs given in place of its own, the glyphs after them left empty
+// and the tables given on top. Side bearings are set so every glyph has its origin at 0.
+pub fn with_glyphs(glyphs: &[Vec<u8>], tables: &[(&str, Vec<u8>)]) -> Vec<u8> {
+    let font = font_data();
+    let (_, maxp) = record(&font, "maxp");
+    let glyph_count = u16::from_be_bytes([font[maxp + 4], font[maxp + 5]]) as usize;
+
+    let (mut glyf, mut loca, mut hmtx) = (Vec::new(), Vec::new(), Vec::new());
+    for index in 0..glyph_count {
+        loca.extend(((glyf.len() / 2) as u16).to_be_bytes());
+        let glyph = glyphs.get(index).map(|glyph| &glyph[..]).unwrap_or(&[]);
+        hmtx.extend(1024u16.to_be_bytes());
+        hmtx.extend(glyph.get(2..4).unwrap_or(&[0, 0]));
+        glyf.extend(glyph);
+        glyf.resize((glyf.len() + 1) & !1, 0);
+    }
+    loca.extend(((glyf.len() / 2) as u16).to_be_bytes());
+
+    let mut all = vec![("glyf", glyf), ("loca", loca), ("hmtx", hmtx)];
+    all.extend(tables.iter().map(|(tag, table)| (*tag, table.clone())));
+    with_tables(&all)
+}
+
+// Writes the outline down as SVG like path commands
+pub struct PathRecorder(pub Vec<String>);
+
+impl OutlineBuilder for PathRecorder {
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.0.push(format!("M{} {}", x, y));
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        self.0.push(format!("L{} {}", x, y));
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        self.0.push(format!("Q{} {} {} {}", x1, y1, x, y));
+    }
+
+    fn close(&mut self) {
+        self.0.push("Z".into());
+    }
+}
@@ -0,0 +1,136 @@
+mod common;
+
+use lipi::{Error, TrueTypeFont};
+
+use common::{font_data, patch, record};
+
+#[test]
+fn truncated_directory() {
+    let data = font_data();
+    let err = TrueTypeFont::from_bytes(&data[..20]).unwrap_err();
+    assert!(matches!(err, Error::Truncated { .. }));
+    assert_eq!(err.table(), Some("sfnt"));
+    assert_eq!(err.to_string(), "sfnt table is truncated at byte 20");
+}
+
+#[test]
+fn missing_table() {
+    let mut data = font_data();
+    let (record, _) = record(&data, "name");
+    data[record..record + 4].copy_from_slice(b"nime");
+
+    let err = TrueTypeFont::from_bytes(&data).unwrap_err();
+    assert!(matches!(&err, Error::MissingTable(table) if table == "name"));
+    assert_eq!(err.offset(), None);
+    assert_eq!(err.to_string(), "the name table is missing");
+}
+
+#[test]
+fn checksum_mismatch() {
+    let mut data = font_data();
+    let (record, offset) = record(&data, "maxp");
+    data[offset + 4] ^= 1;
+
+    let err = TrueTypeFont::from_bytes(&data).unwrap_err();
+    match err {
+        Error::ChecksumMismatch {
+            ref table,
+            offset,
+            expected,
+            actual,
+        } => {
+            assert_eq!((table.as_str(), offset), ("maxp", record));
+            assert_ne!(expected, actual);
+        }
+        _ => panic!("unexpected error {:?}", err),
+    }
+    assert!(err
+        .to_string()
+        .starts_with("checksums don't match for the maxp table"));
+}
+
+#[test]
+fn bad_magic() {
+    // head is left out of the checksums, it holds the adjustment for the whole file
+    let mut data = font_data();
+    let (_, offset) = record(&data, "head");
+    data[offset + 12..offset + 16].copy_from_slice(&[0xDE, 0xAD, 0xBE, 0xEF]);
+
+    let err = TrueTypeFont::from_bytes(&data).unwrap_err();
+    assert!(matches!(
+        err,
+        Error::BadMagic {
+            magic: 0xDEADBEEF,
+            ..
+        }
+    ));
+    assert_eq!(
+        (err.table(), err.offset()),
+        (Some("head"), Some(offset + 12))
+    );
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "wrong magic number 0xdeadbeef in the head table at byte {}",
+            offset + 12
+        )
+    );
+}
+
+#[test]
+fn invalid_platform_id() {
+    // the platform id of the first encoding record
+    let mut data = font_data();
+    patch(&mut data, "cmap", 4, &[0, 7]);
+    let (_, offset) = record(&data, "cmap");
+
+    let err = TrueTypeFont::from_bytes(&data).unwrap_err();
+    assert!(matches!(
+        err,
+        Error::InvalidPlatformId { platform_id: 7, .. }
+    ));
+    assert_eq!(
+        (err.table(), err.offset()),
+        (Some("cmap"), Some(offset + 4))
+    );
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "7 is not a valid platform id in the cmap table at byte {}",
+            offset + 4
+        )
+    );
+}
+
+#[test]
+fn io_errors_keep_their_source() {
+    let err = TrueTypeFont::new("no/such/font.ttf".into()).unwrap_err();
+    assert!(matches!(err, Error::Io(_)));
+    assert_eq!((err.table(), err.offset()), (None, None));
+    assert!(std::error::Error::source(&err).is_some());
+    assert!(err.to_string().starts_with("can't read the font: "));
+}
+
+#[test]
+fn display_of_the_other_variants() {
+    let err = Error::UnsupportedFormat {
+        table: "cmap".into(),
+        offset: 42,
+        format: 9,
+    };
+    assert_eq!(
+        err.to_string(),
+        "9 is not a format of the cmap table supported by lipi, at byte 42"
+    );
+    assert!(std::error::Error::source(&err).is_none());
+
+    let err = Error::Malformed {
+        table: "glyf".into(),
+        offset: 7,
+        reason: "too many points",
+    };
+    assert_eq!(
+        err.to_string(),
+        "malformed glyf table at byte 7: too many points"
+    );
+}
@@ -0,0 +1,241 @@
+mod common;
+
+use lipi::layout::{self, Buffer, FeatureSetting};
+use lipi::tables::gdef::{self, CaretValue};
+use lipi::tables::gsub;
+use lipi::tables::layout::{
+    Device, BASE_GLYPH, LIGATURE_GLYPH, MARK_GLYPH, USE_MARK_FILTERING_SET,
+};
+use lipi::{FontReader, GlyphId, TrueTypeFont};
+
+use common::Item::*;
+use common::{coverage, offset_table, table, with_table, words};
+
+// 1 to 9 are bases, 10 to 19 ligatures and 20 to 29 marks. 20 is in mark attachment class
+// 1 and 21 in class 2, and 21 is the only glyph of mark glyph set 0. Ligature 10 has a
+// caret at 500 and one on point 4 of its outline, ligature 11 one at 300 with a device.
+fn gdef_data() -> Vec<u8> {
+    let glyph_class_def = words(&[2, 3, 1, 9, 1, 10, 19, 2, 20, 29, 3]);
+    let attach_list = table(&[O(0), W(1), O(1)], &[coverage(&[5]), words(&[2, 3, 7])]);
+    let lig_caret_list = table(
+        &[O(0), W(2), O(1), O(2)],
+        &[
+            coverage(&[10, 11]),
+            table(&[W(2), O(0), O(1)], &[words(&[1, 500]), words(&[2, 4])]),
+            table(
+                &[W(1), O(0)],
+                &[table(&[W(3), W(300), O(0)], &[words(&[12, 12, 3, 0xFE00])])],
+            ),
+        ],
+    );
+    let mark_attach_class_def = words(&[1, 20, 2, 1, 2]);
+
+    // the coverage offsets of the mark glyph sets are 32 bits
+    let mut mark_glyph_sets_def = words(&[1, 1, 0, 8]);
+    mark_glyph_sets_def.extend(coverage(&[21]));
+
+    table(
+        &[W(1), W(2), O(0), O(1), O(2), O(3), O(4)],
+        &[
+            glyph_class_def,
+            attach_list,
+            lig_caret_list,
+            mark_attach_class_def,
+            mark_glyph_sets_def,
+        ],
+    )
+}
+
+fn gdef_table() -> gdef::Gdef {
+    let data = gdef_data();
+    gdef::read(&mut FontReader::new(&data), offset_table(&data)).unwrap()
+}
+
+#[test]
+fn gdef_tables() {
+    let gdef = gdef_table();
+    assert_eq!((gdef.major_version, gdef.minor_version), (1, 2));
+
+    let classes: Vec<u16> = [0, 1, 9, 10, 19, 20, 29, 30]
+        .iter()
+        .map(|&glyph| gdef.glyph_class(GlyphId(glyph)))
+        .collect();
+    assert_eq!(classes, [0, BASE_GLYPH, BASE_GLYPH, 2, 2, MARK_GLYPH, 3, 0]);
+    let mark_classes: Vec<u16> = [19, 20, 21, 22]
+        .iter()
+        .map(|&glyph| gdef.mark_attachment_class(GlyphId(glyph)))
+        .collect();
+    assert_eq!(mark_classes, [0, 1, 2, 0]);
+
+    assert_eq!(gdef.attach_points(GlyphId(5)), Some(&[3, 7][..]));
+    assert_eq!(gdef.attach_points(GlyphId(6)), None);
+
+    assert_eq!(
+        gdef.ligature_carets(GlyphId(10)).unwrap(),
+        [
+            CaretValue::Format1 { coordinate: 500 },
+            CaretValue::Format2 {
+                caret_value_point_index: 4
+            }
+        ]
+    );
+    assert_eq!(
+        gdef.ligature_carets(GlyphId(11)).unwrap(),
+        [CaretValue::Format3 {
+            coordinate: 300,
+            device: Some(Device::Hinting {
+                start_size: 12,
+                end_size: 12,
+                delta_values: vec![-2]
+            })
+        }]
+    );
+    assert!(gdef.ligature_carets(GlyphId(12)).is_none());
+
+    assert_eq!(gdef.mark_glyph_sets.len(), 1);
+    assert!(gdef.mark_glyph_sets[0].contains(GlyphId(21)));
+    assert!(!gdef.mark_glyph_sets[0].contains(GlyphId(20)));
+}
+
+#[test]
+fn gdef_version_1_0() {
+    // no mark glyph sets, the offset after the mark attachment classes isn't there
+    let data = table(
+        &[W(1), W(0), W(0), W(0), W(0), O(0)],
+        &[words(&[1, 20, 1, 1])],
+    );
+    let gdef = gdef::read(&mut FontReader::new(&data), offset_table(&data)).unwrap();
+    assert!(gdef.glyph_class_def.is_none());
+    assert_eq!(gdef.glyph_class(GlyphId(20)), 0);
+    assert_eq!(gdef.mark_attachment_class(GlyphId(20)), 1);
+    assert!(gdef.mark_glyph_sets.is_empty());
+
+    let data = words(&[2, 0]);
+    assert!(gdef::read(&mut FontReader::new(&data), offset_table(&data)).is_err());
+
+    // a font with such a GDEF goes without one
+    let font = with_table("GDEF", &data);
+    let font = TrueTypeFont::from_bytes(&font).unwrap();
+    assert!(font.gdef().is_none());
+    assert_eq!(font.table_errors().len(), 1);
+}
+
+#[test]
+fn broken_subtables() {
+    // the glyph classes point past the end and the attach list has a bad format, the
+    // mark attachment classes still read
+    let data = table(
+        &[W(1), W(0), W(200), O(0), W(0), O(1)],
+        &[words(&[9]), words(&[1, 20, 1, 1])],
+    );
+    let gdef = gdef::read(&mut FontReader::new(&data), offset_table(&data)).unwrap();
+    assert!(gdef.glyph_class_def.is_none() && gdef.attach_list.is_none());
+    assert_eq!(gdef.mark_attachment_class(GlyphId(20)), 1);
+}
+
+#[test]
+fn font_glyph_classes_and_carets() {
+    let data = with_table("GDEF", &gdef_data());
+    let font = TrueTypeFont::from_bytes(&data).unwrap();
+    assert_eq!(font.glyph_class(GlyphId(10)), LIGATURE_GLYPH);
+    assert_eq!(font.glyph_class(GlyphId(40)), 0);
+
+    // the point caret lands on the x of the point
+    let outline = font.resolve_glyph(10).unwrap().unwrap();
+    assert_eq!(
+        font.ligature_carets(GlyphId(10)).unwrap(),
+        [500, outline.x_coordinates[4]]
+    );
+    assert_eq!(font.ligature_carets(GlyphId(11)).unwrap(), [300]);
+    assert!(font.ligature_carets(GlyphId(12)).unwrap().is_empty());
+
+    // without GDEF nothing has a class or carets
+    let path = format!("{}/FontAwesome.ttf", env!("CARGO_MANIFEST_DIR"));
+    let font = TrueTypeFont::new(path).unwrap();
+    assert!(font.gdef().is_none());
+    assert_eq!(font.glyph_class(GlyphId(10)), 0);
+    assert!(font.ligature_carets(GlyphId(10)).unwrap().is_empty());
+}
+
+// liga ligates 1 2 into 10 looking only at the marks of set 0, ccmp 1 2 into 11 looking
+// only at the marks of attachment class 1
+fn gsub_table() -> gsub::Gsub {
+    let ligature = |lookup_flag: i32, mark_filtering_set: Option<i32>, ligature: i32| {
+        let subtable = table(
+            &[W(1), O(0), W(1), O(1)],
+            &[
+                coverage(&[1]),
+                table(&[W(1), O(0)], &[words(&[ligature, 2, 2])]),
+            ],
+        );
+        let mut items = vec![W(4), W(lookup_flag), W(1), O(0)];
+        items.extend(mark_filtering_set.map(W));
+        table(&items, &[subtable])
+    };
+
+    let lang_sys = words(&[0, 0xFFFF, 2, 0, 1]);
+    let script_list = table(
+        &[W(1), W(0x4446), W(0x4C54), O(0)],
+        &[table(&[O(0), W(0)], &[lang_sys])],
+    );
+    let feature_list = table(
+        &[W(2), W(0x6363), W(0x6D70), O(0), W(0x6C69), W(0x6761), O(1)],
+        &[words(&[0, 1, 1]), words(&[0, 1, 0])],
+    );
+    let lookup_list = table(
+        &[W(2), O(0), O(1)],
+        &[
+            ligature(USE_MARK_FILTERING_SET as i32, Some(0), 10),
+            // mark attachment type 1
+            ligature(1 << 8, None, 11),
+        ],
+    );
+
+    let data = table(
+        &[W(1), W(0), O(0), O(1), O(2)],
+        &[script_list, feature_list, lookup_list],
+    );
+    gsub::read(&mut FontReader::new(&data), offset_table(&data)).unwrap()
+}
+
+fn ligate(feature: &str, glyphs: &[u16]) -> Vec<(u16, u16)> {
+    let (gsub, gdef) = (gsub_table(), gdef_table());
+    let ids: Vec<GlyphId> = glyphs.iter().map(|&glyph| GlyphId(glyph)).collect();
+    let mut buffer = Buffer::new(&ids);
+    layout::apply_gsub(
+        &gsub,
+        Some(&gdef),
+        &mut buffer,
+        "latn",
+        None,
+        &[FeatureSetting::new(feature)],
+    );
+    buffer
+        .glyphs
+        .iter()
+        .map(|info| (info.glyph.0, info.glyph_class))
+        .collect()
+}
+
+#[test]
+fn classes_drive_the_lookups() {
+    // marks outside the filtering set are looked past, the ones in it are not
+    assert_eq!(
+        ligate("liga", &[1, 20, 2]),
+        [(10, LIGATURE_GLYPH), (20, MARK_GLYPH)]
+    );
+    assert_eq!(
+        ligate("liga", &[1, 21, 2]),
+        [(1, BASE_GLYPH), (21, MARK_GLYPH), (2, BASE_GLYPH)]
+    );
+
+    // the same with marks of another attachment class
+    assert_eq!(
+        ligate("ccmp", &[1, 21, 2]),
+        [(11, LIGATURE_GLYPH), (21, MARK_GLYPH)]
+    );
+    assert_eq!(
+        ligate("ccmp", &[1, 20, 2]),
+        [(1, BASE_GLYPH), (20, MARK_GLYPH), (2, BASE_GLYPH)]
+    );
+}
@@ -0,0 +1,356 @@
+// Regression corpus for the glyf decoder, the expected outlines were dumped from
+// FontAwesome.ttf with an independent decoder written against the spec.
+mod common;
+
+use lipi::tables::glyf::*;
+use lipi::{Error, GlyphId, TrueTypeFont};
+
+use common::{font_data, patch, simple_glyph, with_glyphs, words, PathRecorder};
+
+fn points(font: &TrueTypeFont, index: u32) -> Vec<(i16, i16, bool)> {
+    let data = font.resolve_glyph(index).unwrap().unwrap();
+
+    data.x_coordinates
+        .iter()
+        .zip(data.y_coordinates.iter())
+        .zip(data.flags.iter())
+        .map(|((&x, &y), &flag)| (x, y, flag & 1 != 0))
+        .collect()
+}
+
+#[test]
+fn check_mark_points() {
+    let data = font_data();
+    let font = TrueTypeFont::from_bytes(&data).unwrap();
+
+    let expected = vec![
+        (335, 0, true),
+        (318, 0, false),
+        (279, 16, false),
+        (268, 27, true),
+        (11, 285, true),
+        (0, 296, false),
+        (0, 329, false),
+        (11, 340, true),
+        (96, 425, true),
+        (107, 436, false),
+        (140, 436, false),
+        (151, 425, true),
+        (329, 247, true),
+        (340, 236, false),
+        (373, 236, false),
+        (384, 247, true),
+        (748, 611, true),
+        (759, 622, false),
+        (792, 622, false),
+        (803, 611, true),
+        (888, 526, true),
+        (899, 515, false),
+        (899, 482, false),
+        (888, 471, true),
+        (444, 27, true),
+        (433, 16, false),
+        (394, 0, false),
+        (378, 0, true),
+    ];
+
+    assert_eq!(points(&font, 14), expected);
+}
+
+#[test]
+fn chevron_points() {
+    let data = font_data();
+    let font = TrueTypeFont::from_bytes(&data).unwrap();
+
+    let expected = vec![
+        (15, 341, true),
+        (1, 355, false),
+        (1, 393, false),
+        (15, 407, true),
+        (64, 456, true),
+        (343, 736, true),
+        (357, 750, false),
+        (395, 750, false),
+        (409, 736, true),
+        (458, 686, true),
+        (472, 672, false),
+        (472, 635, false),
+        (458, 621, true),
+        (212, 374, true),
+        (458, 128, true),
+        (472, 114, false),
+        (472, 76, false),
+        (458, 62, true),
+        (409, 13, true),
+        (395, -1, false),
+        (357, -1, false),
+        (343, 13, true),
+        (15, 341, true),
+    ];
+
+    assert_eq!(points(&font, 39), expected);
+}
+
+#[test]
+fn contour_end_points() {
+    let data = font_data();
+    let font = TrueTypeFont::from_bytes(&data).unwrap();
+
+    let data = font.resolve_glyph(5).unwrap().unwrap();
+    assert_eq!(data.end_pts_of_contours, vec![34, 69, 92]);
+    assert_eq!(data.x_coordinates.len(), 93);
+    assert_eq!(data.y_coordinates.len(), 93);
+}
+
+#[test]
+fn check_mark_outline() {
+    let data = font_data();
+    let font = TrueTypeFont::from_bytes(&data).unwrap();
+
+    let mut recorder = PathRecorder(Vec::new());
+    let bbox = font
+        .outline_glyph(GlyphId(14), &mut recorder)
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(
+        recorder.0.join(" "),
+        "M335 0 Q318 0 298.5 8 Q279 16 268 27 L11 285 Q0 296 0 312.5 Q0 329 11 340 L96 425 \
+         Q107 436 123.5 436 Q140 436 151 425 L329 247 Q340 236 356.5 236 Q373 236 384 247 \
+         L748 611 Q759 622 775.5 622 Q792 622 803 611 L888 526 Q899 515 899 498.5 \
+         Q899 482 888 471 L444 27 Q433 16 413.5 8 Q394 0 378 0 L335 0 Z"
+    );
+    assert_eq!(
+        (bbox.x_min, bbox.y_min, bbox.x_max, bbox.y_max),
+        (0, 0, 899, 622)
+    );
+}
+
+// The bounding box in the glyph header is computed from the very same points, so every
+// simple glyph in the font has to agree with it
+#[test]
+fn points_match_bounding_boxes() {
+    let data = font_data();
+    let font = TrueTypeFont::from_bytes(&data).unwrap();
+
+    for index in 0..font.maxp().glyph_count as u32 {
+        let glyph = match font.glyph(index).unwrap() {
+            Some(val) => val,
+            None => continue,
+        };
+        let data = match &glyph.glyph_data {
+            GlyphData::Simple(simple) => simple,
+            GlyphData::Composite(_) => continue,
+        };
+
+        assert_eq!(
+            data.x_coordinates.iter().min(),
+            Some(&glyph.xmin),
+            "glyph {}",
+            index
+        );
+        assert_eq!(
+            data.x_coordinates.iter().max(),
+            Some(&glyph.xmax),
+            "glyph {}",
+            index
+        );
+        assert_eq!(
+            data.y_coordinates.iter().min(),
+            Some(&glyph.ymin),
+            "glyph {}",
+            index
+        );
+        assert_eq!(
+            data.y_coordinates.iter().max(),
+            Some(&glyph.ymax),
+            "glyph {}",
+            index
+        );
+    }
+}
+
+// Composite glyph of (flags, glyph, arguments, transform) components, the arguments are
+// always words
+fn composite(bounds: [i32; 4], components: &[(u16, i32, [i32; 2], &[i32])]) -> Vec<u8> {
+    let mut values = vec![-1];
+    values.extend(bounds);
+    for (i, (flags, glyph, arguments, transform)) in components.iter().enumerate() {
+        let mut flags = flags | ARG_1_AND_2_ARE_WORDS;
+        if i + 1 < components.len() {
+            flags |= MORE_COMPONENTS;
+        }
+        values.extend([flags as i32, *glyph]);
+        values.extend(arguments);
+        values.extend(transform.iter());
+    }
+    words(&values)
+}
+
+const TRIANGLE: &[(i32, i32)] = &[(0, 0), (100, 0), (0, 200)];
+const SQUARE: &[(i32, i32)] = &[(0, 0), (10, 0), (10, 10), (0, 10)];
+
+fn resolved(font: &TrueTypeFont, index: u32) -> (Vec<u16>, Vec<(i16, i16)>) {
+    let glyph = font.resolve_glyph(index).unwrap().unwrap();
+    let points = glyph
+        .x_coordinates
+        .into_iter()
+        .zip(glyph.y_coordinates)
+        .collect();
+    (glyph.end_pts_of_contours, points)
+}
+
+fn malformed(result: Result<impl std::fmt::Debug, Error>) -> &'static str {
+    match result {
+        Err(Error::Malformed { reason, .. }) => reason,
+        other => panic!("expected a malformed glyph, got {:?}", other),
+    }
+}
+
+#[test]
+fn transformed_components() {
+    // the triangle turned a quarter to the left and moved right, the square at half size
+    let glyphs = [
+        simple_glyph(&[TRIANGLE], &[]),
+        simple_glyph(&[SQUARE], &[]),
+        composite(
+            [-150, 0, 50, 100],
+            &[
+                (
+                    ARGS_ARE_XY_VALUES | WE_HAVE_A_TWO_BY_TWO,
+                    0,
+                    [50, 0],
+                    &[0, 0x4000, -0x4000, 0],
+                ),
+                (ARGS_ARE_XY_VALUES | WE_HAVE_A_SCALE, 1, [0, 0], &[0x2000]),
+            ],
+        ),
+    ];
+    let data = with_glyphs(&glyphs, &[]);
+    let font = TrueTypeFont::from_bytes(&data).unwrap();
+
+    let (ends, points) = resolved(&font, 2);
+    assert_eq!(ends, vec![2, 6]);
+    assert_eq!(
+        points,
+        vec![
+            (50, 0),
+            (50, 100),
+            (-150, 0),
+            (0, 0),
+            (5, 0),
+            (5, 5),
+            (0, 5)
+        ]
+    );
+}
+
+#[test]
+fn components_anchored_by_points() {
+    // point 2 of the square lands on point 1 of the triangle
+    let glyphs = [
+        simple_glyph(&[TRIANGLE], &[]),
+        simple_glyph(&[SQUARE], &[]),
+        composite(
+            [0, -10, 100, 200],
+            &[(ARGS_ARE_XY_VALUES, 0, [0, 0], &[]), (0, 1, [1, 2], &[])],
+        ),
+        composite([0, 0, 0, 0], &[(0, 1, [9, 0], &[])]),
+    ];
+    let data = with_glyphs(&glyphs, &[]);
+    let font = TrueTypeFont::from_bytes(&data).unwrap();
+
+    let (_, points) = resolved(&font, 2);
+    assert_eq!(&points[3..], &[(90, -10), (100, -10), (100, 0), (90, 0)]);
+
+    // there is no point 9 to anchor to
+    assert_eq!(
+        malformed(font.resolve_glyph(3)),
+        "component anchor point out of range"
+    );
+}
+
+#[test]
+fn scaled_and_unscaled_offsets() {
+    // the square at 1.5 times its size, moved by 10 before or after scaling
+    let component = |flags: u16| composite([0, 0, 30, 15], &[(flags, 0, [10, 0], &[0x6000])]);
+    let flags = ARGS_ARE_XY_VALUES | WE_HAVE_A_SCALE;
+    let glyphs = [
+        simple_glyph(&[SQUARE], &[]),
+        component(flags | SCALED_COMPONENT_OFFSET),
+        component(flags | UNSCALED_COMPONENT_OFFSET),
+        component(flags),
+    ];
+    let data = with_glyphs(&glyphs, &[]);
+    let font = TrueTypeFont::from_bytes(&data).unwrap();
+
+    let scaled = vec![(15, 0), (30, 0), (30, 15), (15, 15)];
+    let unscaled = vec![(10, 0), (25, 0), (25, 15), (10, 15)];
+    assert_eq!(resolved(&font, 1).1, scaled);
+    assert_eq!(resolved(&font, 2).1, unscaled);
+    // neither flag goes with the unscaled offset
+    assert_eq!(resolved(&font, 3).1, unscaled);
+}
+
+#[test]
+fn use_my_metrics() {
+    let glyphs = [
+        simple_glyph(&[SQUARE], &[]),
+        composite(
+            [0, 0, 10, 10],
+            &[(ARGS_ARE_XY_VALUES | USE_MY_METRICS, 0, [0, 0], &[])],
+        ),
+        composite([0, 0, 10, 10], &[(ARGS_ARE_XY_VALUES, 0, [0, 0], &[])]),
+    ];
+    let mut data = with_glyphs(&glyphs, &[("fpgm", vec![]), ("prep", vec![])]);
+    // the square advances by half an em, the composites by a whole one
+    patch(&mut data, "hmtx", 0, &512u16.to_be_bytes());
+    patch(&mut data, "head", 18, &1024u16.to_be_bytes());
+    let font = TrueTypeFont::from_bytes(&data).unwrap();
+
+    let glyph = font.glyph(1).unwrap().unwrap();
+    match glyph.glyph_data {
+        GlyphData::Composite(components) => assert!(components[0].use_my_metrics()),
+        GlyphData::Simple(_) => panic!("glyph 1 is a composite"),
+    }
+
+    // 26.6 pixels at 16 ppem on an em of 1024 units are font units
+    let mut hinter = font.hinter(16).unwrap();
+    assert_eq!(hinter.glyph(GlyphId(1)).unwrap().advance_width, 512);
+    assert_eq!(hinter.glyph(GlyphId(2)).unwrap().advance_width, 1024);
+}
+
+#[test]
+fn self_referencing_composite() {
+    let glyphs = [composite(
+        [0, 0, 0, 0],
+        &[(ARGS_ARE_XY_VALUES, 0, [0, 0], &[])],
+    )];
+    let mut data = with_glyphs(&glyphs, &[("fpgm", vec![]), ("prep", vec![])]);
+    // maxp allowing any depth still stops after a few levels
+    patch(&mut data, "maxp", 30, &[0xFF, 0xFF]);
+    let font = TrueTypeFont::from_bytes(&data).unwrap();
+
+    let reason = "composite glyph nested deeper than maxp allows";
+    assert_eq!(malformed(font.resolve_glyph(0)), reason);
+    let mut hinter = font.hinter(16).unwrap();
+    assert_eq!(malformed(hinter.glyph(GlyphId(0))), reason);
+}
+
+#[test]
+fn composite_point_count_fits_u16() {
+    // 17 times 4096 points is more than contour ends can count
+    let points: Vec<(i32, i32)> = (0..4096).map(|i| (i % 64, i / 64)).collect();
+    let component: (u16, i32, [i32; 2], &[i32]) = (ARGS_ARE_XY_VALUES, 0, [0, 0], &[]);
+    let glyphs = [
+        simple_glyph(&[&points], &[]),
+        composite([0, 0, 63, 63], &[component; 17]),
+    ];
+    let data = with_glyphs(&glyphs, &[("fpgm", vec![]), ("prep", vec![])]);
+    let font = TrueTypeFont::from_bytes(&data).unwrap();
+
+    let reason = "composite glyph with too many points";
+    assert_eq!(malformed(font.resolve_glyph(1)), reason);
+    let mut hinter = font.hinter(16).unwrap();
+    assert_eq!(malformed(hinter.glyph(GlyphId(1))), reason);
+}
@@ -0,0 +1,386 @@
+mod common;
+
+use lipi::layout::{self, Buffer, Direction, FeatureSetting, GlyphPosition};
+use lipi::tables::gpos::{self, Anchor, PositioningSubtable, SinglePositioning};
+use lipi::tables::layout::{Device, LIGATURE_GLYPH, MARK_GLYPH};
+use lipi::{FontReader, GlyphId, TrueTypeFont};
+
+use common::Item::*;
+use common::{coverage, layout_data, lookup, offset_table, table, with_table, words};
+
+fn anchor(x: i32, y: i32) -> Vec<u8> {
+    words(&[1, x, y])
+}
+
+fn gpos_table(features: &[(&str, &[i32])], lookups: &[Vec<u8>]) -> gpos::Gpos {
+    let data = layout_data(features, lookups);
+    let table = offset_table(&data);
+    gpos::read(&mut FontReader::new(&data), table).unwrap()
+}
+
+// Glyphs with their advance, marks have none
+fn buffer(glyphs: &[(u16, i32)]) -> Buffer {
+    let ids: Vec<GlyphId> = glyphs.iter().map(|&(glyph, _)| GlyphId(glyph)).collect();
+    let mut buffer = Buffer::new(&ids);
+    for (info, &(_, advance)) in buffer.glyphs.iter_mut().zip(glyphs) {
+        if advance == 0 {
+            info.glyph_class = MARK_GLYPH;
+        }
+    }
+    buffer.positions = glyphs
+        .iter()
+        .map(|&(_, advance)| GlyphPosition::new(advance, 0))
+        .collect();
+    buffer
+}
+
+// Advance and offsets of every glyph
+fn positions(buffer: &Buffer) -> Vec<(i32, i32, i32)> {
+    buffer
+        .positions
+        .iter()
+        .map(|position| (position.x_advance, position.x_offset, position.y_offset))
+        .collect()
+}
+
+fn position(gpos: &gpos::Gpos, buffer: &mut Buffer, features: &[&str]) {
+    let features: Vec<FeatureSetting> = features
+        .iter()
+        .map(|&tag| FeatureSetting::new(tag))
+        .collect();
+    layout::apply_gpos(gpos, None, buffer, "latn", None, &features);
+}
+
+#[test]
+fn devices() {
+    let read =
+        |values: &[i32]| lipi::tables::layout::read_device(&mut FontReader::new(&words(values)), 0);
+
+    // 2 bit values 1, -1, 0, -2 and 4 bit values 7, -8, 1
+    let device = read(&[10, 13, 1, 0b0111_0010_0000_0000]).unwrap();
+    assert_eq!(
+        (9..15).map(|ppem| device.delta(ppem)).collect::<Vec<i16>>(),
+        [0, 1, -1, 0, -2, 0]
+    );
+    let device = read(&[12, 14, 2, 0x7810]).unwrap();
+    assert!(
+        matches!(device, Device::Hinting { ref delta_values, .. } if delta_values == &[7, -8, 1])
+    );
+    let device = read(&[12, 13, 3, 0x80FF]).unwrap();
+    assert!(
+        matches!(device, Device::Hinting { ref delta_values, .. } if delta_values == &[-128, -1])
+    );
+
+    let device = read(&[2, 5, 0x8000]).unwrap();
+    assert_eq!(
+        device,
+        Device::VariationIndex {
+            delta_set_outer_index: 2,
+            delta_set_inner_index: 5
+        }
+    );
+    assert_eq!(device.delta(12), 0);
+    assert!(read(&[12, 13, 4]).is_err());
+}
+
+#[test]
+fn value_records_and_anchors() {
+    // single adjustment with an x placement device, cursive with anchor formats 2 and 3
+    let device = words(&[12, 12, 3, 0x0300]);
+    let single = table(
+        &[
+            W(1),
+            O(0),
+            W((gpos::X_ADVANCE | gpos::X_PLACEMENT_DEVICE) as i32),
+            W(-40),
+            O(1),
+        ],
+        &[coverage(&[5]), device.clone()],
+    );
+    let cursive = table(
+        &[W(1), O(0), W(1), O(1), O(2)],
+        &[
+            coverage(&[6]),
+            words(&[2, 10, 20, 4]),
+            table(&[W(3), W(30), W(40), W(0), O(0)], &[device]),
+        ],
+    );
+    let gpos = gpos_table(&[], &[lookup(1, 0, single), lookup(3, 0, cursive)]);
+
+    let lookups = &gpos.lookup_list.lookups;
+    match &lookups[0].subtables[0] {
+        PositioningSubtable::Single(SinglePositioning::Format1 { value_record, .. }) => {
+            assert_eq!((value_record.x_placement, value_record.x_advance), (0, -40));
+            let device = value_record.x_placement_device.as_ref().unwrap();
+            assert_eq!(device.delta(12), 3);
+            assert!(value_record.x_advance_device.is_none());
+        }
+        subtable => panic!("{:?}", subtable),
+    }
+    match &lookups[1].subtables[0] {
+        PositioningSubtable::Cursive(cursive) => {
+            let record = &cursive.entry_exit_records[0];
+            assert_eq!(
+                record.entry_anchor,
+                Some(Anchor::Format2 {
+                    x_coordinate: 10,
+                    y_coordinate: 20,
+                    anchor_point: 4
+                })
+            );
+            let exit = record.exit_anchor.as_ref().unwrap();
+            assert_eq!(exit.coordinates(), (30, 40));
+            assert!(
+                matches!(exit, Anchor::Format3 { x_device: None, y_device: Some(device), .. } if device.delta(12) == 3)
+            );
+        }
+        subtable => panic!("{:?}", subtable),
+    }
+}
+
+#[test]
+fn unknown_lookup_types() {
+    // lookup 0 is of a type GPOS doesn't have, lookup 1 takes 20 off the advance of 1
+    let single = table(&[W(1), O(0), W(4), W(-20)], &[coverage(&[1])]);
+    let gpos = gpos_table(
+        &[("kern", &[0, 1])],
+        &[lookup(10, 0, words(&[1])), lookup(1, 0, single)],
+    );
+    assert_eq!(gpos.lookup_list.lookups[0].lookup_type, 10);
+    assert!(gpos.lookup_list.lookups[0].subtables.is_empty());
+
+    let mut glyphs = buffer(&[(1, 100), (2, 100)]);
+    position(&gpos, &mut glyphs, &["kern"]);
+    assert_eq!(positions(&glyphs), [(80, 0, 0), (100, 0, 0)]);
+}
+
+#[test]
+fn single_and_pair_adjustment() {
+    let single = table(&[W(1), O(0), W(5), W(10), W(-20)], &[coverage(&[1])]);
+    // 1 then 2 tightens the 1, and 2 then 3 the 2
+    let pairs = table(
+        &[W(1), O(0), W(4), W(0), W(2), O(1), O(2)],
+        &[coverage(&[1, 2]), words(&[1, 2, -100]), words(&[1, 3, -50])],
+    );
+    let pairs_with_second = table(
+        &[W(1), O(0), W(4), W(1), W(1), O(1)],
+        &[coverage(&[4]), words(&[1, 4, -30, 70])],
+    );
+    // classes: 5 is class 1 on the left, 6 class 1 on the right
+    let class_pairs = table(
+        &[
+            W(2),
+            O(0),
+            W(4),
+            W(0),
+            O(1),
+            O(2),
+            W(2),
+            W(2),
+            W(0),
+            W(0),
+            W(0),
+            W(-200),
+        ],
+        &[coverage(&[5]), words(&[1, 5, 1, 1]), words(&[1, 6, 1, 1])],
+    );
+    let gpos = gpos_table(
+        &[("ss01", &[0]), ("kern", &[1, 2, 3])],
+        &[
+            lookup(1, 0, single),
+            lookup(2, 0, pairs),
+            lookup(2, 0, pairs_with_second),
+            lookup(2, 0, class_pairs),
+        ],
+    );
+
+    let mut glyphs = buffer(&[(1, 500), (2, 500), (3, 500)]);
+    position(&gpos, &mut glyphs, &["kern"]);
+    assert_eq!(positions(&glyphs), [(400, 0, 0), (450, 0, 0), (500, 0, 0)]);
+    position(&gpos, &mut glyphs, &["ss01"]);
+    assert_eq!(positions(&glyphs), [(380, 10, 0), (450, 0, 0), (500, 0, 0)]);
+
+    // the second glyph of a pair that moved it doesn't start the next pair
+    let mut glyphs = buffer(&[(4, 500), (4, 500), (4, 500)]);
+    position(&gpos, &mut glyphs, &["kern"]);
+    assert_eq!(positions(&glyphs), [(470, 0, 0), (500, 70, 0), (500, 0, 0)]);
+
+    let mut glyphs = buffer(&[(5, 500), (6, 500)]);
+    position(&gpos, &mut glyphs, &["kern"]);
+    assert_eq!(positions(&glyphs)[0], (300, 0, 0));
+
+    // the lookups don't skip marks, so one in between breaks the pair
+    let mut glyphs = buffer(&[(5, 500), (7, 0), (6, 500)]);
+    position(&gpos, &mut glyphs, &["kern"]);
+    assert_eq!(positions(&glyphs)[0], (500, 0, 0));
+}
+
+#[test]
+fn cursive_attachment() {
+    let records = [(1, [0, 100], [500, 300]), (2, [20, 200], [600, -100])];
+    let cursive = |lookup_flag| {
+        let mut items = vec![W(1), O(0), W(2)];
+        items.extend((1..5).map(O));
+        let mut children = vec![coverage(&[1, 2])];
+        for (_, entry, exit) in records {
+            children.push(anchor(entry[0], entry[1]));
+            children.push(anchor(exit[0], exit[1]));
+        }
+        lookup(3, lookup_flag, table(&items, &children))
+    };
+    let gpos = gpos_table(&[("curs", &[0]), ("rcur", &[1])], &[cursive(0), cursive(1)]);
+
+    // the first glyph stays on the baseline and the rest hang off it
+    let mut glyphs = buffer(&[(1, 700), (2, 700), (1, 700)]);
+    position(&gpos, &mut glyphs, &["curs"]);
+    assert_eq!(
+        positions(&glyphs),
+        [(500, 0, 0), (580, -20, 100), (700, 0, -100)]
+    );
+
+    // with RIGHT_TO_LEFT the last one does
+    let mut glyphs = buffer(&[(1, 700), (2, 700), (1, 700)]);
+    position(&gpos, &mut glyphs, &["rcur"]);
+    assert_eq!(
+        positions(&glyphs),
+        [(500, 0, 100), (580, -20, 200), (700, 0, 0)]
+    );
+    let mut glyphs = buffer(&[(1, 700), (2, 700), (1, 700)]);
+    glyphs.direction = Direction::RightToLeft;
+    position(&gpos, &mut glyphs, &["rcur"]);
+    assert_eq!(
+        positions(&glyphs),
+        [(200, -500, 100), (-580, -600, 200), (0, 0, 0)]
+    );
+}
+
+#[test]
+fn mark_attachment() {
+    // mark 10 of class 0 and mark 11 of class 1, on base 1, ligature 2 or mark 10
+    let mark_array = table(
+        &[W(2), W(0), O(0), W(1), O(1)],
+        &[anchor(50, 0), anchor(50, 600)],
+    );
+    let mark_to_base = table(
+        &[W(1), O(0), O(1), W(2), O(2), O(3)],
+        &[
+            coverage(&[10, 11]),
+            coverage(&[1]),
+            mark_array.clone(),
+            table(&[W(1), O(0), O(1)], &[anchor(300, 700), anchor(300, -100)]),
+        ],
+    );
+    let ligature_attach = table(
+        &[W(2), O(0), W(0), O(1), W(0)],
+        &[anchor(200, 700), anchor(600, 800)],
+    );
+    let mark_to_ligature = table(
+        &[W(1), O(0), O(1), W(2), O(2), O(3)],
+        &[
+            coverage(&[10, 11]),
+            coverage(&[2]),
+            mark_array,
+            table(&[W(1), O(0)], &[ligature_attach]),
+        ],
+    );
+    let mark_to_mark = table(
+        &[W(1), O(0), O(1), W(1), O(2), O(3)],
+        &[
+            coverage(&[10]),
+            coverage(&[10]),
+            table(&[W(1), W(0), O(0)], &[anchor(40, -50)]),
+            table(&[W(1), O(0)], &[anchor(60, 900)]),
+        ],
+    );
+    let gpos = gpos_table(
+        &[("mark", &[0, 1]), ("mkmk", &[2])],
+        &[
+            lookup(4, 0, mark_to_base),
+            lookup(5, 0, mark_to_ligature),
+            lookup(6, 0, mark_to_mark),
+        ],
+    );
+
+    // offsets end up relative to the pen position of the mark itself
+    let mut glyphs = buffer(&[(1, 600), (10, 0), (10, 0), (11, 0)]);
+    position(&gpos, &mut glyphs, &["mark", "mkmk"]);
+    assert_eq!(
+        positions(&glyphs),
+        [
+            (600, 0, 0),
+            (0, -350, 700),
+            (0, -330, 1650),
+            (0, -350, -700)
+        ]
+    );
+
+    let mut glyphs = buffer(&[(1, 600), (10, 0)]);
+    glyphs.direction = Direction::RightToLeft;
+    position(&gpos, &mut glyphs, &["mark"]);
+    assert_eq!(positions(&glyphs)[1], (0, 250, 700));
+
+    // marks go on the component they followed, or the last one if it has an anchor
+    let mut glyphs = buffer(&[(2, 1000), (10, 0), (10, 0), (11, 0)]);
+    glyphs.glyphs[0].glyph_class = LIGATURE_GLYPH;
+    glyphs.glyphs[0].ligature_id = 1;
+    glyphs.glyphs[1].ligature_id = 1;
+    glyphs.glyphs[1].ligature_component = 1;
+    position(&gpos, &mut glyphs, &["mark"]);
+    assert_eq!(
+        positions(&glyphs),
+        [(1000, 0, 0), (0, -850, 700), (0, -450, 800), (0, 0, 0)]
+    );
+
+    // marks of different components don't stack
+    let mut glyphs = buffer(&[(2, 1000), (10, 0), (10, 0)]);
+    glyphs.glyphs[0].ligature_id = 1;
+    glyphs.glyphs[1].ligature_id = 1;
+    glyphs.glyphs[1].ligature_component = 1;
+    position(&gpos, &mut glyphs, &["mkmk"]);
+    assert_eq!(positions(&glyphs)[2], (0, 0, 0));
+}
+
+#[test]
+fn contextual_positioning() {
+    // 2 after 1 and before 3 moves right by 10, either way
+    let single = table(&[W(1), O(0), W(1), W(10)], &[coverage(&[2])]);
+    let context = table(
+        &[W(3), W(3), W(1), O(0), O(1), O(2), W(1), W(0)],
+        &[coverage(&[1]), coverage(&[2]), coverage(&[3])],
+    );
+    let chained = table(
+        &[W(3), W(1), O(0), W(1), O(1), W(1), O(2), W(1), W(0), W(0)],
+        &[coverage(&[1]), coverage(&[2]), coverage(&[3])],
+    );
+    let gpos = gpos_table(
+        &[("kern", &[1]), ("dist", &[2])],
+        &[
+            lookup(1, 0, single),
+            lookup(7, 0, context),
+            lookup(8, 0, chained),
+        ],
+    );
+
+    // the context runs lookup 0 at its second glyph, the chained context at its only one
+    let mut glyphs = buffer(&[(1, 500), (2, 500), (3, 500), (2, 500), (3, 500)]);
+    let x_offsets = |glyphs: &Buffer| -> Vec<i32> {
+        glyphs
+            .positions
+            .iter()
+            .map(|position| position.x_offset)
+            .collect()
+    };
+    position(&gpos, &mut glyphs, &["kern"]);
+    assert_eq!(x_offsets(&glyphs), [0, 10, 0, 0, 0]);
+    position(&gpos, &mut glyphs, &["dist"]);
+    assert_eq!(x_offsets(&glyphs), [0, 20, 0, 0, 0]);
+}
+
+#[test]
+fn unreadable_gpos_is_left_out() {
+    // the lists lie past the end of the table
+    let data = with_table("GPOS", &words(&[1, 0, 0x100, 0x100, 0x100]));
+    let font = TrueTypeFont::from_bytes(&data).unwrap();
+    assert!(font.gpos().is_none());
+    assert_eq!(font.table_errors().len(), 1);
+}
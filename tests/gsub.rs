@@ -0,0 +1,246 @@
+mod common;
+
+use lipi::layout::{self, Buffer, FeatureSetting};
+use lipi::tables::gsub::{self, SubstitutionSubtable};
+use lipi::tables::layout::MARK_GLYPH;
+use lipi::{FontReader, GlyphId, TrueTypeFont};
+
+use common::Item::*;
+use common::{coverage, layout_data, lookup, offset_table, table, with_table, words};
+
+// A DFLT script whose default language system has liga, salt, ccmp, calt and rclt, each
+// running one lookup of a different type. Lookup 4 is only reached from the calt context.
+#[rustfmt::skip]
+fn gsub_table() -> gsub::Gsub {
+    let header = [W(1), W(0), W(10), W(38), W(100)];
+    let script_list = [
+        W(1), T("DFLT"), W(8),
+        W(4), W(0),
+        W(0), W(0xFFFF), W(5), W(0), W(1), W(2), W(3), W(4),
+    ];
+    let feature_list = [
+        W(5), T("liga"), W(32), T("salt"), W(38), T("ccmp"), W(44), T("calt"), W(50),
+        T("rclt"), W(56),
+        W(0), W(1), W(1),
+        W(0), W(1), W(2),
+        W(0), W(1), W(0),
+        W(0), W(1), W(3),
+        W(0), W(1), W(5),
+    ];
+    let lookup_list = [
+        W(6), W(14), W(42), W(84), W(114), W(160), W(180),
+        // 10 becomes 11 12
+        W(2), W(0), W(1), W(8),
+        W(1), W(14), W(1), W(8),
+        W(2), W(11), W(12),
+        W(1), W(1), W(10),
+        // 20 21 22 becomes 30 and 20 21 becomes 31, looking past marks
+        W(4), W(8), W(1), W(8),
+        W(1), W(28), W(1), W(8),
+        W(2), W(6), W(14),
+        W(30), W(3), W(21), W(22),
+        W(31), W(2), W(21),
+        W(1), W(1), W(20),
+        // 25 has the alternates 40, 41 and 42
+        W(3), W(0), W(1), W(8),
+        W(1), W(16), W(1), W(8),
+        W(3), W(40), W(41), W(42),
+        W(1), W(1), W(25),
+        // 51 between 50 and 52 goes through lookup 4
+        W(6), W(0), W(1), W(8),
+        W(3), W(1), W(20), W(1), W(26), W(1), W(32), W(1), W(0), W(4),
+        W(1), W(1), W(50),
+        W(1), W(1), W(51),
+        W(1), W(1), W(52),
+        // adds 10 to 51
+        W(1), W(0), W(1), W(8),
+        W(1), W(6), W(10),
+        W(1), W(1), W(51),
+        // 70 followed by 71 becomes 71
+        W(8), W(0), W(1), W(8),
+        W(1), W(14), W(0), W(1), W(20), W(1), W(71),
+        W(1), W(1), W(70),
+        W(1), W(1), W(71),
+    ];
+
+    let mut data = table(&header, &[]);
+    for items in [&script_list[..], &feature_list[..], &lookup_list[..]] {
+        data.extend(table(items, &[]));
+    }
+    assert_eq!(data.len(), 314);
+
+    let table = offset_table(&data);
+    gsub::read(&mut FontReader::new(&data), table).unwrap()
+}
+
+fn buffer(glyphs: &[u16]) -> Buffer {
+    let glyphs: Vec<GlyphId> = glyphs.iter().map(|&glyph| GlyphId(glyph)).collect();
+    Buffer::new(&glyphs)
+}
+
+fn ids(buffer: &Buffer) -> Vec<u16> {
+    buffer.glyphs.iter().map(|info| info.glyph.0).collect()
+}
+
+fn substitute(gsub: &gsub::Gsub, buffer: &mut Buffer, features: &[FeatureSetting]) {
+    layout::apply_gsub(gsub, None, buffer, "latn", None, features);
+}
+
+#[test]
+fn gsub_lookups() {
+    let gsub = gsub_table();
+    let types: Vec<u16> = gsub
+        .lookup_list
+        .lookups
+        .iter()
+        .map(|lookup| lookup.lookup_type)
+        .collect();
+    assert_eq!(types, [2, 4, 3, 6, 1, 8]);
+    assert!(matches!(
+        gsub.lookup_list.lookups[5].subtables[0],
+        SubstitutionSubtable::ReverseChainSingle(_)
+    ));
+}
+
+#[test]
+fn single_multiple_and_alternate() {
+    let gsub = gsub_table();
+
+    let mut glyphs = buffer(&[10, 5, 10]);
+    substitute(&gsub, &mut glyphs, &[FeatureSetting::new("ccmp")]);
+    assert_eq!(ids(&glyphs), [11, 12, 5, 11, 12]);
+    let clusters: Vec<u32> = glyphs.glyphs.iter().map(|info| info.cluster).collect();
+    assert_eq!(clusters, [0, 0, 1, 2, 2]);
+
+    let alternate = |value| {
+        let mut glyphs = buffer(&[25]);
+        let mut salt = FeatureSetting::new("salt");
+        salt.value = value;
+        substitute(&gsub, &mut glyphs, &[salt]);
+        ids(&glyphs)[0]
+    };
+    assert_eq!(
+        [alternate(0), alternate(1), alternate(3), alternate(4)],
+        [25, 40, 42, 25]
+    );
+
+    // features not asked for stay off
+    let mut glyphs = buffer(&[10, 25, 50, 51, 52]);
+    substitute(&gsub, &mut glyphs, &[FeatureSetting::new("kern")]);
+    assert_eq!(ids(&glyphs), [10, 25, 50, 51, 52]);
+}
+
+#[test]
+fn ligatures() {
+    let gsub = gsub_table();
+    let liga = [FeatureSetting::new("liga")];
+
+    let mut glyphs = buffer(&[20, 21, 22, 20, 21, 5, 20]);
+    substitute(&gsub, &mut glyphs, &liga);
+    assert_eq!(ids(&glyphs), [30, 31, 5, 20]);
+    let clusters: Vec<u32> = glyphs.glyphs.iter().map(|info| info.cluster).collect();
+    assert_eq!(clusters, [0, 3, 5, 6]);
+
+    // the lookup ignores marks, the one in between stays and belongs to the first component
+    let mut glyphs = buffer(&[20, 90, 21, 22]);
+    glyphs.glyphs[1].glyph_class = MARK_GLYPH;
+    substitute(&gsub, &mut glyphs, &liga);
+    assert_eq!(ids(&glyphs), [30, 90]);
+    let (ligature, mark) = (glyphs.glyphs[0], glyphs.glyphs[1]);
+    assert_ne!(ligature.ligature_id, 0);
+    assert_eq!(mark.ligature_id, ligature.ligature_id);
+    assert_eq!(mark.ligature_component, 1);
+    assert_eq!((ligature.cluster, mark.cluster), (0, 0));
+
+    // glyphs outside the mask of the feature are left alone
+    let mut glyphs = buffer(&[20, 21]);
+    let mut masked = FeatureSetting::new("liga");
+    masked.mask = 2;
+    substitute(&gsub, &mut glyphs, &[masked]);
+    assert_eq!(ids(&glyphs), [20, 21]);
+    glyphs.glyphs[1].mask |= 2;
+    substitute(&gsub, &mut glyphs, &[masked]);
+    assert_eq!(ids(&glyphs), [20, 21]);
+    glyphs.glyphs[0].mask |= 2;
+    substitute(&gsub, &mut glyphs, &[masked]);
+    assert_eq!(ids(&glyphs), [31]);
+}
+
+#[test]
+fn chained_and_reverse_chained() {
+    let gsub = gsub_table();
+
+    let mut glyphs = buffer(&[50, 51, 52, 51, 50, 51, 53]);
+    substitute(&gsub, &mut glyphs, &[FeatureSetting::new("calt")]);
+    assert_eq!(ids(&glyphs), [50, 61, 52, 51, 50, 51, 53]);
+
+    // going backwards every 70 sees the 71 it turned the next one into
+    let mut glyphs = buffer(&[70, 70, 70, 71, 70]);
+    substitute(&gsub, &mut glyphs, &[FeatureSetting::new("rclt")]);
+    assert_eq!(ids(&glyphs), [71, 71, 71, 71, 70]);
+}
+
+fn read(features: &[(&str, &[i32])], lookups: &[Vec<u8>]) -> gsub::Gsub {
+    let data = layout_data(features, lookups);
+    gsub::read(&mut FontReader::new(&data), offset_table(&data)).unwrap()
+}
+
+#[test]
+fn unknown_lookup_types() {
+    // lookup 0 is of a type GSUB doesn't have, lookup 1 turns 5 into 6
+    let single = table(&[W(1), O(0), W(1)], &[coverage(&[5])]);
+    let gsub = read(
+        &[("ccmp", &[0, 1])],
+        &[lookup(9, 0, words(&[1])), lookup(1, 0, single)],
+    );
+    assert_eq!(gsub.lookup_list.lookups[0].lookup_type, 9);
+    assert!(gsub.lookup_list.lookups[0].subtables.is_empty());
+
+    let mut glyphs = buffer(&[5, 7]);
+    substitute(&gsub, &mut glyphs, &[FeatureSetting::new("ccmp")]);
+    assert_eq!(ids(&glyphs), [6, 7]);
+}
+
+#[test]
+fn buffer_growth_is_capped() {
+    // every lookup turns glyph 5 into 128 of them
+    let mut sequence = vec![128];
+    sequence.extend([5; 128]);
+    let multiple = table(
+        &[W(1), O(0), W(1), O(1)],
+        &[coverage(&[5]), words(&sequence)],
+    );
+    let lookups = vec![lookup(2, 0, multiple); 3];
+    let gsub = read(&[("ccmp", &[0, 1, 2])], &lookups);
+
+    // short text can grow to 16384 glyphs, the third lookup finds no room left
+    let mut glyphs = buffer(&[5]);
+    substitute(&gsub, &mut glyphs, &[FeatureSetting::new("ccmp")]);
+    assert_eq!(glyphs.glyphs.len(), 16384);
+}
+
+#[test]
+fn font_without_gsub() {
+    let path = format!("{}/FontAwesome.ttf", env!("CARGO_MANIFEST_DIR"));
+    let font = TrueTypeFont::new(path).unwrap();
+    assert!(font.gsub().is_none());
+
+    let mut glyphs = buffer(&[20, 21, 22]);
+    layout::substitute(
+        &font,
+        &mut glyphs,
+        "latn",
+        None,
+        &[FeatureSetting::new("liga")],
+    );
+    assert_eq!(ids(&glyphs), [20, 21, 22]);
+}
+
+#[test]
+fn unreadable_gsub_is_left_out() {
+    // the lists lie past the end of the table
+    let data = with_table("GSUB", &words(&[1, 0, 0x100, 0x100, 0x100]));
+    let font = TrueTypeFont::from_bytes(&data).unwrap();
+    assert!(font.gsub().is_none());
+    assert_eq!(font.table_errors().len(), 1);
+}
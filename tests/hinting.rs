@@ -0,0 +1,268 @@
+mod common;
+
+use lipi::tables::gasp::{self, Gasp, GaspRange};
+use lipi::{Error, GlyphId, TrueTypeFont};
+
+use common::{font_data, patch, simple_glyph, with_glyphs, with_table, words};
+
+#[test]
+fn check_mark_matches_freetype() {
+    let data = font_data();
+    let font = TrueTypeFont::from_bytes(&data).unwrap();
+    let mut hinter = font.hinter(12).unwrap();
+
+    let glyph = hinter.glyph(GlyphId(14)).unwrap();
+    assert_eq!(glyph.advance_width, 704);
+    assert_eq!(glyph.end_pts_of_contours, vec![27]);
+
+    // FreeType 2.12 with the v35 interpreter
+    #[rustfmt::skip]
+    let expected = vec![
+        (257, 0), (244, 0), (214, 12), (206, 21), (8, 219), (0, 227), (0, 253),
+        (8, 261), (74, 326), (82, 335), (108, 335), (116, 326), (253, 190), (261, 181),
+        (286, 181), (295, 190), (574, 469), (583, 478), (608, 478), (617, 469), (682, 404),
+        (690, 396), (690, 370), (682, 362), (341, 21), (333, 12), (303, 0), (290, 0),
+    ];
+    assert_eq!(glyph.points, expected);
+}
+
+#[test]
+fn advances_are_whole_pixels() {
+    let data = font_data();
+    let font = TrueTypeFont::from_bytes(&data).unwrap();
+
+    for ppem in [9, 16, 33] {
+        let mut hinter = font.hinter(ppem).unwrap();
+        for index in 0..font.maxp().glyph_count {
+            let glyph = hinter.glyph(GlyphId(index)).unwrap();
+            assert_eq!(glyph.advance_width % 64, 0);
+        }
+    }
+}
+
+#[test]
+fn hinted_bitmap() {
+    let data = font_data();
+    let font = TrueTypeFont::from_bytes(&data).unwrap();
+    let mut hinter = font.hinter(12).unwrap();
+
+    let bitmap = hinter.glyph(GlyphId(14)).unwrap().rasterize((0., 0.));
+    assert_eq!((bitmap.width, bitmap.height), (11, 8));
+    assert_eq!((bitmap.left, bitmap.top), (0, 8));
+
+    let space = font.glyph_index(' ').unwrap();
+    let glyph = hinter.glyph(space).unwrap();
+    assert!(glyph.points.is_empty());
+    assert_eq!(glyph.rasterize((0., 0.)).coverage.len(), 0);
+}
+
+// Why a font program given as its fpgm fails
+fn fpgm_error(fpgm: &[u8]) -> &'static str {
+    let data = with_table("fpgm", fpgm);
+    let font = TrueTypeFont::from_bytes(&data).unwrap();
+    match font.hinter(12) {
+        Err(Error::Malformed { table, reason, .. }) => {
+            assert_eq!(table, "fpgm");
+            reason
+        }
+        _ => panic!("the program should fail"),
+    }
+}
+
+#[test]
+fn loop_calls_count_against_the_budget() {
+    // defines an empty function 0 and calls it 32767 * 64 * 64 times
+    #[rustfmt::skip]
+    let fpgm = [
+        0xB0, 0, 0x2C, 0x2D,
+        0xB8, 0x7F, 0xFF, 0xB0, 1, 0x62, 0xB0, 1, 0x62,
+        0xB0, 0, 0x2A,
+    ];
+    assert_eq!(fpgm_error(&fpgm), "program never finishes");
+}
+
+#[test]
+fn stack_is_limited() {
+    // duplicates the top of the stack over and over
+    let fpgm = [0xB0, 0, 0x20, 0xB8, 0xFF, 0xFC, 0x1C];
+    assert_eq!(fpgm_error(&fpgm), "stack overflow");
+}
+
+#[test]
+fn far_away_points_wrap_around() {
+    // moves a twilight point to i32::MIN and then back to the other end
+    #[rustfmt::skip]
+    let fpgm = [
+        0xB0, 0, 0x15,
+        0xB0, 0, 0xB8, 0x20, 0x00,
+        0xB0, 1, 0x62, 0xB0, 1, 0x62, 0xB0, 1, 0x62, 0x48,
+        0xB0, 0, 0xB8, 0x7F, 0xFF, 0x48,
+    ];
+    let mut data = with_table("fpgm", &fpgm);
+    patch(&mut data, "maxp", 16, &[0, 4]);
+    let font = TrueTypeFont::from_bytes(&data).unwrap();
+    // the fpgm runs through, prep then misses the functions the real fpgm defines
+    match font.hinter(12) {
+        Err(Error::Malformed { table, .. }) => assert_eq!(table, "prep"),
+        _ => panic!("prep should fail"),
+    }
+}
+
+#[test]
+fn control_values() {
+    let data = font_data();
+    let font = TrueTypeFont::from_bytes(&data).unwrap();
+
+    let cvt = font.cvt().unwrap();
+    assert_eq!(
+        cvt.values,
+        vec![20, 113, 75, 125, 185, 208, 125, 75, 113, 250, 0, 0, 753, 0]
+    );
+    assert!(font.gasp().is_none());
+}
+
+#[test]
+fn disassembled_programs() {
+    let data = font_data();
+    let font = TrueTypeFont::from_bytes(&data).unwrap();
+
+    let prep = lipi::hinting::disassemble("prep", &font.prep().unwrap().instructions).unwrap();
+    let text: Vec<String> = prep.iter().take(5).map(|i| i.to_string()).collect();
+    assert_eq!(
+        text,
+        vec!["PUSHW 0", "CALL", "SVTCA[0]", "PUSHW 1 5 2", "CALL"]
+    );
+    assert_eq!(prep[3].offset, 5);
+
+    let glyph = font.glyph(14).unwrap().unwrap();
+    let instructions = lipi::hinting::disassemble("glyf", &glyph.instructions).unwrap();
+    let mnemonics: Vec<String> = instructions.iter().map(|i| i.mnemonic()).collect();
+    assert_eq!(mnemonics[..4], ["SVTCA[0]", "PUSHW", "RCVT", "IF"]);
+    assert_eq!(mnemonics[mnemonics.len() - 2..], ["IUP[0]", "IUP[1]"]);
+
+    // a push cut short by the end of the program
+    assert!(lipi::hinting::disassemble("prep", &[0xB1, 0x01]).is_err());
+}
+
+#[test]
+fn gasp_ranges() {
+    let gasp = Gasp {
+        version: 0,
+        ranges: vec![
+            GaspRange {
+                range_max_ppem: 8,
+                range_gasp_behavior: gasp::DOGRAY,
+            },
+            GaspRange {
+                range_max_ppem: 0xFFFF,
+                range_gasp_behavior: gasp::GRIDFIT | gasp::DOGRAY,
+            },
+        ],
+    };
+
+    assert_eq!(gasp.behavior(8), gasp::DOGRAY);
+    assert!(!gasp.grid_fit(7));
+    assert!(gasp.smooth(7));
+    assert!(gasp.grid_fit(9));
+    assert!(gasp.grid_fit(0xFFFF));
+}
+
+// Hints a glyph of one contour with the program given, without the font's own fpgm and
+// prep. At 16 ppem on an em of 1024 units the points are in 26.6 from the start.
+fn hint(points: &[(i32, i32)], cvt: &[i32], program: &[u8]) -> Vec<(i32, i32)> {
+    let glyph = simple_glyph(&[points], program);
+    let tables = [("fpgm", vec![]), ("prep", vec![]), ("cvt ", words(cvt))];
+    let mut data = with_glyphs(&[glyph], &tables);
+    patch(&mut data, "head", 18, &1024u16.to_be_bytes());
+    // room for a few twilight points
+    patch(&mut data, "maxp", 16, &[0, 4]);
+
+    let font = TrueTypeFont::from_bytes(&data).unwrap();
+    let mut hinter = font.hinter(16).unwrap();
+    hinter.glyph(GlyphId(0)).unwrap().points
+}
+
+#[test]
+fn mdrp_rounds_and_keeps_minimum_distance() {
+    // MDRP[rnd, min] on point 1, then MDRP[rnd] on point 2, both from point 0
+    let program = [0xB0, 1, 0xCC, 0xB0, 2, 0xC4];
+    let points = hint(&[(0, 0), (10, 0), (100, 50)], &[], &program);
+    assert_eq!(points, vec![(0, 0), (64, 0), (128, 50)]);
+}
+
+#[test]
+fn mirp_cut_in() {
+    // MIRP[rnd] with a cvt close to the outline, MIRP[rnd, min] with one past the cut-in
+    let program = [0xB1, 1, 0, 0xE4, 0xB1, 2, 1, 0xEC];
+    let points = hint(&[(0, 0), (100, 0), (30, 50)], &[110, 200], &program);
+    assert_eq!(points, vec![(0, 0), (128, 0), (64, 50)]);
+}
+
+#[test]
+fn iup_interpolates_untouched_points() {
+    // MDAP touches point 0, SHPIX moves point 2 a pixel to the right, then IUP[x]
+    let program = [0xB0, 0, 0x2E, 0xB1, 2, 64, 0x38, 0x31];
+    let points = hint(&[(0, 0), (100, 50), (200, 100), (300, 50)], &[], &program);
+    // point 1 scales along between 0 and 2, point 3 lies past 2 and moves with it
+    assert_eq!(points, vec![(0, 0), (132, 50), (264, 100), (364, 50)]);
+}
+
+#[test]
+fn deltas_apply_at_their_ppem() {
+    // DELTAP1 moving point 1 a pixel at 16 ppem and point 2 at 15 ppem, then DELTAC1
+    // taking 1/8 of a pixel off cvt 0 at 16 ppem and MIAP putting point 3 there
+    #[rustfmt::skip]
+    let program = [
+        0xB4, 0x7F, 1, 0x6F, 2, 2, 0x5D,
+        0xB2, 0x77, 0, 1, 0x73,
+        0xB1, 3, 0, 0x3E,
+    ];
+    let points = hint(&[(0, 0), (10, 0), (20, 50), (30, 50)], &[100], &program);
+    assert_eq!(points, vec![(0, 0), (74, 0), (20, 50), (92, 50)]);
+}
+
+#[test]
+fn isect_moves_to_the_intersection() {
+    // point 0 goes where the diagonals through 1, 2 and 3, 4 cross
+    let program = [0xB4, 0, 1, 2, 3, 4, 0x0F];
+    let points = hint(
+        &[(50, 20), (0, 0), (200, 200), (0, 200), (200, 0)],
+        &[],
+        &program,
+    );
+    assert_eq!(points[0], (100, 100));
+}
+
+#[test]
+fn super_rounding() {
+    // SROUND to half pixels then MDRP[rnd] on point 1, S45ROUND to the 45 degree grid
+    // then MDRP[rnd] on point 2
+    let program = [0xB1, 1, 0x68, 0x76, 0xC4, 0xB1, 2, 0x48, 0x77, 0xC4];
+    let points = hint(&[(0, 0), (100, 0), (100, 50)], &[], &program);
+    assert_eq!(points, vec![(0, 0), (96, 0), (90, 50)]);
+}
+
+#[test]
+fn twilight_reference_point() {
+    // MIAP[rnd] puts twilight point 0 at cvt 0 and rounds it, MDRP then places point 1 from
+    // it by their distance in the original outline
+    let program = [0xB0, 0, 0x13, 0xB1, 0, 0, 0x3F, 0xB0, 1, 0xC0];
+    let points = hint(&[(0, 0), (130, 0), (0, 100)], &[100], &program);
+    assert_eq!(points, vec![(0, 0), (158, 0), (0, 100)]);
+}
+
+#[test]
+fn far_flung_points_are_refused() {
+    // SHPIX moving point 1 by 32767 pixels, a bitmap for that would take gigabytes
+    let program = [0xB0, 1, 0xB8, 0x7F, 0xFF, 0xB0, 1, 0x62, 0x38];
+    let glyph = simple_glyph(&[&[(0, 0), (100, 0), (0, 100)]], &program);
+    let data = with_glyphs(&[glyph], &[("fpgm", vec![]), ("prep", vec![])]);
+    let font = TrueTypeFont::from_bytes(&data).unwrap();
+    let mut hinter = font.hinter(16).unwrap();
+    match hinter.glyph(GlyphId(0)) {
+        Err(Error::Malformed { reason, .. }) => {
+            assert_eq!(reason, "hinted glyph far larger than its pixel size")
+        }
+        _ => panic!("the glyph should be refused"),
+    }
+}
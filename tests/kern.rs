@@ -0,0 +1,112 @@
+mod common;
+
+use lipi::tables::kern::{self, KernFormatTable};
+use lipi::{Error, FontReader, GlyphId, TrueTypeFont};
+
+use common::{font_data, offset_table, with_table, words};
+
+fn read(data: &[u8]) -> kern::Kern {
+    let table = offset_table(data);
+    kern::read(&mut FontReader::new(data), table).unwrap()
+}
+
+type Pair = (i32, i32, i32); // left, right and value
+
+// Microsoft header with a format 0 subtable per entry of coverage and pairs
+fn microsoft(subtables: &[(i32, &[Pair])]) -> Vec<u8> {
+    let mut data = words(&[0, subtables.len() as i32]);
+    for &(coverage, pairs) in subtables {
+        let length = 14 + pairs.len() as i32 * 6;
+        data.extend(words(&[0, length, coverage, pairs.len() as i32, 0, 0, 0]));
+        for &(left, right, value) in pairs {
+            data.extend(words(&[left, right, value]));
+        }
+    }
+    data
+}
+
+#[test]
+fn microsoft_format0() {
+    let data = microsoft(&[
+        (0x0001, &[(3, 4, -50), (1, 2, -30), (1, 5, 12)]), // out of order
+        (0x0005, &[(1, 2, 100)]),                          // cross-stream
+        (0x0009, &[(3, 4, -20)]),                          // override
+        (0x0001, &[(1, 2, -5)]),
+    ]);
+
+    let kern = read(&data);
+    assert_eq!(kern.version, 0);
+    assert_eq!(kern.subtables.len(), 4);
+    match &kern.subtables[0].format_table {
+        KernFormatTable::Format0(table) => {
+            let pairs: Vec<(u16, u16)> = table.pairs.iter().map(|p| (p.left, p.right)).collect();
+            assert_eq!(pairs, [(1, 2), (1, 5), (3, 4)]);
+        }
+        _ => panic!("expected a format 0 subtable"),
+    }
+
+    assert_eq!(kern.kerning(GlyphId(1), GlyphId(2)), -35);
+    assert_eq!(kern.kerning(GlyphId(1), GlyphId(5)), 12);
+    assert_eq!(kern.kerning(GlyphId(3), GlyphId(4)), -20);
+    assert_eq!(kern.kerning(GlyphId(2), GlyphId(1)), 0);
+}
+
+#[test]
+fn apple_format2() {
+    // left classes point at the rows from the start of the subtable, which puts the
+    // array at 32, right classes are offsets into a row of two values
+    let mut data = words(&[1, 0, 0, 1]);
+    data.extend(words(&[0, 40, 0x0002, 0])); // length, coverage and tupleIndex
+    data.extend(words(&[4, 16, 24, 32]));
+    data.extend(words(&[10, 2, 32, 36]));
+    data.extend(words(&[20, 2, 0, 2]));
+    data.extend(words(&[0, -10, -20, -40]));
+
+    let kern = read(&data);
+    assert_eq!(kern.version, 0x00010000);
+    assert_eq!(kern.subtables[0].coverage, kern::HORIZONTAL);
+
+    assert_eq!(kern.kerning(GlyphId(10), GlyphId(20)), 0);
+    assert_eq!(kern.kerning(GlyphId(10), GlyphId(21)), -10);
+    assert_eq!(kern.kerning(GlyphId(11), GlyphId(20)), -20);
+    assert_eq!(kern.kerning(GlyphId(11), GlyphId(21)), -40);
+    assert_eq!(kern.kerning(GlyphId(12), GlyphId(21)), 0);
+    assert_eq!(kern.kerning(GlyphId(11), GlyphId(22)), -20);
+
+    // vertical subtables don't kern along the line
+    data[12] = 0x80;
+    assert_eq!(read(&data).kerning(GlyphId(11), GlyphId(21)), 0);
+}
+
+#[test]
+fn zero_length_subtables() {
+    // as many subtables as the count allows, the first has a length of zero
+    let mut data = words(&[1, 0, -1, -1]);
+    data.extend(words(&[0, 0, 0x0003, 0]));
+    data.extend(words(&[0, 8, 0x0003, 0]));
+
+    let kern = read(&data);
+    assert_eq!(kern.subtables.len(), 1);
+    assert!(matches!(
+        kern.subtables[0].format_table,
+        KernFormatTable::Unsupported(3)
+    ));
+}
+
+#[test]
+fn fonts_without_kern() {
+    let data = font_data();
+    let font = TrueTypeFont::from_bytes(&data).unwrap();
+    assert!(font.kern().is_none());
+    assert_eq!(font.kerning(GlyphId(7), GlyphId(14)), 0);
+}
+
+#[test]
+fn unreadable_kern_is_left_out() {
+    // three subtables promised, none there
+    let data = with_table("kern", &words(&[0, 3]));
+    let font = TrueTypeFont::from_bytes(&data).unwrap();
+    assert!(font.kern().is_none());
+    assert_eq!(font.kerning(GlyphId(7), GlyphId(14)), 0);
+    assert!(matches!(font.table_errors(), [Error::Truncated { .. }]));
+}
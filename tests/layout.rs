@@ -0,0 +1,256 @@
+mod common;
+
+use std::cell::Cell;
+
+use lipi::tables::layout::{self, ClassDef, Coverage, FeatureParams};
+use lipi::{Error, FontReader, GlyphId};
+
+use common::Item::*;
+use common::{offset_table, table};
+
+fn glyphs(coverage: &Coverage, glyphs: &[u16]) -> Vec<Option<u16>> {
+    glyphs
+        .iter()
+        .map(|&glyph| coverage.index(GlyphId(glyph)))
+        .collect()
+}
+
+#[test]
+fn coverage_formats() {
+    let data = table(&[W(1), W(3), W(5), W(9), W(40)], &[]);
+    let coverage = layout::read_coverage(&mut FontReader::new(&data), 0).unwrap();
+    assert_eq!(
+        glyphs(&coverage, &[4, 5, 9, 10, 40]),
+        [None, Some(0), Some(1), None, Some(2)]
+    );
+
+    // ranges out of order still find their glyphs
+    let data = table(&[W(2), W(2), W(30), W(32), W(3), W(10), W(12), W(0)], &[]);
+    let coverage = layout::read_coverage(&mut FontReader::new(&data), 0).unwrap();
+    assert_eq!(
+        glyphs(&coverage, &[9, 10, 12, 13, 30, 32, 33]),
+        [None, Some(0), Some(2), None, Some(3), Some(5), None]
+    );
+    assert!(coverage.contains(GlyphId(31)));
+
+    let data = table(&[W(3), W(0)], &[]);
+    assert!(layout::read_coverage(&mut FontReader::new(&data), 0).is_err());
+}
+
+#[test]
+fn class_def_formats() {
+    let data = table(&[W(1), W(10), W(3), W(1), W(0), W(2)], &[]);
+    let class_def = layout::read_class_def(&mut FontReader::new(&data), 0).unwrap();
+    assert!(matches!(class_def, ClassDef::Format1 { .. }));
+    let classes: Vec<u16> = (9..14)
+        .map(|glyph| class_def.class(GlyphId(glyph)))
+        .collect();
+    assert_eq!(classes, [0, 1, 0, 2, 0]);
+
+    let data = table(&[W(2), W(2), W(5), W(7), W(3), W(20), W(20), W(1)], &[]);
+    let class_def = layout::read_class_def(&mut FontReader::new(&data), 0).unwrap();
+    let classes: Vec<u16> = [4, 5, 7, 8, 20, 21]
+        .iter()
+        .map(|&glyph| class_def.class(GlyphId(glyph)))
+        .collect();
+    assert_eq!(classes, [0, 3, 3, 0, 1, 0]);
+}
+
+// GSUB 1.1 with a script list at 14, lookup list at 48, feature variations at 84 and the
+// feature list last at 134
+#[rustfmt::skip]
+fn layout_table() -> Vec<u8> {
+    let header = [W(1), W(1), W(14), W(134), W(48), L(84)];
+    let script_list = [
+        W(1), T("latn"), W(8),
+        // script with a default language system and Turkish
+        W(10), W(1), T("TRK "), W(18),
+        W(0), W(0xFFFF), W(1), W(0),
+        W(0), W(1), W(1), W(0),
+    ];
+    let lookup_list = [
+        W(2), W(6), W(18),
+        // single substitution ignoring marks but those of set 3
+        W(1), W(0x0018), W(1), W(10), W(3),
+        W(0),
+        // ligature substitution wrapped in an extension, attaching to class 2 marks
+        W(7), W(0x0200), W(1), W(8),
+        W(1), W(4), L(8),
+        W(0),
+    ];
+    let feature_variations = [
+        W(1), W(0), L(1), L(16), L(30),
+        // the first axis from 0.5 to 1
+        W(1), L(6),
+        W(1), W(0), W(0x2000), W(0x4000),
+        // liga runs both lookups there
+        W(1), W(0), W(1), W(0), L(12),
+        W(0), W(2), W(1), W(0),
+    ];
+    let feature_list = [
+        W(3), T("liga"), W(20), T("ss01"), W(26), T("size"), W(36),
+        W(0), W(1), W(0),
+        W(6), W(1), W(0),
+        W(0), W(256),
+        // size parameters the old way, counted from the feature list
+        W(40), W(0),
+        W(100), W(1), W(256), W(80), W(120),
+    ];
+
+    let mut data = table(&header, &[]);
+    for items in [&script_list[..], &lookup_list[..], &feature_variations[..], &feature_list[..]] {
+        data.extend(table(items, &[]));
+    }
+    assert_eq!(data.len(), 184);
+    data
+}
+
+#[test]
+fn unsupported_subtables() {
+    let data = layout_table();
+    let read = |error: fn(u16) -> Error| {
+        layout::read(
+            &mut FontReader::new(&data),
+            "GSUB",
+            offset_table(&data),
+            7,
+            &|_, lookup_type, offset| match lookup_type {
+                1 => Err(error(lookup_type)),
+                _ => Ok(offset),
+            },
+        )
+    };
+
+    // the single substitution lookup is kept without its subtable
+    let layout = read(|format| Error::UnsupportedFormat {
+        table: "GSUB".into(),
+        offset: 0,
+        format: format as u32,
+    })
+    .unwrap();
+    let lookups = &layout.lookup_list.lookups;
+    assert_eq!((lookups[0].lookup_type, lookups[0].subtables.len()), (1, 0));
+    assert_eq!(lookups[1].subtables, [48 + 34]);
+
+    // anything else wrong with a subtable still fails the table
+    let malformed = read(|_| Error::Malformed {
+        table: "GSUB".into(),
+        offset: 0,
+        reason: "broken",
+    });
+    assert!(matches!(malformed, Err(Error::Malformed { .. })));
+}
+
+#[test]
+fn layout_lists() {
+    let data = layout_table();
+    let table = offset_table(&data);
+    let layout = layout::read(
+        &mut FontReader::new(&data),
+        "GSUB",
+        table,
+        7,
+        &|_, lookup_type, offset| Ok((lookup_type, offset)),
+    )
+    .unwrap();
+    assert_eq!((layout.major_version, layout.minor_version), (1, 1));
+
+    let script = layout.script_list.script("latn").unwrap();
+    assert!(layout.script_list.script("DFLT").is_none());
+    let turkish = script.lang_sys(Some("TRK ")).unwrap();
+    assert_eq!(turkish.required_feature_index, Some(1));
+    let default = script.lang_sys(Some("DEU ")).unwrap();
+    assert_eq!(default.required_feature_index, None);
+    assert_eq!(default.feature_indices, [0]);
+
+    let tags: Vec<&str> = layout
+        .feature_list
+        .features
+        .iter()
+        .map(|record| record.tag.as_str())
+        .collect();
+    assert_eq!(tags, ["liga", "ss01", "size"]);
+    assert_eq!(
+        layout.feature_list.features[1].feature.feature_params,
+        Some(FeatureParams::StylisticSet {
+            version: 0,
+            ui_name_id: 256
+        })
+    );
+    assert_eq!(
+        layout.feature_list.features[2].feature.feature_params,
+        Some(FeatureParams::Size {
+            design_size: 100,
+            subfamily_id: 1,
+            subfamily_name_id: 256,
+            range_start: 80,
+            range_end: 120,
+        })
+    );
+
+    let lookups = &layout.lookup_list.lookups;
+    assert_eq!(lookups[0].lookup_type, 1);
+    assert_eq!(
+        lookups[0].lookup_flag & layout::IGNORE_MARKS,
+        layout::IGNORE_MARKS
+    );
+    assert_eq!(lookups[0].mark_filtering_set, Some(3));
+    assert_eq!(lookups[0].subtables, [(1, 48 + 16)]);
+    assert_eq!(lookups[1].lookup_type, 4);
+    assert_eq!(lookups[1].mark_attachment_type(), 2);
+    assert_eq!(lookups[1].mark_filtering_set, None);
+    assert_eq!(lookups[1].subtables, [(4, 48 + 34)]);
+
+    let variations = layout.feature_variations.as_ref().unwrap();
+    assert!(variations.find(&[]).is_none());
+    assert!(variations.find(&[0.25]).is_none());
+    let substitution = variations.find(&[0.75]);
+    assert!(substitution.is_some());
+
+    let (tag, feature) = layout.feature(0, substitution).unwrap();
+    assert_eq!(
+        (tag, &feature.lookup_list_indices[..]),
+        ("liga", &[1, 0][..])
+    );
+    let (_, feature) = layout.feature(0, None).unwrap();
+    assert_eq!(feature.lookup_list_indices, [0]);
+    let (tag, feature) = layout.feature(1, substitution).unwrap();
+    assert_eq!((tag, &feature.lookup_list_indices[..]), ("ss01", &[0][..]));
+}
+
+#[test]
+fn shared_subtables_are_read_once() {
+    // two lookups both listing the same subtable three times
+    let lookup = table(&[W(1), W(0), W(3), W(0), W(0), W(0)], &[]);
+    let data = table(&[W(2), O(0), O(0)], &[lookup]);
+    let reads = Cell::new(0);
+    let lookup_list =
+        layout::read_lookup_list(&mut FontReader::new(&data), 0, 7, &|_, _, offset| {
+            reads.set(reads.get() + 1);
+            Ok(offset)
+        })
+        .unwrap();
+
+    assert_eq!(reads.get(), 1);
+    assert_eq!(lookup_list.lookups[1].subtables, [6; 3]);
+}
+
+#[test]
+fn subtable_count_is_capped() {
+    // two lookups listing 40000 subtables each
+    let mut items = vec![W(1), W(0), W(40000)];
+    items.extend((0..40000).map(|_| W(0)));
+    let data = table(&[W(2), O(0), O(0)], &[table(&items, &[])]);
+    let lookup_list =
+        layout::read_lookup_list(&mut FontReader::new(&data), 0, 7, &|_, _, offset| {
+            Ok(offset)
+        });
+
+    assert!(matches!(
+        lookup_list,
+        Err(Error::Malformed {
+            reason: "too many lookup subtables",
+            ..
+        })
+    ));
+}
@@ -0,0 +1,41 @@
+use lipi::TrueTypeFont;
+
+const PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/FontAwesome.ttf");
+static EMBEDDED: &[u8] = include_bytes!("../FontAwesome.ttf");
+
+// Whatever the font was loaded from, it has to read the same
+fn check(font: &TrueTypeFont) {
+    assert_eq!(font.data().len(), EMBEDDED.len());
+    assert_eq!(font.maxp().glyph_count, 63);
+    assert_eq!(font.glyph_index(' ').map(|glyph| glyph.0), Some(3));
+    assert!(font.table_data("glyf").is_some());
+}
+
+#[test]
+fn from_bytes_borrows() {
+    let font = TrueTypeFont::from_bytes(EMBEDDED).unwrap();
+    check(&font);
+
+    // the data is the slice handed in, not a copy of it
+    assert_eq!(font.data().as_ptr(), EMBEDDED.as_ptr());
+    let head = font.table_data("head").unwrap().as_ptr() as usize;
+    let offset = font.offset_tables()["head"].offset as usize;
+    assert_eq!(head, EMBEDDED.as_ptr() as usize + offset);
+}
+
+#[test]
+fn from_a_file() {
+    let font = TrueTypeFont::new(PATH.into()).unwrap();
+    check(&font);
+    assert_eq!(font.data(), EMBEDDED);
+}
+
+#[cfg(feature = "mmap")]
+#[test]
+fn from_a_mapped_file() {
+    let font = TrueTypeFont::from_mmap(PATH.into()).unwrap();
+    check(&font);
+    assert_eq!(font.data(), EMBEDDED);
+
+    assert!(TrueTypeFont::from_mmap("no/such/font.ttf".into()).is_err());
+}
@@ -0,0 +1,112 @@
+mod common;
+
+use lipi::tables::hmtx::{Hmtx, HmtxLongHorMetric};
+use lipi::tables::{vhea, vmtx};
+use lipi::{FontReader, GlyphId, TrueTypeFont};
+
+use common::{font_data, offset_table};
+
+#[test]
+fn horizontal_metrics() {
+    let data = font_data();
+    let font = TrueTypeFont::from_bytes(&data).unwrap();
+
+    // the bounding box runs from -4 to 790
+    let glyph = GlyphId(7);
+    assert_eq!(font.advance_width(glyph), 787);
+    assert_eq!(font.left_side_bearing(glyph), -4);
+    assert_eq!(font.right_side_bearing(glyph).unwrap(), -3);
+
+    let space = font.glyph_index(' ').unwrap();
+    assert_eq!(font.advance_width(space), 250);
+    assert_eq!(font.right_side_bearing(space).unwrap(), 250);
+}
+
+#[test]
+fn last_advance_repeats() {
+    let hmtx = Hmtx {
+        hmetrics: vec![
+            HmtxLongHorMetric {
+                advance_width: 500,
+                left_side_bearing: 10,
+            },
+            HmtxLongHorMetric {
+                advance_width: 600,
+                left_side_bearing: 20,
+            },
+        ],
+        left_side_bearings: vec![30, 40],
+    };
+
+    let metric = |index| {
+        let metric = hmtx.metric(index);
+        (metric.advance_width, metric.left_side_bearing)
+    };
+    assert_eq!(metric(0), (500, 10));
+    assert_eq!(metric(1), (600, 20));
+    assert_eq!(metric(2), (600, 30));
+    assert_eq!(metric(3), (600, 40));
+    assert_eq!(metric(4), (600, 0));
+}
+
+#[test]
+fn scaled_line_metrics() {
+    let data = font_data();
+    let font = TrueTypeFont::from_bytes(&data).unwrap();
+
+    // 750, -250 and 200 on a 1000 unit em
+    let metrics = font.scaled_line_metrics(16.);
+    assert!((metrics.ascender - 12.).abs() < 1e-5);
+    assert!((metrics.descender + 4.).abs() < 1e-5);
+    assert!((metrics.line_gap - 3.2).abs() < 1e-5);
+}
+
+#[test]
+fn synthesized_vertical_metrics() {
+    let data = font_data();
+    let font = TrueTypeFont::from_bytes(&data).unwrap();
+    assert!(font.vhea().is_none() && font.vmtx().is_none());
+
+    // OS/2 has a typographic ascender of 750 and descender of -250, glyph 7 reaches 751
+    assert_eq!(font.advance_height(GlyphId(7)), 1000);
+    assert_eq!(font.top_side_bearing(GlyphId(7)).unwrap(), -1);
+    assert_eq!(font.top_side_bearing(GlyphId(14)).unwrap(), 128);
+    assert_eq!(font.top_side_bearing(GlyphId(0)).unwrap(), 750);
+}
+
+#[test]
+fn vertical_tables() {
+    let mut data = vec![0x00, 0x01, 0x10, 0x00]; // version 1.1
+    for value in [880i16, -120, 0, 1000, -50, -30, 1050, 0, 1, 0] {
+        data.extend_from_slice(&value.to_be_bytes());
+    }
+    data.extend_from_slice(&[0; 10]); // reserved and metricDataFormat
+    data.extend_from_slice(&2u16.to_be_bytes());
+
+    let vhea = vhea::read(&mut FontReader::new(&data), offset_table(&data)).unwrap();
+    assert_eq!(vhea.version, 1.0625);
+    assert_eq!((vhea.ascent, vhea.descent, vhea.line_gap), (880, -120, 0));
+    assert_eq!(vhea.advance_height_max, 1000);
+    assert_eq!(vhea.y_max_extent, 1050);
+    assert_eq!((vhea.caret_slope_rise, vhea.caret_slope_run), (0, 1));
+    assert_eq!(vhea.num_of_long_ver_metrics, 2);
+
+    let mut data = Vec::new();
+    for value in [1000i16, 120, 900, 80, 60, 40] {
+        data.extend_from_slice(&value.to_be_bytes());
+    }
+
+    let vmtx = vmtx::read(&mut FontReader::new(&data), offset_table(&data), 2, 4).unwrap();
+    let metric = |index| {
+        let metric = vmtx.metric(index);
+        (metric.advance_height, metric.top_side_bearing)
+    };
+    assert_eq!(metric(0), (1000, 120));
+    assert_eq!(metric(1), (900, 80));
+    assert_eq!(metric(2), (900, 60));
+    assert_eq!(metric(3), (900, 40));
+    assert_eq!(metric(4), (900, 0));
+
+    // the bearings of glyphs past the long metrics run past the end of the table
+    assert!(vmtx::read(&mut FontReader::new(&data), offset_table(&data), 2, 5).is_err());
+}
@@ -0,0 +1,175 @@
+mod common;
+
+use lipi::tables::name::{self, NameId, Names};
+use lipi::{FontReader, TrueTypeFont};
+
+use common::{font_data, offset_table};
+
+fn utf16(text: &str) -> Vec<u8> {
+    text.encode_utf16()
+        .flat_map(|unit| unit.to_be_bytes())
+        .collect()
+}
+
+// Format 0 table with a record per (platform, encoding, language, name id, string)
+fn read(records: &[(u16, u16, u16, u16, Vec<u8>)]) -> name::Name {
+    let header = 6 + 12 * records.len();
+    let mut data = Vec::new();
+    for value in [0, records.len(), header] {
+        data.extend((value as u16).to_be_bytes());
+    }
+
+    let mut offset = 0;
+    for (platform, encoding, language, name_id, string) in records {
+        for value in [*platform, *encoding, *language, *name_id] {
+            data.extend(value.to_be_bytes());
+        }
+        data.extend((string.len() as u16).to_be_bytes());
+        data.extend((offset as u16).to_be_bytes());
+        offset += string.len();
+    }
+    for (_, _, _, _, string) in records {
+        data.extend(string);
+    }
+
+    let table = offset_table(&data);
+    name::read(&mut FontReader::new(&data), table).unwrap()
+}
+
+fn strings(name: &name::Name) -> Vec<Option<&str>> {
+    name.name_records
+        .iter()
+        .map(|record| record.string.as_deref())
+        .collect()
+}
+
+#[test]
+fn unicode_and_windows_strings() {
+    let name = read(&[
+        (0, 3, 0, 1, utf16("Lipi Sans")),
+        (3, 1, 0x0409, 1, utf16("Lipi \u{1F600}")),
+        (3, 10, 0x0409, 2, utf16("Regular")),
+        // lone surrogates don't decode
+        (3, 1, 0x0409, 4, vec![0xD8, 0x00, 0x00, 0x41]),
+        // Shift-JIS with the single byte characters padded to 16 bits, and Johab
+        (3, 2, 0x0411, 1, b"\x00A\x83t\x83H\x83\x93\x83g".to_vec()),
+        (3, 6, 0x0412, 1, b"\xd0e\x8bi".to_vec()),
+    ]);
+
+    assert_eq!(
+        strings(&name),
+        [
+            Some("Lipi Sans"),
+            Some("Lipi \u{1F600}"),
+            Some("Regular"),
+            None,
+            Some("Aフォント"),
+            Some("한글"),
+        ]
+    );
+    let languages: Vec<Option<&str>> = name
+        .name_records
+        .iter()
+        .map(|record| record.language.as_deref())
+        .collect();
+    assert_eq!(
+        languages,
+        [
+            None,
+            Some("en-US"),
+            Some("en-US"),
+            Some("en-US"),
+            Some("ja-JP"),
+            Some("ko-KR")
+        ]
+    );
+}
+
+#[test]
+fn mac_strings() {
+    let name = read(&[
+        (1, 0, 0, 1, b"Caf\x8E \xA5".to_vec()),
+        // the language picks the variant of Mac Roman
+        (1, 0, 17, 1, b"\xDErift".to_vec()),
+        (1, 6, 14, 1, b"\xa1\xf2\xe1\xed\xed\xe1".to_vec()),
+        (1, 7, 32, 1, b"\x98\xf0\xe8\xf4\xf2".to_vec()),
+        (1, 29, 25, 1, b"\xfc\x97d\x90".to_vec()),
+        (1, 1, 11, 1, b"\x83t\x83H\x83\x93\x83g".to_vec()),
+        // no table for Mac Hebrew, ASCII still gets through
+        (1, 5, 10, 1, b"Lipi".to_vec()),
+        (1, 5, 10, 2, b"\xE0".to_vec()),
+    ]);
+
+    assert_eq!(
+        strings(&name),
+        [
+            Some("Café •"),
+            Some("Şrift"),
+            Some("Γραμμα"),
+            Some("Шрифт"),
+            Some("Łódź"),
+            Some("フォント"),
+            Some("Lipi"),
+            None,
+        ]
+    );
+}
+
+#[test]
+fn strings_past_the_storage_area() {
+    // the second record points past the end of the table, the table still reads
+    let mut data = Vec::new();
+    for value in [0u16, 2, 30, 3, 1, 0x0409, 1, 8, 0, 3, 1, 0x0409, 4, 8, 4] {
+        data.extend(value.to_be_bytes());
+    }
+    data.extend(utf16("Lipi"));
+    let table = offset_table(&data);
+
+    // bytes after the table don't count as storage either
+    data.extend(utf16("Sans"));
+    let name = name::read(&mut FontReader::new(&data), table).unwrap();
+    assert_eq!(strings(&name), [Some("Lipi"), None]);
+    assert_eq!(Names::new(&name).full_name("en"), None);
+    assert_eq!(Names::new(&name).family("en"), Some("Lipi"));
+}
+
+#[test]
+fn family_by_language() {
+    let name = read(&[
+        (0, 3, 0, 1, utf16("Lipi Unicode")),
+        (1, 0, 0, 1, b"Lipi Mac".to_vec()),
+        (1, 7, 32, 1, b"\x98\xf0\xe8\xf4\xf2".to_vec()),
+        (3, 1, 0x0407, 1, utf16("Lipi Deutsch")),
+        (3, 1, 0x0409, 1, utf16("Lipi")),
+        (3, 1, 0x0409, 2, utf16("Regular")),
+        (3, 1, 0x0411, 1, utf16("リピ")),
+        (3, 1, 0x0409, 17, utf16("Book")),
+    ]);
+    let names = Names::new(&name);
+
+    // exact tag, then the primary language, English and Windows ahead of Mac
+    assert_eq!(names.family("de-DE"), Some("Lipi Deutsch"));
+    assert_eq!(names.family("de-AT"), Some("Lipi Deutsch"));
+    assert_eq!(names.family("ja"), Some("リピ"));
+    assert_eq!(names.family("ru"), Some("Шрифт"));
+    assert_eq!(names.family("en-US"), Some("Lipi"));
+    assert_eq!(names.family("fr-FR"), Some("Lipi"));
+    // the Mac record is tagged plain English, an exact match beats the platform
+    assert_eq!(names.family("en"), Some("Lipi Mac"));
+    assert_eq!(names.get(NameId::Family, "en-GB"), Some("Lipi"));
+
+    // the typographic subfamily wins over the plain one
+    assert_eq!(names.subfamily("en"), Some("Book"));
+    assert_eq!(names.get(NameId::Subfamily, "en"), Some("Regular"));
+    assert_eq!(names.postscript_name(), None);
+}
+
+#[test]
+fn font_names() {
+    let data = font_data();
+    let font = TrueTypeFont::from_bytes(&data).unwrap();
+    let names = font.names();
+
+    assert_eq!(names.family("en"), Some("FontAwesome"));
+    assert!(names.records().iter().all(|record| record.string.is_some()));
+}
@@ -0,0 +1,139 @@
+mod common;
+
+use lipi::tables::os2::{self, Embedding, LineMetrics};
+use lipi::{Error, FontReader, TrueTypeFont};
+
+use common::{font_data, offset_table, patch, record, with_table};
+
+fn read(data: &[u8]) -> lipi::Result<os2::Os2> {
+    let table = offset_table(data);
+    os2::read(&mut FontReader::new(data), table)
+}
+
+// Where fsSelection sits in OS/2 and the ascender and descender in hhea
+const FS_SELECTION: usize = 62;
+const HHEA_ASCENT: usize = 4;
+
+fn metrics(font: &[u8]) -> (i16, i16, i16) {
+    let font = TrueTypeFont::from_bytes(font).unwrap();
+    let LineMetrics {
+        ascender,
+        descender,
+        line_gap,
+    } = font.line_metrics();
+    (ascender, descender, line_gap)
+}
+
+#[test]
+fn version3_fields() {
+    let data = font_data();
+    let font = TrueTypeFont::from_bytes(&data).unwrap();
+    let os2 = font.os2().unwrap();
+
+    assert_eq!(os2.version, 3);
+    assert_eq!((os2.us_weight_class, os2.us_width_class), (400, 5));
+    assert_eq!(os2.ach_vend_id, "pyrs");
+    assert_eq!((os2.s_typo_ascender, os2.s_typo_descender), (750, -250));
+    assert_eq!((os2.us_win_ascent, os2.us_win_descent), (940, 260));
+    assert_eq!((os2.sx_height, os2.s_cap_height), (Some(500), Some(700)));
+    assert_eq!(os2.us_break_char, Some(32));
+    assert_eq!(os2.us_lower_optical_point_size, None);
+
+    assert!(os2.use_typo_metrics());
+    assert!(!os2.is_bold() && !os2.is_italic() && !os2.is_oblique());
+    assert!(os2.has_unicode_range(0) && os2.has_unicode_range(2) && os2.has_unicode_range(38));
+    assert!(!os2.has_unicode_range(3) && !os2.has_unicode_range(127));
+    assert!(os2.has_code_page(0) && !os2.has_code_page(1));
+
+    let permissions = os2.embedding_permissions();
+    assert_eq!(permissions.embedding, Embedding::PreviewPrint);
+    assert!(!permissions.no_subsetting && !permissions.bitmap_only);
+}
+
+#[test]
+fn fields_by_version() {
+    let data = font_data();
+    let font = TrueTypeFont::from_bytes(&data).unwrap();
+    let table = font.table_data("OS/2").unwrap();
+
+    // version 0 ends with the win metrics, 1 adds the code pages
+    let mut version0 = table[..78].to_vec();
+    version0[..2].copy_from_slice(&[0, 0]);
+    let os2 = read(&version0).unwrap();
+    assert_eq!((os2.us_win_ascent, os2.ul_code_page_range), (940, None));
+    assert_eq!(os2.sx_height, None);
+    assert!(!os2.has_code_page(0));
+
+    let mut version1 = table[..86].to_vec();
+    version1[..2].copy_from_slice(&[0, 1]);
+    let os2 = read(&version1).unwrap();
+    assert_eq!(
+        (os2.ul_code_page_range, os2.sx_height),
+        (Some([1, 0]), None)
+    );
+
+    // version 5 has the optical point sizes at the end
+    let mut version5 = table.to_vec();
+    version5[..2].copy_from_slice(&[0, 5]);
+    version5.extend([0, 160, 0xFF, 0xFF]);
+    let os2 = read(&version5).unwrap();
+    assert_eq!(os2.us_lower_optical_point_size, Some(160));
+    assert_eq!(os2.us_upper_optical_point_size, Some(0xFFFF));
+
+    // a version 2 table cut short
+    version5[..2].copy_from_slice(&[0, 2]);
+    assert!(matches!(
+        read(&version5[..90]),
+        Err(Error::Truncated { .. })
+    ));
+
+    version5[..2].copy_from_slice(&[0, 6]);
+    assert!(matches!(
+        read(&version5),
+        Err(Error::UnsupportedFormat { format: 6, .. })
+    ));
+}
+
+#[test]
+fn line_metrics_policy() {
+    // typographic metrics asked for, they win over a different hhea
+    let mut data = font_data();
+    patch(&mut data, "hhea", HHEA_ASCENT, &800i16.to_be_bytes());
+    assert_eq!(metrics(&data), (750, -250, 200));
+
+    // without the flag hhea is used
+    patch(&mut data, "OS/2", FS_SELECTION, &[0, 0]);
+    assert_eq!(metrics(&data), (800, -250, 200));
+
+    // an empty hhea gets the win metrics, which leave no room for a line gap here
+    patch(&mut data, "hhea", HHEA_ASCENT, &[0, 0, 0, 0]);
+    assert_eq!(metrics(&data), (940, -260, 0));
+
+    // no OS/2 at all
+    let mut data = font_data();
+    patch(&mut data, "hhea", HHEA_ASCENT, &800i16.to_be_bytes());
+    let (record, _) = record(&data, "OS/2");
+    data[record..record + 4].copy_from_slice(b"OS/3");
+    assert_eq!(metrics(&data), (800, -250, 200));
+}
+
+#[test]
+fn unreadable_os2_is_left_out() {
+    let data = font_data();
+    let (_, offset) = record(&data, "OS/2");
+
+    // a version from the future and a table cut short
+    let mut future = data.clone();
+    patch(&mut future, "OS/2", 0, &[0, 6]);
+    let short = with_table("OS/2", &data[offset..offset + 40]);
+
+    for font in [future, short] {
+        let parsed = TrueTypeFont::from_bytes(&font).unwrap();
+        assert!(parsed.os2().is_none());
+        assert_eq!(parsed.table_errors().len(), 1);
+
+        // the line metrics come from hhea alone
+        let hhea = parsed.hhea();
+        assert_eq!(metrics(&font), (hhea.ascent, hhea.descent, hhea.line_gap));
+    }
+}
@@ -0,0 +1,100 @@
+mod common;
+
+use lipi::outline::outline_points;
+use lipi::{GlyphId, TrueTypeFont};
+
+use common::{font_data, PathRecorder};
+
+fn path(end_pts_of_contours: &[u16], points: &[(f32, f32, bool)]) -> String {
+    let mut recorder = PathRecorder(Vec::new());
+    outline_points(end_pts_of_contours, points, &mut recorder);
+    recorder.0.join(" ")
+}
+
+#[test]
+fn lines_and_curves() {
+    // a square, then a contour with a single control point
+    let points = [
+        (0., 0., true),
+        (10., 0., true),
+        (10., 10., true),
+        (0., 10., true),
+        (20., 0., true),
+        (25., 10., false),
+        (30., 0., true),
+    ];
+    assert_eq!(
+        path(&[3, 6], &points),
+        "M0 0 L10 0 L10 10 L0 10 L0 0 Z M20 0 Q25 10 30 0 L20 0 Z"
+    );
+}
+
+#[test]
+fn implied_on_curve_points() {
+    // two control points in a row have an on-curve point halfway between them
+    let points = [
+        (0., 0., true),
+        (0., 10., false),
+        (10., 10., false),
+        (10., 0., true),
+    ];
+    assert_eq!(path(&[3], &points), "M0 0 Q0 10 5 10 Q10 10 10 0 L0 0 Z");
+
+    // a control point at the end curves back to the start
+    let points = [(0., 0., true), (10., 0., true), (5., 10., false)];
+    assert_eq!(path(&[2], &points), "M0 0 L10 0 Q5 10 0 0 Z");
+}
+
+#[test]
+fn off_curve_starts() {
+    // the contour starts at its last point when that one is on the curve
+    let points = [
+        (0., 10., false),
+        (10., 10., true),
+        (10., 0., false),
+        (0., 0., true),
+    ];
+    assert_eq!(path(&[3], &points), "M0 0 Q0 10 10 10 Q10 0 0 0 Z");
+
+    // and halfway between the last and first points when no point is on it
+    let points = [
+        (0., 0., false),
+        (10., 0., false),
+        (10., 10., false),
+        (0., 10., false),
+    ];
+    assert_eq!(
+        path(&[3], &points),
+        "M0 5 Q0 0 5 0 Q10 0 10 5 Q10 10 5 10 Q0 10 0 5 Z"
+    );
+}
+
+#[test]
+fn broken_contours() {
+    // end points past the points or going backwards stop the walk
+    let points = [(0., 0., true), (10., 0., true), (10., 10., true)];
+    assert_eq!(path(&[2, 1], &points), "M0 0 L10 0 L10 10 L0 0 Z");
+    assert_eq!(path(&[9], &points), "M0 0 L10 0 L10 10 L0 0 Z");
+    assert_eq!(path(&[], &points), "");
+}
+
+#[test]
+fn glyphs_from_the_font() {
+    let data = font_data();
+    let font = TrueTypeFont::from_bytes(&data).unwrap();
+
+    // the space has no outline at all
+    let mut recorder = PathRecorder(Vec::new());
+    let space = font.glyph_index(' ').unwrap();
+    assert_eq!(font.outline_glyph(space, &mut recorder).unwrap(), None);
+    assert!(recorder.0.is_empty());
+
+    // glyph 5 has three contours, each opened and closed once
+    let bbox = font
+        .outline_glyph(GlyphId(5), &mut recorder)
+        .unwrap()
+        .unwrap();
+    let count = |prefix| recorder.0.iter().filter(|c| c.starts_with(prefix)).count();
+    assert_eq!((count("M"), count("Z")), (3, 3));
+    assert!(bbox.width() > 0 && bbox.height() > 0);
+}
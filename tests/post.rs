@@ -0,0 +1,53 @@
+mod common;
+
+use lipi::{Error, GlyphId, TrueTypeFont};
+
+use common::{font_data, patch};
+
+#[test]
+fn version2_names() {
+    let data = font_data();
+    let font = TrueTypeFont::from_bytes(&data).unwrap();
+    assert_eq!(font.post().unwrap().version, 2.0);
+
+    // glyphs 3 and 51 take the standard Mac names 3 and 77, glyphs 1 and 11 have names of
+    // their own stored in the table
+    let name = |glyph| font.glyph_name(GlyphId(glyph));
+    assert_eq!(name(0), Some(".notdef"));
+    assert_eq!(name(3), Some("space"));
+    assert_eq!(name(51), Some("j"));
+    assert_eq!(name(1), Some("NULL"));
+    assert_eq!(name(11), Some("HT"));
+    assert_eq!(name(62), Some("Eth"));
+    assert_eq!(name(63), None);
+
+    let glyph = |name| font.glyph_id_by_name(name).map(|glyph| glyph.0);
+    assert_eq!(glyph("space"), Some(3));
+    assert_eq!(glyph("j"), Some(51));
+    assert_eq!(glyph("HT"), Some(11));
+    assert_eq!(glyph("uni2603"), None);
+    assert_eq!(glyph(""), None);
+
+    // every name leads back to its glyph
+    for index in 0..font.maxp().glyph_count {
+        let name = font.glyph_name(GlyphId(index)).unwrap();
+        assert_eq!(glyph(name), Some(index));
+    }
+}
+
+#[test]
+fn unsupported_version_leaves_post_out() {
+    let mut data = font_data();
+    patch(&mut data, "post", 0, &[0, 4, 0, 0]);
+    let font = TrueTypeFont::from_bytes(&data).unwrap();
+
+    assert!(font.post().is_none());
+    assert_eq!(font.glyph_name(GlyphId(3)), None);
+    assert!(matches!(
+        font.table_errors(),
+        [Error::UnsupportedFormat {
+            format: 0x40000,
+            ..
+        }]
+    ));
+}
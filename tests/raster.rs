@@ -0,0 +1,85 @@
+mod common;
+
+use lipi::{GlyphId, OutlineBuilder, Rasterizer, TrueTypeFont};
+
+use common::font_data;
+
+fn square(rasterizer: &mut Rasterizer, x0: f32, y0: f32, x1: f32, y1: f32) {
+    rasterizer.move_to(x0, y0);
+    rasterizer.line_to(x1, y0);
+    rasterizer.line_to(x1, y1);
+    rasterizer.line_to(x0, y1);
+    rasterizer.close();
+}
+
+#[test]
+fn half_covered_edges() {
+    let mut rasterizer = Rasterizer::new(4, 4, 1., (0., 4.));
+    square(&mut rasterizer, 0.5, 0., 3.5, 3.);
+
+    #[rustfmt::skip]
+    let expected = vec![
+        0, 0, 0, 0,
+        128, 255, 255, 128,
+        128, 255, 255, 128,
+        128, 255, 255, 128,
+    ];
+    assert_eq!(rasterizer.coverage(), expected);
+}
+
+#[test]
+fn overlapping_contours_are_non_zero() {
+    let mut rasterizer = Rasterizer::new(4, 1, 1., (0., 1.));
+    square(&mut rasterizer, 0., 0., 3., 1.);
+    square(&mut rasterizer, 1., 0., 4., 1.);
+
+    assert_eq!(rasterizer.coverage(), vec![255, 255, 255, 255]);
+}
+
+#[test]
+fn opposite_contours_cut_holes() {
+    let mut rasterizer = Rasterizer::new(3, 1, 1., (0., 1.));
+    square(&mut rasterizer, 0., 0., 3., 1.);
+    // wound the other way round
+    square(&mut rasterizer, 2., 0., 1., 1.);
+
+    assert_eq!(rasterizer.coverage(), vec![255, 0, 255]);
+}
+
+#[test]
+fn check_mark_bitmap() {
+    let data = font_data();
+    let font = TrueTypeFont::from_bytes(&data).unwrap();
+
+    let bitmap = font
+        .rasterize_glyph(GlyphId(14), 24., (0., 0.))
+        .unwrap()
+        .unwrap();
+    assert_eq!((bitmap.width, bitmap.height), (22, 15));
+    assert_eq!((bitmap.left, bitmap.top), (0, 15));
+    assert_eq!(bitmap.coverage.len(), 22 * 15);
+
+    // the stroke runs through the middle of the bottom row and misses its corners
+    let bottom = &bitmap.coverage[14 * 22..];
+    assert_eq!(bottom[0], 0);
+    assert_eq!(bottom[8], 255);
+    assert_eq!(bottom[21], 0);
+
+    let shifted = font
+        .rasterize_glyph(GlyphId(14), 24., (0.5, 0.))
+        .unwrap()
+        .unwrap();
+    assert_eq!(shifted.width, 23);
+}
+
+#[test]
+fn empty_glyph_has_no_bitmap() {
+    let data = font_data();
+    let font = TrueTypeFont::from_bytes(&data).unwrap();
+
+    let space = font.glyph_index(' ').unwrap();
+    assert!(font
+        .rasterize_glyph(space, 24., (0., 0.))
+        .unwrap()
+        .is_none());
+}
@@ -0,0 +1,200 @@
+mod common;
+
+use lipi::layout::{Direction, FeatureSetting};
+use lipi::{shape, GlyphId, ShapedGlyph, ShapingOptions, TrueTypeFont};
+
+use common::Item::*;
+use common::{font_data, table, with_table, words};
+
+// FontAwesome maps j, k and l to glyphs 51, 52 and 53 and the space to 3, it has no GDEF,
+// GSUB, GPOS or kern table of its own
+const J: u16 = 51;
+const K: u16 = 52;
+const L: u16 = 53;
+const SPACE: u16 = 3;
+
+// j followed by k kerns by -100
+fn kern_data() -> Vec<u8> {
+    words(&[0, 1, 0, 20, 1, 1, 6, 0, 0, J as i32, K as i32, -100])
+}
+
+// liga ligates j k into l, in the default script only, Greek has no features
+fn gsub_data() -> Vec<u8> {
+    let ligature = table(
+        &[W(1), O(0), W(1), O(1)],
+        &[
+            words(&[1, 1, J as i32]),
+            table(&[W(1), O(0)], &[words(&[L as i32, 2, K as i32])]),
+        ],
+    );
+
+    let script_list = table(
+        &[W(2), W(0x4446), W(0x4C54), O(0), W(0x6772), W(0x656B), O(1)],
+        &[
+            table(&[O(0), W(0)], &[words(&[0, 0xFFFF, 1, 0])]),
+            table(&[O(0), W(0)], &[words(&[0, 0xFFFF, 0])]),
+        ],
+    );
+    let feature_list = table(&[W(1), W(0x6C69), W(0x6761), O(0)], &[words(&[0, 1, 0])]);
+    let lookup_list = table(
+        &[W(1), O(0)],
+        &[table(&[W(4), W(0), W(1), O(0)], &[ligature])],
+    );
+
+    table(
+        &[W(1), W(0), O(0), O(1), O(2)],
+        &[script_list, feature_list, lookup_list],
+    )
+}
+
+fn glyphs(shaped: &[ShapedGlyph]) -> Vec<(u16, u32)> {
+    shaped
+        .iter()
+        .map(|glyph| (glyph.glyph.0, glyph.cluster))
+        .collect()
+}
+
+fn advance(font: &TrueTypeFont, glyph: u16) -> i32 {
+    font.advance_width(GlyphId(glyph)) as i32
+}
+
+#[test]
+fn maps_characters_to_glyphs() {
+    let data = font_data();
+    let font = TrueTypeFont::from_bytes(&data).unwrap();
+
+    // clusters are byte offsets, characters the font lacks get glyph 0
+    let shaped = shape(&font, "jé k", &ShapingOptions::default());
+    assert_eq!(glyphs(&shaped), [(J, 0), (0, 1), (SPACE, 3), (K, 4)]);
+    let advances: Vec<i32> = shaped.iter().map(|glyph| glyph.x_advance).collect();
+    assert_eq!(
+        advances,
+        [
+            advance(&font, J),
+            advance(&font, 0),
+            advance(&font, SPACE),
+            advance(&font, K)
+        ]
+    );
+    assert!(shaped
+        .iter()
+        .all(|glyph| (glyph.y_advance, glyph.x_offset, glyph.y_offset) == (0, 0, 0)));
+
+    assert!(shape(&font, "", &ShapingOptions::default()).is_empty());
+}
+
+#[test]
+fn marks_and_ignorables() {
+    let data = font_data();
+    let font = TrueTypeFont::from_bytes(&data).unwrap();
+
+    // the marks join the cluster of their base and lose their advance, without GPOS they
+    // move back over the base with it
+    let shaped = shape(&font, "j\u{323}\u{301}k", &ShapingOptions::default());
+    assert_eq!(glyphs(&shaped), [(J, 0), (0, 0), (0, 0), (K, 5)]);
+    assert_eq!(
+        (shaped[1].x_advance, shaped[1].x_offset),
+        (0, -advance(&font, 0))
+    );
+
+    // invisible characters become empty spaces, the joiner goes with the character before
+    let shaped = shape(&font, "j\u{200D}k\u{AD}", &ShapingOptions::default());
+    assert_eq!(glyphs(&shaped), [(J, 0), (SPACE, 0), (K, 4), (SPACE, 5)]);
+    assert_eq!(shaped[1].x_advance, 0);
+    assert_eq!(shaped[3].x_advance, 0);
+}
+
+#[test]
+fn kern_table_without_gpos() {
+    let data = with_table("kern", &kern_data());
+    let font = TrueTypeFont::from_bytes(&data).unwrap();
+    let (j, k) = (advance(&font, J), advance(&font, K));
+
+    // the kerning is split between the pair, the second glyph moving back with its half
+    let shaped = shape(&font, "jk", &ShapingOptions::default());
+    assert_eq!(
+        (shaped[0].x_advance, shaped[1].x_advance, shaped[1].x_offset),
+        (j - 50, k - 50, -50)
+    );
+
+    // invisible characters in between don't break the pair
+    let shaped = shape(&font, "j\u{AD}k", &ShapingOptions::default());
+    assert_eq!((shaped[0].x_advance, shaped[2].x_offset), (j - 50, -50));
+
+    let features = [FeatureSetting {
+        value: 0,
+        ..FeatureSetting::new("kern")
+    }];
+    let options = ShapingOptions {
+        features: &features,
+        ..Default::default()
+    };
+    let shaped = shape(&font, "jk", &options);
+    assert_eq!(
+        (shaped[0].x_advance, shaped[1].x_advance, shaped[1].x_offset),
+        (j, k, 0)
+    );
+}
+
+#[test]
+fn features_scripts_and_joiners() {
+    let data = with_table("GSUB", &gsub_data());
+    let font = TrueTypeFont::from_bytes(&data).unwrap();
+    let options = ShapingOptions::default();
+
+    assert_eq!(glyphs(&shape(&font, "jk", &options)), [(L, 0)]);
+
+    // the joiner doesn't keep the ligature from forming, the non-joiner does
+    assert_eq!(
+        glyphs(&shape(&font, "j\u{200D}k", &options)),
+        [(L, 0), (SPACE, 0)]
+    );
+    assert_eq!(
+        glyphs(&shape(&font, "j\u{200C}k", &options)),
+        [(J, 0), (SPACE, 1), (K, 4)]
+    );
+
+    let features = [FeatureSetting {
+        value: 0,
+        ..FeatureSetting::new("liga")
+    }];
+    let off = ShapingOptions {
+        features: &features,
+        ..Default::default()
+    };
+    assert_eq!(glyphs(&shape(&font, "jk", &off)), [(J, 0), (K, 1)]);
+
+    // the script picks the features, Greek text gets the Greek ones
+    let greek = ShapingOptions {
+        script: Some("grek"),
+        ..Default::default()
+    };
+    assert_eq!(glyphs(&shape(&font, "jk", &greek)), [(J, 0), (K, 1)]);
+    assert_eq!(glyphs(&shape(&font, "jkα", &options)), [(L, 0), (0, 2)]);
+    assert_eq!(
+        glyphs(&shape(&font, "αjk", &options)),
+        [(0, 0), (J, 2), (K, 3)]
+    );
+}
+
+#[test]
+fn right_to_left() {
+    let data = font_data();
+    let font = TrueTypeFont::from_bytes(&data).unwrap();
+
+    // visual order, the last character first
+    let options = ShapingOptions {
+        direction: Some(Direction::RightToLeft),
+        ..Default::default()
+    };
+    assert_eq!(
+        glyphs(&shape(&font, "jkl", &options)),
+        [(L, 2), (K, 1), (J, 0)]
+    );
+
+    // Hebrew and Arabic text goes right to left on its own
+    assert_eq!(
+        glyphs(&shape(&font, "\u{5D0}j", &ShapingOptions::default())),
+        [(J, 2), (0, 0)]
+    );
+}